@@ -0,0 +1,63 @@
+//! Benchmarks of parsing and serializing the messages that are sent most often.
+//!
+//! ```text
+//! cargo bench --bench parse
+//! ```
+use artnet_protocol::*;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn output() -> ArtCommand {
+    Output::new(1.into(), vec![0xAB; 512]).into_command()
+}
+
+fn poll_reply() -> ArtCommand {
+    PollReply::new_node(
+        [10, 0, 0, 5].into(),
+        [2, 0, 0, 0, 0, 5],
+        "Dimmer",
+        &[1.into(), 2.into(), 3.into(), 4.into()],
+    )
+    .unwrap()
+    .into_command()
+}
+
+fn parse(c: &mut Criterion) {
+    let output = output().write_to_buffer().unwrap();
+    c.bench_function("parse ArtDmx", |b| {
+        b.iter(|| ArtCommand::from_buffer(black_box(&output)).unwrap())
+    });
+
+    let poll_reply = poll_reply().write_to_buffer().unwrap();
+    c.bench_function("parse ArtPollReply", |b| {
+        b.iter(|| ArtCommand::from_buffer(black_box(&poll_reply)).unwrap())
+    });
+
+    // Art-Net 3 nodes send shorter replies, which are padded with zeroes
+    let truncated = &poll_reply[..213];
+    c.bench_function("parse truncated ArtPollReply", |b| {
+        b.iter(|| ArtCommand::from_buffer(black_box(truncated)).unwrap())
+    });
+}
+
+fn serialize(c: &mut Criterion) {
+    let output = output();
+    c.bench_function("serialize ArtDmx", |b| {
+        b.iter(|| {
+            let mut buffer = Vec::new();
+            black_box(&output).write_to(&mut buffer).unwrap();
+            buffer
+        })
+    });
+    let mut encoder = Encoder::new();
+    c.bench_function("encode ArtDmx", |b| {
+        b.iter(|| encoder.encode(black_box(&output)).unwrap().len())
+    });
+
+    let poll_reply = poll_reply();
+    c.bench_function("encode ArtPollReply", |b| {
+        b.iter(|| encoder.encode(black_box(&poll_reply)).unwrap().len())
+    });
+}
+
+criterion_group!(benches, parse, serialize);
+criterion_main!(benches);
@@ -0,0 +1,9 @@
+#![no_main]
+use artnet_protocol::{ArtCommand, ParseOptions};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Parsing hostile input should never panic, in either mode
+    let _ = ArtCommand::from_buffer_with(data, &ParseOptions::lenient());
+    let _ = ArtCommand::from_buffer_with(data, &ParseOptions::strict());
+});
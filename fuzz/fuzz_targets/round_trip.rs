@@ -0,0 +1,16 @@
+#![no_main]
+use artnet_protocol::ArtCommand;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|command: ArtCommand| {
+    let opcode = command.opcode();
+    // Not every arbitrary command is valid, e.g. Output data can be longer than 512 bytes
+    if let Ok(buffer) = command.write_to_buffer() {
+        // Commands without a body are too short to be parsed again
+        if buffer.len() > 10 {
+            let parsed =
+                ArtCommand::from_buffer(&buffer).expect("Could not parse a serialized command");
+            assert_eq!(parsed.opcode(), opcode);
+        }
+    }
+});
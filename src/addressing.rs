@@ -0,0 +1,119 @@
+use crate::PollReply;
+use core::net::Ipv4Addr;
+
+/// The networks of the Art-Net IP addressing scheme, for nodes that are not configured by DHCP or by hand.
+///
+/// A node on one of these networks takes its address from its MAC address and Oem code, as `A.B.C.D`: A is 2 or 10, B is the sum of byte 3 of the MAC address and both bytes of the Oem code, C and D are bytes 4 and 5 of the MAC address. Both networks use the netmask 255.0.0.0.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::net::Ipv4Addr;
+///
+/// let mac = [0x00, 0x50, 0xC2, 0x12, 0x34, 0x56];
+/// let address = ArtNetNetwork::Primary.address(mac, 0x0100);
+/// assert_eq!(address, Ipv4Addr::new(2, 0x13, 0x34, 0x56));
+/// assert_eq!(ArtNetNetwork::of(address), Some(ArtNetNetwork::Primary));
+/// assert_eq!(ArtNetNetwork::Primary.broadcast_address(), Ipv4Addr::new(2, 255, 255, 255));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArtNetNetwork {
+    /// The 2.x.x.x network, the default of most nodes
+    Primary,
+
+    /// The 10.x.x.x network
+    Secondary,
+}
+
+impl ArtNetNetwork {
+    /// The netmask of both networks
+    pub const NETMASK: Ipv4Addr = Ipv4Addr::new(255, 0, 0, 0);
+
+    /// The network that the given address is on, if any
+    pub fn of(address: Ipv4Addr) -> Option<ArtNetNetwork> {
+        match address.octets()[0] {
+            2 => Some(ArtNetNetwork::Primary),
+            10 => Some(ArtNetNetwork::Secondary),
+            _ => None,
+        }
+    }
+
+    /// The first byte of the addresses on this network
+    pub fn network_id(self) -> u8 {
+        match self {
+            ArtNetNetwork::Primary => 2,
+            ArtNetNetwork::Secondary => 10,
+        }
+    }
+
+    /// The address of the network, 2.0.0.0 or 10.0.0.0
+    pub fn network_address(self) -> Ipv4Addr {
+        Ipv4Addr::new(self.network_id(), 0, 0, 0)
+    }
+
+    /// The directed broadcast address of the network, 2.255.255.255 or 10.255.255.255
+    pub fn broadcast_address(self) -> Ipv4Addr {
+        directed_broadcast(self.network_address(), ArtNetNetwork::NETMASK)
+    }
+
+    /// The address on this network of a node with the given MAC address and Oem code
+    pub fn address(self, mac: [u8; 6], oem: u16) -> Ipv4Addr {
+        let [oem_hi, oem_lo] = oem.to_be_bytes();
+        Ipv4Addr::new(
+            self.network_id(),
+            mac[3].wrapping_add(oem_hi).wrapping_add(oem_lo),
+            mac[4],
+            mac[5],
+        )
+    }
+
+    /// The address on this network of the node that sent the given reply, from its MAC address and Oem code
+    pub fn address_of(self, reply: &PollReply) -> Ipv4Addr {
+        self.address(reply.mac, u16::from_be_bytes(reply.oem))
+    }
+}
+
+/// The directed broadcast address of the network with the given address and netmask, e.g. 192.168.1.255 for 192.168.1.20 and 255.255.255.0
+pub fn directed_broadcast(address: Ipv4Addr, netmask: Ipv4Addr) -> Ipv4Addr {
+    (u32::from(address) | !u32::from(netmask)).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn address() {
+        // The sum of the MAC address and Oem code wraps around
+        let mac = [0, 0, 0, 0xF0, 1, 2];
+        assert_eq!(
+            ArtNetNetwork::Secondary.address(mac, 0x0820),
+            Ipv4Addr::new(10, 0x18, 1, 2)
+        );
+        let reply = PollReply {
+            mac,
+            oem: [0x08, 0x20],
+            ..PollReply::default()
+        };
+        assert_eq!(
+            ArtNetNetwork::Primary.address_of(&reply),
+            Ipv4Addr::new(2, 0x18, 1, 2)
+        );
+        assert_eq!(ArtNetNetwork::of(Ipv4Addr::new(192, 168, 0, 1)), None);
+        assert_eq!(
+            ArtNetNetwork::Secondary.broadcast_address(),
+            Ipv4Addr::new(10, 255, 255, 255)
+        );
+    }
+
+    #[test]
+    fn directed_broadcast() {
+        assert_eq!(
+            super::directed_broadcast([192, 168, 1, 20].into(), [255, 255, 255, 0].into()),
+            Ipv4Addr::new(192, 168, 1, 255)
+        );
+        assert_eq!(
+            super::directed_broadcast([172, 16, 5, 4].into(), [255, 255, 240, 0].into()),
+            Ipv4Addr::new(172, 16, 15, 255)
+        );
+    }
+}
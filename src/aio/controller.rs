@@ -0,0 +1,192 @@
+use super::{ArtNetSocket, AsyncTransport};
+use crate::controller::{ControllerCore, Outgoing};
+#[cfg(feature = "dmx")]
+use crate::{Address, NodeId};
+use crate::{ArtCommand, DiscoveryEvent, DiscoveryManager, DmxBroadcastPolicy, PortAddress};
+use std::io;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// The async version of [Controller](../struct.Controller.html), which discovers the nodes on the network and sends ArtDmx to them.
+///
+/// Call `recv` in a loop to handle the replies of the nodes, and call `update` regularly, e.g. from a timer of the runtime. It polls the network, reports the changes in the list of nodes and re-transmits data that did not change.
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "tokio")]
+/// # async fn run() -> std::io::Result<()> {
+/// use artnet_protocol::aio::AsyncController;
+/// use std::time::Duration;
+///
+/// let socket = tokio::net::UdpSocket::bind(("0.0.0.0", 6454)).await?;
+/// socket.set_broadcast(true)?;
+/// let broadcast = "255.255.255.255:6454".parse().unwrap();
+/// let mut controller = AsyncController::new(socket, broadcast);
+///
+/// let mut interval = tokio::time::interval(Duration::from_millis(25));
+/// loop {
+///     tokio::select! {
+///         received = controller.recv() => {
+///             let (command, addr) = received?;
+///             println!("Received {:?} from {}", command, addr);
+///         }
+///         _ = interval.tick() => {
+///             for event in controller.update().await? {
+///                 println!("{:?}", event);
+///             }
+///             controller.send_dmx(1.into(), &[255; 512]).await?;
+///         }
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AsyncController<T> {
+    socket: ArtNetSocket<T>,
+    core: ControllerCore,
+    /// The changes in the list of nodes since the last `update`
+    events: Vec<DiscoveryEvent>,
+}
+
+impl<T: AsyncTransport> AsyncController<T> {
+    /// Create a controller on an existing transport, that sends polls to the given broadcast address
+    pub fn new(transport: T, broadcast_address: SocketAddr) -> AsyncController<T> {
+        AsyncController {
+            socket: ArtNetSocket::new(transport),
+            core: ControllerCore::new(broadcast_address),
+            events: Vec::new(),
+        }
+    }
+
+    /// Poll the network at a fixed interval, instead of a random interval between 2.5 and 3 seconds
+    pub fn set_poll_interval(&mut self, interval: Duration) {
+        self.core.poll.set_interval(interval, interval);
+    }
+
+    /// Change how often data that did not change is re-transmitted
+    pub fn set_keepalive_interval(&mut self, interval: Duration) {
+        self.core.keepalive.set_interval(interval);
+    }
+
+    /// Refuse to send a command to an address that does not match its [DeliveryPolicy](../enum.DeliveryPolicy.html), as [Controller::set_strict_delivery](../struct.Controller.html#method.set_strict_delivery) does
+    pub fn set_strict_delivery(&mut self, strict: bool) {
+        self.core.strict_delivery = strict;
+    }
+
+    /// What `send` does with ArtDmx or ArtNzs for the broadcast address while nodes subscribed to its Port-Address, as [Controller::set_dmx_broadcast_policy](../struct.Controller.html#method.set_dmx_broadcast_policy) does
+    pub fn set_dmx_broadcast_policy(&mut self, policy: DmxBroadcastPolicy) {
+        self.core.dmx_broadcast_policy = policy;
+    }
+
+    /// The nodes that were discovered
+    pub fn discovery(&self) -> &DiscoveryManager {
+        &self.core.discovery
+    }
+
+    /// The socket of this controller
+    pub fn socket(&self) -> &ArtNetSocket<T> {
+        &self.socket
+    }
+
+    /// Send any command to the given address
+    pub async fn send(&mut self, command: &ArtCommand, addr: SocketAddr) -> io::Result<()> {
+        self.core.check(command, addr)?;
+        self.socket.send(command, addr).await
+    }
+
+    /// Send the given data to every node that outputs the given Port-Address. The sequence number is increased for every message.
+    pub async fn send_dmx(&mut self, port_address: PortAddress, data: &[u8]) -> io::Result<()> {
+        let outgoing = self.core.send_dmx(port_address, data, Instant::now())?;
+        self.transmit(&outgoing).await
+    }
+
+    /// Send the data of several universes that belong to the same frame, followed by an ArtSync to make the nodes output them at the same time.
+    ///
+    /// After this is called, re-transmitted data is followed by an ArtSync as well, because the nodes hold back ArtDmx until they receive one.
+    pub async fn send_frame(&mut self, frame: &[(PortAddress, &[u8])]) -> io::Result<()> {
+        for outgoing in self.core.send_frame(frame, Instant::now())? {
+            self.transmit(&outgoing).await?;
+        }
+        Ok(())
+    }
+
+    /// Program a discovered node with an ArtAddress. The bind index of the message is set to the one of the node.
+    #[cfg(feature = "dmx")]
+    pub async fn program(&mut self, node: NodeId, address: Address) -> io::Result<()> {
+        let outgoing = self.core.program(node, address)?;
+        self.transmit(&outgoing).await
+    }
+
+    /// Receive the next message, and the address that sent it. Replies of nodes update the list of nodes, which is reported by the next `update`. Messages that can not be parsed are ignored.
+    pub async fn recv(&mut self) -> io::Result<(ArtCommand, SocketAddr)> {
+        let (command, addr) = self.socket.recv_valid().await?;
+        self.events
+            .extend(self.core.discovery.handle(&command, Instant::now()));
+        Ok((command, addr))
+    }
+
+    /// Poll the network when needed and re-transmit data that did not change. Returns the changes in the list of nodes.
+    pub async fn update(&mut self) -> io::Result<Vec<DiscoveryEvent>> {
+        let now = Instant::now();
+        if let Some(poll) = self.core.poll(now)? {
+            self.transmit(&poll).await?;
+            self.core.poll_sent(now);
+        }
+
+        let mut events = std::mem::take(&mut self.events);
+        events.extend(self.core.discovery.expire(now));
+
+        for outgoing in self.core.retransmit(now)? {
+            self.transmit(&outgoing).await?;
+        }
+        Ok(events)
+    }
+
+    async fn transmit(&mut self, outgoing: &Outgoing) -> io::Result<()> {
+        for addr in &outgoing.addrs {
+            self.socket.send(&outgoing.command, *addr).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use super::*;
+    use crate::{Node, NodeEvent, PollReply};
+
+    #[tokio::test]
+    async fn controller_and_node() {
+        let node_socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let node_addr = node_socket.local_addr().unwrap();
+        let mut node = super::super::AsyncNode::new(
+            node_socket,
+            Node::new(PollReply {
+                address: [127, 0, 0, 1].into(),
+                port: node_addr.port(),
+                num_ports: [0, 1],
+                port_types: [0x80, 0, 0, 0],
+                swout: [1, 0, 0, 0],
+                ..PollReply::default()
+            }),
+        );
+        let socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut controller = AsyncController::new(socket, node_addr);
+
+        // The node replies to the poll
+        assert!(controller.update().await.unwrap().is_empty());
+        assert!(matches!(node.recv().await, Ok(NodeEvent::Reply(_))));
+        let (reply, _) = controller.recv().await.unwrap();
+        assert!(matches!(reply, ArtCommand::PollReply(_)));
+        let events = controller.update().await.unwrap();
+        assert!(matches!(events[..], [DiscoveryEvent::NodeAdded(_)]));
+
+        controller.send_dmx(1.into(), &[1, 2, 3, 4]).await.unwrap();
+        match node.recv().await {
+            Ok(NodeEvent::Dmx { port, output }) => {
+                assert_eq!(port, 0);
+                assert_eq!(output.data.as_ref(), &vec![1, 2, 3, 4]);
+            }
+            event => panic!("Unexpected event {:?}", event),
+        }
+    }
+}
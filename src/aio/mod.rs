@@ -0,0 +1,243 @@
+//! Async sockets, controllers and nodes, which work in any runtime that implements [AsyncTransport](trait.AsyncTransport.html).
+//!
+//! The UDP sockets of tokio, async-std and smol implement the trait when the `tokio`, `async-std` or `smol` feature is enabled. Other transports, e.g. a socket of an embedded runtime, can implement it themselves.
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "tokio")]
+//! # async fn run() -> std::io::Result<()> {
+//! use artnet_protocol::{aio::ArtNetSocket, ArtCommand, Poll};
+//!
+//! let socket = tokio::net::UdpSocket::bind(("0.0.0.0", 6454)).await?;
+//! socket.set_broadcast(true)?;
+//! let mut socket = ArtNetSocket::new(socket);
+//!
+//! let broadcast = "255.255.255.255:6454".parse().unwrap();
+//! socket.send(&Poll::default().into(), broadcast).await?;
+//!
+//! loop {
+//!     match socket.recv().await {
+//!         Ok((command, addr)) => println!("Received {:?} from {}", command, addr),
+//!         Err(e) => println!("Received an invalid message: {}", e),
+//!     }
+//! }
+//! # }
+//! ```
+mod controller;
+mod node;
+
+pub use self::controller::AsyncController;
+pub use self::node::AsyncNode;
+
+use crate::controller::invalid_data;
+use crate::{ArtCommand, Encoder, ParseOptions, RecvBuffer};
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+
+/// Sends and receives datagrams, e.g. an async UDP socket.
+///
+/// This is implemented for the UDP sockets of tokio, async-std and smol, behind the feature with the same name.
+pub trait AsyncTransport {
+    /// Send a datagram to the given address, and return the number of bytes that were sent
+    fn send_to(
+        &self,
+        buffer: &[u8],
+        addr: SocketAddr,
+    ) -> impl Future<Output = io::Result<usize>> + Send;
+
+    /// Receive a single datagram, and return its length and the address that sent it
+    fn recv_from(
+        &self,
+        buffer: &mut [u8],
+    ) -> impl Future<Output = io::Result<(usize, SocketAddr)>> + Send;
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncTransport for tokio::net::UdpSocket {
+    async fn send_to(&self, buffer: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        tokio::net::UdpSocket::send_to(self, buffer, addr).await
+    }
+
+    async fn recv_from(&self, buffer: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        tokio::net::UdpSocket::recv_from(self, buffer).await
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl AsyncTransport for async_std::net::UdpSocket {
+    async fn send_to(&self, buffer: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        async_std::net::UdpSocket::send_to(self, buffer, addr).await
+    }
+
+    async fn recv_from(&self, buffer: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        async_std::net::UdpSocket::recv_from(self, buffer).await
+    }
+}
+
+#[cfg(feature = "smol")]
+impl AsyncTransport for smol::net::UdpSocket {
+    async fn send_to(&self, buffer: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        smol::net::UdpSocket::send_to(self, buffer, addr).await
+    }
+
+    async fn recv_from(&self, buffer: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        smol::net::UdpSocket::recv_from(self, buffer).await
+    }
+}
+
+/// A transport that sends and receives `ArtCommand`s. The buffers for encoding and receiving are reused for every message.
+///
+/// Errors of this crate are returned as an `std::io::Error` of kind `InvalidData`, with the `artnet_protocol::Error` as its inner error.
+#[derive(Debug)]
+pub struct ArtNetSocket<T> {
+    transport: T,
+    options: ParseOptions,
+    encoder: Encoder,
+    buffer: RecvBuffer,
+}
+
+#[cfg(feature = "tokio")]
+impl ArtNetSocket<tokio::net::UdpSocket> {
+    /// Bind a tokio socket to the given address, usually port 6454 on all interfaces
+    pub async fn bind(addr: impl tokio::net::ToSocketAddrs) -> io::Result<Self> {
+        Ok(ArtNetSocket::new(tokio::net::UdpSocket::bind(addr).await?))
+    }
+}
+
+impl<T: AsyncTransport> ArtNetSocket<T> {
+    /// Send and receive on an existing transport
+    pub fn new(transport: T) -> ArtNetSocket<T> {
+        ArtNetSocket {
+            transport,
+            options: ParseOptions::default(),
+            encoder: Encoder::new(),
+            buffer: RecvBuffer::new(),
+        }
+    }
+
+    /// Decode received messages with the given options
+    pub fn with_options(mut self, options: ParseOptions) -> ArtNetSocket<T> {
+        self.options = options;
+        self
+    }
+
+    /// The underlying transport, e.g. to enable broadcasting on a socket
+    pub fn socket(&self) -> &T {
+        &self.transport
+    }
+
+    /// Stop using Art-Net, and return the underlying transport
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+
+    /// Send a command to the given address
+    pub async fn send(&mut self, command: &ArtCommand, addr: SocketAddr) -> io::Result<()> {
+        let bytes = self.encoder.encode(command).map_err(invalid_data)?;
+        self.transport.send_to(bytes, addr).await?;
+        Ok(())
+    }
+
+    /// Receive the next message, and the address that sent it. A message that can not be parsed is returned as an error, the next call receives the next message.
+    pub async fn recv(&mut self) -> io::Result<(ArtCommand, SocketAddr)> {
+        let (length, addr) = self.transport.recv_from(&mut self.buffer).await?;
+        let command = self
+            .buffer
+            .parse_with(length, &self.options)
+            .map_err(invalid_data)?;
+        Ok((command, addr))
+    }
+
+    /// Receive the next message that can be parsed, and the address that sent it. Other messages are ignored.
+    pub(crate) async fn recv_valid(&mut self) -> io::Result<(ArtCommand, SocketAddr)> {
+        loop {
+            match self.recv().await {
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => continue,
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "tokio")]
+    use crate::Output;
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn send_recv() {
+        let mut receiver = ArtNetSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = receiver.socket().local_addr().unwrap();
+        let mut sender = ArtNetSocket::bind("127.0.0.1:0").await.unwrap();
+
+        let output = Output::new(3.into(), vec![1, 2, 3, 4]);
+        sender.send(&output.into(), addr).await.unwrap();
+        match receiver.recv().await.unwrap() {
+            (ArtCommand::Output(output), from) => {
+                assert_eq!(output.data.as_ref(), &vec![1, 2, 3, 4]);
+                assert_eq!(from, sender.socket().local_addr().unwrap());
+            }
+            (command, _) => panic!("Unexpected command {:?}", command),
+        }
+
+        let invalid = ArtCommand::Output(Output::default());
+        let err = sender.send(&invalid, addr).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        sender
+            .socket()
+            .send_to(b"Not Art-Net at all", addr)
+            .await
+            .unwrap();
+        let err = receiver.recv().await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "async-std")]
+    #[test]
+    fn async_std() {
+        async_std::task::block_on(async {
+            let receiver = async_std::net::UdpSocket::bind("127.0.0.1:0")
+                .await
+                .unwrap();
+            let addr = receiver.local_addr().unwrap();
+            let mut receiver = ArtNetSocket::new(receiver);
+            let sender = async_std::net::UdpSocket::bind("127.0.0.1:0")
+                .await
+                .unwrap();
+            let mut sender = ArtNetSocket::new(sender);
+
+            sender
+                .send(&crate::ArtSync::default().into(), addr)
+                .await
+                .unwrap();
+            assert!(matches!(
+                receiver.recv().await,
+                Ok((ArtCommand::Sync(_), _))
+            ));
+        });
+    }
+
+    #[cfg(feature = "smol")]
+    #[test]
+    fn smol() {
+        smol::block_on(async {
+            let receiver = smol::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            let addr = receiver.local_addr().unwrap();
+            let mut receiver = ArtNetSocket::new(receiver);
+            let sender = smol::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            let mut sender = ArtNetSocket::new(sender);
+
+            sender
+                .send(&crate::ArtSync::default().into(), addr)
+                .await
+                .unwrap();
+            assert!(matches!(
+                receiver.recv().await,
+                Ok((ArtCommand::Sync(_), _))
+            ));
+        });
+    }
+}
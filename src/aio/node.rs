@@ -0,0 +1,79 @@
+use super::{ArtNetSocket, AsyncTransport};
+use crate::{Node, NodeEvent};
+use std::io;
+use std::time::Instant;
+
+/// Runs a [Node](../struct.Node.html) on an async transport. Polls and ArtAddress messages are answered automatically, with the replies to polls rate limited as in `Node::handle_at`, and the controllers that polled with `ArtTalkToMe::EMIT_CHANGES` are told when the node changes.
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "tokio")]
+/// # async fn run() -> std::io::Result<()> {
+/// use artnet_protocol::{aio::AsyncNode, Node, NodeEvent, PollReply};
+///
+/// let socket = tokio::net::UdpSocket::bind(("0.0.0.0", 6454)).await?;
+/// let mut node = AsyncNode::new(socket, Node::new(PollReply::default()));
+/// loop {
+///     if let NodeEvent::Dmx { port, output } = node.recv().await? {
+///         // Write output.data to the DMX port
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AsyncNode<T> {
+    socket: ArtNetSocket<T>,
+    node: Node,
+}
+
+impl<T: AsyncTransport> AsyncNode<T> {
+    /// Run the given node on an existing transport
+    pub fn new(transport: T, node: Node) -> AsyncNode<T> {
+        AsyncNode {
+            socket: ArtNetSocket::new(transport),
+            node,
+        }
+    }
+
+    /// The state of the node
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+
+    /// Change the state of the node, e.g. to update the node report
+    pub fn node_mut(&mut self) -> &mut Node {
+        &mut self.node
+    }
+
+    /// The socket of this node
+    pub fn socket(&self) -> &ArtNetSocket<T> {
+        &self.socket
+    }
+
+    /// Receive messages until one is meant for this node. The reply to a poll or an ArtAddress is sent to the controller before the event is returned. Messages that can not be parsed are ignored.
+    pub async fn recv(&mut self) -> io::Result<NodeEvent> {
+        loop {
+            let (command, addr) = self.socket.recv_valid().await?;
+            let event = match self.node.handle_at(command, addr, Instant::now()) {
+                Some(event) => event,
+                None => continue,
+            };
+            if let Some(reply) = event.reply() {
+                self.socket.send(reply, addr).await?;
+            }
+            self.send_changes().await?;
+            return Ok(event);
+        }
+    }
+
+    /// Send the changed reply of the node to every controller that asked for changes, e.g. after changing the state with `node_mut`. Returns false if nothing changed.
+    pub async fn send_changes(&mut self) -> io::Result<bool> {
+        let reply = match self.node.changed_reply() {
+            Some(reply) => reply,
+            None => return Ok(false),
+        };
+        for subscriber in self.node.subscribers() {
+            self.socket.send(&reply, *subscriber).await?;
+        }
+        Ok(true)
+    }
+}
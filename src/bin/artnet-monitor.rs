@@ -0,0 +1,151 @@
+//! Prints the Art-Net traffic on the network, with a summary of the nodes and universes every second.
+//!
+//! ```text
+//! artnet-monitor [--all] [--quiet] [--poll] [ADDRESS]
+//! ```
+//!
+//! By default a report of every message but ArtDmx and ArtSync is printed, `--all` prints those as well and `--quiet` prints none. With `--poll`, an ArtPoll is broadcast every 2.5 seconds so nodes reply even when no controller is polling. The monitor binds to 0.0.0.0:6454, or to the given address.
+use artnet_protocol::pretty::Report;
+use artnet_protocol::*;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::process;
+use std::time::{Duration, Instant};
+
+/// How often the summary is printed
+const SUMMARY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often an ArtPoll is sent with `--poll`
+const POLL_INTERVAL: Duration = Duration::from_millis(2500);
+
+const USAGE: &str = "Usage: artnet-monitor [--all] [--quiet] [--poll] [ADDRESS]";
+
+struct Options {
+    all: bool,
+    quiet: bool,
+    poll: bool,
+    addr: SocketAddr,
+}
+
+fn parse_args() -> std::result::Result<Options, String> {
+    let mut options = Options {
+        all: false,
+        quiet: false,
+        poll: false,
+        addr: (Ipv4Addr::UNSPECIFIED, ArtNetSocket::PORT).into(),
+    };
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--all" => options.all = true,
+            "--quiet" => options.quiet = true,
+            "--poll" => options.poll = true,
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            addr => {
+                options.addr = addr
+                    .parse()
+                    .or_else(|_| {
+                        addr.parse()
+                            .map(|ip: Ipv4Addr| (ip, ArtNetSocket::PORT).into())
+                    })
+                    .map_err(|_| format!("Invalid address {:?}\n{}", addr, USAGE))?;
+            }
+        }
+    }
+    Ok(options)
+}
+
+fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(2);
+        }
+    };
+    if let Err(e) = run(&options) {
+        eprintln!("artnet-monitor: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run(options: &Options) -> io::Result<()> {
+    let mut socket = ArtNetSocket::bind(options.addr)?;
+    socket
+        .socket()
+        .set_read_timeout(Some(Duration::from_millis(100)))?;
+    eprintln!("Listening on {}", socket.socket().local_addr()?);
+
+    let mut buffer = RecvBuffer::new();
+    let mut stats = Stats::new();
+    let mut discovery = DiscoveryManager::new();
+    let mut next_summary = Instant::now() + SUMMARY_INTERVAL;
+    let mut next_poll = Instant::now();
+    loop {
+        let now = Instant::now();
+        if options.poll && now >= next_poll {
+            socket.broadcast(&Poll::default().into())?;
+            discovery.poll_sent(now);
+            next_poll = now + POLL_INTERVAL;
+        }
+        if now >= next_summary {
+            for event in discovery.expire(now) {
+                if let DiscoveryEvent::NodeLost(_, reply) = event {
+                    println!("Lost {}", ArtCommand::PollReply(reply));
+                }
+            }
+            print_summary(&stats, &discovery, now);
+            next_summary = now + SUMMARY_INTERVAL;
+        }
+
+        let (length, source) = match socket.socket().recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                continue
+            }
+            Err(e) => return Err(e),
+        };
+        let now = Instant::now();
+        let result = buffer.parse(length);
+        stats.record(length, &result, now);
+        let command = match result {
+            Ok(command) => command,
+            Err(e) => {
+                if !options.quiet {
+                    println!("{}: invalid message of {} bytes: {}", source, length, e);
+                }
+                continue;
+            }
+        };
+        discovery.handle(&command, now);
+        let streaming = matches!(command, ArtCommand::Output(_) | ArtCommand::Sync(_));
+        if !options.quiet && (options.all || !streaming) {
+            print!("{}: {}", source, Report(&command));
+        }
+    }
+}
+
+fn print_summary(stats: &Stats, discovery: &DiscoveryManager, now: Instant) {
+    println!(
+        "--- {} packets, {} bytes, {} parse errors",
+        stats.packets(),
+        stats.bytes(),
+        stats.parse_errors()
+    );
+    for (_, node) in discovery.nodes() {
+        println!("    node {}", node.reply);
+    }
+    for (port_address, universe) in stats.universes() {
+        println!(
+            "    universe {}: {} fps, {} packets, {} sequence gaps",
+            u16::from(port_address),
+            universe.fps(now),
+            universe.packets,
+            universe.sequence_gaps
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Polls the network, and prints a table of the nodes that replied.
+//!
+//! ```text
+//! artnet-scan [--timeout SECONDS] [BROADCAST]
+//! ```
+//!
+//! The ArtPoll is broadcast to 255.255.255.255, or to the given address, e.g. the directed broadcast address 2.255.255.255. Replies are collected for 3 seconds, or the given number of seconds.
+use artnet_protocol::*;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::process;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "Usage: artnet-scan [--timeout SECONDS] [BROADCAST]";
+
+struct Options {
+    timeout: Duration,
+    broadcast: SocketAddr,
+}
+
+fn parse_args() -> std::result::Result<Options, String> {
+    let mut options = Options {
+        timeout: Duration::from_secs(3),
+        broadcast: (Ipv4Addr::BROADCAST, ArtNetSocket::PORT).into(),
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--timeout" => {
+                let seconds = args.next().and_then(|seconds| seconds.parse::<f64>().ok());
+                options.timeout = match seconds {
+                    Some(seconds) if seconds.is_finite() && seconds >= 0.0 => {
+                        Duration::from_secs_f64(seconds)
+                    }
+                    _ => return Err(format!("Invalid timeout\n{}", USAGE)),
+                };
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            addr => {
+                options.broadcast = addr
+                    .parse()
+                    .or_else(|_| {
+                        addr.parse()
+                            .map(|ip: Ipv4Addr| (ip, ArtNetSocket::PORT).into())
+                    })
+                    .map_err(|_| format!("Invalid address {:?}\n{}", addr, USAGE))?;
+            }
+        }
+    }
+    Ok(options)
+}
+
+fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(2);
+        }
+    };
+    if let Err(e) = run(&options) {
+        eprintln!("artnet-scan: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run(options: &Options) -> io::Result<()> {
+    let mut socket = ArtNetSocket::bind_default()?.with_broadcast_address(options.broadcast);
+    let mut discovery = DiscoveryManager::new();
+    let start = Instant::now();
+    socket.broadcast(&Poll::default().into())?;
+    discovery.poll_sent(start);
+
+    let mut buffer = RecvBuffer::new();
+    while let Some(left) = options.timeout.checked_sub(start.elapsed()) {
+        if left == Duration::from_secs(0) {
+            break;
+        }
+        socket.socket().set_read_timeout(Some(left))?;
+        let length = match socket.socket().recv_from(&mut buffer) {
+            Ok((length, _)) => length,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                break
+            }
+            Err(e) => return Err(e),
+        };
+        if let Ok(command) = buffer.parse(length) {
+            discovery.handle(&command, Instant::now());
+        }
+    }
+
+    let mut replies: Vec<&PollReply> = discovery.nodes().map(|(_, node)| &*node.reply).collect();
+    replies.sort_by_key(|reply| (reply.address, reply.bind_index));
+    println!(
+        "{:<15}  {:<17}  {:<17}  {:<10}  {:<23}  Firmware",
+        "IP", "MAC", "Name", "Style", "Universes"
+    );
+    for reply in &replies {
+        let universes: Vec<String> = reply
+            .output_port_addresses()
+            .map(|port_address| u16::from(port_address).to_string())
+            .collect();
+        println!(
+            "{:<15}  {:<17}  {:<17}  {:<10}  {:<23}  {}",
+            reply.address.to_string(),
+            mac(reply.mac),
+            name(&reply.short_name),
+            style(reply),
+            universes.join(","),
+            u16::from_be_bytes(reply.version)
+        );
+    }
+    eprintln!("{} nodes replied", replies.len());
+    Ok(())
+}
+
+fn mac(mac: [u8; 6]) -> String {
+    let bytes: Vec<String> = mac.iter().map(|byte| format!("{:02x}", byte)).collect();
+    bytes.join(":")
+}
+
+/// The text of a null terminated name
+fn name(name: &[u8]) -> String {
+    let end = name
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(name.len());
+    String::from_utf8_lossy(&name[..end]).into_owned()
+}
+
+fn style(reply: &PollReply) -> String {
+    match reply.style_name() {
+        Some(style) => style.to_string(),
+        None => format!("0x{:02X}", reply.style),
+    }
+}
@@ -0,0 +1,128 @@
+//! A [tokio-util](https://docs.rs/tokio-util) codec, so commands can be send and received through a `UdpFramed`.
+//!
+//! ```rust,no_run
+//! # async fn run() -> std::io::Result<()> {
+//! use artnet_protocol::{codec::ArtNetCodec, ArtCommand, Poll};
+//! use futures::{SinkExt, StreamExt};
+//! use tokio::net::UdpSocket;
+//! use tokio_util::udp::UdpFramed;
+//!
+//! let socket = UdpSocket::bind(("0.0.0.0", 6454)).await?;
+//! socket.set_broadcast(true)?;
+//! let mut framed = UdpFramed::new(socket, ArtNetCodec::default());
+//!
+//! let broadcast = "255.255.255.255:6454".parse().unwrap();
+//! framed.send((ArtCommand::Poll(Poll::default()), broadcast)).await?;
+//!
+//! while let Some(received) = framed.next().await {
+//!     match received {
+//!         Ok((command, addr)) => println!("Received {:?} from {}", command, addr),
+//!         Err(e) => println!("Received an invalid message: {}", e),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use crate::{ArtCommand, ParseOptions};
+use bytes::BytesMut;
+use std::io;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Encodes and decodes `ArtCommand`s. Every frame is a single datagram.
+///
+/// Errors of this crate are returned as an `std::io::Error` of kind `InvalidData`, with the `artnet_protocol::Error` as its inner error.
+#[derive(Debug, Clone, Default)]
+pub struct ArtNetCodec {
+    options: ParseOptions,
+}
+
+impl ArtNetCodec {
+    /// Create a codec that decodes messages with the given options
+    pub fn with_options(options: ParseOptions) -> ArtNetCodec {
+        ArtNetCodec { options }
+    }
+}
+
+impl Decoder for ArtNetCodec {
+    type Item = ArtCommand;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<ArtCommand>> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+        // A datagram is always a complete message, so it is consumed even if it can not be parsed
+        let message = src.split().freeze();
+        ArtCommand::from_bytes_with(&message, &self.options)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl Encoder<ArtCommand> for ArtNetCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: ArtCommand, dst: &mut BytesMut) -> io::Result<()> {
+        let buffer = item
+            .write_to_buffer()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        dst.extend_from_slice(&buffer);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Output, Poll};
+
+    #[test]
+    fn encode_decode() {
+        let mut codec = ArtNetCodec::default();
+        let mut buffer = BytesMut::new();
+        codec
+            .encode(ArtCommand::Poll(Poll::default()), &mut buffer)
+            .unwrap();
+
+        match codec.decode(&mut buffer).unwrap() {
+            Some(ArtCommand::Poll(poll)) => assert_eq!(poll.diagnostics_priority, 0x80),
+            c => panic!("Expected a Poll, got {:?}", c),
+        }
+        assert!(buffer.is_empty());
+        assert!(codec.decode(&mut buffer).unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_data() {
+        let mut codec = ArtNetCodec::default();
+        let empty = ArtCommand::Output(Output::default());
+        let err = codec.encode(empty, &mut BytesMut::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut buffer = BytesMut::from(&b"Not Art-Net at all"[..]);
+        let err = codec.decode(&mut buffer).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(buffer.is_empty());
+    }
+
+    #[tokio::test]
+    async fn udp_framed() {
+        use futures::{SinkExt, StreamExt};
+        use tokio::net::UdpSocket;
+        use tokio_util::udp::UdpFramed;
+
+        let receiver = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = receiver.local_addr().unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+        let mut sender = UdpFramed::new(sender, ArtNetCodec::default());
+        let mut receiver = UdpFramed::new(receiver, ArtNetCodec::default());
+        sender
+            .send((ArtCommand::Poll(Poll::default()), addr))
+            .await
+            .unwrap();
+
+        let (command, _) = receiver.next().await.unwrap().unwrap();
+        assert_eq!(command.name(), "ArtPoll");
+    }
+}
@@ -0,0 +1,393 @@
+use crate::{
+    command::{poll_reply::set_null_terminated, ARTNET_PROTOCOL_VERSION},
+    BackgroundQueuePolicy, Error, PortAddress, Result,
+};
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "Sent by a controller to program the names, Port-Addresses and settings of a node. The node replies with an ArtPollReply."]
+    #[doc = ""]
+    #[doc = "Most fields contain a value to program, or a value that means no change. The defaults of this struct change nothing."]
+    pub struct Address {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "Bits 14-8 of the 15 bit Port-Address. The value is only programmed if bit 7 is set, e.g. send 0x87 to program 0x07. Send 0x00 to reset to the physical switch setting, and 0x7F for no change."]
+        pub net_switch: u8,
+        #[doc = "The bind index of the node that should be programmed, 1 for the root device"]
+        pub bind_index: u8,
+        #[doc = "The null terminated short name to program. An empty name means no change."]
+        pub short_name: [u8; 18],
+        #[doc = "The null terminated long name to program. An empty name means no change."]
+        pub long_name: [u8; 64],
+        #[doc = "Bits 3-0 of the Port-Address of each input port. Encoded the same way as `net_switch`."]
+        pub swin: [u8; 4],
+        #[doc = "Bits 3-0 of the Port-Address of each output port. Encoded the same way as `net_switch`."]
+        pub swout: [u8; 4],
+        #[doc = "Bits 7-4 of the 15 bit Port-Address. Encoded the same way as `net_switch`."]
+        pub sub_switch: u8,
+        #[doc = "Formerly SwVideo. The sACN priority of the node, from 0 to 200. 255 means no change."]
+        pub acn_priority: u8,
+        #[doc = "The command that the node should execute, e.g. to change the merge mode of a port or to locate the node. 0 means no command."]
+        pub command: u8,
+    }
+}
+
+impl Default for Address {
+    fn default() -> Address {
+        Address {
+            version: ARTNET_PROTOCOL_VERSION,
+            net_switch: Address::NO_CHANGE,
+            bind_index: 1,
+            short_name: [0; 18],
+            long_name: [0; 64],
+            swin: [Address::NO_CHANGE; 4],
+            swout: [Address::NO_CHANGE; 4],
+            sub_switch: Address::NO_CHANGE,
+            acn_priority: Address::ACN_PRIORITY_NO_CHANGE,
+            command: 0,
+        }
+    }
+}
+
+impl Address {
+    /// The value of `net_switch`, `sub_switch`, `swin` and `swout` that does not change the setting
+    pub const NO_CHANGE: u8 = 0x7F;
+
+    /// Bit 7 of `net_switch`, `sub_switch`, `swin` and `swout`, which is set when the value should be programmed
+    pub const PROGRAM: u8 = 0x80;
+
+    /// The highest sACN priority. Higher values of `acn_priority` do not change the priority.
+    pub const MAX_ACN_PRIORITY: u8 = 200;
+
+    /// The value of `acn_priority` that does not change the priority
+    pub const ACN_PRIORITY_NO_CHANGE: u8 = 255;
+
+    /// Returned when a port of 4 or higher is programmed
+    const INVALID_PORT: Error = Error::InvalidConfig("An ArtAddress only programs ports 0 to 3");
+
+    /// Program bits 14-8 of the Port-Address of the node
+    pub fn program_net(mut self, net: u8) -> Address {
+        self.net_switch = AddressSwitch::Program(net).into();
+        self
+    }
+
+    /// Program bits 7-4 of the Port-Address of the node
+    pub fn program_sub_net(mut self, sub_net: u8) -> Address {
+        self.sub_switch = AddressSwitch::Program(sub_net).into();
+        self
+    }
+
+    /// Program bits 3-0 of the Port-Address of the given input port, from 0 to 3. Returns `Error::InvalidConfig` if the port is 4 or higher.
+    pub fn program_swin(mut self, port: usize, universe: u8) -> Result<Address> {
+        *self.swin.get_mut(port).ok_or(Address::INVALID_PORT)? =
+            AddressSwitch::Program(universe).into();
+        Ok(self)
+    }
+
+    /// Program bits 3-0 of the Port-Address of the given output port, from 0 to 3. Returns `Error::InvalidConfig` if the port is 4 or higher.
+    pub fn program_swout(mut self, port: usize, universe: u8) -> Result<Address> {
+        *self.swout.get_mut(port).ok_or(Address::INVALID_PORT)? =
+            AddressSwitch::Program(universe).into();
+        Ok(self)
+    }
+
+    /// Program the net, sub-net and universe of the given output port, from 0 to 3. The net and sub-net are shared by all ports of the node. Returns `Error::InvalidConfig` if the port is 4 or higher.
+    pub fn program_output(self, port: usize, port_address: PortAddress) -> Result<Address> {
+        let sub_uni = port_address.sub_uni();
+        self.program_swout(port, sub_uni & 0x0F).map(|address| {
+            address
+                .program_net(port_address.net())
+                .program_sub_net(sub_uni >> 4)
+        })
+    }
+
+    /// Program the net, sub-net and universe of the given input port, from 0 to 3. The net and sub-net are shared by all ports of the node. Returns `Error::InvalidConfig` if the port is 4 or higher.
+    pub fn program_input(self, port: usize, port_address: PortAddress) -> Result<Address> {
+        let sub_uni = port_address.sub_uni();
+        self.program_swin(port, sub_uni & 0x0F).map(|address| {
+            address
+                .program_net(port_address.net())
+                .program_sub_net(sub_uni >> 4)
+        })
+    }
+
+    /// Reset the net, sub-net and the universes of all ports to the physical switch settings of the node
+    pub fn reset_port_addresses(mut self) -> Address {
+        self.net_switch = AddressSwitch::Reset.into();
+        self.sub_switch = AddressSwitch::Reset.into();
+        self.swin = [AddressSwitch::Reset.into(); 4];
+        self.swout = [AddressSwitch::Reset.into(); 4];
+        self
+    }
+
+    /// Program the short name of the node. It is truncated to 17 bytes at a character boundary, an empty name does not change the name.
+    pub fn program_short_name(mut self, name: &str) -> Address {
+        set_null_terminated(&mut self.short_name, name);
+        self
+    }
+
+    /// Program the long name of the node. It is truncated to 63 bytes at a character boundary, an empty name does not change the name.
+    pub fn program_long_name(mut self, name: &str) -> Address {
+        set_null_terminated(&mut self.long_name, name);
+        self
+    }
+
+    /// Program the sACN priority of the node, from 0 to 200. Higher values are clamped to 200.
+    pub fn program_acn_priority(mut self, priority: u8) -> Address {
+        self.acn_priority = priority.min(Address::MAX_ACN_PRIORITY);
+        self
+    }
+
+    /// The sACN priority to program, or `None` if it should not change
+    pub fn acn_priority(&self) -> Option<u8> {
+        Some(self.acn_priority).filter(|priority| *priority <= Address::MAX_ACN_PRIORITY)
+    }
+
+    /// Execute the given command on the node
+    pub fn with_command(mut self, command: AddressCommand) -> Address {
+        self.command = command.into();
+        self
+    }
+
+    /// Program the node with the given bind index, instead of the root device
+    pub fn for_bind_index(mut self, bind_index: u8) -> Address {
+        self.bind_index = bind_index;
+        self
+    }
+
+    /// The command that the node should execute
+    pub fn address_command(&self) -> AddressCommand {
+        self.command.into()
+    }
+
+    /// Wrap this message in an `ArtCommand::Address`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+}
+
+/// The meaning of the `net_switch`, `sub_switch`, `swin` and `swout` fields of an [Address](struct.Address.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressSwitch {
+    /// Keep the current setting, encoded as 0x7F
+    NoChange,
+
+    /// Reset the setting to the physical switch setting of the node, encoded as 0x00
+    Reset,
+
+    /// Program the given value, encoded as 0x80 with the value in the lower bits
+    Program(u8),
+}
+
+impl From<u8> for AddressSwitch {
+    fn from(value: u8) -> AddressSwitch {
+        if value & Address::PROGRAM != 0 {
+            AddressSwitch::Program(value & !Address::PROGRAM)
+        } else if value == 0 {
+            AddressSwitch::Reset
+        } else {
+            // Only 0x7F is defined, but any other value without bit 7 does not program anything either
+            AddressSwitch::NoChange
+        }
+    }
+}
+
+impl From<AddressSwitch> for u8 {
+    fn from(switch: AddressSwitch) -> u8 {
+        match switch {
+            AddressSwitch::NoChange => Address::NO_CHANGE,
+            AddressSwitch::Reset => 0,
+            AddressSwitch::Program(value) => Address::PROGRAM | (value & !Address::PROGRAM),
+        }
+    }
+}
+
+/// The `command` of an [Address](struct.Address.html). The commands that apply to a single port contain the port, from 0 to 3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressCommand {
+    /// No action
+    None,
+    /// Cancel merging, if the node is merging
+    CancelMerge,
+    /// Show the normal status on the indicators of the node
+    LedNormal,
+    /// Turn the indicators of the node off
+    LedMute,
+    /// Flash the indicators of the node, to locate it
+    LedLocate,
+    /// Reset the receive error flags of the node
+    ResetRxFlags,
+    /// Enable the analysis and debug mode
+    AnalysisOn,
+    /// Disable the analysis and debug mode
+    AnalysisOff,
+    /// Hold the last output when the data stops
+    FailHold,
+    /// Output zeros when the data stops
+    FailZero,
+    /// Output full when the data stops
+    FailFull,
+    /// Output the recorded fail-over scene when the data stops
+    FailScene,
+    /// Record the current output as the fail-over scene
+    FailRecord,
+    /// Merge the data of the port in Latest Takes Precedence mode
+    MergeLtp(u8),
+    /// Make the port an input, which transmits Art-Net
+    DirectionTx(u8),
+    /// Make the port an output, which receives Art-Net
+    DirectionRx(u8),
+    /// Merge the data of the port in Highest Takes Precedence mode
+    MergeHtp(u8),
+    /// Use the Art-Net protocol on the port
+    ArtNetSel(u8),
+    /// Use the sACN protocol on the port
+    AcnSel(u8),
+    /// Clear the output buffer of the port
+    ClearOp(u8),
+    /// Output the port continuously, only when the data changes
+    StyleDelta(u8),
+    /// Output the port continuously
+    StyleConst(u8),
+    /// Enable RDM on the port
+    RdmEnable(u8),
+    /// Disable RDM on the port
+    RdmDisable(u8),
+    /// Set the policy of the RDM background queue of the node
+    BackgroundQueuePolicy(BackgroundQueuePolicy),
+    /// A command that this library does not know
+    Other(u8),
+}
+
+impl From<u8> for AddressCommand {
+    fn from(command: u8) -> AddressCommand {
+        match command {
+            0x00 => AddressCommand::None,
+            0x01 => AddressCommand::CancelMerge,
+            0x02 => AddressCommand::LedNormal,
+            0x03 => AddressCommand::LedMute,
+            0x04 => AddressCommand::LedLocate,
+            0x05 => AddressCommand::ResetRxFlags,
+            0x06 => AddressCommand::AnalysisOn,
+            0x07 => AddressCommand::AnalysisOff,
+            0x08 => AddressCommand::FailHold,
+            0x09 => AddressCommand::FailZero,
+            0x0A => AddressCommand::FailFull,
+            0x0B => AddressCommand::FailScene,
+            0x0C => AddressCommand::FailRecord,
+            _ => {
+                let port = command & 0x03;
+                match command & 0xFC {
+                    0x10 => AddressCommand::MergeLtp(port),
+                    0x20 => AddressCommand::DirectionTx(port),
+                    0x30 => AddressCommand::DirectionRx(port),
+                    0x50 => AddressCommand::MergeHtp(port),
+                    0x60 => AddressCommand::ArtNetSel(port),
+                    0x70 => AddressCommand::AcnSel(port),
+                    0x90 => AddressCommand::ClearOp(port),
+                    0xA0 => AddressCommand::StyleDelta(port),
+                    0xB0 => AddressCommand::StyleConst(port),
+                    0xC0 => AddressCommand::RdmEnable(port),
+                    0xD0 => AddressCommand::RdmDisable(port),
+                    0xE0..=0xEF => AddressCommand::BackgroundQueuePolicy(command.into()),
+                    _ => AddressCommand::Other(command),
+                }
+            }
+        }
+    }
+}
+
+impl From<AddressCommand> for u8 {
+    fn from(command: AddressCommand) -> u8 {
+        let (base, port) = match command {
+            AddressCommand::None => (0x00, 0),
+            AddressCommand::CancelMerge => (0x01, 0),
+            AddressCommand::LedNormal => (0x02, 0),
+            AddressCommand::LedMute => (0x03, 0),
+            AddressCommand::LedLocate => (0x04, 0),
+            AddressCommand::ResetRxFlags => (0x05, 0),
+            AddressCommand::AnalysisOn => (0x06, 0),
+            AddressCommand::AnalysisOff => (0x07, 0),
+            AddressCommand::FailHold => (0x08, 0),
+            AddressCommand::FailZero => (0x09, 0),
+            AddressCommand::FailFull => (0x0A, 0),
+            AddressCommand::FailScene => (0x0B, 0),
+            AddressCommand::FailRecord => (0x0C, 0),
+            AddressCommand::MergeLtp(port) => (0x10, port),
+            AddressCommand::DirectionTx(port) => (0x20, port),
+            AddressCommand::DirectionRx(port) => (0x30, port),
+            AddressCommand::MergeHtp(port) => (0x50, port),
+            AddressCommand::ArtNetSel(port) => (0x60, port),
+            AddressCommand::AcnSel(port) => (0x70, port),
+            AddressCommand::ClearOp(port) => (0x90, port),
+            AddressCommand::StyleDelta(port) => (0xA0, port),
+            AddressCommand::StyleConst(port) => (0xB0, port),
+            AddressCommand::RdmEnable(port) => (0xC0, port),
+            AddressCommand::RdmDisable(port) => (0xD0, port),
+            AddressCommand::BackgroundQueuePolicy(policy) => (0xE0 | u8::from(policy), 0),
+            AddressCommand::Other(command) => (command, 0),
+        };
+        base | (port & 0x03)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switches() {
+        assert_eq!(u8::from(AddressSwitch::Program(0x05)), 0x85);
+        assert_eq!(u8::from(AddressSwitch::Reset), 0x00);
+        assert_eq!(u8::from(AddressSwitch::NoChange), 0x7F);
+        assert_eq!(AddressSwitch::from(0x85), AddressSwitch::Program(0x05));
+        assert_eq!(AddressSwitch::from(0x7F), AddressSwitch::NoChange);
+
+        let address = Address::default()
+            .program_output(2, PortAddress::from_parts(0x12, 0x34))
+            .unwrap()
+            .program_short_name("Stage left");
+        assert_eq!(address.net_switch, 0x92);
+        assert_eq!(address.sub_switch, 0x83);
+        assert_eq!(address.swout, [0x7F, 0x7F, 0x84, 0x7F]);
+        assert_eq!(address.swin, [0x7F; 4]);
+        assert_eq!(&address.short_name[..11], b"Stage left\0");
+        assert_eq!(address.long_name, [0; 64]);
+        // The 17th byte is the first half of "ä", which is left out
+        let name = Address::default().program_short_name("Bühne links 123ä");
+        assert_eq!(&name.short_name, b"B\xC3\xBChne links 123\0\0");
+        assert_eq!(address.acn_priority(), None);
+        assert_eq!(
+            address.clone().program_acn_priority(250).acn_priority(),
+            Some(Address::MAX_ACN_PRIORITY)
+        );
+
+        assert!(matches!(
+            address.clone().program_swin(4, 1),
+            Err(Error::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            address.program_input(4, 1.into()),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn commands() {
+        for command in 0..=255u8 {
+            assert_eq!(u8::from(AddressCommand::from(command)), command);
+        }
+        assert_eq!(AddressCommand::from(0x52), AddressCommand::MergeHtp(2));
+        assert_eq!(AddressCommand::from(0xD3), AddressCommand::RdmDisable(3));
+        assert_eq!(AddressCommand::from(0x0D), AddressCommand::Other(0x0D));
+        assert_eq!(
+            AddressCommand::from(0xE4),
+            AddressCommand::BackgroundQueuePolicy(BackgroundQueuePolicy::Disabled)
+        );
+        let address = Address::default().with_command(AddressCommand::LedLocate);
+        assert_eq!(address.command, 0x04);
+        assert_eq!(address.address_command(), AddressCommand::LedLocate);
+    }
+}
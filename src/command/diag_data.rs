@@ -0,0 +1,122 @@
+use crate::{command::ARTNET_PROTOCOL_VERSION, Error, ParseOptions, Payload, Result};
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "ArtDiagData is a diagnostic text message of a node. Nodes only send it when a controller enabled diagnostics with `ArtTalkToMe::ENABLE_DIAGNOSTICS`, and only for messages with at least the priority that was asked for in `Poll::diagnostics_priority`."]
+    #[doc = ""]
+    #[doc = "See [DiagnosticsCollector](struct.DiagnosticsCollector.html) to request and collect the messages of the nodes."]
+    pub struct DiagData {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub filler1: u8,
+        #[doc = "The priority of the message, e.g. `DiagData::PRIORITY_LOW`"]
+        pub priority: u8,
+        #[doc = "The logical port of the node that the message is about, or 0"]
+        pub logical_port: u8,
+        #[doc = "Transmit as zero"]
+        pub filler3: u8,
+        #[doc = "The length of the text, including the terminating zero. In big endian"]
+        pub length: [u8; 2],
+        #[doc = "The ASCII text of the message, at most 512 bytes including the terminating zero"]
+        pub data: Payload,
+    }
+}
+
+impl Default for DiagData {
+    fn default() -> DiagData {
+        DiagData {
+            version: ARTNET_PROTOCOL_VERSION,
+            filler1: 0,
+            priority: DiagData::PRIORITY_LOW,
+            logical_port: 0,
+            filler3: 0,
+            length: 1u16.to_be_bytes(),
+            data: Payload::from(&[0][..]),
+        }
+    }
+}
+
+impl DiagData {
+    /// Low priority message
+    pub const PRIORITY_LOW: u8 = 0x10;
+
+    /// Medium priority message
+    pub const PRIORITY_MED: u8 = 0x40;
+
+    /// High priority message
+    pub const PRIORITY_HIGH: u8 = 0x80;
+
+    /// Critical priority message
+    pub const PRIORITY_CRITICAL: u8 = 0xE0;
+
+    /// Volatile message, which is displayed on a single line by the controller
+    pub const PRIORITY_VOLATILE: u8 = 0xF0;
+
+    /// The maximum length of the text, including the terminating zero
+    pub const MAX_LENGTH: usize = 512;
+
+    /// Create a message with the given priority and text. Text that does not fit in the message is cut off.
+    pub fn new(priority: u8, text: &str) -> DiagData {
+        let text = &text.as_bytes()[..text.len().min(DiagData::MAX_LENGTH - 1)];
+        let mut data = Payload::from(text);
+        data.push(0);
+        DiagData {
+            priority,
+            length: (data.len() as u16).to_be_bytes(),
+            data,
+            ..DiagData::default()
+        }
+    }
+
+    /// The text of the message, up to the terminating zero. Bytes that are not valid UTF-8 end the text.
+    pub fn text(&self) -> &str {
+        super::zero_terminated_text(&self.data, u16::from_be_bytes(self.length))
+    }
+
+    /// Wrap this message in an `ArtCommand::DiagData`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+
+    pub(crate) fn from_with(data: &[u8], options: &ParseOptions) -> Result<DiagData> {
+        let mut diag = DiagData::from(data)?;
+        let length = usize::from(u16::from_be_bytes(diag.length));
+        if options.is_strict() {
+            if length > DiagData::MAX_LENGTH || length > diag.data.len() {
+                return Err(Error::SpecViolation(
+                    "DiagData::length does not match the length of the text",
+                ));
+            }
+        } else if length < diag.data.len() {
+            // Ignore any trailing bytes after the text
+            diag.data.truncate(length);
+        }
+        Ok(diag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArtCommand;
+
+    #[test]
+    fn text() {
+        let bytes = DiagData::new(DiagData::PRIORITY_HIGH, "Port 1 shorted")
+            .into_command()
+            .write_to_buffer()
+            .unwrap();
+        assert_eq!(&bytes[10..19], &[0, 14, 0, 0x80, 0, 0, 0, 15, b'P']);
+        match ArtCommand::from_buffer_with(&bytes, &ParseOptions::strict()).unwrap() {
+            ArtCommand::DiagData(diag) => assert_eq!(diag.text(), "Port 1 shorted"),
+            command => panic!("Unexpected command {:?}", command),
+        }
+
+        let long = DiagData::new(DiagData::PRIORITY_LOW, &"x".repeat(600));
+        assert_eq!(long.data.len(), DiagData::MAX_LENGTH);
+        assert_eq!(long.text().len(), 511);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::command::{poll_reply::null_terminated, ARTNET_PROTOCOL_VERSION};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "A block of a user file that a node sends to a controller that downloads it. The controller acknowledges every block with an ArtFileFnReply."]
+    #[doc = ""]
+    #[doc = "A controller starts a download by sending this message with `FileFnMaster::FILE_REQUEST` and the name of the file, without data. Non-standard: the Art-Net specification only names this packet. This layout, including `FileFnMaster::FILE_REQUEST` and the block id, is specific to this crate and does not interoperate with other implementations. It is only available with the `nonstandard-file-transfer` feature. See [FileDownloader](struct.FileDownloader.html)."]
+    pub struct FileFnMaster {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub filler1: u8,
+        #[doc = "Transmit as zero"]
+        pub filler2: u8,
+        #[doc = "If this is the first, a continuation or the last block, e.g. `FileFnMaster::FILE_FIRST`"]
+        pub block_type: u8,
+        #[doc = "The index of this block, starting at 0 for the first block"]
+        pub block_id: u8,
+        #[doc = "The length of the whole file in bytes, in big endian"]
+        pub file_length: [u8; 4],
+        #[doc = "The null terminated name of the file"]
+        pub name: [u8; 14],
+        #[doc = "The 16 bit sum of the bytes in `data`, in big endian"]
+        pub checksum: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub spare: [u8; 4],
+        #[doc = "The data of this block, at most 1024 bytes"]
+        pub data: Vec<u8>,
+    }
+}
+
+impl Default for FileFnMaster {
+    fn default() -> FileFnMaster {
+        FileFnMaster {
+            version: ARTNET_PROTOCOL_VERSION,
+            filler1: 0,
+            filler2: 0,
+            block_type: FileFnMaster::FILE_FIRST,
+            block_id: 0,
+            file_length: [0; 4],
+            name: [0; 14],
+            checksum: [0; 2],
+            spare: [0; 4],
+            data: Vec::new(),
+        }
+    }
+}
+
+impl FileFnMaster {
+    /// The first block of a file
+    pub const FILE_FIRST: u8 = 0x00;
+
+    /// A block of a file, after the first one
+    pub const FILE_CONT: u8 = 0x01;
+
+    /// The last block of a file
+    pub const FILE_LAST: u8 = 0x02;
+
+    /// Sent by a controller to request a file
+    pub const FILE_REQUEST: u8 = 0x03;
+
+    /// The maximum size of the data of a single block
+    pub const BLOCK_SIZE: usize = 1024;
+
+    /// A block with the given data. The checksum is calculated from the data.
+    pub fn new(
+        name: &str,
+        block_type: u8,
+        block_id: u8,
+        file_length: u32,
+        data: Vec<u8>,
+    ) -> FileFnMaster {
+        FileFnMaster {
+            block_type,
+            block_id,
+            file_length: file_length.to_be_bytes(),
+            name: super::file_name(name),
+            checksum: super::checksum(&data).to_be_bytes(),
+            data,
+            ..FileFnMaster::default()
+        }
+    }
+
+    /// The length of the whole file in bytes
+    pub fn file_length(&self) -> u32 {
+        u32::from_be_bytes(self.file_length)
+    }
+
+    /// The name of the file, without the null terminator
+    pub fn name(&self) -> Cow<'_, str> {
+        null_terminated(&self.name)
+    }
+
+    /// If `checksum` matches the data of this block
+    pub fn is_checksum_valid(&self) -> bool {
+        u16::from_be_bytes(self.checksum) == super::checksum(&self.data)
+    }
+
+    /// The request for a file, sent by a controller
+    pub fn request(name: &str) -> FileFnMaster {
+        FileFnMaster::new(name, FileFnMaster::FILE_REQUEST, 0, 0, Vec::new())
+    }
+
+    /// Wrap this message in an `ArtCommand::FileFnMaster`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+}
@@ -0,0 +1,53 @@
+use crate::command::ARTNET_PROTOCOL_VERSION;
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "Sent by a controller to acknowledge a block of a file download, or to report that the download failed."]
+    #[doc = ""]
+    #[doc = "Non-standard: the Art-Net specification only names this packet. This layout, which follows ArtFirmwareReply with the id of the acknowledged block, is specific to this crate and does not interoperate with other implementations. It is only available with the `nonstandard-file-transfer` feature."]
+    pub struct FileFnReply {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub filler1: u8,
+        #[doc = "Transmit as zero"]
+        pub filler2: u8,
+        #[doc = "The result, e.g. `FileFnReply::FILE_BLOCK_GOOD`"]
+        pub reply_type: u8,
+        #[doc = "The `block_id` of the block that was received last. The node continues with the block after it."]
+        pub block_id: u8,
+        #[doc = "Transmit as zero"]
+        pub spare: [u8; 20],
+    }
+}
+
+impl Default for FileFnReply {
+    fn default() -> FileFnReply {
+        FileFnReply {
+            version: ARTNET_PROTOCOL_VERSION,
+            filler1: 0,
+            filler2: 0,
+            reply_type: FileFnReply::FILE_BLOCK_GOOD,
+            block_id: 0,
+            spare: [0; 20],
+        }
+    }
+}
+
+impl FileFnReply {
+    /// The block was received
+    pub const FILE_BLOCK_GOOD: u8 = 0x00;
+
+    /// All blocks were received
+    pub const FILE_ALL_GOOD: u8 = 0x01;
+
+    /// The download failed
+    pub const FILE_FAIL: u8 = 0xFF;
+
+    /// Wrap this message in an `ArtCommand::FileFnReply`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+}
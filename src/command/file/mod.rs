@@ -0,0 +1,21 @@
+mod fn_master;
+mod fn_reply;
+mod tn_master;
+
+pub use self::fn_master::FileFnMaster;
+pub use self::fn_reply::FileFnReply;
+pub use self::tn_master::FileTnMaster;
+
+/// The checksum of the data of a file block, the 16 bit sum of its bytes
+pub(crate) fn checksum(data: &[u8]) -> u16 {
+    data.iter()
+        .fold(0u16, |sum, byte| sum.wrapping_add(u16::from(*byte)))
+}
+
+/// A file name as sent in ArtFileTnMaster and ArtFileFnMaster, null terminated and truncated to 13 bytes
+pub(crate) fn file_name(name: &str) -> [u8; 14] {
+    let mut result = [0; 14];
+    let len = name.len().min(13);
+    result[..len].copy_from_slice(&name.as_bytes()[..len]);
+    result
+}
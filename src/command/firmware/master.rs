@@ -0,0 +1,252 @@
+use crate::command::ARTNET_PROTOCOL_VERSION;
+use crate::{Error, Result, SharedData};
+use core::convert::TryFrom;
+
+/// The kind of image that is uploaded with ArtFirmwareMaster
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FirmwareKind {
+    /// The firmware of the node
+    Firmware,
+
+    /// A User Bios Extension Area
+    Ubea,
+}
+
+/// The `block_type` of an ArtFirmwareMaster
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FirmwareBlockType {
+    /// The first block of a firmware upload
+    FirmFirst,
+
+    /// A block of a firmware upload, after the first one
+    FirmCont,
+
+    /// The last block of a firmware upload
+    FirmLast,
+
+    /// The first block of a UBEA upload
+    UbeaFirst,
+
+    /// A block of a UBEA upload, after the first one
+    UbeaCont,
+
+    /// The last block of a UBEA upload
+    UbeaLast,
+}
+
+impl FirmwareBlockType {
+    /// The type of a block of an upload of the given kind. An upload with a single block only sends a last block.
+    pub fn new(kind: FirmwareKind, first: bool, last: bool) -> FirmwareBlockType {
+        match (kind, first, last) {
+            (FirmwareKind::Firmware, _, true) => FirmwareBlockType::FirmLast,
+            (FirmwareKind::Firmware, true, false) => FirmwareBlockType::FirmFirst,
+            (FirmwareKind::Firmware, false, false) => FirmwareBlockType::FirmCont,
+            (FirmwareKind::Ubea, _, true) => FirmwareBlockType::UbeaLast,
+            (FirmwareKind::Ubea, true, false) => FirmwareBlockType::UbeaFirst,
+            (FirmwareKind::Ubea, false, false) => FirmwareBlockType::UbeaCont,
+        }
+    }
+
+    /// If this is a block of a firmware or a UBEA upload
+    pub fn kind(self) -> FirmwareKind {
+        match self {
+            FirmwareBlockType::FirmFirst
+            | FirmwareBlockType::FirmCont
+            | FirmwareBlockType::FirmLast => FirmwareKind::Firmware,
+            FirmwareBlockType::UbeaFirst
+            | FirmwareBlockType::UbeaCont
+            | FirmwareBlockType::UbeaLast => FirmwareKind::Ubea,
+        }
+    }
+
+    /// If this is the first block of an upload
+    pub fn is_first(self) -> bool {
+        matches!(
+            self,
+            FirmwareBlockType::FirmFirst | FirmwareBlockType::UbeaFirst
+        )
+    }
+
+    /// If this is the last block of an upload
+    pub fn is_last(self) -> bool {
+        matches!(
+            self,
+            FirmwareBlockType::FirmLast | FirmwareBlockType::UbeaLast
+        )
+    }
+}
+
+impl TryFrom<u8> for FirmwareBlockType {
+    type Error = Error;
+
+    /// The variant with the given value of `FirmwareMaster::block_type`
+    fn try_from(block_type: u8) -> Result<FirmwareBlockType> {
+        match block_type {
+            FirmwareMaster::FIRM_FIRST => Ok(FirmwareBlockType::FirmFirst),
+            FirmwareMaster::FIRM_CONT => Ok(FirmwareBlockType::FirmCont),
+            FirmwareMaster::FIRM_LAST => Ok(FirmwareBlockType::FirmLast),
+            FirmwareMaster::UBEA_FIRST => Ok(FirmwareBlockType::UbeaFirst),
+            FirmwareMaster::UBEA_CONT => Ok(FirmwareBlockType::UbeaCont),
+            FirmwareMaster::UBEA_LAST => Ok(FirmwareBlockType::UbeaLast),
+            _ => Err(Error::SpecViolation(
+                "FirmwareMaster::block_type must be from 0 to 5",
+            )),
+        }
+    }
+}
+
+impl From<FirmwareBlockType> for u8 {
+    /// The value of `FirmwareMaster::block_type` for this variant
+    fn from(block_type: FirmwareBlockType) -> u8 {
+        match block_type {
+            FirmwareBlockType::FirmFirst => FirmwareMaster::FIRM_FIRST,
+            FirmwareBlockType::FirmCont => FirmwareMaster::FIRM_CONT,
+            FirmwareBlockType::FirmLast => FirmwareMaster::FIRM_LAST,
+            FirmwareBlockType::UbeaFirst => FirmwareMaster::UBEA_FIRST,
+            FirmwareBlockType::UbeaCont => FirmwareMaster::UBEA_CONT,
+            FirmwareBlockType::UbeaLast => FirmwareMaster::UBEA_LAST,
+        }
+    }
+}
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "A block of a firmware or UBEA upload, sent by a controller to a single node. The node acknowledges every block with an ArtFirmwareReply."]
+    #[doc = ""]
+    #[doc = "See [FirmwareUploader](struct.FirmwareUploader.html) to split an image into blocks."]
+    pub struct FirmwareMaster {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub filler1: u8,
+        #[doc = "Transmit as zero"]
+        pub filler2: u8,
+        #[doc = "If this is the first, a continuation or the last block, see [FirmwareBlockType](enum.FirmwareBlockType.html)"]
+        pub block_type: u8,
+        #[doc = "The index of this block, starting at 0 for the first block"]
+        pub block_id: u8,
+        #[doc = "The length of the whole image in 16 bit words, in big endian"]
+        pub firmware_length: [u8; 4],
+        #[doc = "Transmit as zero"]
+        pub spare: [u8; 20],
+        #[doc = "The data of this block, 1024 bytes"]
+        pub data: SharedData,
+    }
+}
+
+impl Default for FirmwareMaster {
+    fn default() -> FirmwareMaster {
+        FirmwareMaster {
+            version: ARTNET_PROTOCOL_VERSION,
+            filler1: 0,
+            filler2: 0,
+            block_type: FirmwareMaster::FIRM_FIRST,
+            block_id: 0,
+            firmware_length: [0; 4],
+            spare: [0; 20],
+            data: SharedData::new(),
+        }
+    }
+}
+
+impl FirmwareMaster {
+    /// The first block of a firmware upload
+    pub const FIRM_FIRST: u8 = 0x00;
+
+    /// A block of a firmware upload, after the first one
+    pub const FIRM_CONT: u8 = 0x01;
+
+    /// The last block of a firmware upload
+    pub const FIRM_LAST: u8 = 0x02;
+
+    /// The first block of a UBEA upload
+    pub const UBEA_FIRST: u8 = 0x03;
+
+    /// A block of a UBEA upload, after the first one
+    pub const UBEA_CONT: u8 = 0x04;
+
+    /// The last block of a UBEA upload
+    pub const UBEA_LAST: u8 = 0x05;
+
+    /// The size of the data of a single block
+    pub const BLOCK_SIZE: usize = 1024;
+
+    /// The length of the fields before the data, excluding the Art-Net header and opcode
+    #[cfg(feature = "bytes")]
+    pub(crate) const HEADER_LENGTH: usize = 30;
+
+    /// Create a block of an upload. `firmware_length` is the length of the whole image in 16 bit words.
+    pub fn new(
+        block_type: FirmwareBlockType,
+        block_id: u8,
+        firmware_length: u32,
+        data: impl Into<SharedData>,
+    ) -> FirmwareMaster {
+        FirmwareMaster {
+            block_type: block_type.into(),
+            block_id,
+            firmware_length: firmware_length.to_be_bytes(),
+            data: data.into(),
+            ..FirmwareMaster::default()
+        }
+    }
+
+    /// The type of this block, or `None` if `block_type` is unknown
+    pub fn firmware_block_type(&self) -> Option<FirmwareBlockType> {
+        FirmwareBlockType::try_from(self.block_type).ok()
+    }
+
+    /// If this is a block of a firmware or a UBEA upload, or `None` if `block_type` is unknown
+    pub fn kind(&self) -> Option<FirmwareKind> {
+        self.firmware_block_type().map(FirmwareBlockType::kind)
+    }
+
+    /// If this is the first block of an upload
+    pub fn is_first(&self) -> bool {
+        self.firmware_block_type()
+            .is_some_and(FirmwareBlockType::is_first)
+    }
+
+    /// If this is the last block of an upload
+    pub fn is_last(&self) -> bool {
+        self.firmware_block_type()
+            .is_some_and(FirmwareBlockType::is_last)
+    }
+
+    /// The length of the whole image in 16 bit words
+    pub fn firmware_length(&self) -> u32 {
+        u32::from_be_bytes(self.firmware_length)
+    }
+
+    /// Wrap this message in an `ArtCommand::FirmwareMaster`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_types() {
+        for block_type in 0..=u8::MAX {
+            match FirmwareBlockType::try_from(block_type) {
+                Ok(typed) => assert_eq!(u8::from(typed), block_type),
+                Err(_) => assert!(block_type > FirmwareMaster::UBEA_LAST),
+            }
+        }
+        let block_type = FirmwareBlockType::new(FirmwareKind::Ubea, false, false);
+        assert_eq!(block_type, FirmwareBlockType::UbeaCont);
+        let master = FirmwareMaster::new(block_type, 1, 1024, vec![0; 1024]);
+        assert_eq!(master.block_type, FirmwareMaster::UBEA_CONT);
+        assert_eq!(master.kind(), Some(FirmwareKind::Ubea));
+        assert!(!master.is_first() && !master.is_last());
+        assert_eq!(
+            FirmwareBlockType::new(FirmwareKind::Firmware, true, true),
+            FirmwareBlockType::FirmLast
+        );
+    }
+}
@@ -0,0 +1,5 @@
+mod master;
+mod reply;
+
+pub use self::master::{FirmwareBlockType, FirmwareKind, FirmwareMaster};
+pub use self::reply::{FirmwareReply, FirmwareReplyStatus};
@@ -0,0 +1,118 @@
+use crate::command::ARTNET_PROTOCOL_VERSION;
+
+/// The `reply_type` of an ArtFirmwareReply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FirmwareReplyStatus {
+    /// The last block was received
+    FirmBlockGood,
+
+    /// All blocks were received, and the image was accepted
+    FirmAllGood,
+
+    /// The upload failed, e.g. because a block is missing or the image is invalid
+    FirmFail,
+
+    /// A reply type that is not defined by the Art-Net specification
+    Other(u8),
+}
+
+impl From<u8> for FirmwareReplyStatus {
+    fn from(reply_type: u8) -> FirmwareReplyStatus {
+        match reply_type {
+            FirmwareReply::FIRM_BLOCK_GOOD => FirmwareReplyStatus::FirmBlockGood,
+            FirmwareReply::FIRM_ALL_GOOD => FirmwareReplyStatus::FirmAllGood,
+            FirmwareReply::FIRM_FAIL => FirmwareReplyStatus::FirmFail,
+            reply_type => FirmwareReplyStatus::Other(reply_type),
+        }
+    }
+}
+
+impl From<FirmwareReplyStatus> for u8 {
+    fn from(status: FirmwareReplyStatus) -> u8 {
+        match status {
+            FirmwareReplyStatus::FirmBlockGood => FirmwareReply::FIRM_BLOCK_GOOD,
+            FirmwareReplyStatus::FirmAllGood => FirmwareReply::FIRM_ALL_GOOD,
+            FirmwareReplyStatus::FirmFail => FirmwareReply::FIRM_FAIL,
+            FirmwareReplyStatus::Other(reply_type) => reply_type,
+        }
+    }
+}
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "Sent by a node to acknowledge a block of a firmware upload, or to report that the upload failed."]
+    pub struct FirmwareReply {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub filler1: u8,
+        #[doc = "Transmit as zero"]
+        pub filler2: u8,
+        #[doc = "The result, see [FirmwareReplyStatus](enum.FirmwareReplyStatus.html)"]
+        pub reply_type: u8,
+        #[doc = "Transmit as zero"]
+        pub spare: [u8; 21],
+    }
+}
+
+impl Default for FirmwareReply {
+    fn default() -> FirmwareReply {
+        FirmwareReply {
+            version: ARTNET_PROTOCOL_VERSION,
+            filler1: 0,
+            filler2: 0,
+            reply_type: FirmwareReply::FIRM_BLOCK_GOOD,
+            spare: [0; 21],
+        }
+    }
+}
+
+impl FirmwareReply {
+    /// The last block was received
+    pub const FIRM_BLOCK_GOOD: u8 = 0x00;
+
+    /// All blocks were received, and the image was accepted
+    pub const FIRM_ALL_GOOD: u8 = 0x01;
+
+    /// The upload failed, e.g. because a block is missing or the image is invalid
+    pub const FIRM_FAIL: u8 = 0xFF;
+
+    /// Create a reply with the given result
+    pub fn new(status: FirmwareReplyStatus) -> FirmwareReply {
+        FirmwareReply {
+            reply_type: status.into(),
+            ..FirmwareReply::default()
+        }
+    }
+
+    /// The result of the reply
+    pub fn status(&self) -> FirmwareReplyStatus {
+        self.reply_type.into()
+    }
+
+    /// Wrap this message in an `ArtCommand::FirmwareReply`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status() {
+        for reply_type in 0..=u8::MAX {
+            assert_eq!(u8::from(FirmwareReplyStatus::from(reply_type)), reply_type);
+        }
+        let reply = FirmwareReply::new(FirmwareReplyStatus::FirmFail);
+        assert_eq!(reply.reply_type, FirmwareReply::FIRM_FAIL);
+        assert_eq!(reply.status(), FirmwareReplyStatus::FirmFail);
+        assert_eq!(
+            FirmwareReplyStatus::from(0x02),
+            FirmwareReplyStatus::Other(0x02)
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use super::{ArtCommand, HEADER_LENGTH};
+use crate::Result;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::ops::Range;
+
+/// The amount of bytes that are shown on a single line
+const BYTES_PER_LINE: usize = 16;
+
+impl ArtCommand {
+    /// An annotated hex view of the message that `write_to_buffer` produces, with every field on its own line.
+    ///
+    /// This is useful to compare the messages of this library with those of other devices, e.g. in a wireshark capture:
+    ///
+    /// ```text
+    /// ArtSync (0x5200)
+    /// 0000  41 72 74 2d 4e 65 74 00                          Art-Net header
+    /// 0008  00 52                                            opcode
+    /// 000a  00 0e                                            ArtSync::version
+    /// 000c  00                                               ArtSync::aux1
+    /// 000d  00                                               ArtSync::aux2
+    /// ```
+    ///
+    /// The result can be read back with [parse_hexdump](#method.parse_hexdump).
+    pub fn hexdump(&self) -> Result<String> {
+        let mut buffer = Vec::new();
+        self.write_to(&mut buffer)?;
+
+        let mut result = String::new();
+        // Writing to a String can not fail
+        let _ = writeln!(result, "{}", self);
+        for (name, range) in self.message_layout() {
+            write_field(&mut result, &buffer, name, range);
+        }
+        Ok(result)
+    }
+
+    /// The name and byte range of every field in the message that `write_to_buffer` produces, including the Art-Net header and opcode, in order.
+    ///
+    /// This is the structure that [hexdump](#method.hexdump) shows, for tools that highlight the fields in their own view of the bytes. The range of a field with a variable length, e.g. the data of an ArtDmx, depends on this command.
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// let layout = ArtCommand::sync().message_layout();
+    /// assert_eq!(layout[0], ("Art-Net header", 0..8));
+    /// assert_eq!(layout[2], ("ArtSync::version", 10..12));
+    /// ```
+    pub fn message_layout(&self) -> Vec<(&'static str, Range<usize>)> {
+        let mut layout = vec![("Art-Net header", 0..8), ("opcode", 8..HEADER_LENGTH)];
+        layout.extend(
+            self.field_layout().into_iter().map(|(name, range)| {
+                (name, range.start + HEADER_LENGTH..range.end + HEADER_LENGTH)
+            }),
+        );
+        layout
+    }
+
+    /// Parse a hex dump back into a command.
+    ///
+    /// This accepts the output of [hexdump](#method.hexdump), but also most other hex dumps, like the ones that can be copied from wireshark. On every line, an optional offset is skipped, and every following two-digit hex value is read until a token is found that is not a hex value. Lines without any hex values are ignored.
+    pub fn parse_hexdump(dump: &str) -> Result<ArtCommand> {
+        let mut buffer = Vec::new();
+        for line in dump.lines() {
+            let mut tokens = line.split_whitespace().peekable();
+            if let Some(offset) = tokens.peek() {
+                let offset = offset.trim_end_matches(':');
+                if offset.len() > 2 && offset.chars().all(|c| c.is_ascii_hexdigit()) {
+                    tokens.next();
+                }
+            }
+            for token in tokens.take(BYTES_PER_LINE) {
+                match parse_byte(token) {
+                    Some(byte) => buffer.push(byte),
+                    None => break,
+                }
+            }
+        }
+        ArtCommand::from_buffer(&buffer)
+    }
+
+    /// The name and byte range of every field in the body of this command, after the header and opcode.
+    pub(crate) fn field_layout(&self) -> Vec<(&'static str, Range<usize>)> {
+        match self {
+            ArtCommand::Poll(poll) => poll.field_layout(),
+            ArtCommand::PollReply(reply) => reply.field_layout(),
+            ArtCommand::Output(output) => output.field_layout(),
+            ArtCommand::Sync(sync) => sync.field_layout(),
+            #[cfg(feature = "dmx")]
+            ArtCommand::Address(address) => address.field_layout(),
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodRequest(request) => request.field_layout(),
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodData(tod) => tod.field_layout(),
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodControl(control) => control.field_layout(),
+            #[cfg(feature = "rdm")]
+            ArtCommand::Rdm(rdm) => rdm.field_layout(),
+            #[cfg(feature = "firmware")]
+            ArtCommand::FirmwareMaster(master) => master.field_layout(),
+            #[cfg(feature = "firmware")]
+            ArtCommand::FirmwareReply(reply) => reply.field_layout(),
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileTnMaster(master) => master.field_layout(),
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileFnMaster(master) => master.field_layout(),
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileFnReply(reply) => reply.field_layout(),
+            #[cfg(feature = "dmx")]
+            ArtCommand::Nzs(nzs) => nzs.field_layout(),
+            #[cfg(feature = "media")]
+            ArtCommand::OpTimeCode(time_code) => time_code.field_layout(),
+            #[cfg(feature = "media")]
+            ArtCommand::OpTimeSync(time_sync) => time_sync.field_layout(),
+            #[cfg(feature = "dmx")]
+            ArtCommand::Command(command) => command.field_layout(),
+            #[cfg(feature = "dmx")]
+            ArtCommand::DiagData(diag_data) => diag_data.field_layout(),
+            #[cfg(feature = "firmware")]
+            ArtCommand::OpIpProg(ip_prog) => ip_prog.field_layout(),
+            #[cfg(feature = "firmware")]
+            ArtCommand::OpIpProgReply(reply) => reply.field_layout(),
+            #[cfg(feature = "media")]
+            ArtCommand::OpTrigger(trigger) => trigger.field_layout(),
+            #[cfg(feature = "dmx")]
+            ArtCommand::Input(input) => input.field_layout(),
+            // Commands without a body. There are none when the optional opcode families are disabled
+            #[allow(unreachable_patterns)]
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn parse_byte(token: &str) -> Option<u8> {
+    if token.len() == 2 {
+        u8::from_str_radix(token, 16).ok()
+    } else {
+        None
+    }
+}
+
+fn write_field(result: &mut String, buffer: &[u8], name: &str, range: Range<usize>) {
+    let mut first = true;
+    for start in range.clone().step_by(BYTES_PER_LINE) {
+        let end = (start + BYTES_PER_LINE).min(range.end);
+        let _ = write!(result, "{:04x} ", start);
+        for byte in &buffer[start..end] {
+            let _ = write!(result, " {:02x}", byte);
+        }
+        // Align the field names
+        for _ in end - start..BYTES_PER_LINE {
+            result.push_str("   ");
+        }
+        if first {
+            let _ = write!(result, "  {}", name);
+            first = false;
+        }
+        result.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArtCommand, Output, Poll};
+
+    #[test]
+    fn hexdump() {
+        let dump = ArtCommand::Poll(Poll::default()).hexdump().unwrap();
+        assert_eq!(
+            dump,
+            "ArtPoll (0x2000), talk to me ArtTalkToMe(0x0), diagnostics priority 0x80
+0000  41 72 74 2d 4e 65 74 00                          Art-Net header
+0008  00 20                                            opcode
+000a  00 0e                                            Poll::version
+000c  00                                               Poll::talk_to_me
+000d  80                                               Poll::diagnostics_priority
+000e  00 00                                            Poll::target_port_top
+0010  00 00                                            Poll::target_port_bottom
+0012  00 00                                            Poll::esta_code
+0014  00 00                                            Poll::oem
+"
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let command = ArtCommand::Output(Output::new(3.into(), vec![0xAB; 40]));
+        let dump = command.hexdump().unwrap();
+        // The data is split over multiple lines
+        assert_eq!(dump.lines().count(), 1 + 2 + 5 + 3);
+
+        let parsed = ArtCommand::parse_hexdump(&dump).unwrap();
+        let output = parsed.as_output().unwrap();
+        assert_eq!(u16::from(output.port_address), 3);
+        assert_eq!(output.data.as_ref(), &vec![0xAB; 40]);
+    }
+
+    #[test]
+    fn message_layout() {
+        let command = ArtCommand::Output(Output::new(3.into(), vec![0xAB; 40]));
+        let layout = command.message_layout();
+        let (name, data) = layout.last().unwrap();
+        assert_eq!(*name, "Output::data");
+        assert_eq!(data.len(), 40);
+        assert_eq!(data.end, command.serialized_len());
+        // The fields follow each other without gaps
+        for fields in layout.windows(2) {
+            assert_eq!(fields[0].1.end, fields[1].1.start);
+        }
+    }
+
+    #[test]
+    fn wireshark() {
+        let dump = "0000   41 72 74 2d 4e 65 74 00 00 52 00 0e 00 00   Art-Net..R....";
+        let command = ArtCommand::parse_hexdump(dump).unwrap();
+        assert!(matches!(command, ArtCommand::Sync(_)));
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{command::ARTNET_PROTOCOL_VERSION, InputDisable};
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "Sent by a controller to enable or disable the DMX input ports of a node. The node replies with an ArtPollReply."]
+    pub struct Input {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub filler1: u8,
+        #[doc = "The bind index of the node that should be programmed, 1 for the root device"]
+        pub bind_index: u8,
+        #[doc = "The number of input ports, in big endian. At most 4"]
+        pub num_ports: [u8; 2],
+        #[doc = "If each of the input ports is disabled, see `Input::disable_ports`"]
+        pub input: [InputDisable; 4],
+    }
+}
+
+impl Default for Input {
+    fn default() -> Input {
+        Input {
+            version: ARTNET_PROTOCOL_VERSION,
+            filler1: 0,
+            bind_index: 1,
+            num_ports: [0, 4],
+            input: [InputDisable::NONE; 4],
+        }
+    }
+}
+
+impl Input {
+    /// An ArtInput that disables the given input ports, from 0 to 3, and enables the others.
+    ///
+    /// Panics if a port is 4 or higher.
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// let input = Input::disable_ports(&[0, 2]);
+    /// assert!(input.is_disabled(0));
+    /// assert!(!input.is_disabled(1));
+    /// assert_eq!(input.disabled_ports().collect::<Vec<_>>(), vec![0, 2]);
+    /// ```
+    pub fn disable_ports(ports: &[usize]) -> Input {
+        let mut input = Input::default();
+        for port in ports {
+            input.set_disabled(*port, true);
+        }
+        input
+    }
+
+    /// If the given input port, from 0 to 3, is disabled. Panics if the port is 4 or higher.
+    pub fn is_disabled(&self, port: usize) -> bool {
+        self.input[port].contains(InputDisable::DISABLED)
+    }
+
+    /// Disable or enable the given input port, from 0 to 3. Panics if the port is 4 or higher.
+    pub fn set_disabled(&mut self, port: usize, disabled: bool) {
+        self.input[port].set(InputDisable::DISABLED, disabled);
+    }
+
+    /// The input ports that are disabled
+    pub fn disabled_ports(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.input.len()).filter(move |port| self.is_disabled(*port))
+    }
+
+    /// The number of input ports
+    pub fn num_ports(&self) -> u16 {
+        u16::from_be_bytes(self.num_ports)
+    }
+
+    /// Wrap this message in an `ArtCommand::Input`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArtCommand;
+    use alloc::vec;
+
+    #[test]
+    fn round_trip() {
+        let mut input = Input::disable_ports(&[1, 3]);
+        input.set_disabled(3, false);
+        let bytes = input.into_command().write_to_buffer().unwrap();
+        assert_eq!(
+            bytes,
+            vec![65, 114, 116, 45, 78, 101, 116, 0, 0, 0x70, 0, 14, 0, 1, 0, 4, 0, 1, 0, 0]
+        );
+        match ArtCommand::from_buffer(&bytes).unwrap() {
+            ArtCommand::Input(input) => {
+                assert_eq!(
+                    input.disabled_ports().collect::<alloc::vec::Vec<_>>(),
+                    vec![1]
+                );
+                assert_eq!(input.num_ports(), 4);
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+    }
+}
@@ -0,0 +1,5 @@
+mod program;
+mod reply;
+
+pub use self::program::IpProg;
+pub use self::reply::IpProgReply;
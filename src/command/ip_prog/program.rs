@@ -0,0 +1,116 @@
+use crate::command::ARTNET_PROTOCOL_VERSION;
+use core::net::Ipv4Addr;
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "Sent by a controller to a single node to reprogram its IP address, subnet mask and default gateway. The node answers with an ArtIpProgReply."]
+    #[doc = ""]
+    #[doc = "The `command` selects which of the fields are programmed. An ArtIpProg with a `command` of 0 only asks for the current settings of the node."]
+    pub struct IpProg {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub filler1: u8,
+        #[doc = "Transmit as zero"]
+        pub filler2: u8,
+        #[doc = "The action of this packet, a combination of e.g. `IpProg::ENABLE_PROGRAMMING` and `IpProg::PROGRAM_IP`"]
+        pub command: u8,
+        #[doc = "Transmit as zero"]
+        pub filler4: u8,
+        #[doc = "The IP address to program, if `IpProg::PROGRAM_IP` is set"]
+        pub ip: Ipv4Addr,
+        #[doc = "The subnet mask to program, if `IpProg::PROGRAM_SUBNET_MASK` is set"]
+        pub subnet_mask: Ipv4Addr,
+        #[doc = "The UDP port to program, in big endian, if `IpProg::PROGRAM_PORT` is set. Deprecated by the spec"]
+        pub port: [u8; 2],
+        #[doc = "The default gateway to program, if `IpProg::PROGRAM_GATEWAY` is set"]
+        pub gateway: Ipv4Addr,
+        #[doc = "Transmit as zero"]
+        pub spare: [u8; 4],
+    }
+}
+
+impl Default for IpProg {
+    fn default() -> IpProg {
+        IpProg {
+            version: ARTNET_PROTOCOL_VERSION,
+            filler1: 0,
+            filler2: 0,
+            command: 0,
+            filler4: 0,
+            ip: Ipv4Addr::UNSPECIFIED,
+            subnet_mask: Ipv4Addr::UNSPECIFIED,
+            port: [0; 2],
+            gateway: Ipv4Addr::UNSPECIFIED,
+            spare: [0; 4],
+        }
+    }
+}
+
+impl IpProg {
+    /// Any programming is done. Without this bit, the node only replies with its current settings
+    pub const ENABLE_PROGRAMMING: u8 = 0x80;
+
+    /// Enable DHCP. The other bits are ignored
+    pub const ENABLE_DHCP: u8 = 0x40;
+
+    /// Program the default gateway
+    pub const PROGRAM_GATEWAY: u8 = 0x10;
+
+    /// Reset the IP address, subnet mask and gateway to their defaults
+    pub const RESET: u8 = 0x08;
+
+    /// Program the IP address
+    pub const PROGRAM_IP: u8 = 0x04;
+
+    /// Program the subnet mask
+    pub const PROGRAM_SUBNET_MASK: u8 = 0x02;
+
+    /// Program the port
+    pub const PROGRAM_PORT: u8 = 0x01;
+
+    /// An ArtIpProg that programs the given IP address
+    pub fn program_ip(mut self, ip: Ipv4Addr) -> IpProg {
+        self.command |= IpProg::ENABLE_PROGRAMMING | IpProg::PROGRAM_IP;
+        self.ip = ip;
+        self
+    }
+
+    /// An ArtIpProg that programs the given subnet mask
+    pub fn program_subnet_mask(mut self, subnet_mask: Ipv4Addr) -> IpProg {
+        self.command |= IpProg::ENABLE_PROGRAMMING | IpProg::PROGRAM_SUBNET_MASK;
+        self.subnet_mask = subnet_mask;
+        self
+    }
+
+    /// An ArtIpProg that programs the given default gateway
+    pub fn program_gateway(mut self, gateway: Ipv4Addr) -> IpProg {
+        self.command |= IpProg::ENABLE_PROGRAMMING | IpProg::PROGRAM_GATEWAY;
+        self.gateway = gateway;
+        self
+    }
+
+    /// An ArtIpProg that enables DHCP on the node
+    pub fn enable_dhcp(mut self) -> IpProg {
+        self.command |= IpProg::ENABLE_PROGRAMMING | IpProg::ENABLE_DHCP;
+        self
+    }
+
+    /// An ArtIpProg that resets the network settings of the node to their defaults
+    pub fn reset(mut self) -> IpProg {
+        self.command |= IpProg::ENABLE_PROGRAMMING | IpProg::RESET;
+        self
+    }
+
+    /// If this packet changes any setting of the node
+    pub fn is_programming(&self) -> bool {
+        self.command & IpProg::ENABLE_PROGRAMMING != 0
+    }
+
+    /// Wrap this message in an `ArtCommand::OpIpProg`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+}
@@ -0,0 +1,90 @@
+use crate::command::ARTNET_PROTOCOL_VERSION;
+use crate::IpProgStatus;
+use core::net::Ipv4Addr;
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "Sent by a node in reply to an ArtIpProg, with its network settings after the programming."]
+    pub struct IpProgReply {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub filler: [u8; 4],
+        #[doc = "The IP address of the node"]
+        pub ip: Ipv4Addr,
+        #[doc = "The subnet mask of the node"]
+        pub subnet_mask: Ipv4Addr,
+        #[doc = "The UDP port of the node, in big endian. Deprecated by the spec"]
+        pub port: [u8; 2],
+        #[doc = "The status of the node, see `IpProgReply::status_flags`"]
+        pub status: u8,
+        #[doc = "Transmit as zero"]
+        pub spare2: u8,
+        #[doc = "The default gateway of the node"]
+        pub gateway: Ipv4Addr,
+        #[doc = "Transmit as zero"]
+        pub spare: [u8; 2],
+    }
+}
+
+impl Default for IpProgReply {
+    fn default() -> IpProgReply {
+        IpProgReply {
+            version: ARTNET_PROTOCOL_VERSION,
+            filler: [0; 4],
+            ip: Ipv4Addr::UNSPECIFIED,
+            subnet_mask: Ipv4Addr::UNSPECIFIED,
+            port: [0x19, 0x36],
+            status: 0,
+            spare2: 0,
+            gateway: Ipv4Addr::UNSPECIFIED,
+            spare: [0; 2],
+        }
+    }
+}
+
+impl IpProgReply {
+    /// The bit of `status` that is set when DHCP is enabled on the node
+    #[deprecated(note = "use `IpProgStatus::DHCP_ENABLED` with `status_flags` instead")]
+    pub const DHCP_ENABLED: u8 = 0x40;
+
+    /// If DHCP is enabled on the node
+    pub fn dhcp_enabled(&self) -> bool {
+        self.status_flags().contains(IpProgStatus::DHCP_ENABLED)
+    }
+
+    /// The flags of `status`. Bits that are not defined by the Art-Net specification are kept.
+    pub fn status_flags(&self) -> IpProgStatus {
+        IpProgStatus::from_bits_retain(self.status)
+    }
+
+    /// Set `status` to the given flags
+    pub fn set_status_flags(&mut self, flags: IpProgStatus) {
+        self.status = flags.bits();
+    }
+
+    /// Wrap this message in an `ArtCommand::OpIpProgReply`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_flags() {
+        let mut reply = IpProgReply::default();
+        assert_eq!(reply.status_flags(), IpProgStatus::NONE);
+        reply.set_status_flags(IpProgStatus::DHCP_ENABLED);
+        assert_eq!(reply.status, 0x40);
+        assert!(reply.dhcp_enabled());
+
+        reply.status = 0xC1;
+        assert!(reply.status_flags().contains(IpProgStatus::DHCP_ENABLED));
+        assert_eq!(reply.status_flags().bits(), 0xC1);
+    }
+}
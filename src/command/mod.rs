@@ -1,19 +1,114 @@
+#[cfg(feature = "dmx")]
+mod address;
+#[cfg(feature = "dmx")]
+mod diag_data;
+#[cfg(feature = "nonstandard-file-transfer")]
+mod file;
+#[cfg(feature = "firmware")]
+mod firmware;
+mod hexdump;
+#[cfg(feature = "dmx")]
+mod input;
+#[cfg(feature = "firmware")]
+mod ip_prog;
 mod output;
+#[cfg(feature = "dmx")]
+mod payload;
 mod poll;
 mod poll_reply;
+#[cfg(feature = "rdm")]
+mod rdm;
+#[cfg(any(feature = "rdm", feature = "firmware"))]
+mod shared_data;
+mod sync;
+#[cfg(feature = "dmx")]
+mod text_command;
+#[cfg(feature = "media")]
+mod time_code;
+#[cfg(feature = "media")]
+mod time_sync;
+#[cfg(feature = "rdm")]
+mod tod;
+#[cfg(feature = "media")]
+mod trigger;
 
-use crate::{Error, Result};
-use byteorder::{ByteOrder, LittleEndian, WriteBytesExt};
+#[cfg(feature = "heapless")]
+use crate::convert::HeaplessBuffer;
+use crate::convert::WriteBuffer;
+use crate::{Error, ParseOptions, Result};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use byteorder::{ByteOrder, LittleEndian};
 
+#[cfg(feature = "dmx")]
+pub use self::address::{Address, AddressCommand, AddressSwitch};
+#[cfg(feature = "dmx")]
+pub use self::diag_data::DiagData;
+#[cfg(feature = "nonstandard-file-transfer")]
+pub use self::file::{FileFnMaster, FileFnReply, FileTnMaster};
+#[cfg(feature = "firmware")]
+pub use self::firmware::{
+    FirmwareBlockType, FirmwareKind, FirmwareMaster, FirmwareReply, FirmwareReplyStatus,
+};
+#[cfg(feature = "dmx")]
+pub use self::input::Input;
+#[cfg(feature = "firmware")]
+pub use self::ip_prog::{IpProg, IpProgReply};
+#[cfg(feature = "dmx")]
+pub use self::output::Nzs;
 pub use self::output::{Output, PaddedData};
-pub use self::poll::Poll;
-pub use self::poll_reply::PollReply;
+#[cfg(feature = "dmx")]
+pub use self::payload::Payload;
+pub use self::poll::{DiagPriority, Poll, PollBuilder};
+pub(crate) use self::poll_reply::null_terminated;
+pub use self::poll_reply::{BackgroundQueuePolicy, FailsafeState, PollReply};
+#[cfg(feature = "rdm")]
+pub use self::rdm::Rdm;
+#[cfg(any(feature = "rdm", feature = "firmware"))]
+pub use self::shared_data::SharedData;
+pub use self::sync::ArtSync;
+#[cfg(feature = "dmx")]
+pub use self::text_command::{CommandEntry, TextCommand};
+#[cfg(feature = "media")]
+pub use self::time_code::{FrameRate, Timecode};
+#[cfg(feature = "media")]
+pub use self::time_sync::TimeSync;
+#[cfg(feature = "rdm")]
+pub use self::tod::{Count, TodControl, TodData, TodRequest};
+#[cfg(feature = "media")]
+pub use self::trigger::{Trigger, TriggerData, TriggerKey};
+
+/// How a command should be addressed, as returned by `ArtCommand::delivery_policy`.
+///
+/// Art-Net 4 unicasts most traffic to the nodes that need it. Broadcasting ArtDmx floods every device on the network, so it is only meant for networks without any discovered nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryPolicy {
+    /// The command is meant for every device on the network, e.g. ArtPoll and ArtSync
+    Broadcast,
+    /// The command is meant for a single device, e.g. ArtDmx and ArtAddress
+    Unicast,
+    /// The command can be broadcast or unicast
+    Any,
+}
+
+impl DeliveryPolicy {
+    /// Whether a command with this policy may be sent to a broadcast address (`true`) or a unicast address (`false`)
+    pub fn allows(self, broadcast: bool) -> bool {
+        match self {
+            DeliveryPolicy::Broadcast => broadcast,
+            DeliveryPolicy::Unicast => !broadcast,
+            DeliveryPolicy::Any => true,
+        }
+    }
+}
 
 /// The ArtCommand, to be used for ArtNet.
 ///
 /// This struct implements an `write_to_buffer` and `from_buffer` function, to be used with UDP connections.
-
+///
+/// To keep queues of commands cheap, variants with a large payload are boxed. On 64-bit targets an `ArtCommand` is 32 bytes, or 48 with the `bytes` feature.
 #[derive(Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum ArtCommand {
     /// A poll command, used to discover devices on the network
     Poll(Poll),
@@ -21,239 +116,1649 @@ pub enum ArtCommand {
     /// A reply to the poll command, it contains device status information
     PollReply(Box<PollReply>),
 
-    /// [Not implemented] Diagnostics and data logging packet
-    DiagData,
+    /// Diagnostics and data logging packet
+    #[cfg(feature = "dmx")]
+    DiagData(Box<DiagData>),
 
-    /// [Not implemented] Used to send text based parameter commands
-    Command,
+    /// Used to send text based parameter commands
+    #[cfg(feature = "dmx")]
+    Command(Box<TextCommand>),
 
     /// An ArtDmx data packet. Used to send actual data to a node in the network
     Output(Output),
 
-    /// [Not implemented] This is an ArtNzs data packet. It contains non-zero start code (except RDM) DMX512 information for a single Universe
-    Nzs,
+    /// This is an ArtNzs data packet. It contains non-zero start code (except RDM) DMX512 information for a single Universe
+    #[cfg(feature = "dmx")]
+    Nzs(Box<Nzs>),
 
-    /// [Not implemented] This is an ArtSync data packet. It is used to force synchronous transfer of ArtDmx packets to a node's output
-    Sync,
+    /// This is an ArtSync data packet. It is used to force synchronous transfer of ArtDmx packets to a node's output
+    Sync(ArtSync),
 
-    /// [Not implemented] This is an ArtAddress packet. It contains remote programming information for a Node.
-    Address,
+    /// This is an ArtAddress packet. It contains remote programming information for a Node.
+    #[cfg(feature = "dmx")]
+    Address(Box<Address>),
 
-    /// [Not implemented] This is an ArtInput packet. It contains enable – disable data for DMX inputs
-    Input,
+    /// This is an ArtInput packet. It contains enable – disable data for DMX inputs
+    #[cfg(feature = "dmx")]
+    Input(Input),
 
-    /// [Not implemented] This is an ArtTodRequest packet. It is used to request a Table of Devices (ToD) for RDM discovery.
-    TodRequest,
+    /// This is an ArtTodRequest packet. It is used to request a Table of Devices (ToD) for RDM discovery.
+    #[cfg(feature = "rdm")]
+    TodRequest(Box<TodRequest>),
 
-    /// [Not implemented] This is an ArtTodData packet. It is used to send a Table of Devices (ToD) for RDM discovery
-    TodData,
+    /// This is an ArtTodData packet. It is used to send a Table of Devices (ToD) for RDM discovery
+    #[cfg(feature = "rdm")]
+    TodData(Box<TodData>),
 
-    /// [Not implemented] This is an ArtTodControl packet. It is used to send RDM discovery control messages.
-    TodControl,
+    /// This is an ArtTodControl packet. It is used to send RDM discovery control messages.
+    #[cfg(feature = "rdm")]
+    TodControl(TodControl),
 
-    /// [Not implemented] This is an ArtRdm packet. It is used to send all non discovery RDM messages
-    Rdm,
+    /// This is an ArtRdm packet. It is used to send all non discovery RDM messages
+    #[cfg(feature = "rdm")]
+    Rdm(Box<Rdm>),
 
     /// [Not implemented] This is an ArtRdmSub packet. It is used to send compressed, RDM Sub-Device data.
+    #[cfg(feature = "rdm")]
     RdmSub,
 
     /// [Not implemented] This is an ArtVideoSetup packet. It contains video screen setup information for nodes that implement the extended video features.
+    #[cfg(feature = "video")]
     VideoSetup,
 
     /// [Not implemented] This is an ArtVideoPalette packet. It contains colour palette setup information for nodes that implement the extended video features.
+    #[cfg(feature = "video")]
     VideoPalette,
 
     /// [Not implemented] This is an ArtVideoData packet. It contains display data for nodes that implement the extended video features.
+    #[cfg(feature = "video")]
     VideoData,
 
     /// [Not implemented] This packet is deprecated
+    #[cfg(feature = "video")]
     MacMaster,
 
     /// [Not implemented] This packet is deprecated
+    #[cfg(feature = "video")]
     MacSlave,
 
-    /// [Not implemented] This is an ArtFirmwareMaster packet. It is used to upload new firmware or firmware extensions to the Node.
-    FirmwareMaster,
+    /// This is an ArtFirmwareMaster packet. It is used to upload new firmware or firmware extensions to the Node.
+    #[cfg(feature = "firmware")]
+    FirmwareMaster(Box<FirmwareMaster>),
 
-    /// [Not implemented] This is an ArtFirmwareReply packet. It is returned by the node to acknowledge receipt of an ArtFirmwareMaster packet or ArtFileTnMaster packet.
-    FirmwareReply,
+    /// This is an ArtFirmwareReply packet. It is returned by the node to acknowledge receipt of an ArtFirmwareMaster packet or ArtFileTnMaster packet.
+    #[cfg(feature = "firmware")]
+    FirmwareReply(Box<FirmwareReply>),
 
-    /// [Not implemented] Uploads user file to node.
-    FileTnMaster,
+    /// Uploads user file to node. Non-standard, see [FileTnMaster](struct.FileTnMaster.html).
+    #[cfg(feature = "nonstandard-file-transfer")]
+    FileTnMaster(Box<FileTnMaster>),
 
-    /// [Not implemented] Downloads user file from node
-    FileFnMaster,
+    /// Downloads user file from node. Non-standard, see [FileFnMaster](struct.FileFnMaster.html).
+    #[cfg(feature = "nonstandard-file-transfer")]
+    FileFnMaster(Box<FileFnMaster>),
 
-    /// [Not implemented] Server to Node acknowledge for download packets
-    FileFnReply,
+    /// Server to Node acknowledge for download packets. Non-standard, see [FileFnReply](struct.FileFnReply.html).
+    #[cfg(feature = "nonstandard-file-transfer")]
+    FileFnReply(Box<FileFnReply>),
 
-    /// [Not implemented] This is an ArtIpProg packet. It is used to reprogramme the IP address and Mask of the Node
-    OpIpProg,
+    /// This is an ArtIpProg packet. It is used to reprogramme the IP address and Mask of the Node
+    #[cfg(feature = "firmware")]
+    OpIpProg(IpProg),
 
-    /// [Not implemented] This is an ArtIpProgReply packet. It is returned by the node to acknowledge receipt of an ArtIpProg packet.
-    OpIpProgReply,
+    /// This is an ArtIpProgReply packet. It is returned by the node to acknowledge receipt of an ArtIpProg packet.
+    #[cfg(feature = "firmware")]
+    OpIpProgReply(IpProgReply),
 
     /// [Not implemented] This is an ArtMedia packet. It is Unicast by a Media Server and acted upon by a Controller
+    #[cfg(feature = "media")]
     OpMedia,
 
     /// [Not implemented] This is an ArtMediaPatch packet. It is Unicast by a Controller and acted upon by a Media Server
+    #[cfg(feature = "media")]
     OpMediaPatch,
 
     /// [Not implemented] This is an ArtMediaControl packet. It is Unicast by a Controller and acted upon by a Media Server.
+    #[cfg(feature = "media")]
     OpMediaControl,
 
     /// [Not implemented] This is an ArtMediaControlReply packet. It is Unicast by a Media Server and acted upon by a Controller
+    #[cfg(feature = "media")]
     OpMediaControlReply,
 
-    /// [Not implemented] This is an ArtTimeCode packet. It is used to transport time code over the network
-    OpTimeCode,
+    /// This is an ArtTimeCode packet. It is used to transport time code over the network
+    #[cfg(feature = "media")]
+    OpTimeCode(Timecode),
 
-    /// [Not implemented] Used to synchronise real time date and clock
-    OpTimeSync,
+    /// Used to synchronise real time date and clock
+    #[cfg(feature = "media")]
+    OpTimeSync(TimeSync),
 
-    /// [Not implemented] Used to send trigger macros
-    OpTrigger,
+    /// Used to send trigger macros
+    #[cfg(feature = "media")]
+    OpTrigger(Box<Trigger>),
 
     /// [Not implemented] Requests a node's file list
+    #[cfg(feature = "firmware")]
     OpDirectory,
 
     /// [Not implemented] Replies to OpDirectory with file list
+    #[cfg(feature = "firmware")]
     OpDirectoryReply,
 }
 
 /// The ArtNet header. This is the first 8 bytes of each message, and contains the text "Art-Net\0"
 pub const ARTNET_HEADER: &[u8; 8] = b"Art-Net\0";
 
+/// The size of the largest message in the Art-Net protocol, an ArtTodData containing 200 UIDs.
+///
+/// A buffer of this size can receive any valid Art-Net message, see [RecvBuffer](struct.RecvBuffer.html).
+pub const MAX_PACKET_SIZE: usize = 1228;
+
+/// The length of the Art-Net header plus the opcode, after which the body of a command starts
+pub(crate) const HEADER_LENGTH: usize = ARTNET_HEADER.len() + 2;
+
+/// The text in the first `length` bytes of `data`, up to the terminating zero. Bytes that are not valid UTF-8 end the text.
+#[cfg(feature = "dmx")]
+pub(crate) fn zero_terminated_text(data: &[u8], length: u16) -> &str {
+    let data = &data[..usize::from(length).min(data.len())];
+    let data = match data.iter().position(|b| *b == 0) {
+        Some(end) => &data[..end],
+        None => data,
+    };
+    match core::str::from_utf8(data) {
+        Ok(text) => text,
+        Err(e) => core::str::from_utf8(&data[..e.valid_up_to()]).unwrap_or_default(),
+    }
+}
+
 /// The protocol version. Anything above [4, 0] seems to work for the devices that this library was tested on.
 ///
 /// If you need a different or configurable protocol version, please open a PR.
 pub const ARTNET_PROTOCOL_VERSION: [u8; 2] = [0, 14];
 
 impl ArtCommand {
+    /// An ArtPoll with the default settings, which asks every node to reply once
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// let bytes = ArtCommand::poll().write_to_buffer().unwrap();
+    /// assert_eq!(bytes.len(), Poll::MAX_PACKET_SIZE);
+    /// ```
+    pub fn poll() -> ArtCommand {
+        ArtCommand::Poll(Poll::default())
+    }
+
+    /// An ArtSync, which makes the nodes output the ArtDmx that they received since the last ArtSync
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// assert_eq!(ArtCommand::sync().delivery_policy(), DeliveryPolicy::Broadcast);
+    /// ```
+    pub fn sync() -> ArtCommand {
+        ArtCommand::Sync(ArtSync::default())
+    }
+
     /// Convert an ArtCommand in a byte buffer, which can be send to an UDP socket.
     pub fn write_to_buffer(self) -> Result<Vec<u8>> {
-        let mut result = Vec::new();
-        let (opcode, data) = self.get_opcode()?;
+        let mut result = Vec::with_capacity(self.serialized_len());
+        self.write_to(&mut result)?;
+        Ok(result)
+    }
+
+    /// Convert an ArtCommand into a `bytes::Bytes`, which can be cloned cheaply and handed to e.g. tokio without copying.
+    #[cfg(feature = "bytes")]
+    pub fn write_to_bytes(&self) -> Result<bytes::Bytes> {
+        let mut result = Vec::with_capacity(self.serialized_len());
+        self.write_to(&mut result)?;
+        Ok(result.into())
+    }
+
+    /// Append the bytes of this ArtCommand, including the Art-Net header and opcode, to the given buffer.
+    ///
+    /// If an error occurs, the buffer may contain part of the message. See [Encoder](struct.Encoder.html) for a reusable buffer.
+    pub fn write_to(&self, buffer: &mut Vec<u8>) -> Result<()> {
+        buffer.reserve(self.serialized_len());
+        self.write_message(buffer)
+    }
 
+    /// Append the header, opcode and fields of the command
+    fn write_message(&self, buffer: &mut dyn WriteBuffer) -> Result<()> {
         // Append Art-Net\0 header
-        result.extend_from_slice(ARTNET_HEADER);
+        buffer.extend_from_slice(ARTNET_HEADER);
         // Append the opcode of this enum
-        result
-            .write_u16::<LittleEndian>(opcode)
-            .map_err(Error::CursorEof)?;
+        buffer.extend_from_slice(&self.opcode().to_le_bytes());
 
-        result.extend_from_slice(&data);
+        let result = self.write_body(buffer);
+        #[cfg(feature = "tracing")]
+        if let Err(error) = &result {
+            tracing::warn!(command = self.name(), %error, "could not serialize message");
+        }
+        result
+    }
 
+    /// Convert an ArtCommand in a byte buffer, using the given options to determine how strictly the message is checked first. See `write_to_with`.
+    pub fn write_to_buffer_with(self, options: &ParseOptions) -> Result<Vec<u8>> {
+        let mut result = Vec::with_capacity(self.serialized_len());
+        self.write_to_with(&mut result, options)?;
         Ok(result)
     }
 
+    /// Append the bytes of this ArtCommand to the given buffer, like `write_to`. With `ParseMode::Strict`, messages that the specification forbids are rejected before anything is written, e.g. an ArtNzs with the DMX or RDM start code returns `Error::InvalidStartCode`.
+    pub fn write_to_with(&self, buffer: &mut Vec<u8>, options: &ParseOptions) -> Result<()> {
+        #[cfg(feature = "dmx")]
+        if let ArtCommand::Nzs(nzs) = self {
+            if options.is_strict() {
+                nzs.validate()?;
+            }
+        }
+        #[cfg(not(feature = "dmx"))]
+        let _ = options;
+        self.write_to(buffer)
+    }
+
+    /// The exact amount of bytes that `write_to_buffer` will produce, including the Art-Net header and opcode.
+    pub fn serialized_len(&self) -> usize {
+        HEADER_LENGTH
+            + match self {
+                ArtCommand::Poll(poll) => poll.serialized_len(),
+                ArtCommand::PollReply(reply) => reply.serialized_len(),
+                ArtCommand::Output(output) => output.serialized_len(),
+                ArtCommand::Sync(sync) => sync.serialized_len(),
+                #[cfg(feature = "dmx")]
+                ArtCommand::Address(address) => address.serialized_len(),
+                #[cfg(feature = "rdm")]
+                ArtCommand::TodRequest(request) => request.serialized_len(),
+                #[cfg(feature = "rdm")]
+                ArtCommand::TodData(tod) => tod.serialized_len(),
+                #[cfg(feature = "rdm")]
+                ArtCommand::TodControl(control) => control.serialized_len(),
+                #[cfg(feature = "rdm")]
+                ArtCommand::Rdm(rdm) => rdm.serialized_len(),
+                #[cfg(feature = "firmware")]
+                ArtCommand::FirmwareMaster(master) => master.serialized_len(),
+                #[cfg(feature = "firmware")]
+                ArtCommand::FirmwareReply(reply) => reply.serialized_len(),
+                #[cfg(feature = "nonstandard-file-transfer")]
+                ArtCommand::FileTnMaster(master) => master.serialized_len(),
+                #[cfg(feature = "nonstandard-file-transfer")]
+                ArtCommand::FileFnMaster(master) => master.serialized_len(),
+                #[cfg(feature = "nonstandard-file-transfer")]
+                ArtCommand::FileFnReply(reply) => reply.serialized_len(),
+                #[cfg(feature = "dmx")]
+                ArtCommand::Nzs(nzs) => nzs.serialized_len(),
+                #[cfg(feature = "media")]
+                ArtCommand::OpTimeCode(time_code) => time_code.serialized_len(),
+                #[cfg(feature = "media")]
+                ArtCommand::OpTimeSync(time_sync) => time_sync.serialized_len(),
+                #[cfg(feature = "dmx")]
+                ArtCommand::Command(command) => command.serialized_len(),
+                #[cfg(feature = "dmx")]
+                ArtCommand::DiagData(diag_data) => diag_data.serialized_len(),
+                #[cfg(feature = "firmware")]
+                ArtCommand::OpIpProg(ip_prog) => ip_prog.serialized_len(),
+                #[cfg(feature = "firmware")]
+                ArtCommand::OpIpProgReply(reply) => reply.serialized_len(),
+                #[cfg(feature = "media")]
+                ArtCommand::OpTrigger(trigger) => trigger.serialized_len(),
+                #[cfg(feature = "dmx")]
+                ArtCommand::Input(input) => input.serialized_len(),
+                // Commands without a body. There are none when the optional opcode families are disabled
+                #[allow(unreachable_patterns)]
+                _ => 0,
+            }
+    }
+
+    /// Convert an ArtCommand into a fixed-capacity `heapless::Vec`.
+    ///
+    /// The message is written into the `heapless::Vec` directly, so this does not allocate unless it fails. This fails with `MessageSizeInvalid` if the command does not fit in `N` bytes.
+    #[cfg(feature = "heapless")]
+    pub fn write_to_heapless<const N: usize>(self) -> Result<heapless::Vec<u8, N>> {
+        let len = self.serialized_len();
+        let too_long = || Error::MessageSizeInvalid {
+            len,
+            allowed_size: 0..N + 1,
+        };
+        if len > N {
+            return Err(too_long());
+        }
+        let mut buffer = HeaplessBuffer {
+            inner: heapless::Vec::new(),
+            overflowed: false,
+        };
+        self.write_message(&mut buffer)?;
+        if buffer.overflowed {
+            return Err(too_long());
+        }
+        Ok(buffer.inner)
+    }
+
     /// Convert an a byte buffer to a command.
     pub fn from_buffer(buffer: &[u8]) -> Result<ArtCommand> {
+        ArtCommand::from_buffer_with(buffer, &ParseOptions::default())
+    }
+
+    /// Convert an a byte buffer to a command, using the given options to determine how strictly the message is checked.
+    pub fn from_buffer_with(buffer: &[u8], options: &ParseOptions) -> Result<ArtCommand> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("from_buffer", len = buffer.len()).entered();
+
+        let result = ArtCommand::parse(buffer, options);
+        #[cfg(feature = "tracing")]
+        if let Err(error) = &result {
+            trace_parse_error(error);
+        }
+        result
+    }
+
+    /// Convert a `bytes::Bytes` to a command, like `from_buffer`. The data of an ArtDmx, ArtNzs, ArtRdm or ArtFirmwareMaster refers to the given bytes instead of a copy of them.
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// let message = Output::new(1.into(), vec![255; 512]).into_command().write_to_bytes().unwrap();
+    /// let command = ArtCommand::from_bytes(&message).unwrap();
+    /// assert_eq!(command.as_output().unwrap().data.as_ptr(), message[18..].as_ptr());
+    /// ```
+    #[cfg(feature = "bytes")]
+    pub fn from_bytes(bytes: &bytes::Bytes) -> Result<ArtCommand> {
+        ArtCommand::from_bytes_with(bytes, &ParseOptions::default())
+    }
+
+    /// Convert a `bytes::Bytes` to a command, like `from_buffer_with`. The data of an ArtDmx, ArtNzs, ArtRdm or ArtFirmwareMaster refers to the given bytes instead of a copy of them.
+    #[cfg(feature = "bytes")]
+    pub fn from_bytes_with(bytes: &bytes::Bytes, options: &ParseOptions) -> Result<ArtCommand> {
+        let opcode = match bytes.get(8..HEADER_LENGTH) {
+            Some(opcode) => LittleEndian::read_u16(opcode),
+            None => return ArtCommand::from_buffer_with(bytes, options),
+        };
+        let data_offset = HEADER_LENGTH
+            + match opcode {
+                0x5000 | 0x5100 => Output::HEADER_LENGTH,
+                #[cfg(feature = "rdm")]
+                0x8300 => Rdm::HEADER_LENGTH,
+                #[cfg(feature = "firmware")]
+                0xF200 => FirmwareMaster::HEADER_LENGTH,
+                _ => return ArtCommand::from_buffer_with(bytes, options),
+            };
+        if bytes.len() < data_offset {
+            return ArtCommand::from_buffer_with(bytes, options);
+        }
+
+        // Parse the fields before the data first, and check the length once the data is attached
+        let header_options = ParseOptions {
+            mode: crate::ParseMode::Lenient,
+            normalize: false,
+            ..options.clone()
+        };
+        let command = ArtCommand::from_buffer_with(&bytes[..data_offset], &header_options)?;
+        let data = bytes.slice(data_offset..);
+        let result = match command {
+            ArtCommand::Output(mut output) => {
+                output.data = data.into();
+                Output::check_length(output, options)
+                    .map(ArtCommand::Output)
+                    .map_err(|e| Error::OpcodeError {
+                        name: "Output",
+                        opcode,
+                        source: Box::new(e),
+                    })
+            }
+            #[cfg(feature = "dmx")]
+            ArtCommand::Nzs(mut nzs) => {
+                nzs.data = data.into();
+                Nzs::check_length(*nzs, options)
+                    .map(|nzs| ArtCommand::Nzs(Box::new(nzs)))
+                    .map_err(|e| Error::OpcodeError {
+                        name: "ArtNzs",
+                        opcode,
+                        source: Box::new(e),
+                    })
+            }
+            #[cfg(feature = "rdm")]
+            ArtCommand::Rdm(mut rdm) => {
+                rdm.packet = data.into();
+                Ok(ArtCommand::Rdm(rdm))
+            }
+            #[cfg(feature = "firmware")]
+            ArtCommand::FirmwareMaster(mut master) => {
+                master.data = data.into();
+                Ok(ArtCommand::FirmwareMaster(master))
+            }
+            // ArtNzs without the `dmx` feature
+            command => Ok(command),
+        };
+        #[cfg(feature = "tracing")]
+        if let Err(error) = &result {
+            trace_parse_error(error);
+        }
+        result
+    }
+
+    /// Convert a byte buffer to a command, and keep the bytes that the sender appended after the message, e.g. vendor data. A proxy can forward the packet without losing them, see [ArtPacket](struct.ArtPacket.html).
+    ///
+    /// The extra bytes are the bytes after the `serialized_len` of the command. The data of an ArtDmx or ArtNzs ends at its length field in every mode. Other messages that end with data of a variable length, e.g. an ArtDiagData, include any appended bytes in their data, and commands that are not implemented keep their whole body as extra bytes. In strict mode, most messages with trailing bytes are rejected.
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// let mut buffer = ArtCommand::sync().write_to_buffer().unwrap();
+    /// buffer.extend_from_slice(b"vendor");
+    /// let packet = ArtCommand::from_buffer_with_extra(&buffer, &ParseOptions::default()).unwrap();
+    /// assert_eq!(packet.extra, b"vendor");
+    /// assert_eq!(packet.write_to_buffer().unwrap(), buffer);
+    /// ```
+    pub fn from_buffer_with_extra(buffer: &[u8], options: &ParseOptions) -> Result<ArtPacket> {
+        // Split the bytes after the data of an ArtDmx or ArtNzs off, so the packet is forwarded unchanged
+        let options = ParseOptions {
+            normalize: true,
+            ..options.clone()
+        };
+        let command = ArtCommand::from_buffer_with(buffer, &options)?;
+        let extra = buffer
+            .get(command.serialized_len()..)
+            .unwrap_or_default()
+            .to_vec();
+        Ok(ArtPacket { command, extra })
+    }
+
+    fn parse(buffer: &[u8], options: &ParseOptions) -> Result<ArtCommand> {
         const MIN_BUFFER_LENGTH: usize = 14;
 
         if buffer.len() < MIN_BUFFER_LENGTH {
             return Err(Error::MessageTooShort {
-                message: buffer.to_vec(),
+                len: buffer.len(),
                 min_len: MIN_BUFFER_LENGTH,
             });
         }
 
         if !buffer.starts_with(ARTNET_HEADER) {
-            return Err(Error::InvalidArtnetHeader(buffer.to_vec()));
+            return Err(Error::InvalidArtnetHeader { len: buffer.len() });
+        }
+
+        let opcode = LittleEndian::read_u16(&buffer[8..HEADER_LENGTH]);
+        if let Some(min_len) = ArtCommand::min_message_length(opcode) {
+            if buffer.len() < min_len {
+                return Err(Error::MessageTooShort {
+                    len: buffer.len(),
+                    min_len,
+                });
+            }
         }
+        let remaining = &buffer[HEADER_LENGTH..];
 
-        let opcode = LittleEndian::read_u16(&buffer[8..10]);
-        let remaining = &buffer[10..];
+        let command = ArtCommand::opcode_to_enum(opcode, remaining, options)?;
 
-        let command = ArtCommand::opcode_to_enum(opcode, remaining)?;
+        if let (Some(min_version), Some(version)) =
+            (options.min_protocol_version, command.protocol_version())
+        {
+            if version < min_version {
+                return Err(Error::UnsupportedProtocolVersion {
+                    version,
+                    min_version,
+                });
+            }
+        }
 
         Ok(command)
     }
 
-    fn opcode_to_enum(code: u16, data: &[u8]) -> Result<ArtCommand> {
+    /// The protocol version (ProtVer) that the sender of this command uses, if the command contains one.
+    ///
+    /// `PollReply` does not contain a protocol version; its `version` field is the firmware version of the node.
+    pub fn protocol_version(&self) -> Option<u16> {
+        match self {
+            ArtCommand::Poll(poll) => Some(u16::from_be_bytes(poll.version)),
+            ArtCommand::Output(output) => Some(u16::from_be_bytes(output.version)),
+            ArtCommand::Sync(sync) => Some(u16::from_be_bytes(sync.version)),
+            #[cfg(feature = "dmx")]
+            ArtCommand::Address(address) => Some(u16::from_be_bytes(address.version)),
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodRequest(request) => Some(u16::from_be_bytes(request.version)),
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodData(tod) => Some(u16::from_be_bytes(tod.version)),
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodControl(control) => Some(u16::from_be_bytes(control.version)),
+            #[cfg(feature = "rdm")]
+            ArtCommand::Rdm(rdm) => Some(u16::from_be_bytes(rdm.version)),
+            #[cfg(feature = "firmware")]
+            ArtCommand::FirmwareMaster(master) => Some(u16::from_be_bytes(master.version)),
+            #[cfg(feature = "firmware")]
+            ArtCommand::FirmwareReply(reply) => Some(u16::from_be_bytes(reply.version)),
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileTnMaster(master) => Some(u16::from_be_bytes(master.version)),
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileFnMaster(master) => Some(u16::from_be_bytes(master.version)),
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileFnReply(reply) => Some(u16::from_be_bytes(reply.version)),
+            #[cfg(feature = "dmx")]
+            ArtCommand::Nzs(nzs) => Some(u16::from_be_bytes(nzs.version)),
+            #[cfg(feature = "media")]
+            ArtCommand::OpTimeCode(time_code) => Some(u16::from_be_bytes(time_code.version)),
+            #[cfg(feature = "media")]
+            ArtCommand::OpTimeSync(time_sync) => Some(u16::from_be_bytes(time_sync.version)),
+            #[cfg(feature = "dmx")]
+            ArtCommand::Command(command) => Some(u16::from_be_bytes(command.version)),
+            #[cfg(feature = "dmx")]
+            ArtCommand::DiagData(diag_data) => Some(u16::from_be_bytes(diag_data.version)),
+            #[cfg(feature = "firmware")]
+            ArtCommand::OpIpProg(ip_prog) => Some(u16::from_be_bytes(ip_prog.version)),
+            #[cfg(feature = "firmware")]
+            ArtCommand::OpIpProgReply(reply) => Some(u16::from_be_bytes(reply.version)),
+            #[cfg(feature = "media")]
+            ArtCommand::OpTrigger(trigger) => Some(u16::from_be_bytes(trigger.version)),
+            #[cfg(feature = "dmx")]
+            ArtCommand::Input(input) => Some(u16::from_be_bytes(input.version)),
+            _ => None,
+        }
+    }
+
+    /// The minimal length of a message with the given opcode, including the Art-Net header and opcode.
+    ///
+    /// Messages that are shorter than this are rejected before any field is parsed. Returns `None` for unknown opcodes.
+    pub(crate) fn min_message_length(opcode: u16) -> Option<usize> {
+        Some(match opcode {
+            0x2000 => 14,  // Poll, the Art-Net 4 target and ESTA/OEM fields are optional
+            0x2100 => 207, // PollReply, shorter replies are padded with zeroes
+            0x2300 => 18,  // DiagData
+            0x2400 => 16,  // Command
+            0x5000 => 18,  // Output, without any data
+            0x5100 => 18,  // Nzs, without any data
+            0x5200 => 14,  // Sync
+            0x6000 => 107, // Address
+            0x7000 => 20,  // Input
+            0x8000 => 24,  // TodRequest, without any addresses
+            0x8100 => 28,  // TodData, without any UIDs
+            0x8200 => 24,  // TodControl
+            0x8300 => 24,  // Rdm, without the RDM packet
+            0x8400 => 32,  // RdmSub, without any data
+            0xF200 => 40,  // FirmwareMaster, without any data
+            0xF300 => 36,  // FirmwareReply
+            0xF400 => 40,  // FileTnMaster, without any data
+            0xF500 => 40,  // FileFnMaster, without any data
+            0xF600 => 36,  // FileFnReply
+            0xF800 => 34,  // IpProg
+            0xF900 => 34,  // IpProgReply
+            0x9700 => 19,  // TimeCode
+            0x9800 => 24,  // TimeSync
+            0x9900 => 18,  // Trigger, without any data
+            0x9A00 => 18,  // Directory
+            0x9B00 => 169, // DirectoryReply
+            0xA010 | 0xA020 | 0xA040 | 0xF000 | 0xF100 | 0x9000 | 0x9100 | 0x9200 | 0x9300 => 14,
+            _ => return None,
+        })
+    }
+
+    fn opcode_to_enum(code: u16, data: &[u8], options: &ParseOptions) -> Result<ArtCommand> {
         Ok(match code {
-            0x2000 => ArtCommand::Poll(
-                Poll::from(data).map_err(|e| Error::OpcodeError("Poll", Box::new(e)))?,
-            ),
+            0x2000 => ArtCommand::Poll(Poll::from_with(data, options).map_err(|e| {
+                Error::OpcodeError {
+                    name: "Poll",
+                    opcode: code,
+                    source: Box::new(e),
+                }
+            })?),
             0x2100 => ArtCommand::PollReply(Box::new(
-                PollReply::from(data).map_err(|e| Error::OpcodeError("PollReply", Box::new(e)))?,
+                PollReply::from_with(data, options).map_err(|e| Error::OpcodeError {
+                    name: "PollReply",
+                    opcode: code,
+                    source: Box::new(e),
+                })?,
             )),
-            0x2300 => ArtCommand::DiagData,
-            0x2400 => ArtCommand::Command,
-            0x5000 => ArtCommand::Output(
-                Output::from(data).map_err(|e| Error::OpcodeError("Output", Box::new(e)))?,
-            ),
-            0x5100 => ArtCommand::Nzs,
-            0x5200 => ArtCommand::Sync,
-            0x6000 => ArtCommand::Address,
-            0x7000 => ArtCommand::Input,
-            0x8000 => ArtCommand::TodRequest,
-            0x8100 => ArtCommand::TodData,
-            0x8200 => ArtCommand::TodControl,
-            0x8300 => ArtCommand::Rdm,
+            #[cfg(feature = "dmx")]
+            0x2300 => ArtCommand::DiagData(Box::new(DiagData::from_with(data, options).map_err(
+                |e| Error::OpcodeError {
+                    name: "DiagData",
+                    opcode: code,
+                    source: Box::new(e),
+                },
+            )?)),
+            #[cfg(feature = "dmx")]
+            0x2400 => ArtCommand::Command(Box::new(
+                TextCommand::from_with(data, options).map_err(|e| Error::OpcodeError {
+                    name: "TextCommand",
+                    opcode: code,
+                    source: Box::new(e),
+                })?,
+            )),
+            0x5000 => ArtCommand::Output(Output::from_with(data, options).map_err(|e| {
+                Error::OpcodeError {
+                    name: "Output",
+                    opcode: code,
+                    source: Box::new(e),
+                }
+            })?),
+            #[cfg(feature = "dmx")]
+            0x5100 => ArtCommand::Nzs(Box::new(Nzs::from_with(data, options).map_err(|e| {
+                Error::OpcodeError {
+                    name: "ArtNzs",
+                    opcode: code,
+                    source: Box::new(e),
+                }
+            })?)),
+            0x5200 => ArtCommand::Sync(ArtSync::from(data).map_err(|e| Error::OpcodeError {
+                name: "Sync",
+                opcode: code,
+                source: Box::new(e),
+            })?),
+            #[cfg(feature = "dmx")]
+            0x6000 => ArtCommand::Address(Box::new(Address::from(data).map_err(|e| {
+                Error::OpcodeError {
+                    name: "Address",
+                    opcode: code,
+                    source: Box::new(e),
+                }
+            })?)),
+            #[cfg(feature = "dmx")]
+            0x7000 => ArtCommand::Input(Input::from(data).map_err(|e| Error::OpcodeError {
+                name: "Input",
+                opcode: code,
+                source: Box::new(e),
+            })?),
+            #[cfg(feature = "rdm")]
+            0x8000 => ArtCommand::TodRequest(Box::new(
+                TodRequest::from_with(data, options).map_err(|e| Error::OpcodeError {
+                    name: "TodRequest",
+                    opcode: code,
+                    source: Box::new(e),
+                })?,
+            )),
+            #[cfg(feature = "rdm")]
+            0x8100 => ArtCommand::TodData(Box::new(TodData::from_with(data, options).map_err(
+                |e| Error::OpcodeError {
+                    name: "TodData",
+                    opcode: code,
+                    source: Box::new(e),
+                },
+            )?)),
+            #[cfg(feature = "rdm")]
+            0x8200 => {
+                ArtCommand::TodControl(TodControl::from(data).map_err(|e| Error::OpcodeError {
+                    name: "TodControl",
+                    opcode: code,
+                    source: Box::new(e),
+                })?)
+            }
+            #[cfg(feature = "rdm")]
+            0x8300 => ArtCommand::Rdm(Box::new(Rdm::from(data).map_err(|e| {
+                Error::OpcodeError {
+                    name: "Rdm",
+                    opcode: code,
+                    source: Box::new(e),
+                }
+            })?)),
+            #[cfg(feature = "rdm")]
             0x8400 => ArtCommand::RdmSub,
+            #[cfg(feature = "video")]
             0xA010 => ArtCommand::VideoSetup,
+            #[cfg(feature = "video")]
             0xA020 => ArtCommand::VideoPalette,
+            #[cfg(feature = "video")]
             0xA040 => ArtCommand::VideoData,
+            #[cfg(feature = "video")]
             0xF000 => ArtCommand::MacMaster,
+            #[cfg(feature = "video")]
             0xF100 => ArtCommand::MacSlave,
-            0xF200 => ArtCommand::FirmwareMaster,
-            0xF300 => ArtCommand::FirmwareReply,
-            0xF400 => ArtCommand::FileTnMaster,
-            0xF500 => ArtCommand::FileFnMaster,
-            0xF600 => ArtCommand::FileFnReply,
-            0xF800 => ArtCommand::OpIpProg,
-            0xF900 => ArtCommand::OpIpProgReply,
+            #[cfg(feature = "firmware")]
+            0xF200 => {
+                ArtCommand::FirmwareMaster(Box::new(FirmwareMaster::from(data).map_err(|e| {
+                    Error::OpcodeError {
+                        name: "FirmwareMaster",
+                        opcode: code,
+                        source: Box::new(e),
+                    }
+                })?))
+            }
+            #[cfg(feature = "firmware")]
+            0xF300 => {
+                ArtCommand::FirmwareReply(Box::new(FirmwareReply::from(data).map_err(|e| {
+                    Error::OpcodeError {
+                        name: "FirmwareReply",
+                        opcode: code,
+                        source: Box::new(e),
+                    }
+                })?))
+            }
+            #[cfg(feature = "nonstandard-file-transfer")]
+            0xF400 => {
+                ArtCommand::FileTnMaster(Box::new(FileTnMaster::from(data).map_err(|e| {
+                    Error::OpcodeError {
+                        name: "FileTnMaster",
+                        opcode: code,
+                        source: Box::new(e),
+                    }
+                })?))
+            }
+            #[cfg(feature = "nonstandard-file-transfer")]
+            0xF500 => {
+                ArtCommand::FileFnMaster(Box::new(FileFnMaster::from(data).map_err(|e| {
+                    Error::OpcodeError {
+                        name: "FileFnMaster",
+                        opcode: code,
+                        source: Box::new(e),
+                    }
+                })?))
+            }
+            #[cfg(feature = "nonstandard-file-transfer")]
+            0xF600 => ArtCommand::FileFnReply(Box::new(FileFnReply::from(data).map_err(|e| {
+                Error::OpcodeError {
+                    name: "FileFnReply",
+                    opcode: code,
+                    source: Box::new(e),
+                }
+            })?)),
+            #[cfg(feature = "firmware")]
+            0xF800 => ArtCommand::OpIpProg(IpProg::from(data).map_err(|e| Error::OpcodeError {
+                name: "IpProg",
+                opcode: code,
+                source: Box::new(e),
+            })?),
+            #[cfg(feature = "firmware")]
+            0xF900 => ArtCommand::OpIpProgReply(IpProgReply::from(data).map_err(|e| {
+                Error::OpcodeError {
+                    name: "IpProgReply",
+                    opcode: code,
+                    source: Box::new(e),
+                }
+            })?),
+            #[cfg(feature = "media")]
             0x9000 => ArtCommand::OpMedia,
+            #[cfg(feature = "media")]
             0x9100 => ArtCommand::OpMediaPatch,
+            #[cfg(feature = "media")]
             0x9200 => ArtCommand::OpMediaControl,
+            #[cfg(feature = "media")]
             0x9300 => ArtCommand::OpMediaControlReply,
-            0x9700 => ArtCommand::OpTimeCode,
-            0x9800 => ArtCommand::OpTimeSync,
-            0x9900 => ArtCommand::OpTrigger,
+            #[cfg(feature = "media")]
+            0x9700 => ArtCommand::OpTimeCode(Timecode::from_with(data, options).map_err(|e| {
+                Error::OpcodeError {
+                    name: "Timecode",
+                    opcode: code,
+                    source: Box::new(e),
+                }
+            })?),
+            #[cfg(feature = "media")]
+            0x9800 => ArtCommand::OpTimeSync(TimeSync::from_with(data, options).map_err(|e| {
+                Error::OpcodeError {
+                    name: "TimeSync",
+                    opcode: code,
+                    source: Box::new(e),
+                }
+            })?),
+            #[cfg(feature = "media")]
+            0x9900 => ArtCommand::OpTrigger(Box::new(Trigger::from(data).map_err(|e| {
+                Error::OpcodeError {
+                    name: "ArtTrigger",
+                    opcode: code,
+                    source: Box::new(e),
+                }
+            })?)),
+            #[cfg(feature = "firmware")]
             0x9A00 => ArtCommand::OpDirectory,
+            #[cfg(feature = "firmware")]
             0x9B00 => ArtCommand::OpDirectoryReply,
             _ => return Err(Error::UnknownOpcode(code)),
         })
     }
 
-    fn get_opcode(&self) -> Result<(u16, Vec<u8>)> {
-        Ok(match self {
-            ArtCommand::Poll(poll) => (0x2000, poll.to_bytes()?),
-            ArtCommand::PollReply(reply) => (0x2100, reply.to_bytes()?),
-            ArtCommand::DiagData => (0x2300, Vec::new()),
-            ArtCommand::Command => (0x2400, Vec::new()),
-            ArtCommand::Output(output) => (0x5000, output.to_bytes()?),
-            ArtCommand::Nzs => (0x5100, Vec::new()),
-            ArtCommand::Sync => (0x5200, Vec::new()),
-            ArtCommand::Address => (0x6000, Vec::new()),
-            ArtCommand::Input => (0x7000, Vec::new()),
-            ArtCommand::TodRequest => (0x8000, Vec::new()),
-            ArtCommand::TodData => (0x8100, Vec::new()),
-            ArtCommand::TodControl => (0x8200, Vec::new()),
-            ArtCommand::Rdm => (0x8300, Vec::new()),
-            ArtCommand::RdmSub => (0x8400, Vec::new()),
-            ArtCommand::VideoSetup => (0xA010, Vec::new()),
-            ArtCommand::VideoPalette => (0xA020, Vec::new()),
-            ArtCommand::VideoData => (0xA040, Vec::new()),
-            ArtCommand::MacMaster => (0xF000, Vec::new()),
-            ArtCommand::MacSlave => (0xF100, Vec::new()),
-            ArtCommand::FirmwareMaster => (0xF200, Vec::new()),
-            ArtCommand::FirmwareReply => (0xF300, Vec::new()),
-            ArtCommand::FileTnMaster => (0xF400, Vec::new()),
-            ArtCommand::FileFnMaster => (0xF500, Vec::new()),
-            ArtCommand::FileFnReply => (0xF600, Vec::new()),
-            ArtCommand::OpIpProg => (0xF800, Vec::new()),
-            ArtCommand::OpIpProgReply => (0xF900, Vec::new()),
-            ArtCommand::OpMedia => (0x9000, Vec::new()),
-            ArtCommand::OpMediaPatch => (0x9100, Vec::new()),
-            ArtCommand::OpMediaControl => (0x9200, Vec::new()),
-            ArtCommand::OpMediaControlReply => (0x9300, Vec::new()),
-            ArtCommand::OpTimeCode => (0x9700, Vec::new()),
-            ArtCommand::OpTimeSync => (0x9800, Vec::new()),
-            ArtCommand::OpTrigger => (0x9900, Vec::new()),
-            ArtCommand::OpDirectory => (0x9A00, Vec::new()),
-            ArtCommand::OpDirectoryReply => (0x9B00, Vec::new()),
+    /// Returns the poll if this is an `ArtCommand::Poll`
+    pub fn as_poll(&self) -> Option<&Poll> {
+        match self {
+            ArtCommand::Poll(poll) => Some(poll),
+            _ => None,
+        }
+    }
+
+    /// Returns the reply if this is an `ArtCommand::PollReply`
+    pub fn as_poll_reply(&self) -> Option<&PollReply> {
+        match self {
+            ArtCommand::PollReply(reply) => Some(reply),
+            _ => None,
+        }
+    }
+
+    /// Returns the output if this is an `ArtCommand::Output`
+    pub fn as_output(&self) -> Option<&Output> {
+        match self {
+            ArtCommand::Output(output) => Some(output),
+            _ => None,
+        }
+    }
+
+    /// Returns the timecode if this is an `ArtCommand::OpTimeCode`
+    #[cfg(feature = "media")]
+    pub fn as_timecode(&self) -> Option<&Timecode> {
+        match self {
+            ArtCommand::OpTimeCode(time_code) => Some(time_code),
+            _ => None,
+        }
+    }
+
+    /// The opcode of this command
+    pub fn opcode(&self) -> u16 {
+        match self {
+            ArtCommand::Poll(_) => 0x2000,
+            ArtCommand::PollReply(_) => 0x2100,
+            #[cfg(feature = "dmx")]
+            ArtCommand::DiagData(_) => 0x2300,
+            #[cfg(feature = "dmx")]
+            ArtCommand::Command(_) => 0x2400,
+            ArtCommand::Output(_) => 0x5000,
+            #[cfg(feature = "dmx")]
+            ArtCommand::Nzs(_) => 0x5100,
+            ArtCommand::Sync(_) => 0x5200,
+            #[cfg(feature = "dmx")]
+            ArtCommand::Address(_) => 0x6000,
+            #[cfg(feature = "dmx")]
+            ArtCommand::Input(_) => 0x7000,
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodRequest(_) => 0x8000,
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodData(_) => 0x8100,
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodControl(_) => 0x8200,
+            #[cfg(feature = "rdm")]
+            ArtCommand::Rdm(_) => 0x8300,
+            #[cfg(feature = "rdm")]
+            ArtCommand::RdmSub => 0x8400,
+            #[cfg(feature = "video")]
+            ArtCommand::VideoSetup => 0xA010,
+            #[cfg(feature = "video")]
+            ArtCommand::VideoPalette => 0xA020,
+            #[cfg(feature = "video")]
+            ArtCommand::VideoData => 0xA040,
+            #[cfg(feature = "video")]
+            ArtCommand::MacMaster => 0xF000,
+            #[cfg(feature = "video")]
+            ArtCommand::MacSlave => 0xF100,
+            #[cfg(feature = "firmware")]
+            ArtCommand::FirmwareMaster(_) => 0xF200,
+            #[cfg(feature = "firmware")]
+            ArtCommand::FirmwareReply(_) => 0xF300,
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileTnMaster(_) => 0xF400,
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileFnMaster(_) => 0xF500,
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileFnReply(_) => 0xF600,
+            #[cfg(feature = "firmware")]
+            ArtCommand::OpIpProg(_) => 0xF800,
+            #[cfg(feature = "firmware")]
+            ArtCommand::OpIpProgReply(_) => 0xF900,
+            #[cfg(feature = "media")]
+            ArtCommand::OpMedia => 0x9000,
+            #[cfg(feature = "media")]
+            ArtCommand::OpMediaPatch => 0x9100,
+            #[cfg(feature = "media")]
+            ArtCommand::OpMediaControl => 0x9200,
+            #[cfg(feature = "media")]
+            ArtCommand::OpMediaControlReply => 0x9300,
+            #[cfg(feature = "media")]
+            ArtCommand::OpTimeCode(_) => 0x9700,
+            #[cfg(feature = "media")]
+            ArtCommand::OpTimeSync(_) => 0x9800,
+            #[cfg(feature = "media")]
+            ArtCommand::OpTrigger(_) => 0x9900,
+            #[cfg(feature = "firmware")]
+            ArtCommand::OpDirectory => 0x9A00,
+            #[cfg(feature = "firmware")]
+            ArtCommand::OpDirectoryReply => 0x9B00,
+        }
+    }
+
+    /// The name of this command as used in the Art-Net specification, e.g. `"ArtDmx"` for `ArtCommand::Output`
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArtCommand::Poll(_) => "ArtPoll",
+            ArtCommand::PollReply(_) => "ArtPollReply",
+            #[cfg(feature = "dmx")]
+            ArtCommand::DiagData(_) => "ArtDiagData",
+            #[cfg(feature = "dmx")]
+            ArtCommand::Command(_) => "ArtCommand",
+            ArtCommand::Output(_) => "ArtDmx",
+            #[cfg(feature = "dmx")]
+            ArtCommand::Nzs(_) => "ArtNzs",
+            ArtCommand::Sync(_) => "ArtSync",
+            #[cfg(feature = "dmx")]
+            ArtCommand::Address(_) => "ArtAddress",
+            #[cfg(feature = "dmx")]
+            ArtCommand::Input(_) => "ArtInput",
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodRequest(_) => "ArtTodRequest",
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodData(_) => "ArtTodData",
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodControl(_) => "ArtTodControl",
+            #[cfg(feature = "rdm")]
+            ArtCommand::Rdm(_) => "ArtRdm",
+            #[cfg(feature = "rdm")]
+            ArtCommand::RdmSub => "ArtRdmSub",
+            #[cfg(feature = "video")]
+            ArtCommand::VideoSetup => "ArtVideoSetup",
+            #[cfg(feature = "video")]
+            ArtCommand::VideoPalette => "ArtVideoPalette",
+            #[cfg(feature = "video")]
+            ArtCommand::VideoData => "ArtVideoData",
+            #[cfg(feature = "video")]
+            ArtCommand::MacMaster => "ArtMacMaster",
+            #[cfg(feature = "video")]
+            ArtCommand::MacSlave => "ArtMacSlave",
+            #[cfg(feature = "firmware")]
+            ArtCommand::FirmwareMaster(_) => "ArtFirmwareMaster",
+            #[cfg(feature = "firmware")]
+            ArtCommand::FirmwareReply(_) => "ArtFirmwareReply",
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileTnMaster(_) => "ArtFileTnMaster",
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileFnMaster(_) => "ArtFileFnMaster",
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileFnReply(_) => "ArtFileFnReply",
+            #[cfg(feature = "firmware")]
+            ArtCommand::OpIpProg(_) => "ArtIpProg",
+            #[cfg(feature = "firmware")]
+            ArtCommand::OpIpProgReply(_) => "ArtIpProgReply",
+            #[cfg(feature = "media")]
+            ArtCommand::OpMedia => "ArtMedia",
+            #[cfg(feature = "media")]
+            ArtCommand::OpMediaPatch => "ArtMediaPatch",
+            #[cfg(feature = "media")]
+            ArtCommand::OpMediaControl => "ArtMediaControl",
+            #[cfg(feature = "media")]
+            ArtCommand::OpMediaControlReply => "ArtMediaControlReply",
+            #[cfg(feature = "media")]
+            ArtCommand::OpTimeCode(_) => "ArtTimeCode",
+            #[cfg(feature = "media")]
+            ArtCommand::OpTimeSync(_) => "ArtTimeSync",
+            #[cfg(feature = "media")]
+            ArtCommand::OpTrigger(_) => "ArtTrigger",
+            #[cfg(feature = "firmware")]
+            ArtCommand::OpDirectory => "ArtDirectory",
+            #[cfg(feature = "firmware")]
+            ArtCommand::OpDirectoryReply => "ArtDirectoryReply",
+        }
+    }
+
+    /// Whether this command should be broadcast or unicast, see [DeliveryPolicy](enum.DeliveryPolicy.html)
+    pub fn delivery_policy(&self) -> DeliveryPolicy {
+        match self {
+            ArtCommand::Poll(_) | ArtCommand::Sync(_) => DeliveryPolicy::Broadcast,
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodRequest(_) => DeliveryPolicy::Broadcast,
+            ArtCommand::Output(_) => DeliveryPolicy::Unicast,
+            #[cfg(feature = "dmx")]
+            ArtCommand::Nzs(_) | ArtCommand::Address(_) | ArtCommand::Input(_) => {
+                DeliveryPolicy::Unicast
+            }
+            #[cfg(feature = "rdm")]
+            ArtCommand::Rdm(_) | ArtCommand::RdmSub => DeliveryPolicy::Unicast,
+            #[cfg(feature = "firmware")]
+            ArtCommand::FirmwareMaster(_)
+            | ArtCommand::FirmwareReply(_)
+            | ArtCommand::OpIpProg(_)
+            | ArtCommand::OpIpProgReply(_) => DeliveryPolicy::Unicast,
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileTnMaster(_)
+            | ArtCommand::FileFnMaster(_)
+            | ArtCommand::FileFnReply(_) => DeliveryPolicy::Unicast,
+            _ => DeliveryPolicy::Any,
+        }
+    }
+
+    /// If this command may be sent to a broadcast address. False for commands that must be unicast, e.g. ArtDmx in Art-Net 4.
+    pub fn is_broadcast_allowed(&self) -> bool {
+        self.delivery_policy().allows(true)
+    }
+
+    /// If this command must be sent to the unicast address of a single device, e.g. ArtDmx and ArtAddress
+    pub fn must_unicast(&self) -> bool {
+        self.delivery_policy() == DeliveryPolicy::Unicast
+    }
+
+    /// Write the fields of the command, after the header and opcode
+    fn write_body(&self, buffer: &mut dyn WriteBuffer) -> Result<()> {
+        match self {
+            ArtCommand::Poll(poll) => poll.write_to(buffer),
+            ArtCommand::PollReply(reply) => reply.write_to(buffer),
+            ArtCommand::Output(output) => output.write_to(buffer),
+            ArtCommand::Sync(sync) => sync.write_to(buffer),
+            #[cfg(feature = "dmx")]
+            ArtCommand::Address(address) => address.write_to(buffer),
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodRequest(request) => request.write_to(buffer),
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodData(tod) => tod.write_to(buffer),
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodControl(control) => control.write_to(buffer),
+            #[cfg(feature = "rdm")]
+            ArtCommand::Rdm(rdm) => rdm.write_to(buffer),
+            #[cfg(feature = "firmware")]
+            ArtCommand::FirmwareMaster(master) => master.write_to(buffer),
+            #[cfg(feature = "firmware")]
+            ArtCommand::FirmwareReply(reply) => reply.write_to(buffer),
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileTnMaster(master) => master.write_to(buffer),
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileFnMaster(master) => master.write_to(buffer),
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileFnReply(reply) => reply.write_to(buffer),
+            #[cfg(feature = "dmx")]
+            ArtCommand::Nzs(nzs) => nzs.write_to(buffer),
+            #[cfg(feature = "media")]
+            ArtCommand::OpTimeCode(time_code) => time_code.write_to(buffer),
+            #[cfg(feature = "media")]
+            ArtCommand::OpTimeSync(time_sync) => time_sync.write_to(buffer),
+            #[cfg(feature = "dmx")]
+            ArtCommand::Command(command) => command.write_to(buffer),
+            #[cfg(feature = "dmx")]
+            ArtCommand::DiagData(diag_data) => diag_data.write_to(buffer),
+            #[cfg(feature = "firmware")]
+            ArtCommand::OpIpProg(ip_prog) => ip_prog.write_to(buffer),
+            #[cfg(feature = "firmware")]
+            ArtCommand::OpIpProgReply(reply) => reply.write_to(buffer),
+            #[cfg(feature = "media")]
+            ArtCommand::OpTrigger(trigger) => trigger.write_to(buffer),
+            #[cfg(feature = "dmx")]
+            ArtCommand::Input(input) => input.write_to(buffer),
+            // Commands without a body. There are none when the optional opcode families are disabled
+            #[allow(unreachable_patterns)]
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Emit an event for a message that could not be parsed. Non-Art-Net traffic is common, so that is only traced.
+#[cfg(feature = "tracing")]
+fn trace_parse_error(error: &Error) {
+    match error {
+        Error::InvalidArtnetHeader { len } => {
+            tracing::trace!(len, "ignoring a message without an Art-Net header")
+        }
+        Error::MessageTooShort { len, min_len } => {
+            tracing::debug!(len, min_len, "truncated message")
+        }
+        Error::UnknownOpcode(opcode) => tracing::debug!(opcode, "unknown opcode"),
+        Error::UnsupportedProtocolVersion {
+            version,
+            min_version,
+        } => tracing::debug!(version, min_version, "unsupported protocol version"),
+        error => tracing::debug!(%error, offset = error.offset(), "could not parse message"),
+    }
+}
+
+impl core::convert::TryFrom<&[u8]> for ArtCommand {
+    type Error = Error;
+
+    fn try_from(buffer: &[u8]) -> Result<ArtCommand> {
+        ArtCommand::from_buffer(buffer)
+    }
+}
+
+impl From<Poll> for ArtCommand {
+    fn from(poll: Poll) -> ArtCommand {
+        ArtCommand::Poll(poll)
+    }
+}
+
+impl From<PollReply> for ArtCommand {
+    fn from(reply: PollReply) -> ArtCommand {
+        ArtCommand::PollReply(Box::new(reply))
+    }
+}
+
+impl From<Box<PollReply>> for ArtCommand {
+    fn from(reply: Box<PollReply>) -> ArtCommand {
+        ArtCommand::PollReply(reply)
+    }
+}
+
+impl From<Output> for ArtCommand {
+    fn from(output: Output) -> ArtCommand {
+        ArtCommand::Output(output)
+    }
+}
+
+impl From<ArtSync> for ArtCommand {
+    fn from(sync: ArtSync) -> ArtCommand {
+        ArtCommand::Sync(sync)
+    }
+}
+
+#[cfg(feature = "dmx")]
+impl From<Address> for ArtCommand {
+    fn from(address: Address) -> ArtCommand {
+        ArtCommand::Address(Box::new(address))
+    }
+}
+
+#[cfg(feature = "rdm")]
+impl From<TodRequest> for ArtCommand {
+    fn from(request: TodRequest) -> ArtCommand {
+        ArtCommand::TodRequest(Box::new(request))
+    }
+}
+
+#[cfg(feature = "rdm")]
+impl From<TodData> for ArtCommand {
+    fn from(tod: TodData) -> ArtCommand {
+        ArtCommand::TodData(Box::new(tod))
+    }
+}
+
+#[cfg(feature = "rdm")]
+impl From<TodControl> for ArtCommand {
+    fn from(control: TodControl) -> ArtCommand {
+        ArtCommand::TodControl(control)
+    }
+}
+
+#[cfg(feature = "rdm")]
+impl From<Rdm> for ArtCommand {
+    fn from(rdm: Rdm) -> ArtCommand {
+        ArtCommand::Rdm(Box::new(rdm))
+    }
+}
+
+#[cfg(feature = "firmware")]
+impl From<FirmwareMaster> for ArtCommand {
+    fn from(master: FirmwareMaster) -> ArtCommand {
+        ArtCommand::FirmwareMaster(Box::new(master))
+    }
+}
+
+#[cfg(feature = "firmware")]
+impl From<FirmwareReply> for ArtCommand {
+    fn from(reply: FirmwareReply) -> ArtCommand {
+        ArtCommand::FirmwareReply(Box::new(reply))
+    }
+}
+
+#[cfg(feature = "nonstandard-file-transfer")]
+impl From<FileTnMaster> for ArtCommand {
+    fn from(master: FileTnMaster) -> ArtCommand {
+        ArtCommand::FileTnMaster(Box::new(master))
+    }
+}
+
+#[cfg(feature = "nonstandard-file-transfer")]
+impl From<FileFnMaster> for ArtCommand {
+    fn from(master: FileFnMaster) -> ArtCommand {
+        ArtCommand::FileFnMaster(Box::new(master))
+    }
+}
+
+#[cfg(feature = "nonstandard-file-transfer")]
+impl From<FileFnReply> for ArtCommand {
+    fn from(reply: FileFnReply) -> ArtCommand {
+        ArtCommand::FileFnReply(Box::new(reply))
+    }
+}
+
+#[cfg(feature = "dmx")]
+impl From<Nzs> for ArtCommand {
+    fn from(nzs: Nzs) -> ArtCommand {
+        ArtCommand::Nzs(Box::new(nzs))
+    }
+}
+
+#[cfg(feature = "media")]
+impl From<Timecode> for ArtCommand {
+    fn from(time_code: Timecode) -> ArtCommand {
+        ArtCommand::OpTimeCode(time_code)
+    }
+}
+
+#[cfg(feature = "media")]
+impl From<TimeSync> for ArtCommand {
+    fn from(time_sync: TimeSync) -> ArtCommand {
+        ArtCommand::OpTimeSync(time_sync)
+    }
+}
+
+#[cfg(feature = "dmx")]
+impl From<TextCommand> for ArtCommand {
+    fn from(command: TextCommand) -> ArtCommand {
+        ArtCommand::Command(Box::new(command))
+    }
+}
+
+#[cfg(feature = "dmx")]
+impl From<DiagData> for ArtCommand {
+    fn from(diag_data: DiagData) -> ArtCommand {
+        ArtCommand::DiagData(Box::new(diag_data))
+    }
+}
+
+#[cfg(feature = "firmware")]
+impl From<IpProg> for ArtCommand {
+    fn from(ip_prog: IpProg) -> ArtCommand {
+        ArtCommand::OpIpProg(ip_prog)
+    }
+}
+
+#[cfg(feature = "firmware")]
+impl From<IpProgReply> for ArtCommand {
+    fn from(reply: IpProgReply) -> ArtCommand {
+        ArtCommand::OpIpProgReply(reply)
+    }
+}
+
+#[cfg(feature = "media")]
+impl From<Trigger> for ArtCommand {
+    fn from(trigger: Trigger) -> ArtCommand {
+        ArtCommand::OpTrigger(Box::new(trigger))
+    }
+}
+
+#[cfg(feature = "dmx")]
+impl From<Input> for ArtCommand {
+    fn from(input: Input) -> ArtCommand {
+        ArtCommand::Input(input)
+    }
+}
+
+impl core::fmt::Display for ArtCommand {
+    /// A one-line summary of the command, e.g. `ArtDmx (0x5000) to Port-Address 1, 512 bytes, sequence 3`
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(fmt, "{} (0x{:04X})", self.name(), self.opcode())?;
+        match self {
+            ArtCommand::Poll(poll) => write!(
+                fmt,
+                ", talk to me {:?}, diagnostics priority 0x{:02X}",
+                poll.talk_to_me, poll.diagnostics_priority
+            ),
+            ArtCommand::PollReply(reply) => write!(
+                fmt,
+                " from {}:{}, {:?}",
+                reply.address,
+                reply.port,
+                poll_reply::null_terminated(&reply.short_name)
+            ),
+            ArtCommand::Output(output) => write!(
+                fmt,
+                " to Port-Address {}, {} bytes, sequence {}",
+                u16::from(output.port_address),
+                output.data.as_ref().len(),
+                output.sequence
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A command with the bytes that the sender appended after the message, see `ArtCommand::from_buffer_with_extra`
+#[derive(Debug)]
+pub struct ArtPacket {
+    /// The command that was parsed
+    pub command: ArtCommand,
+    /// The bytes after the message, empty if there are none
+    pub extra: Vec<u8>,
+}
+
+impl ArtPacket {
+    /// Convert the command and the extra bytes back into a byte buffer
+    pub fn write_to_buffer(&self) -> Result<Vec<u8>> {
+        let mut result = Vec::with_capacity(self.command.serialized_len() + self.extra.len());
+        self.command.write_to(&mut result)?;
+        result.extend_from_slice(&self.extra);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    /// Counts the allocations of every thread, so a test can check how often parsing allocates
+    struct CountingAllocator;
+
+    std::thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            // The counter is gone while the thread is torn down
+            let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    /// The number of allocations of this thread while running `f`
+    fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+        let before = ALLOCATIONS.with(Cell::get);
+        let result = f();
+        let after = ALLOCATIONS.with(Cell::get);
+        drop(result);
+        after - before
+    }
+
+    #[test]
+    fn parse_allocations() {
+        let output = Output::new(1.into(), vec![0xAB; 512])
+            .into_command()
+            .write_to_buffer()
+            .unwrap();
+        // Only the data is allocated
+        assert_eq!(allocations(|| ArtCommand::from_buffer(&output).unwrap()), 1);
+
+        // Errors do not copy the message
+        assert_eq!(allocations(|| ArtCommand::from_buffer(&output[..12])), 0);
+        let mut invalid = output.clone();
+        invalid[0] = b'X';
+        assert_eq!(allocations(|| ArtCommand::from_buffer(&invalid)), 0);
+    }
+
+    #[test]
+    fn display() {
+        let command = ArtCommand::Output(Output {
+            data: vec![0; 512].into(),
+            sequence: 3,
+            ..Output::default()
+        });
+        assert_eq!(command.name(), "ArtDmx");
+        assert_eq!(
+            command.to_string(),
+            "ArtDmx (0x5000) to Port-Address 1, 512 bytes, sequence 3"
+        );
+
+        let mut reply = PollReply {
+            address: [10, 0, 0, 5].into(),
+            ..PollReply::default()
+        };
+        reply.short_name[..4].copy_from_slice(b"Node");
+        assert_eq!(
+            ArtCommand::PollReply(Box::new(reply)).to_string(),
+            "ArtPollReply (0x2100) from 10.0.0.5:6454, \"Node\""
+        );
+
+        assert_eq!(
+            ArtSync::default().into_command().to_string(),
+            "ArtSync (0x5200)"
+        );
+    }
+
+    #[test]
+    fn truncated_poll_reply() {
+        assert_eq!(
+            ArtCommand::min_message_length(0x2100),
+            Some(PollReply::MIN_PACKET_SIZE)
+        );
+        let mut reply = PollReply::default();
+        reply.short_name[..4].copy_from_slice(b"Node");
+        let buffer = ArtCommand::PollReply(Box::new(reply))
+            .write_to_buffer()
+            .unwrap();
+        for len in [207, 238] {
+            for options in [ParseOptions::lenient(), ParseOptions::strict()] {
+                let command = ArtCommand::from_buffer_with(&buffer[..len], &options).unwrap();
+                let reply = command.as_poll_reply().unwrap();
+                assert_eq!(&reply.short_name[..4], b"Node");
+                assert!(reply.port_is_standard());
+            }
+        }
+    }
+
+    #[test]
+    fn extra_bytes() {
+        let mut buffer = ArtCommand::Output(Output {
+            data: vec![1, 2].into(),
+            ..Output::default()
         })
+        .write_to_buffer()
+        .unwrap();
+        buffer.extend_from_slice(&[0xAA, 0xBB]);
+        let packet = ArtCommand::from_buffer_with_extra(&buffer, &ParseOptions::default()).unwrap();
+        assert_eq!(
+            packet.command.as_output().unwrap().data.as_ref(),
+            &vec![1, 2]
+        );
+        assert_eq!(packet.extra, vec![0xAA, 0xBB]);
+        assert_eq!(packet.write_to_buffer().unwrap(), buffer);
+
+        // A truncated message has no extra bytes
+        let reply = ArtCommand::PollReply(Box::default())
+            .write_to_buffer()
+            .unwrap();
+        let packet =
+            ArtCommand::from_buffer_with_extra(&reply[..220], &ParseOptions::default()).unwrap();
+        assert!(packet.extra.is_empty());
+    }
+
+    #[test]
+    fn extra_bytes_after_data() {
+        let input = [
+            65, 114, 116, 45, 78, 101, 116, 0, 0, 80, 0, 14, 0, 0, 1, 0, 0, 2, 1, 2, 3, 4,
+        ];
+        let packet = ArtCommand::from_buffer_with_extra(&input, &ParseOptions::default()).unwrap();
+        assert_eq!(&packet.command.as_output().unwrap().data[..], &[1, 2]);
+        assert_eq!(packet.extra, vec![3, 4]);
+        assert_eq!(packet.write_to_buffer().unwrap(), input);
+
+        #[cfg(feature = "dmx")]
+        {
+            let mut input = Nzs {
+                start_code: 0x17,
+                data: vec![1, 2].into(),
+                ..Nzs::default()
+            }
+            .into_command()
+            .write_to_buffer()
+            .unwrap();
+            input.extend_from_slice(b"vendor");
+            let packet =
+                ArtCommand::from_buffer_with_extra(&input, &ParseOptions::default()).unwrap();
+            assert_eq!(packet.extra, b"vendor");
+            assert_eq!(packet.write_to_buffer().unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn delivery_policy() {
+        let poll = ArtCommand::Poll(Poll::default());
+        assert_eq!(poll.delivery_policy(), DeliveryPolicy::Broadcast);
+        assert!(poll.delivery_policy().allows(true));
+        assert!(!poll.delivery_policy().allows(false));
+
+        let output = ArtCommand::Output(Output::default());
+        assert_eq!(output.delivery_policy(), DeliveryPolicy::Unicast);
+        assert!(!output.delivery_policy().allows(true));
+
+        let reply = ArtCommand::PollReply(Box::default());
+        assert_eq!(reply.delivery_policy(), DeliveryPolicy::Any);
+        assert!(reply.delivery_policy().allows(true));
+
+        assert!(poll.is_broadcast_allowed());
+        assert!(!poll.must_unicast());
+        assert!(!output.is_broadcast_allowed());
+        assert!(output.must_unicast());
+        assert!(reply.is_broadcast_allowed());
+        assert!(!reply.must_unicast());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_round_trip() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        // A simple xorshift, so we don't need a dependency on `rand`
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        let mut bytes = vec![0u8; 4096];
+        let mut round_trips = 0;
+        for _ in 0..256 {
+            for byte in bytes.iter_mut() {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                *byte = state as u8;
+            }
+            // Parsing random data should never panic
+            let _ = ArtCommand::from_buffer(&bytes[..(state % 600) as usize]);
+
+            let mut unstructured = Unstructured::new(&bytes);
+            let command = ArtCommand::arbitrary(&mut unstructured).unwrap();
+            let opcode = command.opcode();
+            if let Ok(buffer) = command.write_to_buffer() {
+                // Commands that are not implemented are written without a body, which is too short to parse
+                if buffer.len() == HEADER_LENGTH {
+                    continue;
+                }
+                let parsed = ArtCommand::from_buffer(&buffer)
+                    .unwrap_or_else(|e| panic!("{:?} does not parse: {:?}", buffer, e));
+                assert_eq!(parsed.opcode(), opcode);
+                round_trips += 1;
+            }
+        }
+        // Make sure that most commands were checked, not skipped
+        assert!(
+            round_trips > 128,
+            "only {} commands round-tripped",
+            round_trips
+        );
+    }
+
+    #[test]
+    fn into_command() {
+        let bytes = Output::new(3.into(), vec![1, 2])
+            .into_command()
+            .write_to_buffer()
+            .unwrap();
+        assert_eq!(&bytes[14..16], &[3, 0]);
+
+        let command: ArtCommand = Poll::default().into();
+        assert_eq!(command.name(), "ArtPoll");
+        let command: ArtCommand = PollReply::default().into();
+        assert_eq!(command.name(), "ArtPollReply");
+    }
+
+    #[test]
+    fn try_from_slice() {
+        use core::convert::TryFrom;
+
+        let bytes = Output::new(1.into(), vec![1, 2])
+            .into_command()
+            .write_to_buffer()
+            .unwrap();
+        let command = ArtCommand::try_from(&bytes[..]).unwrap();
+        assert_eq!(command.as_output().unwrap().data.as_ref(), &vec![1, 2]);
+        assert!(command.as_poll().is_none());
+        assert!(command.as_poll_reply().is_none());
+        #[cfg(feature = "media")]
+        {
+            assert!(command.as_timecode().is_none());
+            let bytes = ArtCommand::OpTimeCode(Timecode::default())
+                .write_to_buffer()
+                .unwrap();
+            let command = ArtCommand::try_from(&bytes[..]).unwrap();
+            assert_eq!(command.as_timecode(), Some(&Timecode::default()));
+        }
+
+        assert!(ArtCommand::try_from(&bytes[..4]).is_err());
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn size() {
+        #[cfg(not(feature = "bytes"))]
+        {
+            assert_eq!(core::mem::size_of::<Output>(), 32);
+            assert_eq!(core::mem::size_of::<ArtCommand>(), 32);
+        }
+        // A `Bytes` takes one word more than a `Vec`, and has no niche for the other commands
+        #[cfg(feature = "bytes")]
+        {
+            assert_eq!(core::mem::size_of::<Output>(), 40);
+            assert_eq!(core::mem::size_of::<ArtCommand>(), 48);
+        }
+    }
+
+    #[test]
+    fn serialized_len() {
+        let commands = vec![
+            ArtCommand::Poll(Poll::default()),
+            ArtCommand::PollReply(Box::default()),
+            Output::new(1.into(), vec![1, 2, 3]).into_command(),
+            ArtSync::default().into_command(),
+        ];
+        for command in commands {
+            let len = command.serialized_len();
+            let buffer = command.write_to_buffer().unwrap();
+            assert_eq!(buffer.len(), len);
+            assert_eq!(buffer.capacity(), len);
+        }
+    }
+
+    #[cfg(all(feature = "tracing", feature = "std"))]
+    #[test]
+    fn tracing_events() {
+        use std::sync::{Arc, Mutex};
+        use tracing::{span, Event, Level, Metadata, Subscriber};
+
+        /// Records the level of every event
+        struct Recorder(Arc<Mutex<Vec<Level>>>);
+
+        impl Subscriber for Recorder {
+            fn enabled(&self, _: &Metadata) -> bool {
+                true
+            }
+            fn new_span(&self, _: &span::Attributes) -> span::Id {
+                span::Id::from_u64(1)
+            }
+            fn record(&self, _: &span::Id, _: &span::Record) {}
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+            fn event(&self, event: &Event) {
+                self.0.lock().unwrap().push(*event.metadata().level());
+            }
+            fn enter(&self, _: &span::Id) {}
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let levels = Arc::new(Mutex::new(Vec::new()));
+        tracing::subscriber::with_default(Recorder(levels.clone()), || {
+            let mut message = ARTNET_HEADER.to_vec();
+            message.extend_from_slice(&[0x00, 0x21, 0, 14]);
+            assert!(ArtCommand::from_buffer(&message).is_err());
+            assert!(ArtCommand::from_buffer(b"not an art-net message").is_err());
+            assert!(ArtCommand::Output(Output::default())
+                .write_to_buffer()
+                .is_err());
+        });
+        assert_eq!(
+            *levels.lock().unwrap(),
+            vec![Level::DEBUG, Level::TRACE, Level::WARN]
+        );
+    }
+
+    #[cfg(not(feature = "rdm"))]
+    #[test]
+    fn disabled_family() {
+        let mut message = ArtSync::default().into_command().write_to_buffer().unwrap();
+        message[8..10].copy_from_slice(&0x8200u16.to_le_bytes());
+        message.extend_from_slice(&[0; 14]);
+        assert!(matches!(
+            ArtCommand::from_buffer(&message),
+            Err(Error::UnknownOpcode(0x8200))
+        ));
+    }
+
+    #[cfg(feature = "rdm")]
+    #[test]
+    fn opcode_round_trip() {
+        let command =
+            ArtCommand::TodControl(TodControl::flush(crate::PortAddress::from_parts(1, 0x23)));
+        let bytes = command.write_to_buffer().unwrap();
+        let command = ArtCommand::from_buffer(&bytes).unwrap();
+        assert_eq!(command.opcode(), 0x8200);
+        assert_eq!(command.name(), "ArtTodControl");
     }
 }
@@ -1,12 +1,22 @@
+#[cfg(feature = "dmx")]
+mod nzs;
 #[cfg(test)]
 mod tests;
 
-use crate::{command::ARTNET_PROTOCOL_VERSION, convert::Convertable, Error, PortAddress, Result};
-use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
-use std::io::Cursor;
+#[cfg(feature = "dmx")]
+pub use self::nzs::Nzs;
+
+use crate::{
+    command::ARTNET_PROTOCOL_VERSION,
+    convert::{Convertable, Cursor, WriteBuffer},
+    Error, ParseOptions, PortAddress, Result,
+};
+use alloc::vec::Vec;
 
 data_structure! {
+    @test
     #[derive(Debug)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
     #[doc = "ArtDmx is the data packet used to transfer DMX512 data. The format is identical for Node to Controller, Node to Node and Controller to Node."]
     #[doc = ""]
     #[doc = "The Data is output through the DMX O/P port corresponding to the Universe setting. In the absence of received ArtDmx packets, each DMX O/P port re-transmits the same frame continuously. "]
@@ -49,10 +59,71 @@ impl Default for Output {
     }
 }
 
-#[derive(Default)]
+impl Output {
+    /// The size of an ArtDmx message with 512 bytes of data, including the Art-Net header and opcode
+    pub const MAX_PACKET_SIZE: usize = 530;
+
+    /// Create an ArtDmx message with the given data for the given Port-Address. All other fields are set to their default value.
+    pub fn new(port_address: PortAddress, data: impl Into<PaddedData>) -> Output {
+        Output {
+            port_address,
+            data: data.into(),
+            ..Output::default()
+        }
+    }
+
+    /// Wrap this message in an `ArtCommand::Output`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+
+    pub(crate) fn from_with(data: &[u8], options: &ParseOptions) -> Result<Output> {
+        Output::check_length(Output::from(data)?, options)
+    }
+
+    /// The length of the fields before the data, excluding the Art-Net header and opcode
+    #[cfg(feature = "bytes")]
+    pub(crate) const HEADER_LENGTH: usize = 8;
+
+    /// Check the length field against the data, or truncate the data to it when normalizing
+    pub(crate) fn check_length(mut output: Output, options: &ParseOptions) -> Result<Output> {
+        let length = *output.length as usize;
+        if options.is_strict() {
+            if length != output.data.len() {
+                return Err(Error::SpecViolation(
+                    "Output::length does not match the length of the data",
+                ));
+            }
+            if !(2..=512).contains(&length) || !length.is_multiple_of(2) {
+                return Err(Error::SpecViolation(
+                    "Output::length must be an even number from 2 to 512",
+                ));
+            }
+        } else if options.normalizes() && length < output.data.len() {
+            // Ignore any trailing bytes after the data
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                length,
+                trailing = output.data.len() - length,
+                "ignoring trailing bytes after the ArtDmx data"
+            );
+            output.data.inner.truncate(length);
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(feature = "bytes")]
+type Inner = bytes::Bytes;
+#[cfg(not(feature = "bytes"))]
+type Inner = Vec<u8>;
+
+#[derive(Default, Clone)]
 #[doc = "Data in an ArtDmx data packet."]
+#[doc = ""]
+#[doc = "With the `bytes` feature, the data is stored in a `bytes::Bytes`. Cloning the data is cheap then, and `ArtCommand::from_bytes` parses an ArtDmx without copying it. The data can be read as a `[u8]` in both cases, but `AsRef<Vec<u8>>` and `AsMut<Vec<u8>>` are only available without the `bytes` feature."]
 pub struct PaddedData {
-    inner: Vec<u8>,
+    inner: Inner,
 }
 
 impl PaddedData {
@@ -61,75 +132,128 @@ impl PaddedData {
     }
     fn len_rounded_up(&self) -> usize {
         let mut len = self.inner.len();
-        if len % 2 != 0 {
+        if !len.is_multiple_of(2) {
             len += 1;
         }
         len
     }
 }
 
+impl core::ops::Deref for PaddedData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.inner
+    }
+}
+
+#[cfg(not(feature = "bytes"))]
 impl AsRef<Vec<u8>> for PaddedData {
     fn as_ref(&self) -> &Vec<u8> {
         self.inner.as_ref()
     }
 }
 
+#[cfg(not(feature = "bytes"))]
 impl AsMut<Vec<u8>> for PaddedData {
     fn as_mut(&mut self) -> &mut Vec<u8> {
         self.inner.as_mut()
     }
 }
 
+#[cfg(feature = "bytes")]
+impl AsRef<[u8]> for PaddedData {
+    fn as_ref(&self) -> &[u8] {
+        &self.inner
+    }
+}
+
 impl From<Vec<u8>> for PaddedData {
+    /// With the `bytes` feature, the `Vec` is converted into a `Bytes` without copying
+    // Without the `bytes` feature this is the same type
+    #[allow(clippy::useless_conversion)]
     fn from(inner: Vec<u8>) -> Self {
+        Self {
+            inner: inner.into(),
+        }
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> From<heapless::Vec<u8, N>> for PaddedData {
+    fn from(inner: heapless::Vec<u8, N>) -> Self {
+        PaddedData::from(inner.as_slice().to_vec())
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl From<bytes::Bytes> for PaddedData {
+    /// This does not copy the data
+    fn from(inner: bytes::Bytes) -> Self {
         Self { inner }
     }
 }
 
-impl std::fmt::Debug for PaddedData {
-    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(fmt, "{:?}", self.inner)
+#[cfg(feature = "bytes")]
+impl From<PaddedData> for bytes::Bytes {
+    /// This does not copy the data
+    fn from(data: PaddedData) -> Self {
+        data.inner
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for PaddedData {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(PaddedData::from(Vec::<u8>::arbitrary(u)?))
+    }
+}
+
+impl core::fmt::Debug for PaddedData {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(fmt, "{:?}", &self.inner[..])
     }
 }
 
 impl<T> Convertable<T> for PaddedData {
-    fn from_cursor(cursor: &mut Cursor<&[u8]>) -> Result<Self> {
-        let remaining = cursor.get_ref();
-        let inner = remaining[cursor.position() as usize..].to_vec();
-        Ok(Self { inner })
+    fn from_cursor(cursor: &mut Cursor) -> Result<Self> {
+        Ok(PaddedData::from(cursor.remaining().to_vec()))
     }
 
-    fn write_to_buffer(&self, buffer: &mut Vec<u8>, _: &T) -> Result<()> {
+    fn write_to_buffer(&self, buffer: &mut dyn WriteBuffer, _: &T) -> Result<()> {
         let len = self.len();
         if len == 0 {
             // packets must be between 2 and 512 bytes, 1 gets padded up, but 0 is invalid
             return Err(Error::MessageSizeInvalid {
-                message: vec![],
+                len,
                 allowed_size: 2..512,
             });
         }
         if len > 512 {
             // packets must be between 2 and 512 bytes
-            let inner = self.inner.clone();
             return Err(Error::MessageSizeInvalid {
-                message: inner,
+                len,
                 allowed_size: 2..512,
             });
         }
 
         buffer.extend_from_slice(&self.inner[..]);
-        if len % 2 != 0 {
+        if !len.is_multiple_of(2) {
             // the data of an output needs to be an even size, so we add an additional 0-byte
+            #[cfg(feature = "tracing")]
+            tracing::trace!(len, "padding odd-length ArtDmx data");
             buffer.push(0);
         }
         Ok(())
     }
 
+    fn serialized_len(&self, _: &T) -> usize {
+        self.len_rounded_up()
+    }
+
     #[cfg(test)]
     fn get_test_value() -> Self {
-        PaddedData {
-            inner: vec![1, 2, 3, 4],
-        }
+        PaddedData::from(vec![1, 2, 3, 4])
     }
     #[cfg(test)]
     fn is_equal(&self, other: &Self) -> bool {
@@ -139,55 +263,82 @@ impl<T> Convertable<T> for PaddedData {
 
 #[derive(Default)]
 pub struct BigEndianLength<T> {
-    parsed_length: Option<u16>,
-    _pd: std::marker::PhantomData<T>,
+    /// The length that was parsed, or 0 if this was not parsed. A plain `u16` keeps `Output` (and thus `ArtCommand`) small.
+    parsed_length: u16,
+    _pd: core::marker::PhantomData<T>,
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for BigEndianLength<T> {
+    fn arbitrary(_: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        // The length is determined by the data when serializing
+        Ok(BigEndianLength {
+            parsed_length: 0,
+            _pd: core::marker::PhantomData,
+        })
+    }
 }
 
-impl<T> std::fmt::Debug for BigEndianLength<T> {
-    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
-        if let Some(len) = &self.parsed_length {
-            write!(fmt, "{}", len)
+impl<T> core::fmt::Debug for BigEndianLength<T> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        if self.parsed_length != 0 {
+            write!(fmt, "{}", self.parsed_length)
         } else {
             write!(fmt, "Unknown (set during parsing)")
         }
     }
 }
 
-impl<T> std::ops::Deref for BigEndianLength<T> {
+impl<T> core::ops::Deref for BigEndianLength<T> {
     type Target = u16;
 
     fn deref(&self) -> &u16 {
-        self.parsed_length.as_ref().unwrap_or(&0)
+        &self.parsed_length
     }
 }
 
-impl Convertable<Output> for BigEndianLength<Output> {
-    fn from_cursor(cursor: &mut std::io::Cursor<&[u8]>) -> crate::Result<Self> {
-        let length = cursor.read_u16::<BigEndian>().map_err(Error::CursorEof)?;
-        Ok(BigEndianLength {
-            parsed_length: Some(length),
-            _pd: std::marker::PhantomData,
-        })
-    }
-    fn write_to_buffer(&self, buffer: &mut Vec<u8>, context: &Output) -> crate::Result<()> {
-        let len = context.data.len_rounded_up() as u16;
-        buffer.write_u16::<BigEndian>(len).map_err(Error::CursorEof)
-    }
-    #[cfg(test)]
-    fn get_test_value() -> Self {
-        Default::default()
-    }
-    #[cfg(test)]
-    fn is_equal(&self, other: &Self) -> bool {
-        if (self.parsed_length.is_none() && other.parsed_length.is_some())
-            || (self.parsed_length.is_some() && other.parsed_length.is_none())
-        {
-            // one of the two is parsed, but the other one isn't
-            // They are not strictly equal, but we're testing for equality-after-parsing
-            // and we don't know the length beforehand
-            true
-        } else {
-            self.parsed_length == other.parsed_length
+/// Implements `Convertable` for the length of a packet that is followed by `PaddedData` in its `data` field
+macro_rules! big_endian_length {
+    ($packet:ty) => {
+        impl Convertable<$packet> for BigEndianLength<$packet> {
+            fn from_cursor(cursor: &mut Cursor) -> crate::Result<Self> {
+                let length = cursor.read_u16_be()?;
+                Ok(BigEndianLength {
+                    parsed_length: length,
+                    _pd: core::marker::PhantomData,
+                })
+            }
+            fn write_to_buffer(
+                &self,
+                buffer: &mut dyn WriteBuffer,
+                context: &$packet,
+            ) -> crate::Result<()> {
+                let len = context.data.len_rounded_up() as u16;
+                buffer.extend_from_slice(&len.to_be_bytes());
+                Ok(())
+            }
+            fn serialized_len(&self, _: &$packet) -> usize {
+                2
+            }
+            #[cfg(test)]
+            fn get_test_value() -> Self {
+                Default::default()
+            }
+            #[cfg(test)]
+            fn is_equal(&self, other: &Self) -> bool {
+                if (self.parsed_length == 0) != (other.parsed_length == 0) {
+                    // one of the two is parsed, but the other one isn't
+                    // They are not strictly equal, but we're testing for equality-after-parsing
+                    // and we don't know the length beforehand
+                    true
+                } else {
+                    self.parsed_length == other.parsed_length
+                }
+            }
         }
-    }
+    };
 }
+
+big_endian_length!(Output);
+#[cfg(feature = "dmx")]
+big_endian_length!(Nzs);
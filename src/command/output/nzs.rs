@@ -0,0 +1,166 @@
+use super::{BigEndianLength, PaddedData};
+use crate::{command::ARTNET_PROTOCOL_VERSION, Error, ParseOptions, PortAddress, Result};
+
+data_structure! {
+    @test
+    #[derive(Debug)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "ArtNzs is the data packet used to transfer DMX512 data with a non-zero start code, except RDM. The format is identical for Node to Controller, Node to Node and Controller to Node."]
+    #[doc = ""]
+    #[doc = "The Data is output through the DMX O/P port corresponding to the Universe setting, with the given start code."]
+    pub struct Nzs {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "The sequence number is used to ensure that packets are used in the correct order. This field is incremented in the range 0x01 to 0xff to allow the receiving node to resequence packets."]
+        #[doc = ""]
+        #[doc = "The Sequence field is set to 0x00 to disable this feature"]
+        pub sequence: u8,
+        #[doc = "The DMX512 start code of this packet. Must not be zero, or the RDM start code 0xCC"]
+        pub start_code: u8,
+        #[doc = "The 15 bit Port-Address to which this packet is destined"]
+        pub port_address: PortAddress,
+        #[doc = "The length of the message, set by the artnet library itself"]
+        pub length: BigEndianLength<Nzs>,
+        #[doc = "A variable length array of DMX512 data"]
+        pub data: PaddedData,
+    }
+}
+
+impl Default for Nzs {
+    fn default() -> Nzs {
+        Nzs {
+            version: ARTNET_PROTOCOL_VERSION,
+            sequence: 0,
+            start_code: Nzs::TEXT_PACKET,
+            port_address: 1.into(),
+            length: BigEndianLength::default(),
+            data: PaddedData::default(),
+        }
+    }
+}
+
+impl Nzs {
+    /// The start code of the ASCII text packets defined by ANSI E1.11
+    pub const TEXT_PACKET: u8 = 0x17;
+
+    /// The start code of a System Information Packet, as defined by ANSI E1.11
+    pub const SYSTEM_INFORMATION_PACKET: u8 = 0xCF;
+
+    /// Create an ArtNzs message with the given start code and data for the given Port-Address. All other fields are set to their default value.
+    pub fn new(port_address: PortAddress, start_code: u8, data: impl Into<PaddedData>) -> Nzs {
+        Nzs {
+            port_address,
+            start_code,
+            data: data.into(),
+            ..Nzs::default()
+        }
+    }
+
+    /// Wrap this message in an `ArtCommand::Nzs`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+
+    /// Check that the start code may be sent with ArtNzs. The DMX start code 0x00 must be sent with ArtDmx, and the RDM start code 0xCC with ArtRdm.
+    pub fn validate(&self) -> Result<()> {
+        match self.start_code {
+            0x00 | 0xCC => Err(Error::InvalidStartCode(self.start_code)),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn from_with(data: &[u8], options: &ParseOptions) -> Result<Nzs> {
+        Nzs::check_length(Nzs::from(data)?, options)
+    }
+
+    /// Check the start code and the length field against the data, or truncate the data to it when normalizing
+    pub(crate) fn check_length(mut nzs: Nzs, options: &ParseOptions) -> Result<Nzs> {
+        let length = *nzs.length as usize;
+        if options.is_strict() {
+            nzs.validate()?;
+            if length != nzs.data.len() {
+                return Err(Error::SpecViolation(
+                    "Nzs::length does not match the length of the data",
+                ));
+            }
+            if !(1..=512).contains(&length) {
+                return Err(Error::SpecViolation("Nzs::length must be from 1 to 512"));
+            }
+        } else if options.normalizes() && length < nzs.data.len() {
+            // Ignore any trailing bytes after the data
+            nzs.data.inner.truncate(length);
+        }
+        Ok(nzs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArtCommand;
+    use alloc::vec;
+
+    #[test]
+    fn text_packet() {
+        let command = Nzs::new(
+            PortAddress::from_parts(1, 0x23),
+            Nzs::TEXT_PACKET,
+            vec![b'h', b'i', 0],
+        )
+        .into_command();
+        let bytes = command.write_to_buffer().unwrap();
+        assert_eq!(
+            bytes,
+            vec![
+                65, 114, 116, 45, 78, 101, 116, 0, 0, 81, 0, 14, 0, 0x17, 0x23, 1, 0, 4, b'h',
+                b'i', 0, 0,
+            ]
+        );
+        match ArtCommand::from_buffer(&bytes).unwrap() {
+            ArtCommand::Nzs(nzs) => {
+                assert_eq!(nzs.start_code, Nzs::TEXT_PACKET);
+                assert_eq!(nzs.port_address, PortAddress::from_parts(1, 0x23));
+                assert_eq!(nzs.data.as_ref(), &vec![b'h', b'i', 0, 0]);
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+    }
+
+    #[test]
+    fn strict() {
+        let mut bytes = Nzs::new(1.into(), 0x00, vec![1, 2])
+            .into_command()
+            .write_to_buffer()
+            .unwrap();
+        let options = ParseOptions::strict();
+        assert!(ArtCommand::from_buffer_with(&bytes, &options).is_err());
+        bytes[13] = Nzs::SYSTEM_INFORMATION_PACKET;
+        assert!(ArtCommand::from_buffer_with(&bytes, &options).is_ok());
+
+        // Unlike ArtDmx, ArtNzs may have an odd length
+        bytes.truncate(bytes.len() - 1);
+        bytes[17] = 1;
+        assert!(ArtCommand::from_buffer_with(&bytes, &options).is_ok());
+        bytes.truncate(bytes.len() - 1);
+        bytes[17] = 0;
+        assert!(ArtCommand::from_buffer_with(&bytes, &options).is_err());
+    }
+
+    #[test]
+    fn strict_write() {
+        let options = ParseOptions::strict();
+        let rdm = Nzs::new(1.into(), 0xCC, vec![1, 2]).into_command();
+        let mut buffer = Vec::new();
+        assert_eq!(
+            rdm.write_to_with(&mut buffer, &options),
+            Err(Error::InvalidStartCode(0xCC))
+        );
+        assert!(buffer.is_empty());
+        assert!(rdm
+            .write_to_with(&mut buffer, &ParseOptions::lenient())
+            .is_ok());
+
+        let text = Nzs::new(1.into(), Nzs::TEXT_PACKET, vec![b'a', 0]).into_command();
+        assert!(text.write_to_buffer_with(&options).is_ok());
+    }
+}
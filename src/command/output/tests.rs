@@ -1,5 +1,5 @@
 use super::*;
-use crate::ArtCommand;
+use crate::{ArtCommand, ParseOptions};
 
 mod serialization {
     use super::*;
@@ -72,6 +72,30 @@ mod serialization {
         });
         assert!(command.write_to_buffer().is_err());
     }
+
+    #[test]
+    fn write_to_existing_buffer() {
+        let output = Output {
+            data: vec![1, 2].into(),
+            ..Output::default()
+        };
+        let mut buffer = vec![0xAA];
+        output.write_to(&mut buffer).unwrap();
+        assert_eq!(buffer, [&[0xAA][..], &output.to_bytes().unwrap()].concat());
+
+        let output = Output {
+            data: vec![0; 513].into(),
+            ..Output::default()
+        };
+        match output.write_to(&mut buffer) {
+            // The offset is relative to the start of the output, not the start of the buffer
+            Err(Error::SerializeError { field, offset, .. }) => {
+                assert_eq!(field, "Output::data");
+                assert_eq!(offset, 8);
+            }
+            r => panic!("Expected a SerializeError, got {:?}", r),
+        }
+    }
 }
 
 mod parsing {
@@ -91,11 +115,57 @@ mod parsing {
             assert_eq!(output.sequence, 0);
             assert_eq!(output.physical, 0);
             assert_eq!(output.port_address, 1.into());
-            assert_eq!(output.length.parsed_length, Some(2));
+            assert_eq!(output.length.parsed_length, 2);
             assert_eq!(output.data.inner, vec![255, 255]);
         }
     }
 
+    #[test]
+    fn trailing_bytes() {
+        // The length field says 2, but 4 bytes of data follow
+        let packet = &[
+            65, 114, 116, 45, 78, 101, 116, 0, 0, 80, 0, 14, 0, 0, 1, 0, 0, 2, 1, 2, 3, 4,
+        ];
+        let command = ArtCommand::from_buffer(packet).unwrap();
+        assert_eq!(command.as_output().unwrap().data.inner, vec![1, 2, 3, 4]);
+        let command = ArtCommand::from_buffer_with(packet, &ParseOptions::normalizing()).unwrap();
+        assert_eq!(command.as_output().unwrap().data.inner, vec![1, 2]);
+        assert!(ArtCommand::from_buffer_with(packet, &ParseOptions::strict()).is_err());
+    }
+
+    #[test]
+    fn odd_length() {
+        let packet = &[
+            65, 114, 116, 45, 78, 101, 116, 0, 0, 80, 0, 14, 0, 0, 1, 0, 0, 3, 1, 2, 3,
+        ];
+        assert!(ArtCommand::from_buffer_with(packet, &ParseOptions::lenient()).is_ok());
+        assert!(ArtCommand::from_buffer_with(packet, &ParseOptions::strict()).is_err());
+    }
+
+    #[test]
+    fn protver_policy() {
+        let packet = &[
+            65, 114, 116, 45, 78, 101, 116, 0, 0, 80, 0, 13, 0, 0, 1, 0, 0, 2, 255, 255,
+        ];
+        let command = ArtCommand::from_buffer(packet).unwrap();
+        assert_eq!(command.protocol_version(), Some(13));
+
+        let strict = ArtCommand::from_buffer_with(packet, &ParseOptions::strict()).unwrap_err();
+        assert!(matches!(
+            strict,
+            Error::UnsupportedProtocolVersion {
+                version: 13,
+                min_version: 14
+            }
+        ));
+
+        let options = ParseOptions {
+            min_protocol_version: Some(13),
+            ..ParseOptions::lenient()
+        };
+        assert!(ArtCommand::from_buffer_with(packet, &options).is_ok());
+    }
+
     #[test]
     fn invalid_port_address() {
         // Here Port-Address is 32_768
@@ -111,3 +181,76 @@ mod parsing {
         .is_err());
     }
 }
+
+#[cfg(feature = "heapless")]
+mod heapless_buffers {
+    use super::*;
+
+    #[test]
+    fn write_to_heapless() {
+        let command = || {
+            let data: heapless::Vec<u8, 512> = heapless::Vec::from_slice(&[255]).unwrap();
+            ArtCommand::Output(Output {
+                data: data.into(),
+                ..Output::default()
+            })
+        };
+        let bytes = command().write_to_heapless::<530>().unwrap();
+        assert_eq!(
+            &bytes[..],
+            &[65, 114, 116, 45, 78, 101, 116, 0, 0, 80, 0, 14, 0, 0, 1, 0, 0, 2, 255, 0]
+        );
+        assert_eq!(command().write_to_heapless::<20>().unwrap(), bytes);
+        assert!(command().write_to_heapless::<19>().is_err());
+
+        let command = ArtCommand::Output(Output {
+            data: vec![0; 512].into(),
+            ..Output::default()
+        });
+        assert!(command.write_to_heapless::<64>().is_err());
+    }
+}
+
+#[cfg(feature = "bytes")]
+mod bytes_payloads {
+    use super::*;
+    use bytes::Bytes;
+
+    #[test]
+    fn zero_copy() {
+        let data = vec![1, 2, 3, 4];
+        let pointer = data.as_ptr();
+
+        let padded: PaddedData = Bytes::from(data).into();
+        assert_eq!(padded.inner.as_ptr(), pointer);
+        let bytes: Bytes = padded.into();
+        assert_eq!(bytes.as_ptr(), pointer);
+
+        let command = ArtCommand::Output(Output::new(1.into(), bytes));
+        let message = command.write_to_bytes().unwrap();
+        assert_eq!(&message[18..], &[1, 2, 3, 4]);
+        assert_eq!(message.len(), command.serialized_len());
+    }
+
+    #[test]
+    fn from_bytes() {
+        let message = Bytes::from(
+            Output::new(1.into(), vec![1, 2, 3, 4])
+                .into_command()
+                .write_to_buffer()
+                .unwrap(),
+        );
+        let command = ArtCommand::from_bytes(&message).unwrap();
+        let output = command.as_output().unwrap();
+        assert_eq!(&output.data[..], &[1, 2, 3, 4]);
+        assert_eq!(output.data.as_ptr(), message[18..].as_ptr());
+
+        // The length field is checked once the data is attached
+        let mut truncated = message.to_vec();
+        truncated.truncate(20);
+        let truncated = Bytes::from(truncated);
+        assert!(ArtCommand::from_bytes(&truncated).is_ok());
+        assert!(ArtCommand::from_bytes_with(&truncated, &ParseOptions::strict()).is_err());
+        assert!(ArtCommand::from_bytes(&message.slice(..17)).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{
+    convert::{Convertable, Cursor, WriteBuffer},
+    Result,
+};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+
+/// The amount of bytes that a payload stores inline with the `smallvec` feature
+const INLINE_CAPACITY: usize = 64;
+
+#[cfg(feature = "smallvec")]
+type Inner = smallvec::SmallVec<[u8; INLINE_CAPACITY]>;
+#[cfg(not(feature = "smallvec"))]
+type Inner = Vec<u8>;
+
+/// The variable length data of a message that is usually short, e.g. the text of a `DiagData` or `TextCommand`.
+///
+/// With the `smallvec` feature, up to `Payload::INLINE_CAPACITY` bytes are stored inline, so parsing a stream of short messages does not allocate for every one of them. Without it, the data is stored in a `Vec<u8>`. The API is the same in both cases.
+///
+/// ```rust
+/// use artnet_protocol::*;
+///
+/// let mut payload = Payload::from(&b"Port 1"[..]);
+/// payload.push(0);
+/// assert_eq!(&payload[..], b"Port 1\0");
+/// assert_eq!(payload.into_vec(), b"Port 1\0".to_vec());
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct Payload {
+    inner: Inner,
+}
+
+impl Payload {
+    /// The amount of bytes that are stored without allocating, with the `smallvec` feature
+    pub const INLINE_CAPACITY: usize = INLINE_CAPACITY;
+
+    /// An empty payload
+    pub fn new() -> Payload {
+        Payload::default()
+    }
+
+    /// Append a byte
+    pub fn push(&mut self, byte: u8) {
+        self.inner.push(byte);
+    }
+
+    /// Append the given bytes
+    pub fn extend_from_slice(&mut self, bytes: &[u8]) {
+        self.inner.extend_from_slice(bytes);
+    }
+
+    /// Shorten the payload to `len` bytes. Does nothing if it is shorter already.
+    pub fn truncate(&mut self, len: usize) {
+        self.inner.truncate(len);
+    }
+
+    /// Remove every byte
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    /// Convert the payload into a `Vec<u8>`. This does not copy the data if it is stored in a `Vec<u8>` already.
+    pub fn into_vec(self) -> Vec<u8> {
+        #[cfg(feature = "smallvec")]
+        let data = self.inner.into_vec();
+        #[cfg(not(feature = "smallvec"))]
+        let data = self.inner;
+        data
+    }
+}
+
+impl Deref for Payload {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.inner
+    }
+}
+
+impl DerefMut for Payload {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.inner
+    }
+}
+
+impl AsRef<[u8]> for Payload {
+    fn as_ref(&self) -> &[u8] {
+        &self.inner
+    }
+}
+
+impl From<Vec<u8>> for Payload {
+    /// Data that fits inline is moved out of the `Vec` with the `smallvec` feature, otherwise the `Vec` is reused
+    // Without the `smallvec` feature this is the same type
+    #[allow(clippy::useless_conversion)]
+    fn from(data: Vec<u8>) -> Payload {
+        Payload { inner: data.into() }
+    }
+}
+
+impl From<&[u8]> for Payload {
+    fn from(data: &[u8]) -> Payload {
+        Payload { inner: data.into() }
+    }
+}
+
+impl From<Payload> for Vec<u8> {
+    fn from(payload: Payload) -> Vec<u8> {
+        payload.into_vec()
+    }
+}
+
+impl PartialEq<[u8]> for Payload {
+    fn eq(&self, other: &[u8]) -> bool {
+        self[..] == *other
+    }
+}
+
+impl PartialEq<Vec<u8>> for Payload {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl fmt::Debug for Payload {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{:?}", &self[..])
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Payload {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Payload::from(Vec::<u8>::arbitrary(u)?))
+    }
+}
+
+impl<T> Convertable<T> for Payload {
+    fn from_cursor(cursor: &mut Cursor) -> Result<Self> {
+        Ok(Payload::from(cursor.remaining()))
+    }
+
+    fn write_to_buffer(&self, buffer: &mut dyn WriteBuffer, _: &T) -> Result<()> {
+        buffer.extend_from_slice(self);
+        Ok(())
+    }
+
+    fn serialized_len(&self, _: &T) -> usize {
+        self.len()
+    }
+
+    #[cfg(test)]
+    fn get_test_value() -> Self {
+        Payload::from(&[1, 2, 3, 4][..])
+    }
+    #[cfg(test)]
+    fn is_equal(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn payload() {
+        let mut payload = Payload::from(vec![1, 2, 3]);
+        payload.extend_from_slice(&[4, 5]);
+        payload.truncate(4);
+        assert_eq!(payload, vec![1, 2, 3, 4]);
+        assert_eq!(Vec::from(payload.clone()), vec![1, 2, 3, 4]);
+        #[cfg(feature = "smallvec")]
+        assert!(!payload.inner.spilled());
+
+        let long = Payload::from(&[0; Payload::INLINE_CAPACITY + 1][..]);
+        assert_eq!(long.len(), Payload::INLINE_CAPACITY + 1);
+        #[cfg(feature = "smallvec")]
+        assert!(long.inner.spilled());
+    }
+}
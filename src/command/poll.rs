@@ -1,7 +1,11 @@
-use crate::ArtTalkToMe;
+use crate::{ArtTalkToMe, Error, ParseOptions, PortAddress, Result};
+use core::convert::TryFrom;
+use core::ops::RangeInclusive;
 
 data_structure! {
-    #[derive(Debug)]
+    @test
+    #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
     #[doc = "Used to poll the nodes in the network"]
     pub struct Poll {
         #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
@@ -12,6 +16,61 @@ data_structure! {
 
         #[doc = "Determines the priority of the diagnostics that the nodes should send"]
         pub diagnostics_priority: u8,
+
+        #[doc = "The highest Port-Address that the nodes should reply for, in big endian. Only used when `talk_to_me` contains `ArtTalkToMe::TARGETED_MODE`"]
+        pub target_port_top: [u8; 2],
+
+        #[doc = "The lowest Port-Address that the nodes should reply for, in big endian. Only used when `talk_to_me` contains `ArtTalkToMe::TARGETED_MODE`"]
+        pub target_port_bottom: [u8; 2],
+
+        #[doc = "The ESTA manufacturer code of the controller, in big endian"]
+        pub esta_code: [u8; 2],
+
+        #[doc = "The Oem code of the controller, in big endian"]
+        pub oem: [u8; 2],
+    }
+}
+
+/// The priority of a diagnostics message, which a controller asks for in the `diagnostics_priority` of an ArtPoll
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagPriority {
+    /// Low priority message
+    DpLow,
+    /// Medium priority message
+    DpMed,
+    /// High priority message
+    DpHigh,
+    /// Critical priority message
+    DpCritical,
+    /// Volatile message, which is displayed on a single line by the controller
+    DpVolatile,
+    /// A priority that is not defined by the Art-Net specification
+    Other(u8),
+}
+
+impl From<u8> for DiagPriority {
+    fn from(priority: u8) -> DiagPriority {
+        match priority {
+            0x10 => DiagPriority::DpLow,
+            0x40 => DiagPriority::DpMed,
+            0x80 => DiagPriority::DpHigh,
+            0xE0 => DiagPriority::DpCritical,
+            0xF0 => DiagPriority::DpVolatile,
+            priority => DiagPriority::Other(priority),
+        }
+    }
+}
+
+impl From<DiagPriority> for u8 {
+    fn from(priority: DiagPriority) -> u8 {
+        match priority {
+            DiagPriority::DpLow => 0x10,
+            DiagPriority::DpMed => 0x40,
+            DiagPriority::DpHigh => 0x80,
+            DiagPriority::DpCritical => 0xE0,
+            DiagPriority::DpVolatile => 0xF0,
+            DiagPriority::Other(priority) => priority,
+        }
     }
 }
 
@@ -21,6 +80,227 @@ impl Default for Poll {
             version: super::ARTNET_PROTOCOL_VERSION,
             talk_to_me: ArtTalkToMe::NONE,
             diagnostics_priority: 0x80,
+            target_port_top: [0; 2],
+            target_port_bottom: [0; 2],
+            esta_code: [0; 2],
+            oem: [0; 2],
+        }
+    }
+}
+
+impl Poll {
+    /// The size of an Art-Net 4 ArtPoll message, including the Art-Net header and opcode. Older nodes send shorter messages.
+    pub const MAX_PACKET_SIZE: usize = 22;
+
+    /// The length of an Art-Net 4 Poll message, excluding the Art-Net header and opcode
+    const LENGTH: usize = Poll::MAX_PACKET_SIZE - super::HEADER_LENGTH;
+
+    /// Bits 7-6 of the TalkToMe field are unused and should be transmitted as zero
+    const RESERVED_TALK_TO_ME_BITS: u8 = 0b1100_0000;
+
+    /// An ArtPoll with the given flags, that asks for the diagnostics with at least the given priority
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// // Report changes, and send the diagnostics with a medium or higher priority
+    /// let poll = Poll::with_flags(
+    ///     ArtTalkToMe::EMIT_CHANGES | ArtTalkToMe::ENABLE_DIAGNOSTICS,
+    ///     DiagPriority::DpMed,
+    /// );
+    /// assert!(poll.talk_to_me.contains(ArtTalkToMe::EMIT_CHANGES));
+    /// assert_eq!(poll.diagnostics_priority, 0x40);
+    /// let command = poll.into_command();
+    /// ```
+    pub fn with_flags(talk_to_me: ArtTalkToMe, diagnostics_priority: DiagPriority) -> Poll {
+        Poll {
+            talk_to_me,
+            diagnostics_priority: diagnostics_priority.into(),
+            ..Poll::default()
+        }
+    }
+
+    /// Start building an ArtPoll, without knowing the names of the `ArtTalkToMe` flags
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// let poll = Poll::builder()
+    ///     .enable_diagnostics(true)
+    ///     .unicast_diagnostics(true)
+    ///     .emit_changes(true)
+    ///     .priority(DiagPriority::DpMed)
+    ///     .build();
+    /// assert_eq!(
+    ///     poll.talk_to_me,
+    ///     ArtTalkToMe::ENABLE_DIAGNOSTICS | ArtTalkToMe::UNICAST_DIAGNOSTICS | ArtTalkToMe::EMIT_CHANGES
+    /// );
+    /// assert_eq!(poll.diagnostics_priority, 0x40);
+    /// ```
+    pub fn builder() -> PollBuilder {
+        PollBuilder::default()
+    }
+
+    /// Only ask the nodes with a Port-Address in the given range to reply
+    pub fn with_target(mut self, target: RangeInclusive<PortAddress>) -> Poll {
+        self.talk_to_me |= ArtTalkToMe::TARGETED_MODE;
+        self.target_port_bottom = u16::from(*target.start()).to_be_bytes();
+        self.target_port_top = u16::from(*target.end()).to_be_bytes();
+        self
+    }
+
+    /// The range of Port-Addresses that the nodes should reply for, or `None` if every node should reply
+    pub fn target(&self) -> Option<RangeInclusive<PortAddress>> {
+        if !self.talk_to_me.contains(ArtTalkToMe::TARGETED_MODE) {
+            return None;
+        }
+        // An invalid Port-Address is clamped to the highest one
+        let port_address =
+            |bytes: [u8; 2]| PortAddress::try_from(u16::from_be_bytes(bytes).min(0x7FFF)).unwrap();
+        Some(port_address(self.target_port_bottom)..=port_address(self.target_port_top))
+    }
+
+    /// If a node with the given Port-Address should reply to this poll. Always true when the poll is not in targeted mode.
+    pub fn targets(&self, port_address: PortAddress) -> bool {
+        self.target()
+            .is_none_or(|target| target.contains(&port_address))
+    }
+
+    /// Wrap this message in an `ArtCommand::Poll`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+
+    pub(crate) fn from_with(data: &[u8], options: &ParseOptions) -> Result<Poll> {
+        // Controllers before Art-Net 4 do not send the target and ESTA/OEM fields, they are treated as zero
+        let poll = if data.len() < Poll::LENGTH {
+            // Padded on the stack, so parsing does not allocate
+            let mut padded = [0; Poll::LENGTH];
+            padded[..data.len()].copy_from_slice(data);
+            Poll::from(&padded)?
+        } else {
+            Poll::from(data)?
+        };
+        if options.is_strict() && data[2] & Poll::RESERVED_TALK_TO_ME_BITS != 0 {
+            return Err(Error::SpecViolation(
+                "Poll::talk_to_me has reserved bits set",
+            ));
         }
+        Ok(poll)
+    }
+}
+
+/// Builds a [Poll](struct.Poll.html), see `Poll::builder`. Every flag is off by default, and the priority is `DiagPriority::DpHigh`.
+#[derive(Debug, Clone, Default)]
+pub struct PollBuilder {
+    poll: Poll,
+}
+
+impl PollBuilder {
+    fn flag(mut self, flag: ArtTalkToMe, enabled: bool) -> PollBuilder {
+        self.poll.talk_to_me.set(flag, enabled);
+        self
+    }
+
+    /// Ask the nodes to send diagnostics, see `ArtTalkToMe::ENABLE_DIAGNOSTICS`
+    pub fn enable_diagnostics(self, enabled: bool) -> PollBuilder {
+        self.flag(ArtTalkToMe::ENABLE_DIAGNOSTICS, enabled)
+    }
+
+    /// Ask the nodes to unicast their diagnostics to the controller instead of broadcasting them, see `ArtTalkToMe::UNICAST_DIAGNOSTICS`
+    pub fn unicast_diagnostics(self, enabled: bool) -> PollBuilder {
+        self.flag(ArtTalkToMe::UNICAST_DIAGNOSTICS, enabled)
+    }
+
+    /// Ask the nodes to send an ArtPollReply whenever something changes, see `ArtTalkToMe::EMIT_CHANGES`
+    pub fn emit_changes(self, enabled: bool) -> PollBuilder {
+        self.flag(ArtTalkToMe::EMIT_CHANGES, enabled)
+    }
+
+    /// Enable VLC transmission, see `ArtTalkToMe::ENABLE_VLC`
+    pub fn enable_vlc(self, enabled: bool) -> PollBuilder {
+        self.flag(ArtTalkToMe::ENABLE_VLC, enabled)
+    }
+
+    /// The lowest priority of the diagnostics that the nodes should send, e.g. `DiagPriority::DpMed`
+    pub fn priority(mut self, priority: DiagPriority) -> PollBuilder {
+        self.poll.diagnostics_priority = priority.into();
+        self
+    }
+
+    /// Only ask the nodes with a Port-Address in the given range to reply, see `Poll::with_target`
+    pub fn target(mut self, target: RangeInclusive<PortAddress>) -> PollBuilder {
+        self.poll = self.poll.with_target(target);
+        self
+    }
+
+    /// The ESTA manufacturer code and Oem code of the controller
+    pub fn manufacturer(mut self, esta_code: u16, oem: u16) -> PollBuilder {
+        self.poll.esta_code = esta_code.to_be_bytes();
+        self.poll.oem = oem.to_be_bytes();
+        self
+    }
+
+    /// The poll that was built
+    pub fn build(self) -> Poll {
+        self.poll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserved_talk_to_me_bits() {
+        let data = [0, 14, 0b1000_0010, 0x80];
+        let poll = Poll::from_with(&data, &ParseOptions::lenient()).unwrap();
+        assert_eq!(poll.talk_to_me, ArtTalkToMe::EMIT_CHANGES);
+        assert!(Poll::from_with(&data, &ParseOptions::strict()).is_err());
+    }
+
+    #[test]
+    fn builder() {
+        let poll = Poll::builder()
+            .emit_changes(true)
+            .enable_diagnostics(true)
+            .enable_diagnostics(false)
+            .target(PortAddress::from(1)..=PortAddress::from(4))
+            .manufacturer(0x7FF0, 0x00FF)
+            .build();
+        assert_eq!(
+            poll.talk_to_me,
+            ArtTalkToMe::EMIT_CHANGES | ArtTalkToMe::TARGETED_MODE
+        );
+        assert_eq!(poll.diagnostics_priority, 0x80);
+        assert_eq!(poll.esta_code, [0x7F, 0xF0]);
+        assert_eq!(poll.oem, [0x00, 0xFF]);
+        assert!(poll.targets(PortAddress::from(4)));
+    }
+
+    #[test]
+    fn diag_priority() {
+        for priority in 0..=u8::MAX {
+            assert_eq!(u8::from(DiagPriority::from(priority)), priority);
+        }
+        assert_eq!(DiagPriority::from(0xE0), DiagPriority::DpCritical);
+        assert_eq!(DiagPriority::from(0x20), DiagPriority::Other(0x20));
+
+        let poll = Poll::builder().priority(DiagPriority::DpLow).build();
+        assert_eq!(poll.diagnostics_priority, 0x10);
+    }
+
+    #[test]
+    fn target() {
+        let poll = Poll::default().with_target(PortAddress::from(16)..=PortAddress::from(31));
+        assert_eq!(poll.target_port_top, [0, 31]);
+        assert!(poll.targets(PortAddress::from(16)));
+        assert!(poll.targets(PortAddress::from(31)));
+        assert!(!poll.targets(PortAddress::from(32)));
+        assert!(Poll::default().targets(PortAddress::from(32)));
+
+        // Art-Net 3 polls have no target
+        let poll = Poll::from_with(&[0, 14, 0b0010_0000, 0x80], &ParseOptions::strict()).unwrap();
+        assert_eq!(poll.target(), Some(0.into()..=0.into()));
     }
 }
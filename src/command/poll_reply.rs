@@ -1,8 +1,16 @@
-use std::fmt;
-use std::net::Ipv4Addr;
-use std::str;
+use crate::{Error, ParseOptions, PortAddress, Result, Uid};
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+use core::convert::TryFrom;
+use core::fmt;
+use core::net::Ipv4Addr;
+use core::str;
 
 data_structure! {
+    @test
+    #[derive(Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
     #[doc = "Gets send by the nodes in the network as a response to the Poll message"]
     pub struct PollReply {
         #[doc = "The IP address of the node"]
@@ -59,8 +67,321 @@ data_structure! {
         pub bind_index: u8,
         #[doc = "Status 2. Will be expanded in the future"]
         pub status_2: u8,
+        #[doc = "The output status of the node, when it supports RDM and sACN. Will be converted to a `bitflag` enum in the future."]
+        pub good_output_b: [u8; 4],
+        #[doc = "Status 3. Bits 7-6 are the failsafe state, see `PollReply::failsafe_state`. Bit 5 is set when the node supports failover, bit 1 when it supports the RDM background queue."]
+        pub status_3: u8,
+        #[doc = "The UID of the default RDMnet and LLRP responder of the node"]
+        pub default_responder: Uid,
+        #[doc = "Available for user specific data"]
+        pub user: [u8; 2],
+        #[doc = "The maximum refresh rate of the node in Hz, big endian. 0 means up to 44 Hz."]
+        pub refresh_rate: [u8; 2],
+        #[doc = "The policy of the RDM background queue, see [BackgroundQueuePolicy](enum.BackgroundQueuePolicy.html)"]
+        pub background_queue_policy: u8,
         #[doc = "Transmit as zero. For future expansion."]
-        pub filler: [u8; 26],
+        pub filler: [u8; 10],
+    }
+}
+
+/// What the outputs of a node do when the data stops, as reported in `PollReply::status_3`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FailsafeState {
+    /// Hold the last state
+    Hold,
+    /// Set all outputs to zero
+    Zero,
+    /// Set all outputs to full
+    Full,
+    /// Play back the recorded failsafe scene
+    Scene,
+}
+
+#[cfg(feature = "dmx")]
+impl FailsafeState {
+    /// The ArtAddress command that programs a node with this failsafe state
+    pub fn command(self) -> crate::AddressCommand {
+        match self {
+            FailsafeState::Hold => crate::AddressCommand::FailHold,
+            FailsafeState::Zero => crate::AddressCommand::FailZero,
+            FailsafeState::Full => crate::AddressCommand::FailFull,
+            FailsafeState::Scene => crate::AddressCommand::FailScene,
+        }
+    }
+}
+
+/// How a node collects the queued messages of the RDM devices on its ports in the background, as programmed with `AddressCommand::BackgroundQueuePolicy`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BackgroundQueuePolicy {
+    /// Collect with STATUS_MESSAGE set to STATUS_NONE
+    StatusNone,
+    /// Collect with STATUS_MESSAGE set to STATUS_ADVISORY
+    Advisory,
+    /// Collect with STATUS_MESSAGE set to STATUS_WARNING
+    Warning,
+    /// Collect with STATUS_MESSAGE set to STATUS_ERROR
+    Error,
+    /// Do not collect in the background
+    Disabled,
+    /// A manufacturer specific policy, from 5 to 15
+    Other(u8),
+}
+
+impl From<u8> for BackgroundQueuePolicy {
+    /// The policy in the lower 4 bits of the value
+    fn from(policy: u8) -> BackgroundQueuePolicy {
+        match policy & 0x0F {
+            0 => BackgroundQueuePolicy::StatusNone,
+            1 => BackgroundQueuePolicy::Advisory,
+            2 => BackgroundQueuePolicy::Warning,
+            3 => BackgroundQueuePolicy::Error,
+            4 => BackgroundQueuePolicy::Disabled,
+            policy => BackgroundQueuePolicy::Other(policy),
+        }
+    }
+}
+
+impl From<BackgroundQueuePolicy> for u8 {
+    fn from(policy: BackgroundQueuePolicy) -> u8 {
+        match policy {
+            BackgroundQueuePolicy::StatusNone => 0,
+            BackgroundQueuePolicy::Advisory => 1,
+            BackgroundQueuePolicy::Warning => 2,
+            BackgroundQueuePolicy::Error => 3,
+            BackgroundQueuePolicy::Disabled => 4,
+            BackgroundQueuePolicy::Other(policy) => policy & 0x0F,
+        }
+    }
+}
+
+/// Interpret a fixed length, null terminated field as a string
+pub(crate) fn null_terminated(bytes: &[u8]) -> Cow<'_, str> {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end])
+}
+
+/// Write a string into a fixed length field, truncated at a character boundary so it stays null terminated
+pub(crate) fn set_null_terminated(field: &mut [u8], value: &str) {
+    let mut end = value.len().min(field.len() - 1);
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    field.fill(0);
+    field[..end].copy_from_slice(&value.as_bytes()[..end]);
+}
+
+impl PollReply {
+    /// The size of an ArtPollReply message, including the Art-Net header and opcode
+    pub const PACKET_SIZE: usize = 239;
+
+    /// The length of a PollReply message, excluding the Art-Net header and opcode
+    pub(crate) const LENGTH: usize = PollReply::PACKET_SIZE - super::HEADER_LENGTH;
+
+    /// The size of the shortest ArtPollReply that receivers must accept, including the Art-Net header and opcode. The missing trailing fields are treated as zero.
+    pub(crate) const MIN_PACKET_SIZE: usize = 207;
+
+    /// The length of the shortest PollReply message, excluding the Art-Net header and opcode
+    const MIN_LENGTH: usize = PollReply::MIN_PACKET_SIZE - super::HEADER_LENGTH;
+
+    /// The port that every node must report, 0x1936
+    pub const STANDARD_PORT: u16 = 6454;
+
+    /// Set in `status_3` when the node supports failover, and can be programmed with the `Fail*` commands of an ArtAddress
+    pub const STATUS_3_FAILOVER: u8 = 0x20;
+
+    /// Set in `status_3` when the policy of the RDM background queue can be programmed with an ArtAddress
+    pub const STATUS_3_BACKGROUND_QUEUE: u8 = 0x02;
+
+    /// Set in `status_2` when the node can switch its ports between Art-Net and sACN
+    pub const STATUS_2_SACN: u8 = 0x10;
+
+    /// Set in `status_2` when the IP address of the node was configured by DHCP
+    pub const STATUS_2_DHCP: u8 = 0x02;
+
+    /// Set in `status_2` when the node supports 15 bit Port-Addresses, as every Art-Net 3 and 4 node does
+    pub const STATUS_2_PORT_ADDRESS_15_BIT: u8 = 0x08;
+
+    /// The node report of a node that started without problems
+    pub const NODE_REPORT_POWER_OK: &'static str = "#0001 [0000] Power On Tests successful";
+
+    /// Create the reply of a root node with the given output ports, which are the universes that it wants to receive ArtDmx for.
+    ///
+    /// The port, style, bind index and node report are set as the specification requires, `short_name` is used as both the short and the long name and is truncated to fit. Every other field is zero, e.g. the `oem` and `esta_code` should be set to the codes of the manufacturer.
+    ///
+    /// Returns `Error::InvalidConfig` if there are more than 4 ports, or if the ports are not in the same Net and Sub-Net, which the reply can not describe.
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// let reply = PollReply::new_node(
+    ///     [10, 0, 0, 5].into(),
+    ///     [0x02, 0, 0, 0, 0, 0x05],
+    ///     "Dimmer",
+    ///     &[1.into(), 2.into()],
+    /// )
+    /// .unwrap();
+    /// assert_eq!(reply.to_string(), "Dimmer @ 10.0.0.5 (Node, 2 ports, universes 1, 2)");
+    /// ```
+    pub fn new_node(
+        address: Ipv4Addr,
+        mac: [u8; 6],
+        short_name: &str,
+        ports: &[PortAddress],
+    ) -> Result<PollReply> {
+        if ports.len() > 4 {
+            return Err(Error::InvalidConfig(
+                "A PollReply can not have more than 4 ports",
+            ));
+        }
+        let first = ports.first().copied().unwrap_or(PortAddress::from(0));
+        if ports
+            .iter()
+            .any(|port| port.net() != first.net() || port.sub_uni() >> 4 != first.sub_uni() >> 4)
+        {
+            return Err(Error::InvalidConfig(
+                "The ports of a PollReply must be in the same Net and Sub-Net",
+            ));
+        }
+        let mut reply = PollReply {
+            address,
+            port_address: [first.net(), first.sub_uni() >> 4],
+            num_ports: [0, ports.len() as u8],
+            mac,
+            bind_ip: address.octets(),
+            bind_index: 1,
+            status_2: PollReply::STATUS_2_PORT_ADDRESS_15_BIT,
+            ..PollReply::default()
+        };
+        for (index, port) in ports.iter().enumerate() {
+            reply.port_types[index] = 0x80;
+            reply.swout[index] = port.sub_uni() & 0x0F;
+        }
+        set_null_terminated(&mut reply.short_name, short_name);
+        set_null_terminated(&mut reply.long_name, short_name);
+        set_null_terminated(&mut reply.node_report, PollReply::NODE_REPORT_POWER_OK);
+        Ok(reply)
+    }
+
+    /// Wrap this message in an `ArtCommand::PollReply`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+
+    /// The name of the `style` of the node, e.g. `"Node"` or `"Controller"`, or `None` if the style is unknown
+    pub fn style_name(&self) -> Option<&'static str> {
+        match self.style {
+            0x00 => Some("Node"),
+            0x01 => Some("Controller"),
+            0x02 => Some("Media"),
+            0x03 => Some("Route"),
+            0x04 => Some("Backup"),
+            0x05 => Some("Config"),
+            0x06 => Some("Visual"),
+            _ => None,
+        }
+    }
+
+    /// The Port-Address of the given output port, from 0 to 3. Returns `None` if the port does not exist or is not an output.
+    pub fn output_port_address(&self, port: usize) -> Option<PortAddress> {
+        let ports = usize::from(self.num_ports[1]).min(4);
+        if port >= ports || self.port_types[port] & 0x80 == 0 {
+            return None;
+        }
+        let address = (u16::from(self.port_address[0] & 0x7F) << 8)
+            | (u16::from(self.port_address[1] & 0x0F) << 4)
+            | u16::from(self.swout[port] & 0x0F);
+        PortAddress::try_from(address).ok()
+    }
+
+    /// The Port-Address of the given input port, from 0 to 3. Returns `None` if the port does not exist or is not an input.
+    pub fn input_port_address(&self, port: usize) -> Option<PortAddress> {
+        let ports = usize::from(self.num_ports[1]).min(4);
+        if port >= ports || self.port_types[port] & 0x40 == 0 {
+            return None;
+        }
+        let address = (u16::from(self.port_address[0] & 0x7F) << 8)
+            | (u16::from(self.port_address[1] & 0x0F) << 4)
+            | u16::from(self.swin[port] & 0x0F);
+        PortAddress::try_from(address).ok()
+    }
+
+    /// The Port-Addresses of the output ports of this node, which are the universes that it wants to receive ArtDmx for
+    pub fn output_port_addresses(&self) -> impl Iterator<Item = PortAddress> + '_ {
+        (0..4).filter_map(move |port| self.output_port_address(port))
+    }
+
+    /// If the node supports failover, and its failsafe state can be programmed
+    pub fn supports_failover(&self) -> bool {
+        self.status_3 & PollReply::STATUS_3_FAILOVER != 0
+    }
+
+    /// If the ports of the node can be switched between Art-Net and sACN
+    pub fn supports_sacn(&self) -> bool {
+        self.status_2 & PollReply::STATUS_2_SACN != 0
+    }
+
+    /// If the IP address of the node was configured by DHCP
+    pub fn dhcp_enabled(&self) -> bool {
+        self.status_2 & PollReply::STATUS_2_DHCP != 0
+    }
+
+    /// If the node reports the standard Art-Net port 6454 (0x1936). Some broken devices report a different port, but still listen on the standard one.
+    pub fn port_is_standard(&self) -> bool {
+        self.port == PollReply::STANDARD_PORT
+    }
+
+    /// If the policy of the RDM background queue of the node can be programmed
+    pub fn supports_background_queue(&self) -> bool {
+        self.status_3 & PollReply::STATUS_3_BACKGROUND_QUEUE != 0
+    }
+
+    /// What the outputs of the node do when the data stops, from bits 7-6 of `status_3`
+    pub fn failsafe_state(&self) -> FailsafeState {
+        match self.status_3 >> 6 {
+            0 => FailsafeState::Hold,
+            1 => FailsafeState::Zero,
+            2 => FailsafeState::Full,
+            _ => FailsafeState::Scene,
+        }
+    }
+
+    pub(crate) fn from_with(data: &[u8], options: &ParseOptions) -> Result<PollReply> {
+        if options.is_strict() && data.len() > PollReply::LENGTH {
+            return Err(Error::SpecViolation(
+                "PollReply has trailing bytes after the message",
+            ));
+        }
+        let reply = if (PollReply::MIN_LENGTH..PollReply::LENGTH).contains(&data.len()) {
+            // Older nodes send shorter replies, the missing fields are treated as zero
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                len = data.len(),
+                "padding a truncated PollReply with zeroes"
+            );
+            // Padded on the stack, so parsing does not allocate
+            let mut padded = [0; PollReply::LENGTH];
+            padded[..data.len()].copy_from_slice(data);
+            PollReply::from(&padded)?
+        } else {
+            PollReply::from(data)?
+        };
+        if options.is_strict() {
+            if !reply.port_is_standard() {
+                return Err(Error::SpecViolation("PollReply::port must be 0x1936"));
+            }
+            if reply
+                .spare
+                .iter()
+                .chain(reply.filler.iter())
+                .any(|b| *b != 0)
+            {
+                return Err(Error::SpecViolation(
+                    "PollReply::spare and PollReply::filler must be zero",
+                ));
+            }
+        }
+        Ok(reply)
     }
 }
 
@@ -98,17 +419,50 @@ impl fmt::Debug for PollReply {
             .field("mac", &self.mac)
             .field("bind_ip", &self.bind_ip)
             .field("bind_index", &self.bind_index)
+            .field("status_2", &self.status_2)
+            .field("good_output_b", &self.good_output_b)
+            .field("status_3", &self.status_3)
+            .field("default_responder", &self.default_responder)
+            .field("user", &self.user)
+            .field("refresh_rate", &self.refresh_rate)
+            .field("background_queue_policy", &self.background_queue_policy)
             .field("filler", &self.filler)
             .finish()
     }
 }
 
+impl fmt::Display for PollReply {
+    /// A one-line summary of the node, e.g. `Dimmer @ 10.0.0.5 (Node, 2 ports, universes 1, 2)`
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let name = null_terminated(&self.short_name);
+        let name = if name.is_empty() { "unnamed" } else { &name };
+        write!(fmt, "{} @ {} (", name, self.address)?;
+        match self.style_name() {
+            Some(style) => write!(fmt, "{}", style)?,
+            None => write!(fmt, "style 0x{:02X}", self.style)?,
+        }
+        let ports = self.num_ports[1];
+        write!(fmt, ", {} port{}", ports, if ports == 1 { "" } else { "s" })?;
+        let mut universes = self.output_port_addresses().peekable();
+        if universes.peek().is_some() {
+            fmt.write_str(", universes ")?;
+            for (index, universe) in universes.enumerate() {
+                if index > 0 {
+                    fmt.write_str(", ")?;
+                }
+                write!(fmt, "{}", u16::from(universe))?;
+            }
+        }
+        fmt.write_str(")")
+    }
+}
+
 impl Default for PollReply {
     fn default() -> Self {
         // Per Art-Net spec, unused fields are zero
         PollReply {
             address: Ipv4Addr::from_bits(0),
-            port: 6454,
+            port: PollReply::STANDARD_PORT,
             version: [0; 2],
             port_address: [0; 2],
             oem: [0; 2],
@@ -133,7 +487,196 @@ impl Default for PollReply {
             bind_ip: [0; 4],
             bind_index: 0,
             status_2: 0,
-            filler: [0; 26],
+            good_output_b: [0; 4],
+            status_3: 0,
+            default_responder: Uid([0; 6]),
+            user: [0; 2],
+            refresh_rate: [0; 2],
+            background_queue_policy: 0,
+            filler: [0; 10],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn display() {
+        let mut reply = PollReply {
+            address: [10, 0, 0, 5].into(),
+            num_ports: [0, 2],
+            port_types: [0x80, 0x80, 0, 0],
+            swout: [1, 2, 0, 0],
+            ..PollReply::default()
+        };
+        reply.short_name[..6].copy_from_slice(b"Dimmer");
+        assert_eq!(
+            reply.to_string(),
+            "Dimmer @ 10.0.0.5 (Node, 2 ports, universes 1, 2)"
+        );
+
+        let reply = PollReply {
+            style: 0x10,
+            num_ports: [0, 1],
+            ..PollReply::default()
+        };
+        assert_eq!(reply.to_string(), "unnamed @ 0.0.0.0 (style 0x10, 1 port)");
+    }
+
+    #[test]
+    fn new_node() {
+        let ports = [
+            PortAddress::from_parts(1, 0x23),
+            PortAddress::from_parts(1, 0x25),
+        ];
+        let reply = PollReply::new_node([10, 0, 0, 5].into(), [2, 0, 0, 0, 0, 5], "Dimmer", &ports)
+            .unwrap();
+        assert_eq!(reply.port, 6454);
+        assert_eq!(reply.bind_ip, [10, 0, 0, 5]);
+        assert_eq!(reply.bind_index, 1);
+        assert_eq!(reply.style_name(), Some("Node"));
+        assert_eq!(reply.output_port_addresses().collect::<Vec<_>>(), ports);
+        assert_eq!(null_terminated(&reply.long_name), "Dimmer");
+        assert_eq!(
+            null_terminated(&reply.node_report),
+            PollReply::NODE_REPORT_POWER_OK
+        );
+        let bytes = reply.to_bytes().unwrap();
+        assert_eq!(
+            PollReply::from_with(&bytes, &ParseOptions::strict()).unwrap(),
+            reply
+        );
+
+        // Long names are truncated and stay null terminated
+        let reply =
+            PollReply::new_node(Ipv4Addr::UNSPECIFIED, [0; 6], "Überlange Bezeichnung", &[])
+                .unwrap();
+        assert_eq!(null_terminated(&reply.short_name), "Überlange Bezeic");
+        assert_eq!(reply.num_ports, [0, 0]);
+
+        let other_net = [
+            PortAddress::from_parts(1, 0x23),
+            PortAddress::from_parts(2, 0x23),
+        ];
+        assert!(PollReply::new_node(Ipv4Addr::UNSPECIFIED, [0; 6], "", &other_net).is_err());
+        let too_many = [PortAddress::from(0); 5];
+        assert!(PollReply::new_node(Ipv4Addr::UNSPECIFIED, [0; 6], "", &too_many).is_err());
+    }
+
+    #[test]
+    fn output_port_addresses() {
+        let reply = PollReply {
+            port_address: [0x01, 0x02],
+            num_ports: [0, 3],
+            port_types: [0x80, 0x40, 0xC0, 0x80],
+            swout: [0x00, 0x01, 0x12, 0x03],
+            ..PollReply::default()
+        };
+        let addresses: Vec<u16> = reply.output_port_addresses().map(u16::from).collect();
+        // The second port is an input, the fourth port is not in use
+        assert_eq!(addresses, vec![0x0120, 0x0122]);
+    }
+
+    #[test]
+    fn status_3() {
+        let reply = PollReply {
+            status_3: 0xA0,
+            ..PollReply::default()
+        };
+        assert!(reply.supports_failover());
+        assert_eq!(reply.failsafe_state(), FailsafeState::Full);
+        assert!(!PollReply::default().supports_failover());
+        assert_eq!(PollReply::default().failsafe_state(), FailsafeState::Hold);
+    }
+
+    #[test]
+    fn background_queue_policy() {
+        for policy in 0..16 {
+            assert_eq!(u8::from(BackgroundQueuePolicy::from(policy)), policy);
         }
+        assert_eq!(
+            BackgroundQueuePolicy::from(0xE4),
+            BackgroundQueuePolicy::Disabled
+        );
+    }
+
+    #[test]
+    fn length() {
+        assert_eq!(
+            PollReply::default().to_bytes().unwrap().len(),
+            PollReply::LENGTH
+        );
+    }
+
+    #[test]
+    fn truncated_reply() {
+        let mut reply = PollReply::default();
+        reply.short_name[..4].copy_from_slice(b"Node");
+        reply.bind_index = 2;
+        let bytes = reply.to_bytes().unwrap();
+        let truncated = &bytes[..200];
+
+        for options in &[
+            ParseOptions::strict(),
+            ParseOptions::lenient(),
+            ParseOptions::normalizing(),
+        ] {
+            let parsed = PollReply::from_with(truncated, options).unwrap();
+            assert_eq!(&parsed.short_name[..4], b"Node");
+            assert_eq!(parsed.bind_index, 0);
+        }
+        assert!(PollReply::from_with(
+            &bytes[..PollReply::MIN_LENGTH - 1],
+            &ParseOptions::lenient()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn trailing_bytes() {
+        let mut bytes = PollReply::default().to_bytes().unwrap();
+        bytes.push(0);
+        assert!(PollReply::from_with(&bytes, &ParseOptions::lenient()).is_ok());
+        assert!(PollReply::from_with(&bytes, &ParseOptions::strict()).is_err());
+    }
+
+    #[test]
+    fn too_short() {
+        let mut message = crate::ArtCommand::PollReply(Box::default())
+            .write_to_buffer()
+            .unwrap();
+        message.truncate(206);
+
+        match crate::ArtCommand::from_buffer(&message) {
+            Err(Error::MessageTooShort { min_len, .. }) => assert_eq!(min_len, 207),
+            r => panic!("Expected MessageTooShort, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn non_standard_port() {
+        let reply = PollReply {
+            port: 6455,
+            ..PollReply::default()
+        };
+        let bytes = reply.to_bytes().unwrap();
+
+        let parsed = PollReply::from_with(&bytes, &ParseOptions::lenient()).unwrap();
+        assert!(!parsed.port_is_standard());
+        assert!(PollReply::from_with(&bytes, &ParseOptions::strict()).is_err());
+        assert!(PollReply::default().port_is_standard());
+    }
+
+    #[test]
+    fn non_zero_filler() {
+        let mut reply = PollReply::default();
+        reply.filler[0] = 1;
+        let bytes = reply.to_bytes().unwrap();
+
+        assert!(PollReply::from_with(&bytes, &ParseOptions::lenient()).is_ok());
+        assert!(PollReply::from_with(&bytes, &ParseOptions::strict()).is_err());
     }
 }
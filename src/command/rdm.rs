@@ -0,0 +1,80 @@
+use crate::{command::ARTNET_PROTOCOL_VERSION, PortAddress, RdmMessage, Result, SharedData};
+use alloc::vec::Vec;
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "Carries a non-discovery RDM message, e.g. a GET or SET of a parameter, between a controller and the devices on a Port-Address."]
+    #[doc = ""]
+    #[doc = "Use `Rdm::new` and `Rdm::message` to convert the packet from and to an [RdmMessage](trait.RdmMessage.html)."]
+    pub struct Rdm {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "The RDM version, 1 for RDM STANDARD V1.0"]
+        pub rdm_version: u8,
+        #[doc = "Transmit as zero"]
+        pub filler: u8,
+        #[doc = "Transmit as zero"]
+        pub spare: [u8; 7],
+        #[doc = "Bits 14-8 of the Port-Address"]
+        pub net: u8,
+        #[doc = "Only `Rdm::AR_PROCESS` is defined"]
+        pub command: u8,
+        #[doc = "Bits 7-0 of the Port-Address"]
+        pub address: u8,
+        #[doc = "The RDM packet, without the DMX start code"]
+        pub packet: SharedData,
+    }
+}
+
+impl Default for Rdm {
+    fn default() -> Rdm {
+        Rdm {
+            version: ARTNET_PROTOCOL_VERSION,
+            rdm_version: 1,
+            filler: 0,
+            spare: [0; 7],
+            net: 0,
+            command: Rdm::AR_PROCESS,
+            address: 0,
+            packet: SharedData::new(),
+        }
+    }
+}
+
+impl Rdm {
+    /// Process the RDM packet
+    pub const AR_PROCESS: u8 = 0x00;
+
+    /// The length of the fields before the RDM packet, excluding the Art-Net header and opcode
+    #[cfg(feature = "bytes")]
+    pub(crate) const HEADER_LENGTH: usize = 14;
+
+    /// A message with the given RDM message, for the given Port-Address
+    pub fn new(port_address: PortAddress, message: &impl RdmMessage) -> Result<Rdm> {
+        let mut packet = Vec::new();
+        message.write_rdm(&mut packet)?;
+        Ok(Rdm {
+            net: port_address.net(),
+            address: port_address.sub_uni(),
+            packet: packet.into(),
+            ..Rdm::default()
+        })
+    }
+
+    /// Parse the RDM packet into a message
+    pub fn message<M: RdmMessage>(&self) -> Result<M> {
+        M::parse_rdm(&self.packet)
+    }
+
+    /// The Port-Address of the devices
+    pub fn port_address(&self) -> PortAddress {
+        PortAddress::from_parts(self.net, self.address)
+    }
+
+    /// Wrap this message in an `ArtCommand::Rdm`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{
+    convert::{Convertable, Cursor, WriteBuffer},
+    Result,
+};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Deref;
+
+#[cfg(feature = "bytes")]
+type Inner = bytes::Bytes;
+#[cfg(not(feature = "bytes"))]
+type Inner = Vec<u8>;
+
+/// The variable length data of a message that can be large, e.g. the RDM packet of an ArtRdm or a block of an ArtFirmwareMaster.
+///
+/// With the `bytes` feature, the data is stored in a `bytes::Bytes`. Cloning the data is cheap then, and `ArtCommand::from_bytes` parses the message without copying it. Without it, the data is stored in a `Vec<u8>`. The data can be read as a `[u8]` in both cases.
+///
+/// ```rust
+/// use artnet_protocol::*;
+///
+/// let data = SharedData::from(vec![1, 2, 3]);
+/// assert_eq!(&data[..], &[1, 2, 3]);
+/// assert_eq!(data.into_vec(), vec![1, 2, 3]);
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct SharedData {
+    inner: Inner,
+}
+
+impl SharedData {
+    /// Empty data
+    pub fn new() -> SharedData {
+        SharedData::default()
+    }
+
+    /// Convert the data into a `Vec<u8>`. This does not copy the data if it is stored in a `Vec<u8>` already.
+    pub fn into_vec(self) -> Vec<u8> {
+        #[cfg(feature = "bytes")]
+        let data = Vec::from(self.inner);
+        #[cfg(not(feature = "bytes"))]
+        let data = self.inner;
+        data
+    }
+}
+
+impl Deref for SharedData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.inner
+    }
+}
+
+impl AsRef<[u8]> for SharedData {
+    fn as_ref(&self) -> &[u8] {
+        &self.inner
+    }
+}
+
+impl From<Vec<u8>> for SharedData {
+    /// With the `bytes` feature, the `Vec` is converted into a `Bytes` without copying
+    // Without the `bytes` feature this is the same type
+    #[allow(clippy::useless_conversion)]
+    fn from(data: Vec<u8>) -> SharedData {
+        SharedData { inner: data.into() }
+    }
+}
+
+impl From<&[u8]> for SharedData {
+    fn from(data: &[u8]) -> SharedData {
+        SharedData::from(data.to_vec())
+    }
+}
+
+impl From<SharedData> for Vec<u8> {
+    fn from(data: SharedData) -> Vec<u8> {
+        data.into_vec()
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl From<bytes::Bytes> for SharedData {
+    /// This does not copy the data
+    fn from(inner: bytes::Bytes) -> SharedData {
+        SharedData { inner }
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl From<SharedData> for bytes::Bytes {
+    /// This does not copy the data
+    fn from(data: SharedData) -> bytes::Bytes {
+        data.inner
+    }
+}
+
+impl PartialEq<[u8]> for SharedData {
+    fn eq(&self, other: &[u8]) -> bool {
+        self[..] == *other
+    }
+}
+
+impl PartialEq<Vec<u8>> for SharedData {
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl fmt::Debug for SharedData {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{:?}", &self[..])
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for SharedData {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(SharedData::from(Vec::<u8>::arbitrary(u)?))
+    }
+}
+
+impl<T> Convertable<T> for SharedData {
+    fn from_cursor(cursor: &mut Cursor) -> Result<Self> {
+        Ok(SharedData::from(cursor.remaining()))
+    }
+
+    fn write_to_buffer(&self, buffer: &mut dyn WriteBuffer, _: &T) -> Result<()> {
+        buffer.extend_from_slice(self);
+        Ok(())
+    }
+
+    fn serialized_len(&self, _: &T) -> usize {
+        self.len()
+    }
+
+    #[cfg(test)]
+    fn get_test_value() -> Self {
+        SharedData::from(alloc::vec![1, 2, 3, 4])
+    }
+    #[cfg(test)]
+    fn is_equal(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+#[cfg(all(test, feature = "bytes"))]
+mod tests {
+    use super::*;
+    use crate::ArtCommand;
+    use alloc::vec;
+    use bytes::Bytes;
+
+    #[cfg(feature = "rdm")]
+    #[test]
+    fn rdm_from_bytes() {
+        let rdm = crate::Rdm {
+            packet: vec![1, 2, 3, 4].into(),
+            ..crate::Rdm::default()
+        };
+        let message = Bytes::from(rdm.into_command().write_to_buffer().unwrap());
+        let command = ArtCommand::from_bytes(&message).unwrap();
+        let rdm = match &command {
+            ArtCommand::Rdm(rdm) => rdm,
+            command => panic!("Expected an Rdm, got {:?}", command),
+        };
+        assert_eq!(rdm.packet, vec![1, 2, 3, 4]);
+        assert_eq!(rdm.packet.as_ptr(), message[24..].as_ptr());
+    }
+
+    #[cfg(feature = "firmware")]
+    #[test]
+    fn firmware_master_from_bytes() {
+        let master = crate::FirmwareMaster::new(
+            crate::FirmwareBlockType::FirmFirst,
+            0,
+            512,
+            vec![7; crate::FirmwareMaster::BLOCK_SIZE],
+        );
+        let message = Bytes::from(master.clone().into_command().write_to_buffer().unwrap());
+        let command = ArtCommand::from_bytes(&message).unwrap();
+        let parsed = match &command {
+            ArtCommand::FirmwareMaster(master) => master,
+            command => panic!("Expected a FirmwareMaster, got {:?}", command),
+        };
+        assert_eq!(**parsed, master);
+        assert_eq!(parsed.data.as_ptr(), message[40..].as_ptr());
+        assert_eq!(Vec::from(parsed.data.clone()), vec![7; 1024]);
+    }
+}
@@ -0,0 +1,37 @@
+use crate::command::ARTNET_PROTOCOL_VERSION;
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "Sent by a controller after the ArtDmx packets of a frame, to make the nodes output them at the same time."]
+    #[doc = ""]
+    #[doc = "A node that received an ArtSync buffers ArtDmx until the next ArtSync. If no ArtSync is received for 4 seconds, it returns to outputting ArtDmx immediately. See [SyncReceiver](struct.SyncReceiver.html)."]
+    #[doc = ""]
+    #[doc = "This is not called `Sync`, to not shadow the `Sync` trait when this crate is glob-imported."]
+    pub struct ArtSync {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub aux1: u8,
+        #[doc = "Transmit as zero"]
+        pub aux2: u8,
+    }
+}
+
+impl Default for ArtSync {
+    fn default() -> ArtSync {
+        ArtSync {
+            version: ARTNET_PROTOCOL_VERSION,
+            aux1: 0,
+            aux2: 0,
+        }
+    }
+}
+
+impl ArtSync {
+    /// Wrap this message in an `ArtCommand::Sync`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{command::ARTNET_PROTOCOL_VERSION, Error, ParseOptions, Payload, Result};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// A single `key=value` command of a [TextCommand](struct.TextCommand.html)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandEntry {
+    /// `SwoutText`, the text of the playback (output) button of a node, e.g. `"Playback"`
+    SwoutText(String),
+
+    /// `SwinText`, the text of the record (input) button of a node, e.g. `"Record"`
+    SwinText(String),
+
+    /// Any other command, e.g. a command of a single manufacturer
+    Other {
+        /// The name of the command
+        key: String,
+        /// The value of the command, which may be empty
+        value: String,
+    },
+}
+
+impl CommandEntry {
+    /// A command with the given key and value. The standard keys are recognized regardless of their case.
+    pub fn new(key: &str, value: &str) -> CommandEntry {
+        if key.eq_ignore_ascii_case("SwoutText") {
+            CommandEntry::SwoutText(value.into())
+        } else if key.eq_ignore_ascii_case("SwinText") {
+            CommandEntry::SwinText(value.into())
+        } else {
+            CommandEntry::Other {
+                key: key.into(),
+                value: value.into(),
+            }
+        }
+    }
+
+    /// The name of the command
+    pub fn key(&self) -> &str {
+        match self {
+            CommandEntry::SwoutText(_) => "SwoutText",
+            CommandEntry::SwinText(_) => "SwinText",
+            CommandEntry::Other { key, .. } => key,
+        }
+    }
+
+    /// The value of the command
+    pub fn value(&self) -> &str {
+        match self {
+            CommandEntry::SwoutText(value) | CommandEntry::SwinText(value) => value,
+            CommandEntry::Other { value, .. } => value,
+        }
+    }
+}
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "ArtCommand sends text commands to nodes, e.g. to change the labels of their buttons. The text is a list of `key=value&` commands, see [CommandEntry](enum.CommandEntry.html)."]
+    #[doc = ""]
+    #[doc = "This is not called `Command`, to not be confused with the `ArtCommand` enum or `std::process::Command`."]
+    pub struct TextCommand {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "The ESTA manufacturer code of the nodes that should handle the commands, or `TextCommand::ESTA_ANY` for all nodes. In big endian"]
+        pub esta_manufacturer: [u8; 2],
+        #[doc = "The length of the text, including the terminating zero. In big endian"]
+        pub length: [u8; 2],
+        #[doc = "The text of the commands, at most 512 bytes including the terminating zero"]
+        pub data: Payload,
+    }
+}
+
+impl Default for TextCommand {
+    fn default() -> TextCommand {
+        TextCommand {
+            version: ARTNET_PROTOCOL_VERSION,
+            esta_manufacturer: TextCommand::ESTA_ANY.to_be_bytes(),
+            length: 1u16.to_be_bytes(),
+            data: Payload::from(&[0][..]),
+        }
+    }
+}
+
+impl TextCommand {
+    /// The ESTA manufacturer code that addresses the nodes of every manufacturer
+    pub const ESTA_ANY: u16 = 0xFFFF;
+
+    /// The maximum length of the text, including the terminating zero
+    pub const MAX_LENGTH: usize = 512;
+
+    /// An empty list of commands for the nodes of the given manufacturer
+    pub fn new(esta_manufacturer: u16) -> TextCommand {
+        TextCommand {
+            esta_manufacturer: esta_manufacturer.to_be_bytes(),
+            ..TextCommand::default()
+        }
+    }
+
+    /// Append a command. Returns an error if the key or value contains `=`, `&` or a character that is not printable ASCII, or if the text would not fit in the message.
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// let command = TextCommand::new(TextCommand::ESTA_ANY)
+    ///     .with_entry(CommandEntry::SwoutText("Playback".into()))?
+    ///     .with_entry(CommandEntry::SwinText("Record".into()))?;
+    /// assert_eq!(command.text(), "SwoutText=Playback&SwinText=Record&");
+    /// assert_eq!(command.entries()[1], CommandEntry::SwinText("Record".into()));
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn with_entry(mut self, entry: CommandEntry) -> Result<TextCommand> {
+        let valid = |text: &str| {
+            text.bytes().all(|b| b.is_ascii_graphic() || b == b' ') && !text.contains(['=', '&'])
+        };
+        if entry.key().is_empty() || !valid(entry.key()) || !valid(entry.value()) {
+            return Err(Error::SpecViolation(
+                "TextCommand entries must be printable ASCII without '=' or '&'",
+            ));
+        }
+        let mut text = self.text().to_string();
+        text.push_str(entry.key());
+        text.push('=');
+        text.push_str(entry.value());
+        text.push('&');
+        if text.len() + 1 > TextCommand::MAX_LENGTH {
+            return Err(Error::SpecViolation(
+                "TextCommand can contain at most 511 bytes of text",
+            ));
+        }
+        self.data = text.into_bytes().into();
+        self.data.push(0);
+        self.length = (self.data.len() as u16).to_be_bytes();
+        Ok(self)
+    }
+
+    /// The ESTA manufacturer code of the nodes that should handle the commands
+    pub fn esta_manufacturer(&self) -> u16 {
+        u16::from_be_bytes(self.esta_manufacturer)
+    }
+
+    /// The text of the commands, up to the terminating zero. Bytes that are not valid UTF-8 end the text.
+    pub fn text(&self) -> &str {
+        super::zero_terminated_text(&self.data, u16::from_be_bytes(self.length))
+    }
+
+    /// Parse the commands in the text. A command without `=` has an empty value.
+    pub fn entries(&self) -> Vec<CommandEntry> {
+        self.text()
+            .split('&')
+            .filter(|command| !command.is_empty())
+            .map(|command| match command.split_once('=') {
+                Some((key, value)) => CommandEntry::new(key, value),
+                None => CommandEntry::new(command, ""),
+            })
+            .collect()
+    }
+
+    /// Wrap this message in an `ArtCommand::Command`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+
+    pub(crate) fn from_with(data: &[u8], options: &ParseOptions) -> Result<TextCommand> {
+        let mut command = TextCommand::from(data)?;
+        let length = usize::from(u16::from_be_bytes(command.length));
+        if options.is_strict() {
+            if length > TextCommand::MAX_LENGTH || length > command.data.len() {
+                return Err(Error::SpecViolation(
+                    "TextCommand::length does not match the length of the text",
+                ));
+            }
+        } else if length < command.data.len() {
+            // Ignore any trailing bytes after the text
+            command.data.truncate(length);
+        }
+        Ok(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArtCommand;
+
+    #[test]
+    fn round_trip() {
+        let command = TextCommand::new(0x4141)
+            .with_entry(CommandEntry::new("swouttext", "Go"))
+            .unwrap()
+            .with_entry(CommandEntry::new("Mode", "2"))
+            .unwrap();
+        let bytes = command.clone().into_command().write_to_buffer().unwrap();
+        assert_eq!(&bytes[12..16], &[0x41, 0x41, 0, 21]);
+        assert_eq!(&bytes[16..], b"SwoutText=Go&Mode=2&\0");
+
+        match ArtCommand::from_buffer_with(&bytes, &ParseOptions::strict()).unwrap() {
+            ArtCommand::Command(parsed) => {
+                assert_eq!(*parsed, command);
+                assert_eq!(parsed.esta_manufacturer(), 0x4141);
+                assert_eq!(
+                    parsed.entries(),
+                    vec![
+                        CommandEntry::SwoutText("Go".into()),
+                        CommandEntry::Other {
+                            key: "Mode".into(),
+                            value: "2".into()
+                        }
+                    ]
+                );
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let command = TextCommand::default();
+        assert!(command.entries().is_empty());
+        assert!(command
+            .clone()
+            .with_entry(CommandEntry::new("Key", "a&b"))
+            .is_err());
+        assert!(command
+            .with_entry(CommandEntry::new("Key", &"x".repeat(512)))
+            .is_err());
+
+        let raw = TextCommand {
+            length: 6u16.to_be_bytes(),
+            data: Payload::from(&b"A=1&B\0C=3&"[..]),
+            ..TextCommand::default()
+        };
+        assert_eq!(
+            raw.entries(),
+            vec![CommandEntry::new("A", "1"), CommandEntry::new("B", "")]
+        );
+    }
+}
@@ -0,0 +1,605 @@
+use crate::{command::ARTNET_PROTOCOL_VERSION, Error, ParseOptions, Result};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::time::Duration;
+
+/// The frame rate of a [Timecode](struct.Timecode.html). The values are the same in Art-Net and MIDI Timecode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameRate {
+    /// 24 frames per second
+    Film,
+
+    /// 25 frames per second
+    Ebu,
+
+    /// 29.97 frames per second, which drops frame 0 and 1 of every minute that is not a multiple of 10
+    DropFrame,
+
+    /// 30 frames per second
+    Smpte,
+}
+
+impl FrameRate {
+    /// The rate with the given value of `Timecode::kind`
+    pub fn from_kind(kind: u8) -> Option<FrameRate> {
+        match kind {
+            0 => Some(FrameRate::Film),
+            1 => Some(FrameRate::Ebu),
+            2 => Some(FrameRate::DropFrame),
+            3 => Some(FrameRate::Smpte),
+            _ => None,
+        }
+    }
+
+    /// The value of `Timecode::kind` for this rate
+    pub fn kind(self) -> u8 {
+        match self {
+            FrameRate::Film => 0,
+            FrameRate::Ebu => 1,
+            FrameRate::DropFrame => 2,
+            FrameRate::Smpte => 3,
+        }
+    }
+
+    /// The number of frames that is counted every second, which is 30 for drop frame time code
+    pub fn frames_per_second(self) -> u8 {
+        match self {
+            FrameRate::Film => 24,
+            FrameRate::Ebu => 25,
+            FrameRate::DropFrame | FrameRate::Smpte => 30,
+        }
+    }
+
+    /// The number of frames that are played every second, e.g. 29.97 for drop frame time code. See `fps_rational` for the exact value.
+    pub fn fps(self) -> f64 {
+        let (frames, seconds) = self.fps_rational();
+        f64::from(frames) / f64::from(seconds)
+    }
+
+    /// The exact number of frames that are played every second, as `(frames, seconds)`, e.g. `(30_000, 1_001)` for drop frame time code
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// assert_eq!(FrameRate::Ebu.fps_rational(), (25, 1));
+    /// assert_eq!(FrameRate::DropFrame.fps_rational(), (30_000, 1_001));
+    /// ```
+    pub fn fps_rational(self) -> (u32, u32) {
+        match self {
+            FrameRate::DropFrame => (30_000, 1_001),
+            _ => (u32::from(self.frames_per_second()), 1),
+        }
+    }
+
+    /// The number of frames that are counted in an hour of time code. Drop frame time code skips 108 frame numbers every hour, so this is 107_892.
+    pub fn frames_per_hour(self) -> u32 {
+        match self {
+            // 1800 frames in the first minute of every 10 minutes, 1798 in the others
+            FrameRate::DropFrame => 6 * FRAMES_PER_10_MINUTES,
+            _ => 3_600 * u32::from(self.frames_per_second()),
+        }
+    }
+
+    /// The time that a single frame takes, rounded up to the nanosecond. Drop frame time code runs at 29.97 frames per second.
+    pub fn frame_duration(self) -> Duration {
+        self.frame_offset(1)
+    }
+
+    /// The time from the start of the first frame to the start of the given frame, rounded up to the nanosecond
+    pub(crate) fn frame_offset(self, frame: u64) -> Duration {
+        let (frames, seconds) = self.real_rate();
+        let nanos = (u128::from(frame) * seconds * 1_000_000_000).div_ceil(frames);
+        Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+    }
+
+    /// The number of frames that started in the given time since the start of the first frame, including the first frame
+    #[cfg(feature = "std")]
+    pub(crate) fn frames_in(self, elapsed: Duration) -> u64 {
+        let (frames, seconds) = self.real_rate();
+        let count = elapsed.as_nanos() * frames / (seconds * 1_000_000_000) + 1;
+        u64::try_from(count).unwrap_or(u64::MAX)
+    }
+
+    /// `fps_rational`, for calculations with nanoseconds
+    fn real_rate(self) -> (u128, u128) {
+        let (frames, seconds) = self.fps_rational();
+        (u128::from(frames), u128::from(seconds))
+    }
+
+    /// The number of frames in a day
+    pub(crate) fn frames_per_day(self) -> u32 {
+        24 * self.frames_per_hour()
+    }
+}
+
+/// The number of frames in 10 minutes of drop frame time code
+const FRAMES_PER_10_MINUTES: u32 = 17_982;
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "ArtTimeCode transports time code over the network, e.g. to synchronize show control software or media servers."]
+    #[doc = ""]
+    #[doc = "The time code can be converted to and from an absolute frame count, e.g. of an LTC reader, and to and from MIDI Timecode quarter frames."]
+    pub struct Timecode {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub filler1: u8,
+        #[doc = "The stream of the time code, 0 for the master stream"]
+        pub stream_id: u8,
+        #[doc = "The frames, from 0 to one less than the frame rate"]
+        pub frames: u8,
+        #[doc = "The seconds, from 0 to 59"]
+        pub seconds: u8,
+        #[doc = "The minutes, from 0 to 59"]
+        pub minutes: u8,
+        #[doc = "The hours, from 0 to 23"]
+        pub hours: u8,
+        #[doc = "The frame rate, see [FrameRate](enum.FrameRate.html)"]
+        pub kind: u8,
+    }
+}
+
+impl Default for Timecode {
+    fn default() -> Timecode {
+        Timecode {
+            version: ARTNET_PROTOCOL_VERSION,
+            filler1: 0,
+            stream_id: 0,
+            frames: 0,
+            seconds: 0,
+            minutes: 0,
+            hours: 0,
+            kind: FrameRate::Smpte.kind(),
+        }
+    }
+}
+
+impl Timecode {
+    /// Create an ArtTimeCode for the master stream with the given time
+    pub fn new(hours: u8, minutes: u8, seconds: u8, frames: u8, rate: FrameRate) -> Timecode {
+        Timecode {
+            frames,
+            seconds,
+            minutes,
+            hours,
+            kind: rate.kind(),
+            ..Timecode::default()
+        }
+    }
+
+    /// The frame rate, or `None` if `kind` is unknown
+    pub fn frame_rate(&self) -> Option<FrameRate> {
+        FrameRate::from_kind(self.kind)
+    }
+
+    /// The number of frames since midnight, or `None` if `kind` is unknown. Drop frame time code skips the frames that are dropped.
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// assert_eq!(Timecode::new(0, 1, 0, 2, FrameRate::DropFrame).frame_count(), Some(1800));
+    /// assert_eq!(Timecode::from_frame_count(1800, FrameRate::DropFrame).frames, 2);
+    /// ```
+    pub fn frame_count(&self) -> Option<u32> {
+        let rate = self.frame_rate()?;
+        let total_minutes = u32::from(self.hours) * 60 + u32::from(self.minutes);
+        let seconds = total_minutes * 60 + u32::from(self.seconds);
+        let count = seconds * u32::from(rate.frames_per_second()) + u32::from(self.frames);
+        Some(match rate {
+            FrameRate::DropFrame => count - 2 * (total_minutes - total_minutes / 10),
+            _ => count,
+        })
+    }
+
+    /// The time code of the given number of frames since midnight. Counts of more than a day wrap around.
+    pub fn from_frame_count(count: u32, rate: FrameRate) -> Timecode {
+        let mut count = count % rate.frames_per_day();
+        if rate == FrameRate::DropFrame {
+            // Add the frame numbers that were skipped, to get a count at 30 frames per second
+            let tens = count / FRAMES_PER_10_MINUTES;
+            let rest = count % FRAMES_PER_10_MINUTES;
+            count += 18 * tens;
+            if rest >= 2 {
+                count += 2 * ((rest - 2) / 1798);
+            }
+        }
+        let fps = u32::from(rate.frames_per_second());
+        let seconds = count / fps;
+        Timecode::new(
+            (seconds / 3600) as u8,
+            (seconds / 60 % 60) as u8,
+            (seconds % 60) as u8,
+            (count % fps) as u8,
+            rate,
+        )
+    }
+
+    /// The time code the given number of frames later, or earlier if `frames` is negative, or `None` if `kind` is unknown. The time code wraps around at midnight, and drop frame time code skips the frames that are dropped. The stream stays the same.
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// let timecode = Timecode::new(0, 0, 59, 29, FrameRate::DropFrame);
+    /// let next = timecode.add_frames(1).unwrap();
+    /// assert_eq!(next.to_string(), "00:01:00;02");
+    /// assert_eq!(next.add_frames(-1), Some(timecode));
+    /// ```
+    pub fn add_frames(&self, frames: i64) -> Option<Timecode> {
+        let rate = self.frame_rate()?;
+        let per_day = i64::from(rate.frames_per_day());
+        let count = (i64::from(self.frame_count()?) + frames).rem_euclid(per_day);
+        Some(Timecode {
+            version: self.version,
+            stream_id: self.stream_id,
+            ..Timecode::from_frame_count(count as u32, rate)
+        })
+    }
+
+    /// The number of frames from `earlier` to this time code, which is negative if `earlier` is later. Returns `None` if the frame rates differ or are unknown. This does not wrap around at midnight.
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// let start = Timecode::new(1, 0, 0, 0, FrameRate::Ebu);
+    /// let end = Timecode::new(1, 0, 2, 5, FrameRate::Ebu);
+    /// assert_eq!(end.frames_since(&start), Some(55));
+    /// assert_eq!(start.frames_since(&end), Some(-55));
+    /// ```
+    pub fn frames_since(&self, earlier: &Timecode) -> Option<i64> {
+        if self.kind != earlier.kind {
+            return None;
+        }
+        Some(i64::from(self.frame_count()?) - i64::from(earlier.frame_count()?))
+    }
+
+    /// The data bytes of the 8 MIDI Timecode quarter frame messages of this time code, without the 0xF1 status byte. Unknown frame rates are sent as 30 fps.
+    ///
+    /// A receiver has the complete time code after the 8 messages, which take 2 frames to send.
+    pub fn to_mtc_quarter_frames(&self) -> [u8; 8] {
+        let rate = self.frame_rate().unwrap_or(FrameRate::Smpte).kind();
+        let values = [
+            self.frames & 0x0F,
+            (self.frames >> 4) & 0x01,
+            self.seconds & 0x0F,
+            (self.seconds >> 4) & 0x03,
+            self.minutes & 0x0F,
+            (self.minutes >> 4) & 0x03,
+            self.hours & 0x0F,
+            (rate << 1) | ((self.hours >> 4) & 0x01),
+        ];
+        let mut messages = [0; 8];
+        for (piece, (message, value)) in messages.iter_mut().zip(values.iter()).enumerate() {
+            *message = ((piece as u8) << 4) | value;
+        }
+        messages
+    }
+
+    /// Combine the data bytes of 8 MIDI Timecode quarter frame messages, in the order of their piece number. Returns `None` if a message is missing.
+    pub fn from_mtc_quarter_frames(messages: &[u8; 8]) -> Option<Timecode> {
+        let mut values = [0; 8];
+        for (piece, (value, message)) in values.iter_mut().zip(messages.iter()).enumerate() {
+            if usize::from(message >> 4) != piece {
+                return None;
+            }
+            *value = message & 0x0F;
+        }
+        let rate = FrameRate::from_kind((values[7] >> 1) & 0x03)?;
+        Some(Timecode::new(
+            values[6] | ((values[7] & 0x01) << 4),
+            values[4] | (values[5] << 4),
+            values[2] | (values[3] << 4),
+            values[0] | (values[1] << 4),
+            rate,
+        ))
+    }
+
+    /// Wrap this message in an `ArtCommand::OpTimeCode`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+
+    /// Parse a time code in the "HH:MM:SS:FF" notation at the given frame rate. Drop frame time code may also use the "HH:MM:SS;FF" notation.
+    ///
+    /// Returns `Error::InvalidTimecode` if the text is not a valid time at this frame rate, e.g. because the frame number is dropped.
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// let timecode = Timecode::parse_with_rate("01:02:03:24", FrameRate::Ebu).unwrap();
+    /// assert_eq!(timecode, Timecode::new(1, 2, 3, 24, FrameRate::Ebu));
+    /// assert!(Timecode::parse_with_rate("01:02:03:25", FrameRate::Ebu).is_err());
+    /// ```
+    pub fn parse_with_rate(text: &str, rate: FrameRate) -> Result<Timecode> {
+        const NOTATION: Error = Error::InvalidTimecode("expected HH:MM:SS:FF");
+        let parts: Vec<&str> = text.split([':', ';']).collect();
+        let (hours, minutes, seconds, frames) = match parts[..] {
+            [hours, minutes, seconds, frames] => (hours, minutes, seconds, frames),
+            _ => return Err(NOTATION),
+        };
+        // Only the separator before the frames may be a semicolon
+        let separator = text.len() - frames.len() - 1;
+        if text[..separator].contains(';') {
+            return Err(NOTATION);
+        }
+        if text.as_bytes()[separator] == b';' && rate != FrameRate::DropFrame {
+            return Err(Error::InvalidTimecode(
+                "only drop frame time code can be written as HH:MM:SS;FF",
+            ));
+        }
+
+        let hours = parse_field(hours, 24, "the hours must be from 0 to 23")?;
+        let minutes = parse_field(minutes, 60, "the minutes must be from 0 to 59")?;
+        let seconds = parse_field(seconds, 60, "the seconds must be from 0 to 59")?;
+        let frames = parse_field(
+            frames,
+            rate.frames_per_second(),
+            "the frames must be lower than the frame rate",
+        )?;
+        let timecode = Timecode::new(hours, minutes, seconds, frames, rate);
+        if rate == FrameRate::DropFrame && timecode.is_dropped() {
+            return Err(Error::InvalidTimecode(
+                "frame 0 and 1 are dropped in this minute",
+            ));
+        }
+        Ok(timecode)
+    }
+
+    /// Check that the time code is a valid time at its frame rate: the frames are lower than the frame rate, the seconds and minutes lower than 60 and the hours lower than 24. Drop frame time code must not use the frame numbers that are dropped.
+    ///
+    /// Returns `Error::SpecViolation` otherwise. This is checked when parsing with `ParseMode::Strict`.
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// assert!(Timecode::new(0, 10, 0, 0, FrameRate::DropFrame).validate().is_ok());
+    /// assert!(Timecode::new(0, 11, 0, 0, FrameRate::DropFrame).validate().is_err());
+    /// assert!(Timecode::new(0, 0, 0, 25, FrameRate::Ebu).validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        let rate = self
+            .frame_rate()
+            .ok_or(Error::SpecViolation("Timecode::kind must be from 0 to 3"))?;
+        if self.frames >= rate.frames_per_second() {
+            return Err(Error::SpecViolation(
+                "Timecode::frames must be lower than the frame rate",
+            ));
+        }
+        if self.seconds >= 60 {
+            return Err(Error::SpecViolation(
+                "Timecode::seconds must be from 0 to 59",
+            ));
+        }
+        if self.minutes >= 60 {
+            return Err(Error::SpecViolation(
+                "Timecode::minutes must be from 0 to 59",
+            ));
+        }
+        if self.hours >= 24 {
+            return Err(Error::SpecViolation("Timecode::hours must be from 0 to 23"));
+        }
+        if rate == FrameRate::DropFrame && self.is_dropped() {
+            return Err(Error::SpecViolation(
+                "Timecode::frames is a frame number that is dropped",
+            ));
+        }
+        Ok(())
+    }
+
+    /// If this is frame 0 or 1 of a minute that is not a multiple of 10, which drop frame time code skips
+    fn is_dropped(&self) -> bool {
+        self.seconds == 0 && self.frames < 2 && !self.minutes.is_multiple_of(10)
+    }
+
+    pub(crate) fn from_with(data: &[u8], options: &ParseOptions) -> Result<Timecode> {
+        let timecode = Timecode::from(data)?;
+        if options.is_strict() {
+            timecode.validate()?;
+        }
+        Ok(timecode)
+    }
+}
+
+/// Parse a field of one or two digits, which must be lower than `max`
+fn parse_field(text: &str, max: u8, invalid: &'static str) -> Result<u8> {
+    if text.is_empty() || text.len() > 2 || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Error::InvalidTimecode("expected HH:MM:SS:FF"));
+    }
+    match text.parse() {
+        Ok(value) if value < max => Ok(value),
+        _ => Err(Error::InvalidTimecode(invalid)),
+    }
+}
+
+impl core::fmt::Display for Timecode {
+    /// Formats the time code as "HH:MM:SS:FF", or "HH:MM:SS;FF" for drop frame time code
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let separator = if self.frame_rate() == Some(FrameRate::DropFrame) {
+            ';'
+        } else {
+            ':'
+        };
+        write!(
+            fmt,
+            "{:02}:{:02}:{:02}{}{:02}",
+            self.hours, self.minutes, self.seconds, separator, self.frames
+        )
+    }
+}
+
+impl core::str::FromStr for Timecode {
+    type Err = Error;
+
+    /// Parse "HH:MM:SS;FF" as drop frame time code, and "HH:MM:SS:FF" at 30 frames per second. See `Timecode::parse_with_rate` for the other frame rates.
+    fn from_str(text: &str) -> Result<Timecode> {
+        let rate = if text.contains(';') {
+            FrameRate::DropFrame
+        } else {
+            FrameRate::Smpte
+        };
+        Timecode::parse_with_rate(text, rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text() {
+        let timecode = Timecode::new(1, 2, 3, 4, FrameRate::Film);
+        assert_eq!(timecode.to_string(), "01:02:03:04");
+        let drop_frame = Timecode::new(23, 59, 0, 2, FrameRate::DropFrame);
+        assert_eq!(drop_frame.to_string(), "23:59:00;02");
+
+        assert_eq!("23:59:00;02".parse::<Timecode>(), Ok(drop_frame.clone()));
+        assert_eq!(
+            Timecode::parse_with_rate("23:59:00:02", FrameRate::DropFrame),
+            Ok(drop_frame)
+        );
+        assert_eq!(
+            "1:2:3:29".parse::<Timecode>(),
+            Ok(Timecode::new(1, 2, 3, 29, FrameRate::Smpte))
+        );
+        assert!(Timecode::parse_with_rate("01:02:03;04", FrameRate::Film).is_err());
+        for invalid in [
+            "",
+            "01:02:03",
+            "01:02:03:04:05",
+            "01;02:03;04",
+            "24:00:00:00",
+            "00:60:00:00",
+            "00:00:00:30",
+            "00:01:00;01",
+            "+1:02:03:04",
+            "001:02:03:04",
+        ] {
+            assert!(invalid.parse::<Timecode>().is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn rates() {
+        assert_eq!(FrameRate::Film.fps(), 24.0);
+        assert!((FrameRate::DropFrame.fps() - 29.97).abs() < 0.001);
+        assert_eq!(FrameRate::Smpte.frames_per_hour(), 108_000);
+        assert_eq!(FrameRate::DropFrame.frames_per_hour(), 107_892);
+        assert_eq!(
+            Timecode::new(1, 0, 0, 0, FrameRate::DropFrame).frame_count(),
+            Some(FrameRate::DropFrame.frames_per_hour())
+        );
+        assert_eq!(FrameRate::Ebu.frame_duration(), Duration::from_millis(40));
+        assert_eq!(
+            FrameRate::DropFrame.frame_duration(),
+            Duration::from_nanos(33_366_667)
+        );
+    }
+
+    #[test]
+    fn add_frames() {
+        let mut timecode = Timecode::new(23, 59, 59, 23, FrameRate::Film);
+        timecode.stream_id = 3;
+        let next = timecode.add_frames(2).unwrap();
+        assert_eq!(next.to_string(), "00:00:00:01");
+        assert_eq!(next.stream_id, 3);
+        assert_eq!(next.add_frames(-2), Some(timecode.clone()));
+        assert_eq!(next.frames_since(&timecode), Some(-(24 * 86_400 - 2)));
+
+        // 10 minutes of drop frame time code, forward and back
+        let start = Timecode::new(0, 10, 0, 0, FrameRate::DropFrame);
+        let later = start.add_frames(17_982).unwrap();
+        assert_eq!(later.to_string(), "00:20:00;00");
+        assert_eq!(later.frames_since(&start), Some(17_982));
+        assert_eq!(later.add_frames(-17_982), Some(start.clone()));
+        assert_eq!(
+            start.frames_since(&Timecode::new(0, 10, 0, 0, FrameRate::Smpte)),
+            None
+        );
+
+        let unknown = Timecode {
+            kind: 4,
+            ..Timecode::default()
+        };
+        assert_eq!(unknown.add_frames(1), None);
+    }
+
+    #[test]
+    fn frame_count() {
+        for rate in [
+            FrameRate::Film,
+            FrameRate::Ebu,
+            FrameRate::DropFrame,
+            FrameRate::Smpte,
+        ] {
+            for count in (0..rate.frames_per_day()).step_by(997) {
+                let timecode = Timecode::from_frame_count(count, rate);
+                assert_eq!(timecode.frame_count(), Some(count), "{:?}", timecode);
+            }
+        }
+
+        let timecode = Timecode::new(1, 56, 34, 12, FrameRate::Ebu);
+        assert_eq!(
+            timecode.frame_count(),
+            Some((3_600 + 56 * 60 + 34) * 25 + 12)
+        );
+        // 00:00:59:29 is followed by 00:01:00:02 in drop frame time code
+        let last = Timecode::new(0, 0, 59, 29, FrameRate::DropFrame);
+        let next =
+            Timecode::from_frame_count(last.frame_count().unwrap() + 1, FrameRate::DropFrame);
+        assert_eq!((next.minutes, next.seconds, next.frames), (1, 0, 2));
+        let tenth = Timecode::from_frame_count(FRAMES_PER_10_MINUTES, FrameRate::DropFrame);
+        assert_eq!((tenth.minutes, tenth.frames), (10, 0));
+        assert_eq!(
+            Timecode::from_frame_count(24 * 86_400, FrameRate::Film).frame_count(),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn mtc_quarter_frames() {
+        let timecode = Timecode::new(23, 59, 58, 29, FrameRate::DropFrame);
+        let messages = timecode.to_mtc_quarter_frames();
+        assert_eq!(messages, [0x0D, 0x11, 0x2A, 0x33, 0x4B, 0x53, 0x67, 0x75]);
+        assert_eq!(Timecode::from_mtc_quarter_frames(&messages), Some(timecode));
+
+        let mut out_of_order = messages;
+        out_of_order.swap(0, 1);
+        assert_eq!(Timecode::from_mtc_quarter_frames(&out_of_order), None);
+    }
+
+    #[test]
+    fn strict() {
+        let options = ParseOptions::strict();
+        let mut bytes = Timecode::new(1, 2, 3, 29, FrameRate::Smpte)
+            .into_command()
+            .write_to_buffer()
+            .unwrap();
+        assert!(crate::ArtCommand::from_buffer_with(&bytes, &options).is_ok());
+        bytes[18] = FrameRate::Ebu.kind();
+        assert!(crate::ArtCommand::from_buffer_with(&bytes, &options).is_err());
+        assert!(crate::ArtCommand::from_buffer(&bytes).is_ok());
+
+        // 00:01:00;00 does not exist in drop frame time code
+        let mut bytes = Timecode::new(0, 1, 0, 0, FrameRate::DropFrame)
+            .into_command()
+            .write_to_buffer()
+            .unwrap();
+        assert!(crate::ArtCommand::from_buffer_with(&bytes, &options).is_err());
+        bytes[14] = 2;
+        assert!(crate::ArtCommand::from_buffer_with(&bytes, &options).is_ok());
+        for invalid in [
+            Timecode::new(24, 0, 0, 0, FrameRate::Film),
+            Timecode::new(0, 60, 0, 0, FrameRate::Film),
+            Timecode::new(0, 0, 60, 0, FrameRate::Film),
+            Timecode::new(0, 0, 0, 24, FrameRate::Film),
+            Timecode {
+                kind: 4,
+                ..Timecode::default()
+            },
+        ] {
+            assert!(invalid.validate().is_err(), "{:?}", invalid);
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use crate::{command::ARTNET_PROTOCOL_VERSION, Error, ParseOptions, Result};
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "ArtTimeSync sets the real time clock of the nodes, e.g. so they can run timed shows without a controller."]
+    #[doc = ""]
+    #[doc = "The time is the local time of the sender, with the fields of the `struct tm` of C. It converts to and from `SystemTime`, and to and from `chrono::NaiveDateTime` with the `chrono` feature."]
+    pub struct TimeSync {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub filler1: u8,
+        #[doc = "Transmit as zero"]
+        pub filler2: u8,
+        #[doc = "The programming command, transmit as zero"]
+        pub prog: u8,
+        #[doc = "The seconds, from 0 to 60 to allow for a leap second"]
+        pub seconds: u8,
+        #[doc = "The minutes, from 0 to 59"]
+        pub minutes: u8,
+        #[doc = "The hours, from 0 to 23"]
+        pub hours: u8,
+        #[doc = "The day of the month, from 1 to 31"]
+        pub day: u8,
+        #[doc = "The month, from 0 for January to 11 for December"]
+        pub month: u8,
+        #[doc = "The number of years since 1900, in big endian"]
+        pub year: [u8; 2],
+        #[doc = "The day of the week, from 0 for Sunday to 6 for Saturday"]
+        pub weekday: u8,
+        #[doc = "Non-zero if daylight saving time is in effect"]
+        pub dst: u8,
+    }
+}
+
+impl Default for TimeSync {
+    /// Midnight of 1 January 1970
+    fn default() -> TimeSync {
+        TimeSync {
+            version: ARTNET_PROTOCOL_VERSION,
+            filler1: 0,
+            filler2: 0,
+            prog: 0,
+            seconds: 0,
+            minutes: 0,
+            hours: 0,
+            day: 1,
+            month: 0,
+            year: 70u16.to_be_bytes(),
+            weekday: 4,
+            dst: 0,
+        }
+    }
+}
+
+impl TimeSync {
+    /// The time of the clock at the given local time. The day of the week is calculated.
+    #[cfg(any(feature = "std", feature = "chrono"))]
+    fn from_local_seconds(seconds: i64, dst: bool) -> TimeSync {
+        let days = seconds.div_euclid(86_400);
+        let time = seconds.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        TimeSync {
+            seconds: (time % 60) as u8,
+            minutes: (time / 60 % 60) as u8,
+            hours: (time / 3600) as u8,
+            day: day as u8,
+            month: (month - 1) as u8,
+            year: ((year - 1900) as u16).to_be_bytes(),
+            // 1 January 1970 was a Thursday
+            weekday: (days + 4).rem_euclid(7) as u8,
+            dst: u8::from(dst),
+            ..TimeSync::default()
+        }
+    }
+
+    /// The local time of the clock in seconds since 1970, or `None` if a field is out of range. A leap second is counted as the next second.
+    fn local_seconds(&self) -> Option<i64> {
+        if self.month > 11
+            || !(1..=31).contains(&self.day)
+            || self.hours > 23
+            || self.minutes > 59
+            || self.seconds > 60
+        {
+            return None;
+        }
+        let year = 1900 + i64::from(u16::from_be_bytes(self.year));
+        let days = days_from_civil(year, i64::from(self.month) + 1, i64::from(self.day));
+        Some(
+            days * 86_400
+                + i64::from(self.hours) * 3600
+                + i64::from(self.minutes) * 60
+                + i64::from(self.seconds),
+        )
+    }
+
+    /// Whether daylight saving time is in effect
+    pub fn is_dst(&self) -> bool {
+        self.dst != 0
+    }
+
+    /// The clock of a sender in the given time zone. `utc_offset` is the offset of standard time in seconds, e.g. 3600 for Central European Time. If `dst` is set, the clock is one hour ahead of standard time.
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// // 2024-07-01 12:00:00 UTC is 14:00 in Amsterdam
+    /// let time = UNIX_EPOCH + Duration::from_secs(1_719_835_200);
+    /// let sync = TimeSync::from_system_time(time, 3600, true);
+    /// assert_eq!(sync.hours, 14);
+    /// assert_eq!(sync.to_system_time(3600), Some(time));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn from_system_time(time: SystemTime, utc_offset: i32, dst: bool) -> TimeSync {
+        let seconds = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs() as i64),
+        };
+        let offset = i64::from(utc_offset) + if dst { 3600 } else { 0 };
+        TimeSync::from_local_seconds(seconds + offset, dst)
+    }
+
+    /// The moment that the clock shows, for a sender in the given time zone. `utc_offset` is the offset of standard time in seconds, one hour is added when `dst` is set. Returns `None` if a field is out of range.
+    #[cfg(feature = "std")]
+    pub fn to_system_time(&self, utc_offset: i32) -> Option<SystemTime> {
+        let offset = i64::from(utc_offset) + if self.is_dst() { 3600 } else { 0 };
+        let seconds = self.local_seconds()? - offset;
+        if seconds >= 0 {
+            UNIX_EPOCH.checked_add(Duration::from_secs(seconds as u64))
+        } else {
+            UNIX_EPOCH.checked_sub(Duration::from_secs(seconds.unsigned_abs()))
+        }
+    }
+
+    /// The clock at the given local time
+    #[cfg(feature = "chrono")]
+    pub fn from_naive(time: chrono::NaiveDateTime, dst: bool) -> TimeSync {
+        TimeSync::from_local_seconds(time.and_utc().timestamp(), dst)
+    }
+
+    /// The local time of the clock, or `None` if a field is out of range
+    #[cfg(feature = "chrono")]
+    pub fn to_naive(&self) -> Option<chrono::NaiveDateTime> {
+        chrono::DateTime::from_timestamp(self.local_seconds()?, 0).map(|time| time.naive_utc())
+    }
+
+    /// Wrap this message in an `ArtCommand::OpTimeSync`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+
+    pub(crate) fn from_with(data: &[u8], options: &ParseOptions) -> Result<TimeSync> {
+        let sync = TimeSync::from(data)?;
+        if options.is_strict() && (sync.local_seconds().is_none() || sync.weekday > 6) {
+            return Err(Error::SpecViolation("TimeSync is not a valid time"));
+        }
+        Ok(sync)
+    }
+}
+
+/// The number of days since 1970-01-01 of the given date, with the month from 1 to 12
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // Count from 1 March, so the leap day is the last day of the year
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The year, month from 1 to 12 and day of the given number of days since 1970-01-01
+#[cfg(any(feature = "std", feature = "chrono"))]
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn system_time() {
+        // Thursday 29 February 2024, 23:59:30 UTC
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_251_170);
+        let sync = TimeSync::from_system_time(time, 0, false);
+        assert_eq!(
+            (
+                sync.day,
+                sync.month,
+                u16::from_be_bytes(sync.year),
+                sync.weekday
+            ),
+            (29, 1, 124, 4)
+        );
+        assert_eq!((sync.hours, sync.minutes, sync.seconds), (23, 59, 30));
+        assert_eq!(sync.to_system_time(0), Some(time));
+
+        // Daylight saving time moves the clock to the next day
+        let sync = TimeSync::from_system_time(time, 0, true);
+        assert!(sync.is_dst());
+        assert_eq!(
+            (sync.day, sync.month, sync.hours, sync.weekday),
+            (1, 2, 0, 5)
+        );
+        assert_eq!(sync.to_system_time(0), Some(time));
+        assert_eq!(
+            sync.to_system_time(-5 * 3600),
+            Some(time + Duration::from_secs(5 * 3600))
+        );
+
+        assert_eq!(
+            TimeSync::from_system_time(UNIX_EPOCH, 0, false),
+            TimeSync::default()
+        );
+        let before = UNIX_EPOCH - Duration::from_secs(86_400);
+        let sync = TimeSync::from_system_time(before, 0, false);
+        assert_eq!((sync.day, sync.month, sync.weekday), (31, 11, 3));
+        assert_eq!(sync.to_system_time(0), Some(before));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono() {
+        let time = chrono::NaiveDate::from_ymd_opt(2030, 12, 31)
+            .unwrap()
+            .and_hms_opt(8, 15, 0)
+            .unwrap();
+        let sync = TimeSync::from_naive(time, false);
+        assert_eq!((sync.day, sync.month, sync.hours), (31, 11, 8));
+        assert_eq!(sync.to_naive(), Some(time));
+    }
+
+    #[test]
+    fn strict() {
+        let options = ParseOptions::strict();
+        let mut bytes = TimeSync::default()
+            .into_command()
+            .write_to_buffer()
+            .unwrap();
+        assert!(crate::ArtCommand::from_buffer_with(&bytes, &options).is_ok());
+        // Month 12 does not exist
+        bytes[19] = 12;
+        assert!(crate::ArtCommand::from_buffer_with(&bytes, &options).is_err());
+        assert!(crate::ArtCommand::from_buffer(&bytes).is_ok());
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{command::ARTNET_PROTOCOL_VERSION, PortAddress};
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "Sent by a controller to control the RDM discovery of a node, e.g. to flush its Table of Devices and start a full discovery."]
+    pub struct TodControl {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub filler: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub spare: [u8; 7],
+        #[doc = "Bits 14-8 of the Port-Address"]
+        pub net: u8,
+        #[doc = "The command, e.g. `TodControl::FLUSH`"]
+        pub command: u8,
+        #[doc = "Bits 7-0 of the Port-Address"]
+        pub address: u8,
+    }
+}
+
+impl Default for TodControl {
+    fn default() -> TodControl {
+        TodControl {
+            version: ARTNET_PROTOCOL_VERSION,
+            filler: [0; 2],
+            spare: [0; 7],
+            net: 0,
+            command: TodControl::NONE,
+            address: 0,
+        }
+    }
+}
+
+impl TodControl {
+    /// No action
+    pub const NONE: u8 = 0x00;
+
+    /// Flush the Table of Devices and start a full discovery
+    pub const FLUSH: u8 = 0x01;
+
+    /// Stop discovery and incremental discovery
+    pub const END: u8 = 0x02;
+
+    /// Enable incremental discovery
+    pub const INC_ON: u8 = 0x03;
+
+    /// Disable incremental discovery
+    pub const INC_OFF: u8 = 0x04;
+
+    /// A message that makes the nodes flush the Table of Devices of the given Port-Address, and start a full discovery
+    pub fn flush(port_address: PortAddress) -> TodControl {
+        TodControl {
+            net: port_address.net(),
+            command: TodControl::FLUSH,
+            address: port_address.sub_uni(),
+            ..TodControl::default()
+        }
+    }
+
+    /// The Port-Address that this message is for
+    pub fn port_address(&self) -> PortAddress {
+        PortAddress::from_parts(self.net, self.address)
+    }
+
+    /// Wrap this message in an `ArtCommand::TodControl`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+}
@@ -0,0 +1,170 @@
+use super::{check_count, Count};
+use crate::{command::ARTNET_PROTOCOL_VERSION, Error, ParseOptions, PortAddress, Result, Uid};
+use alloc::vec;
+use alloc::vec::Vec;
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "Sent by a node with (part of) the Table of Devices (ToD) of one of its ports. A table with more than 200 devices is split over several blocks."]
+    pub struct TodData {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "The RDM version, 1 for RDM STANDARD V1.0"]
+        pub rdm_version: u8,
+        #[doc = "The physical port, from 1 to 4"]
+        pub port: u8,
+        #[doc = "Transmit as zero"]
+        pub spare: [u8; 6],
+        #[doc = "The bind index of the node that sent this, 1 for the root device"]
+        pub bind_index: u8,
+        #[doc = "Bits 14-8 of the Port-Address"]
+        pub net: u8,
+        #[doc = "`TodData::TOD_FULL` for a complete table, or `TodData::TOD_NAK` if the table is not available"]
+        pub command_response: u8,
+        #[doc = "Bits 7-0 of the Port-Address"]
+        pub address: u8,
+        #[doc = "The total number of devices on this Port-Address, over all blocks, in big endian"]
+        pub uid_total: [u8; 2],
+        #[doc = "The index of this block, starting at 0"]
+        pub block_count: u8,
+        #[doc = "The number of entries in `uids`, set by the artnet library itself"]
+        pub uid_count: Count<TodData>,
+        #[doc = "The devices in this block, at most 200"]
+        pub uids: Vec<Uid>,
+    }
+}
+
+impl Default for TodData {
+    fn default() -> TodData {
+        TodData {
+            version: ARTNET_PROTOCOL_VERSION,
+            rdm_version: 1,
+            port: 1,
+            spare: [0; 6],
+            bind_index: 1,
+            net: 0,
+            command_response: TodData::TOD_FULL,
+            address: 0,
+            uid_total: [0; 2],
+            block_count: 0,
+            uid_count: Count::default(),
+            uids: Vec::new(),
+        }
+    }
+}
+
+impl TodData {
+    /// `command_response` of a (block of a) complete table
+    pub const TOD_FULL: u8 = 0x00;
+
+    /// `command_response` when the Table of Devices is not available, e.g. because discovery is not complete yet
+    pub const TOD_NAK: u8 = 0xFF;
+
+    /// The maximum number of UIDs in a single block
+    pub const MAX_UIDS: usize = 200;
+
+    /// The size of a message with the maximum number of UIDs, including the Art-Net header and opcode
+    pub const MAX_PACKET_SIZE: usize = 28 + TodData::MAX_UIDS * 6;
+
+    /// The maximum number of UIDs in a table, in 256 blocks
+    pub const MAX_TABLE_UIDS: usize = 256 * TodData::MAX_UIDS;
+
+    /// Split the Table of Devices of a Port-Address into blocks of at most 200 UIDs, numbered from 0. An empty table is sent as a single block without UIDs.
+    ///
+    /// Returns `Error::SpecViolation` if the table has more than `TodData::MAX_TABLE_UIDS` devices.
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// let uids: Vec<Uid> = (0..450).map(|device| Uid::new(0x7FF0, device)).collect();
+    /// let blocks = TodData::blocks(1.into(), &uids).unwrap();
+    /// assert_eq!(blocks.len(), 3);
+    /// assert_eq!(blocks[2].block_count, 2);
+    /// assert_eq!(blocks[2].uids.len(), 50);
+    /// assert_eq!(blocks[2].uid_total(), 450);
+    /// ```
+    pub fn blocks(port_address: PortAddress, uids: &[Uid]) -> Result<Vec<TodData>> {
+        if uids.len() > TodData::MAX_TABLE_UIDS {
+            return Err(Error::SpecViolation(
+                "A Table of Devices can contain at most 51200 UIDs",
+            ));
+        }
+        let template = TodData {
+            net: port_address.net(),
+            address: port_address.sub_uni(),
+            uid_total: (uids.len() as u16).to_be_bytes(),
+            ..TodData::default()
+        };
+        if uids.is_empty() {
+            return Ok(vec![template]);
+        }
+        Ok(uids
+            .chunks(TodData::MAX_UIDS)
+            .enumerate()
+            .map(|(block, uids)| TodData {
+                block_count: block as u8,
+                uids: uids.to_vec(),
+                ..template.clone()
+            })
+            .collect())
+    }
+
+    /// The Port-Address of the table
+    pub fn port_address(&self) -> PortAddress {
+        PortAddress::from_parts(self.net, self.address)
+    }
+
+    /// The total number of devices on this Port-Address, over all blocks
+    pub fn uid_total(&self) -> u16 {
+        u16::from_be_bytes(self.uid_total)
+    }
+
+    /// Wrap this message in an `ArtCommand::TodData`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+
+    pub(crate) fn from_with(data: &[u8], options: &ParseOptions) -> Result<TodData> {
+        let mut tod = TodData::from(data)?;
+        let count = *tod.uid_count as usize;
+        // A partial UID at the end is not parsed, so compare the length in bytes as well
+        if options.is_strict() && data.len() != TodData::LENGTH + count * 6 {
+            return Err(Error::SpecViolation(
+                "TodData::uid_count does not match the number of UIDs",
+            ));
+        }
+        check_count(
+            &mut tod.uids,
+            count,
+            options,
+            "TodData::uid_count does not match the number of UIDs",
+        )?;
+        Ok(tod)
+    }
+
+    /// The length of a TodData without UIDs, excluding the Art-Net header and opcode
+    const LENGTH: usize = 18;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trailing_bytes() {
+        let tod = TodData {
+            uid_total: [0, 1],
+            uids: vec![Uid::new(0x7FF0, 1)],
+            ..TodData::default()
+        };
+        let mut bytes = tod.to_bytes().unwrap();
+        bytes.extend_from_slice(&[0; 6]);
+
+        let parsed = TodData::from_with(&bytes, &ParseOptions::lenient()).unwrap();
+        assert_eq!(parsed.uids, vec![Uid::new(0x7FF0, 1)]);
+        assert_eq!(parsed.uid_total(), 1);
+        assert!(TodData::from_with(&bytes, &ParseOptions::strict()).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+mod control;
+mod data;
+mod request;
+
+use crate::{
+    convert::{Convertable, Cursor, WriteBuffer},
+    Error, ParseOptions, Result,
+};
+use alloc::vec::Vec;
+
+pub use self::control::TodControl;
+pub use self::data::TodData;
+pub use self::request::TodRequest;
+
+/// Ignore trailing entries after the count, or reject them when parsing strictly
+pub(crate) fn check_count<T>(
+    list: &mut Vec<T>,
+    count: usize,
+    options: &ParseOptions,
+    violation: &'static str,
+) -> Result<()> {
+    if options.is_strict() && list.len() != count {
+        return Err(Error::SpecViolation(violation));
+    }
+    list.truncate(count);
+    Ok(())
+}
+
+/// The number of entries in the list that follows, which is set by the artnet library when serializing
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct Count<T> {
+    /// The count that was parsed, or 0 if this was not parsed
+    parsed_count: u8,
+    _pd: core::marker::PhantomData<T>,
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for Count<T> {
+    fn arbitrary(_: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        // The count is determined by the list when serializing
+        Ok(Count {
+            parsed_count: 0,
+            _pd: core::marker::PhantomData,
+        })
+    }
+}
+
+impl<T> core::fmt::Debug for Count<T> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(fmt, "{}", self.parsed_count)
+    }
+}
+
+impl<T> core::ops::Deref for Count<T> {
+    type Target = u8;
+
+    fn deref(&self) -> &u8 {
+        &self.parsed_count
+    }
+}
+
+macro_rules! convert_count {
+    ($context:ty, $list:ident, $max:expr) => {
+        impl Convertable<$context> for Count<$context> {
+            fn from_cursor(cursor: &mut Cursor) -> Result<Self> {
+                Ok(Count {
+                    parsed_count: cursor.read_u8()?,
+                    _pd: core::marker::PhantomData,
+                })
+            }
+            fn write_to_buffer(
+                &self,
+                buffer: &mut dyn WriteBuffer,
+                context: &$context,
+            ) -> Result<()> {
+                let len = context.$list.len();
+                if len > $max {
+                    return Err(Error::MessageSizeInvalid {
+                        len,
+                        allowed_size: 0..$max + 1,
+                    });
+                }
+                buffer.push(len as u8);
+                Ok(())
+            }
+            fn serialized_len(&self, _: &$context) -> usize {
+                1
+            }
+            #[cfg(test)]
+            fn get_test_value() -> Self {
+                Default::default()
+            }
+            #[cfg(test)]
+            fn is_equal(&self, _: &Self) -> bool {
+                // The count is only known after parsing
+                true
+            }
+        }
+    };
+}
+
+convert_count!(TodRequest, addresses, TodRequest::MAX_ADDRESSES);
+convert_count!(TodData, uids, TodData::MAX_UIDS);
@@ -0,0 +1,100 @@
+use super::{check_count, Count};
+use crate::{command::ARTNET_PROTOCOL_VERSION, ParseOptions, PortAddress, Result};
+use alloc::vec::Vec;
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "Sent by a controller to request the Table of Devices (ToD) of the given Port-Addresses. Nodes reply with ArtTodData."]
+    pub struct TodRequest {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub filler: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub spare: [u8; 7],
+        #[doc = "Bits 14-8 of the Port-Addresses in `addresses`"]
+        pub net: u8,
+        #[doc = "Only `TodRequest::TOD_FULL` is defined"]
+        pub command: u8,
+        #[doc = "The number of entries in `addresses`, set by the artnet library itself"]
+        pub address_count: Count<TodRequest>,
+        #[doc = "Bits 7-0 of the Port-Addresses that should reply, at most 32"]
+        pub addresses: Vec<u8>,
+    }
+}
+
+impl Default for TodRequest {
+    fn default() -> TodRequest {
+        TodRequest {
+            version: ARTNET_PROTOCOL_VERSION,
+            filler: [0; 2],
+            spare: [0; 7],
+            net: 0,
+            command: TodRequest::TOD_FULL,
+            address_count: Count::default(),
+            addresses: Vec::new(),
+        }
+    }
+}
+
+impl TodRequest {
+    /// Request the full Table of Devices
+    pub const TOD_FULL: u8 = 0x00;
+
+    /// The maximum number of Port-Addresses in a single request
+    pub const MAX_ADDRESSES: usize = 32;
+
+    /// The Port-Addresses that should reply
+    pub fn port_addresses(&self) -> impl Iterator<Item = PortAddress> + '_ {
+        self.addresses
+            .iter()
+            .map(move |address| PortAddress::from_parts(self.net, *address))
+    }
+
+    /// Wrap this message in an `ArtCommand::TodRequest`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+
+    pub(crate) fn from_with(data: &[u8], options: &ParseOptions) -> Result<TodRequest> {
+        let mut request = TodRequest::from(data)?;
+        let count = *request.address_count as usize;
+        check_count(
+            &mut request.addresses,
+            count,
+            options,
+            "TodRequest::address_count does not match the number of addresses",
+        )?;
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn port_addresses() {
+        let request = TodRequest {
+            net: 2,
+            addresses: vec![0x10, 0x11],
+            ..TodRequest::default()
+        };
+        let bytes = request.to_bytes().unwrap();
+        let parsed = TodRequest::from_with(&bytes, &ParseOptions::strict()).unwrap();
+        assert_eq!(*parsed.address_count, 2);
+        let addresses: Vec<u16> = parsed.port_addresses().map(u16::from).collect();
+        assert_eq!(addresses, vec![0x0210, 0x0211]);
+    }
+
+    #[test]
+    fn too_many_addresses() {
+        let request = TodRequest {
+            addresses: vec![0; 33],
+            ..TodRequest::default()
+        };
+        assert!(request.to_bytes().is_err());
+    }
+}
@@ -0,0 +1,318 @@
+use crate::command::ARTNET_PROTOCOL_VERSION;
+use crate::convert::{Convertable, Cursor, WriteBuffer};
+use crate::{Error, Result};
+use alloc::vec::Vec;
+
+/// The meaning of the `key` of an [ArtTrigger](struct.Trigger.html) for all devices, see `Trigger::trigger_key`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TriggerKey {
+    /// The sub key is an ASCII character, e.g. a key press on a keyboard
+    Ascii,
+
+    /// The sub key is the number of a macro to run
+    Macro,
+
+    /// The sub key is a soft key, e.g. a button on the front panel of a console
+    Soft,
+
+    /// The sub key is the number of a show to run
+    Show,
+
+    /// A key from 4 to 0xFF of a general trigger, which is not defined by the Art-Net specification
+    Undefined(u8),
+
+    /// A key from 0x80 to 0xFF of a trigger for the devices of a single manufacturer, which that manufacturer defines
+    ManufacturerSpecific(u8),
+}
+
+impl TriggerKey {
+    /// The first key of the manufacturer specific range
+    pub const MANUFACTURER_SPECIFIC_START: u8 = 0x80;
+
+    /// The variant with the given value of `Trigger::key`. The raw value of undefined and manufacturer specific keys is kept, so `key` returns the same value.
+    ///
+    /// This does not know the Oem code of the trigger, see `Trigger::trigger_key` for the kind of a received trigger.
+    pub fn from_key(key: u8) -> TriggerKey {
+        match key {
+            0 => TriggerKey::Ascii,
+            1 => TriggerKey::Macro,
+            2 => TriggerKey::Soft,
+            3 => TriggerKey::Show,
+            key if key >= TriggerKey::MANUFACTURER_SPECIFIC_START => {
+                TriggerKey::ManufacturerSpecific(key)
+            }
+            key => TriggerKey::Undefined(key),
+        }
+    }
+
+    /// The value of `Trigger::key` for this variant
+    pub fn key(self) -> u8 {
+        match self {
+            TriggerKey::Ascii => 0,
+            TriggerKey::Macro => 1,
+            TriggerKey::Soft => 2,
+            TriggerKey::Show => 3,
+            TriggerKey::Undefined(key) | TriggerKey::ManufacturerSpecific(key) => key,
+        }
+    }
+}
+
+impl From<u8> for TriggerKey {
+    fn from(key: u8) -> TriggerKey {
+        TriggerKey::from_key(key)
+    }
+}
+
+impl From<TriggerKey> for u8 {
+    fn from(key: TriggerKey) -> u8 {
+        key.key()
+    }
+}
+
+/// The payload of an ArtTrigger, up to 512 bytes.
+///
+/// A shorter payload is padded with zeroes when it is sent, so a trigger can be created with only the bytes that it uses. When parsing, at most 512 bytes are read.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct TriggerData {
+    inner: Vec<u8>,
+}
+
+impl TriggerData {
+    /// The number of bytes in the payload, before it is padded
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// If the payload is empty
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+impl AsRef<Vec<u8>> for TriggerData {
+    fn as_ref(&self) -> &Vec<u8> {
+        &self.inner
+    }
+}
+
+impl AsMut<Vec<u8>> for TriggerData {
+    fn as_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.inner
+    }
+}
+
+impl From<Vec<u8>> for TriggerData {
+    fn from(inner: Vec<u8>) -> TriggerData {
+        TriggerData { inner }
+    }
+}
+
+impl From<&[u8]> for TriggerData {
+    fn from(data: &[u8]) -> TriggerData {
+        TriggerData {
+            inner: data.to_vec(),
+        }
+    }
+}
+
+impl<T> Convertable<T> for TriggerData {
+    fn from_cursor(cursor: &mut Cursor) -> Result<Self> {
+        let len = cursor.remaining().len().min(Trigger::DATA_LENGTH);
+        Ok(TriggerData {
+            inner: cursor.read_slice(len)?.to_vec(),
+        })
+    }
+
+    fn write_to_buffer(&self, buffer: &mut dyn WriteBuffer, _: &T) -> Result<()> {
+        if self.inner.len() > Trigger::DATA_LENGTH {
+            return Err(Error::MessageSizeInvalid {
+                len: self.inner.len(),
+                allowed_size: 0..Trigger::DATA_LENGTH + 1,
+            });
+        }
+        buffer.extend_from_slice(&self.inner);
+        buffer.extend_from_slice(&[0; Trigger::DATA_LENGTH][self.inner.len()..]);
+        Ok(())
+    }
+
+    fn serialized_len(&self, _: &T) -> usize {
+        Trigger::DATA_LENGTH
+    }
+
+    #[cfg(test)]
+    fn get_test_value() -> Self {
+        TriggerData {
+            inner: alloc::vec![1; Trigger::DATA_LENGTH],
+        }
+    }
+    #[cfg(test)]
+    fn is_equal(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+data_structure! {
+    @test
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[doc = "ArtTrigger triggers macros, shows or key presses on remote devices, e.g. to start a cue on a media server."]
+    #[doc = ""]
+    #[doc = "If `oem` is `Trigger::OEM_GENERAL`, the `key` is one of the [TriggerKey](enum.TriggerKey.html)s. Otherwise `key` and `sub_key` are specific to the devices of that manufacturer. See [TriggerDispatcher](struct.TriggerDispatcher.html) to call a handler for incoming triggers."]
+    pub struct Trigger {
+        #[doc = "Determines which version the server has. Will be ARTNET_PROTOCOL_VERSION by default"]
+        pub version: [u8; 2],
+        #[doc = "Transmit as zero"]
+        pub filler1: u8,
+        #[doc = "Transmit as zero"]
+        pub filler2: u8,
+        #[doc = "The Oem code of the devices that should act on this trigger, in big endian, or `Trigger::OEM_GENERAL` for all devices"]
+        pub oem: [u8; 2],
+        #[doc = "The kind of trigger, see [TriggerKey](enum.TriggerKey.html)"]
+        pub key: u8,
+        #[doc = "The macro, show, key or character to trigger"]
+        pub sub_key: u8,
+        #[doc = "The payload of the trigger, up to 512 bytes. Unused by the general keys."]
+        pub data: TriggerData,
+    }
+}
+
+impl Default for Trigger {
+    fn default() -> Trigger {
+        Trigger {
+            version: ARTNET_PROTOCOL_VERSION,
+            filler1: 0,
+            filler2: 0,
+            oem: Trigger::OEM_GENERAL.to_be_bytes(),
+            key: 0,
+            sub_key: 0,
+            data: TriggerData::default(),
+        }
+    }
+}
+
+impl Trigger {
+    /// The Oem code of a trigger for all devices, with a key from [TriggerKey](enum.TriggerKey.html)
+    pub const OEM_GENERAL: u16 = 0xFFFF;
+
+    /// The length of the data of a trigger, after it is padded
+    pub const DATA_LENGTH: usize = 512;
+
+    /// Create a trigger for all devices
+    pub fn new(key: TriggerKey, sub_key: u8) -> Trigger {
+        Trigger {
+            key: key.key(),
+            sub_key,
+            ..Trigger::default()
+        }
+    }
+
+    /// Create a trigger for the devices with the given Oem code, with a manufacturer specific key
+    pub fn with_oem(oem: u16, key: u8, sub_key: u8) -> Trigger {
+        Trigger {
+            oem: oem.to_be_bytes(),
+            key,
+            sub_key,
+            ..Trigger::default()
+        }
+    }
+
+    /// Set the payload of the trigger, which is padded with zeroes to 512 bytes when it is sent
+    pub fn with_data(mut self, data: impl Into<TriggerData>) -> Trigger {
+        self.data = data.into();
+        self
+    }
+
+    /// The Oem code of the devices that should act on this trigger
+    pub fn oem_code(&self) -> u16 {
+        u16::from_be_bytes(self.oem)
+    }
+
+    /// If this trigger is for all devices
+    pub fn is_general(&self) -> bool {
+        self.oem_code() == Trigger::OEM_GENERAL
+    }
+
+    /// The kind of trigger.
+    ///
+    /// For a general trigger, keys from 4 are `TriggerKey::Undefined`. For a trigger for the devices of a single manufacturer, keys from `TriggerKey::MANUFACTURER_SPECIFIC_START` are `TriggerKey::ManufacturerSpecific`, and the other keys return `None`, because their meaning is defined by that manufacturer, see `TriggerRoute::Oem`.
+    pub fn trigger_key(&self) -> Option<TriggerKey> {
+        match TriggerKey::from_key(self.key) {
+            TriggerKey::ManufacturerSpecific(key) if self.is_general() => {
+                Some(TriggerKey::Undefined(key))
+            }
+            key @ TriggerKey::ManufacturerSpecific(_) => Some(key),
+            key if self.is_general() => Some(key),
+            _ => None,
+        }
+    }
+
+    /// Wrap this message in an `ArtCommand::OpTrigger`
+    pub fn into_command(self) -> crate::ArtCommand {
+        self.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArtCommand;
+
+    #[test]
+    fn keys() {
+        for key in 0..=u8::MAX {
+            assert_eq!(TriggerKey::from_key(key).key(), key);
+        }
+        assert_eq!(TriggerKey::from(4), TriggerKey::Undefined(4));
+        assert_eq!(
+            TriggerKey::from(0x80),
+            TriggerKey::ManufacturerSpecific(0x80)
+        );
+
+        let trigger = Trigger::with_oem(0x1234, 0x85, 1);
+        let bytes = trigger.into_command().write_to_buffer().unwrap();
+        match ArtCommand::from_buffer(&bytes).unwrap() {
+            ArtCommand::OpTrigger(trigger) => {
+                assert_eq!(trigger.key, 0x85);
+                assert_eq!(
+                    trigger.trigger_key(),
+                    Some(TriggerKey::ManufacturerSpecific(0x85))
+                );
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+
+        // Keys from 4 are undefined for general triggers
+        let general = Trigger::new(TriggerKey::from(0x85), 1);
+        assert_eq!(general.trigger_key(), Some(TriggerKey::Undefined(0x85)));
+        // The lower keys of a single manufacturer are not interpreted
+        assert_eq!(Trigger::with_oem(0x1234, 1, 1).trigger_key(), None);
+    }
+
+    #[test]
+    fn short_data() {
+        let trigger = Trigger::with_oem(0x1234, 0x80, 1).with_data(&[1, 2, 3][..]);
+        let mut bytes = trigger.into_command().write_to_buffer().unwrap();
+        assert_eq!(bytes.len(), 18 + Trigger::DATA_LENGTH);
+        assert_eq!(&bytes[18..22], &[1, 2, 3, 0]);
+
+        // Trailing bytes after the payload are not part of it
+        bytes.extend_from_slice(&[9; 4]);
+        match ArtCommand::from_buffer(&bytes).unwrap() {
+            ArtCommand::OpTrigger(trigger) => {
+                assert_eq!(trigger.data.len(), Trigger::DATA_LENGTH);
+                assert_eq!(&trigger.data.as_ref()[..4], &[1, 2, 3, 0]);
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+
+        // A shorter payload is accepted as it is
+        match ArtCommand::from_buffer(&bytes[..21]).unwrap() {
+            ArtCommand::OpTrigger(trigger) => assert_eq!(trigger.data.as_ref(), &[1, 2, 3]),
+            command => panic!("Unexpected command {:?}", command),
+        }
+
+        let long = Trigger::default().with_data(alloc::vec![0; Trigger::DATA_LENGTH + 1]);
+        assert!(long.into_command().write_to_buffer().is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use crate::ArtCommand;
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::time::{Duration, Instant};
+
+/// A misconfiguration of the network that was detected by a [ConflictDetector](struct.ConflictDetector.html)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictEvent {
+    /// Two nodes with a different MAC address reply with the same IP address. Their commands and data are mixed up, which shows as flicker.
+    DuplicateIp {
+        /// The IP address that both nodes use
+        address: Ipv4Addr,
+        /// The MAC address that was seen first, and the MAC address of the new node
+        macs: [[u8; 6]; 2],
+    },
+
+    /// Another controller started polling while a controller was already active. Nodes that receive ArtDmx from both of them merge or alternate between the data.
+    MultipleControllers(Vec<Ipv4Addr>),
+}
+
+/// Detects duplicate IP addresses of nodes and multiple active controllers on the network, the most common causes of flicker.
+///
+/// Duplicate IP addresses are found in the ArtPollReply of the nodes, by their MAC address. Nodes that do not report a MAC address are not checked. Controllers are found by the source of ArtPoll. Sources are forgotten when they were not seen within the timeout. Every conflict is reported once, when it starts.
+///
+/// Pass every received command to `handle`, together with the IP address that sent it. Add the addresses of the application itself with `ignore`, as its own broadcast polls are received as well.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::Instant;
+///
+/// let mut detector = ConflictDetector::new();
+/// let now = Instant::now();
+/// let reply = |mac| PollReply {
+///     address: [10, 0, 0, 5].into(),
+///     mac,
+///     ..PollReply::default()
+/// };
+/// let source = [10, 0, 0, 5].into();
+/// assert!(detector.handle(&reply([2, 0, 0, 0, 0, 1]).into(), source, now).is_none());
+/// let event = detector.handle(&reply([2, 0, 0, 0, 0, 2]).into(), source, now);
+/// assert!(matches!(event, Some(ConflictEvent::DuplicateIp { .. })));
+/// ```
+#[derive(Debug)]
+pub struct ConflictDetector {
+    timeout: Duration,
+    ignored: Vec<Ipv4Addr>,
+    /// The MAC addresses of the nodes with an IP address, and when they were last seen
+    nodes: HashMap<Ipv4Addr, Vec<([u8; 6], Instant)>>,
+    /// The controllers, and when they last sent a poll
+    controllers: HashMap<Ipv4Addr, Instant>,
+}
+
+impl Default for ConflictDetector {
+    fn default() -> ConflictDetector {
+        ConflictDetector::new()
+    }
+}
+
+impl ConflictDetector {
+    /// How long a node or controller is remembered after it was last seen. Controllers should poll every 2.5 to 3 seconds.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Create a detector with the default timeout
+    pub fn new() -> ConflictDetector {
+        ConflictDetector::with_timeout(ConflictDetector::DEFAULT_TIMEOUT)
+    }
+
+    /// Create a detector that remembers nodes and controllers for the given time after they were last seen
+    pub fn with_timeout(timeout: Duration) -> ConflictDetector {
+        ConflictDetector {
+            timeout,
+            ignored: Vec::new(),
+            nodes: HashMap::new(),
+            controllers: HashMap::new(),
+        }
+    }
+
+    /// Do not count polls from this address as a controller, e.g. the address of the application itself
+    pub fn ignore(&mut self, address: Ipv4Addr) {
+        self.ignored.push(address);
+        self.controllers.remove(&address);
+    }
+
+    /// Handle a command that was received from the given IP address. Returns a conflict if this command started one.
+    pub fn handle(
+        &mut self,
+        command: &ArtCommand,
+        source: Ipv4Addr,
+        now: Instant,
+    ) -> Option<ConflictEvent> {
+        self.expire(now);
+        match command {
+            ArtCommand::PollReply(reply) if reply.mac != [0; 6] => {
+                let macs = self.nodes.entry(reply.address).or_default();
+                if let Some((_, last_seen)) = macs.iter_mut().find(|(mac, _)| *mac == reply.mac) {
+                    *last_seen = now;
+                    return None;
+                }
+                let first = macs.first().map(|(mac, _)| *mac);
+                macs.push((reply.mac, now));
+                first.map(|first| ConflictEvent::DuplicateIp {
+                    address: reply.address,
+                    macs: [first, reply.mac],
+                })
+            }
+            ArtCommand::Poll(_) if !self.ignored.contains(&source) => {
+                if self.controllers.insert(source, now).is_some() || self.controllers.len() < 2 {
+                    return None;
+                }
+                let mut controllers: Vec<_> = self.controllers.keys().copied().collect();
+                controllers.sort();
+                Some(ConflictEvent::MultipleControllers(controllers))
+            }
+            _ => None,
+        }
+    }
+
+    /// Forget the nodes and controllers that were not seen within the timeout
+    pub fn expire(&mut self, now: Instant) {
+        let timeout = self.timeout;
+        let active = |last_seen: &Instant| now.saturating_duration_since(*last_seen) < timeout;
+        self.controllers.retain(|_, last_seen| active(last_seen));
+        for macs in self.nodes.values_mut() {
+            macs.retain(|(_, last_seen)| active(last_seen));
+        }
+        self.nodes.retain(|_, macs| !macs.is_empty());
+    }
+
+    /// The controllers that polled within the timeout
+    pub fn controllers(&self) -> impl Iterator<Item = Ipv4Addr> + '_ {
+        self.controllers.keys().copied()
+    }
+
+    /// The IP addresses that are used by more than one node, with their MAC addresses
+    pub fn duplicate_ips(&self) -> impl Iterator<Item = (Ipv4Addr, Vec<[u8; 6]>)> + '_ {
+        self.nodes
+            .iter()
+            .filter(|(_, macs)| macs.len() > 1)
+            .map(|(address, macs)| (*address, macs.iter().map(|(mac, _)| *mac).collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Poll;
+
+    #[test]
+    fn controllers() {
+        let mut detector = ConflictDetector::new();
+        let own = [10, 0, 0, 1].into();
+        let other = [10, 0, 0, 2].into();
+        let third = [10, 0, 0, 3].into();
+        detector.ignore(own);
+        let poll: ArtCommand = Poll::default().into();
+        let start = Instant::now();
+
+        assert!(detector.handle(&poll, own, start).is_none());
+        assert!(detector.handle(&poll, other, start).is_none());
+        assert!(detector.handle(&poll, other, start).is_none());
+        assert_eq!(
+            detector.handle(&poll, third, start),
+            Some(ConflictEvent::MultipleControllers(vec![other, third]))
+        );
+        assert_eq!(detector.controllers().count(), 2);
+
+        // The third controller went away, and is reported again when it comes back
+        let later = start + Duration::from_secs(8);
+        assert!(detector.handle(&poll, other, later).is_none());
+        let later = later + Duration::from_secs(5);
+        detector.expire(later);
+        assert_eq!(detector.controllers().collect::<Vec<_>>(), vec![other]);
+        assert!(detector.handle(&poll, third, later).is_some());
+    }
+
+    #[test]
+    fn duplicate_ip() {
+        let mut detector = ConflictDetector::new();
+        let start = Instant::now();
+        let address = [10, 0, 0, 5].into();
+        let reply = |mac| -> ArtCommand {
+            crate::PollReply {
+                address,
+                mac,
+                ..crate::PollReply::default()
+            }
+            .into()
+        };
+
+        // Nodes without a MAC address are not checked
+        assert!(detector.handle(&reply([0; 6]), address, start).is_none());
+        assert!(detector.handle(&reply([1; 6]), address, start).is_none());
+        assert_eq!(
+            detector.handle(&reply([2; 6]), address, start),
+            Some(ConflictEvent::DuplicateIp {
+                address,
+                macs: [[1; 6], [2; 6]],
+            })
+        );
+        assert!(detector.handle(&reply([2; 6]), address, start).is_none());
+        assert_eq!(
+            detector.duplicate_ips().collect::<Vec<_>>(),
+            vec![(address, vec![[1; 6], [2; 6]])]
+        );
+
+        detector.expire(start + ConflictDetector::DEFAULT_TIMEOUT);
+        assert!(detector.duplicate_ips().next().is_none());
+    }
+}
@@ -0,0 +1,607 @@
+use crate::transport::drain;
+#[cfg(feature = "dmx")]
+use crate::{Address, NodeId};
+use crate::{
+    ArtCommand, ArtNetSocket, ArtSync, DeliveryPolicy, DiscoveryEvent, DiscoveryManager, Encoder,
+    Interface, KeepaliveScheduler, Output, PollScheduler, PortAddress, RecvBuffer, Transport,
+};
+use std::collections::BTreeSet;
+use std::io;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// What a controller does when ArtDmx is sent to a broadcast address while nodes subscribed to its Port-Address, see `Controller::set_dmx_broadcast_policy`
+///
+/// Art-Net 4 asks controllers to unicast ArtDmx to the nodes that subscribed to the universe. Broadcasting it floods every device on the network, which can saturate it with only a few universes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DmxBroadcastPolicy {
+    /// Send the ArtDmx
+    Allow,
+    /// Send the ArtDmx, and log a warning with the `tracing` feature
+    #[default]
+    Warn,
+    /// Refuse to send the ArtDmx. The send returns an `std::io::Error` of kind `InvalidInput`.
+    Deny,
+}
+
+/// A simple Art-Net controller, that discovers the nodes on the network and sends ArtDmx to them.
+///
+/// Call `update` regularly, e.g. every few milliseconds or after every frame. It polls the network, handles the replies and re-transmits data that did not change, as the Art-Net specification requires.
+///
+/// ArtDmx is unicast to the nodes that have an output port with the given Port-Address. No data is sent for a universe until such a node replied to a poll.
+///
+/// The controller uses a non-blocking UDP socket by default. Use `with_transport` to run it on any other [Transport](trait.Transport.html).
+///
+/// ```rust,no_run
+/// use artnet_protocol::*;
+///
+/// let mut controller = Controller::bind(("0.0.0.0", 6454), ("255.255.255.255", 6454)).unwrap();
+/// loop {
+///     for event in controller.update().unwrap() {
+///         println!("{:?}", event);
+///     }
+///     controller.send_dmx(1.into(), &[255; 512]).unwrap();
+///     std::thread::sleep(std::time::Duration::from_millis(25));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Controller<T = UdpSocket> {
+    socket: T,
+    core: ControllerCore,
+    encoder: Encoder,
+    buffer: RecvBuffer,
+}
+
+impl Controller {
+    /// The shortest time between two polls. The Art-Net specification asks for an ArtPoll every 2.5 to 3 seconds, the controller picks a random interval within that range.
+    pub const DEFAULT_POLL_INTERVAL: Duration = PollScheduler::DEFAULT_MIN_INTERVAL;
+
+    /// Bind a socket to the given address, and send polls to the given broadcast address
+    pub fn bind(
+        addr: impl ToSocketAddrs,
+        broadcast_address: impl ToSocketAddrs,
+    ) -> io::Result<Controller> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_broadcast(true)?;
+        let broadcast_address = broadcast_address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No broadcast address"))?;
+        Controller::new(socket, broadcast_address)
+    }
+
+    /// Bind a socket to port 6454 on the given interface, and send polls to the directed broadcast address of its network, see [ArtNetSocket::bind_interface](struct.ArtNetSocket.html#method.bind_interface)
+    pub fn bind_interface(interface: Interface) -> io::Result<Controller> {
+        let socket = ArtNetSocket::bind_interface(interface)?;
+        let broadcast_address = socket.broadcast_address();
+        Controller::new(socket.into_inner(), broadcast_address)
+    }
+
+    /// Create a controller on an existing socket. The socket is made non-blocking.
+    pub fn new(socket: UdpSocket, broadcast_address: SocketAddr) -> io::Result<Controller> {
+        socket.set_nonblocking(true)?;
+        Ok(Controller::with_transport(socket, broadcast_address))
+    }
+}
+
+impl<T: Transport> Controller<T> {
+    /// Create a controller that sends and receives with the given transport, and sends polls to the given broadcast address
+    pub fn with_transport(transport: T, broadcast_address: SocketAddr) -> Controller<T> {
+        Controller {
+            socket: transport,
+            core: ControllerCore::new(broadcast_address),
+            encoder: Encoder::new(),
+            buffer: RecvBuffer::new(),
+        }
+    }
+
+    /// Poll the network at a fixed interval, instead of a random interval between 2.5 and 3 seconds
+    pub fn set_poll_interval(&mut self, interval: Duration) {
+        self.core.poll.set_interval(interval, interval);
+    }
+
+    /// Change how often unchanged data is re-transmitted, see [KeepaliveScheduler](struct.KeepaliveScheduler.html)
+    pub fn set_keepalive_interval(&mut self, interval: Duration) {
+        self.core.keepalive.set_interval(interval);
+    }
+
+    /// Refuse to send a command to an address that does not match its [DeliveryPolicy](enum.DeliveryPolicy.html), e.g. ArtDmx to the broadcast address. Such a send returns an `std::io::Error` of kind `InvalidInput`.
+    ///
+    /// This is off by default. An address counts as a broadcast address if it is the broadcast address of this controller, or `255.255.255.255`.
+    pub fn set_strict_delivery(&mut self, strict: bool) {
+        self.core.strict_delivery = strict;
+    }
+
+    /// What `send` does with ArtDmx or ArtNzs for the broadcast address while nodes subscribed to its Port-Address. By default a warning is logged.
+    ///
+    /// The data of `send_dmx` and `send_frame` is always unicast to the subscribed nodes, so this only affects `send`.
+    pub fn set_dmx_broadcast_policy(&mut self, policy: DmxBroadcastPolicy) {
+        self.core.dmx_broadcast_policy = policy;
+    }
+
+    /// The nodes that were discovered
+    pub fn discovery(&self) -> &DiscoveryManager {
+        &self.core.discovery
+    }
+
+    /// The socket or transport of this controller
+    pub fn socket(&self) -> &T {
+        &self.socket
+    }
+
+    /// Send any command to the given address
+    pub fn send(&mut self, command: &ArtCommand, addr: SocketAddr) -> io::Result<()> {
+        self.core.check(command, addr)?;
+        let bytes = self.encoder.encode(command).map_err(invalid_data)?;
+        self.socket.send_to(bytes, addr)?;
+        Ok(())
+    }
+
+    /// Send the given data to every node that outputs the given Port-Address. The sequence number is increased for every message.
+    pub fn send_dmx(&mut self, port_address: PortAddress, data: &[u8]) -> io::Result<()> {
+        let outgoing = self.core.send_dmx(port_address, data, Instant::now())?;
+        self.transmit(&outgoing)
+    }
+
+    /// Send the data of several universes that belong to the same frame, followed by an ArtSync to make the nodes output them at the same time.
+    ///
+    /// After this is called, re-transmitted data is followed by an ArtSync as well, because the nodes hold back ArtDmx until they receive one.
+    pub fn send_frame(&mut self, frame: &[(PortAddress, &[u8])]) -> io::Result<()> {
+        for outgoing in self.core.send_frame(frame, Instant::now())? {
+            self.transmit(&outgoing)?;
+        }
+        Ok(())
+    }
+
+    /// Program a discovered node with an ArtAddress, e.g. to rename it or change its Port-Addresses. The bind index of the message is set to the one of the node.
+    ///
+    /// ```rust,no_run
+    /// # use artnet_protocol::*;
+    /// # let mut controller = Controller::bind(("0.0.0.0", 6454), ("255.255.255.255", 6454)).unwrap();
+    /// # let node = controller.discovery().nodes().next().map(|(id, _)| *id).unwrap();
+    /// let address = Address::default()
+    ///     .program_short_name("Stage left")
+    ///     .program_output(0, 5.into())
+    ///     .unwrap()
+    ///     .with_command(AddressCommand::LedLocate);
+    /// controller.program(node, address).unwrap();
+    /// ```
+    ///
+    /// The node replies with an ArtPollReply, which is reported by the next `update` as `DiscoveryEvent::NodeUpdated`.
+    #[cfg(feature = "dmx")]
+    pub fn program(&mut self, node: NodeId, address: Address) -> io::Result<()> {
+        let outgoing = self.core.program(node, address)?;
+        self.transmit(&outgoing)
+    }
+
+    /// Poll the network when needed, handle all received messages and re-transmit data that did not change. Returns the changes in the list of nodes.
+    pub fn update(&mut self) -> io::Result<Vec<DiscoveryEvent>> {
+        let now = Instant::now();
+        if let Some(poll) = self.core.poll(now)? {
+            self.transmit(&poll)?;
+            self.core.poll_sent(now);
+        }
+
+        let mut events = self.handle_readable()?;
+        events.extend(self.core.discovery.expire(now));
+
+        for outgoing in self.core.retransmit(now)? {
+            self.transmit(&outgoing)?;
+        }
+        Ok(events)
+    }
+
+    /// Handle all messages that are waiting on the socket, without polling or re-transmitting. Call this when an event loop, e.g. mio, reports the socket as readable, and call `update` at `next_deadline`.
+    pub fn handle_readable(&mut self) -> io::Result<Vec<DiscoveryEvent>> {
+        let now = Instant::now();
+        let mut events = Vec::new();
+        let discovery = &mut self.core.discovery;
+        drain(&self.socket, &mut self.buffer, |command, _| {
+            events.extend(discovery.handle(&command, now));
+            Ok(())
+        })?;
+        Ok(events)
+    }
+
+    /// When `update` should be called next, to poll the network or re-transmit data
+    pub fn next_deadline(&self) -> Instant {
+        self.core.next_deadline(Instant::now())
+    }
+
+    fn transmit(&mut self, outgoing: &Outgoing) -> io::Result<()> {
+        let bytes = self
+            .encoder
+            .encode(&outgoing.command)
+            .map_err(invalid_data)?;
+        for addr in &outgoing.addrs {
+            self.socket.send_to(bytes, *addr)?;
+        }
+        Ok(())
+    }
+}
+
+/// A command that a `ControllerCore` decided to send, with the addresses to send it to. It was checked against the delivery policy already.
+#[derive(Debug)]
+pub(crate) struct Outgoing {
+    pub(crate) command: ArtCommand,
+    pub(crate) addrs: Vec<SocketAddr>,
+}
+
+/// The state of a controller, shared by the blocking `Controller` and the async `AsyncController`. It decides what to send and when, the wrappers only send the returned commands.
+#[derive(Debug)]
+pub(crate) struct ControllerCore {
+    pub(crate) broadcast_address: SocketAddr,
+    pub(crate) discovery: DiscoveryManager,
+    pub(crate) keepalive: KeepaliveScheduler,
+    pub(crate) poll: PollScheduler,
+    /// If data is followed by an ArtSync, after `send_frame` was called
+    synchronous: bool,
+    pub(crate) strict_delivery: bool,
+    pub(crate) dmx_broadcast_policy: DmxBroadcastPolicy,
+}
+
+impl ControllerCore {
+    pub(crate) fn new(broadcast_address: SocketAddr) -> ControllerCore {
+        ControllerCore {
+            broadcast_address,
+            discovery: DiscoveryManager::new(),
+            keepalive: KeepaliveScheduler::new(),
+            poll: PollScheduler::new(),
+            synchronous: false,
+            strict_delivery: false,
+            dmx_broadcast_policy: DmxBroadcastPolicy::default(),
+        }
+    }
+
+    /// Check that the command may be sent to the given address, with `send`
+    pub(crate) fn check(&self, command: &ArtCommand, addr: SocketAddr) -> io::Result<()> {
+        if self.strict_delivery {
+            check_delivery(command, addr, self.broadcast_address)?;
+        }
+        check_dmx_broadcast(
+            self.dmx_broadcast_policy,
+            command,
+            addr,
+            self.broadcast_address,
+            &self.discovery,
+        )
+    }
+
+    /// The given command for a single address, checked with `check`
+    fn single(&self, command: ArtCommand, addr: SocketAddr) -> io::Result<Outgoing> {
+        self.check(&command, addr)?;
+        Ok(Outgoing {
+            command,
+            addrs: vec![addr],
+        })
+    }
+
+    pub(crate) fn send_dmx(
+        &mut self,
+        port_address: PortAddress,
+        data: &[u8],
+        now: Instant,
+    ) -> io::Result<Outgoing> {
+        let output = self.keepalive.send(port_address, data, now);
+        self.output(output)
+    }
+
+    pub(crate) fn send_frame(
+        &mut self,
+        frame: &[(PortAddress, &[u8])],
+        now: Instant,
+    ) -> io::Result<Vec<Outgoing>> {
+        let mut outgoing = Vec::with_capacity(frame.len() + 1);
+        for (port_address, data) in frame {
+            outgoing.push(self.send_dmx(*port_address, data, now)?);
+        }
+        self.synchronous = true;
+        outgoing.push(self.sync()?);
+        Ok(outgoing)
+    }
+
+    #[cfg(feature = "dmx")]
+    pub(crate) fn program(&self, node: NodeId, address: Address) -> io::Result<Outgoing> {
+        let addr = node_address(&self.discovery, &node)?;
+        self.single(address.for_bind_index(node.bind_index).into(), addr)
+    }
+
+    /// The poll to broadcast, if one is due. Call `poll_sent` once it was sent.
+    pub(crate) fn poll(&mut self, now: Instant) -> io::Result<Option<Outgoing>> {
+        match self.poll.tick(now) {
+            Some(poll) => Ok(Some(self.single(poll.into(), self.broadcast_address)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Record that the poll returned by `poll` was sent, so the nodes that do not reply to it are lost
+    pub(crate) fn poll_sent(&mut self, now: Instant) {
+        self.discovery.poll_sent(now);
+    }
+
+    /// The data that did not change and must be re-transmitted, followed by an ArtSync after `send_frame` was used
+    pub(crate) fn retransmit(&mut self, now: Instant) -> io::Result<Vec<Outgoing>> {
+        let mut outgoing = Vec::new();
+        for output in self.keepalive.tick(now) {
+            outgoing.push(self.output(output)?);
+        }
+        if !outgoing.is_empty() && self.synchronous {
+            outgoing.push(self.sync()?);
+        }
+        Ok(outgoing)
+    }
+
+    pub(crate) fn next_deadline(&self, now: Instant) -> Instant {
+        let poll = self.poll.next_deadline(now);
+        match self.keepalive.next_deadline() {
+            Some(keepalive) => poll.min(keepalive),
+            None => poll,
+        }
+    }
+
+    fn sync(&self) -> io::Result<Outgoing> {
+        self.single(ArtSync::default().into(), self.broadcast_address)
+    }
+
+    /// The output for every subscribed node
+    fn output(&self, output: Output) -> io::Result<Outgoing> {
+        let addrs: Vec<SocketAddr> = subscribers(&self.discovery, output.port_address)
+            .into_iter()
+            .collect();
+        let command = ArtCommand::Output(output);
+        if self.strict_delivery {
+            for addr in &addrs {
+                check_delivery(&command, *addr, self.broadcast_address)?;
+            }
+        }
+        Ok(Outgoing { command, addrs })
+    }
+}
+
+/// The addresses of the nodes that have an output port with the given Port-Address
+fn subscribers(discovery: &DiscoveryManager, port_address: PortAddress) -> BTreeSet<SocketAddr> {
+    // A single device can contain several subscribed nodes, but it only needs the data once
+    discovery
+        .nodes()
+        .filter(|(_, node)| {
+            node.reply
+                .output_port_addresses()
+                .any(|a| a == port_address)
+        })
+        .map(|(_, node)| SocketAddr::new(node.reply.address.into(), node.reply.port))
+        .collect()
+}
+
+/// The address of a discovered node
+#[cfg(feature = "dmx")]
+fn node_address(discovery: &DiscoveryManager, node: &NodeId) -> io::Result<SocketAddr> {
+    match discovery.node(node) {
+        Some(discovered) => Ok(SocketAddr::new(
+            discovered.reply.address.into(),
+            discovered.reply.port,
+        )),
+        None => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "The node was not discovered",
+        )),
+    }
+}
+
+/// Check that the command may be sent to the given address, according to its delivery policy
+fn check_delivery(
+    command: &ArtCommand,
+    addr: SocketAddr,
+    broadcast_address: SocketAddr,
+) -> io::Result<()> {
+    if command
+        .delivery_policy()
+        .allows(is_broadcast(addr, broadcast_address))
+    {
+        return Ok(());
+    }
+    let message = match command.delivery_policy() {
+        DeliveryPolicy::Broadcast => "must be broadcast",
+        _ => "must be unicast",
+    };
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("{} {} to {}", command.name(), message, addr),
+    ))
+}
+
+/// Check the ArtDmx or ArtNzs that is sent to the given address against the policy, when nodes subscribed to its Port-Address
+fn check_dmx_broadcast(
+    policy: DmxBroadcastPolicy,
+    command: &ArtCommand,
+    addr: SocketAddr,
+    broadcast_address: SocketAddr,
+    discovery: &DiscoveryManager,
+) -> io::Result<()> {
+    let port_address = match command {
+        ArtCommand::Output(output) => output.port_address,
+        #[cfg(feature = "dmx")]
+        ArtCommand::Nzs(nzs) => nzs.port_address,
+        _ => return Ok(()),
+    };
+    if policy == DmxBroadcastPolicy::Allow || !is_broadcast(addr, broadcast_address) {
+        return Ok(());
+    }
+    let nodes = subscribers(discovery, port_address).len();
+    if nodes == 0 {
+        return Ok(());
+    }
+    if policy == DmxBroadcastPolicy::Deny {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} for Port-Address {} is broadcast to {}, but {} nodes subscribed to it",
+                command.name(),
+                u16::from(port_address),
+                addr,
+                nodes
+            ),
+        ));
+    }
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        command = command.name(),
+        port_address = u16::from(port_address),
+        %addr,
+        nodes,
+        "broadcasting data to a Port-Address that nodes subscribed to, it should be unicast to them"
+    );
+    Ok(())
+}
+
+/// If the address is the broadcast address of the controller, or `255.255.255.255`
+fn is_broadcast(addr: SocketAddr, broadcast_address: SocketAddr) -> bool {
+    addr.ip() == broadcast_address.ip() || matches!(addr.ip(), IpAddr::V4(ip) if ip.is_broadcast())
+}
+
+pub(crate) fn invalid_data(e: crate::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Poll, PollReply};
+
+    /// A controller and a node with 2 output ports for universe 1 and 2, that was discovered by the controller
+    fn connect() -> (Controller, UdpSocket) {
+        let node = UdpSocket::bind("127.0.0.1:0").unwrap();
+        node.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let mut controller = Controller::bind("127.0.0.1:0", node.local_addr().unwrap()).unwrap();
+        let controller_addr = controller.socket().local_addr().unwrap();
+
+        // Nothing is sent before a node subscribes
+        controller.send_dmx(1.into(), &[1, 2, 3, 4]).unwrap();
+        assert!(controller.update().unwrap().is_empty());
+        assert!(matches!(receive(&node), ArtCommand::Poll(_)));
+
+        let reply = ArtCommand::PollReply(Box::new(PollReply {
+            address: [127, 0, 0, 1].into(),
+            port: node.local_addr().unwrap().port(),
+            num_ports: [0, 2],
+            port_types: [0x80, 0x80, 0, 0],
+            swout: [1, 2, 0, 0],
+            ..PollReply::default()
+        }));
+        node.send_to(&reply.write_to_buffer().unwrap(), controller_addr)
+            .unwrap();
+
+        let start = Instant::now();
+        let mut events = Vec::new();
+        while events.is_empty() && start.elapsed() < Duration::from_secs(5) {
+            events = controller.update().unwrap();
+        }
+        assert!(matches!(events[..], [DiscoveryEvent::NodeAdded(_)]));
+        (controller, node)
+    }
+
+    fn receive(socket: &UdpSocket) -> ArtCommand {
+        let mut buffer = RecvBuffer::new();
+        let (length, _) = socket.recv_from(&mut buffer).unwrap();
+        buffer.parse(length).unwrap()
+    }
+
+    #[test]
+    fn send_to_subscribers() {
+        let (mut controller, node) = connect();
+
+        controller.send_dmx(1.into(), &[5, 6, 7, 8]).unwrap();
+        let output = match receive(&node) {
+            ArtCommand::Output(output) => output,
+            command => panic!("Unexpected command {:?}", command),
+        };
+        assert_eq!(output.data.as_ref(), &vec![5, 6, 7, 8]);
+        assert_eq!(output.sequence, 2);
+
+        // Unchanged data is re-transmitted
+        controller.set_keepalive_interval(Duration::from_millis(0));
+        controller.update().unwrap();
+        assert_eq!(receive(&node).as_output().unwrap().sequence, 3);
+    }
+
+    #[test]
+    fn send_frame() {
+        let (mut controller, node) = connect();
+
+        controller
+            .send_frame(&[(1.into(), &[1, 2]), (2.into(), &[3, 4])])
+            .unwrap();
+        assert_eq!(
+            u16::from(receive(&node).as_output().unwrap().port_address),
+            1
+        );
+        assert_eq!(
+            u16::from(receive(&node).as_output().unwrap().port_address),
+            2
+        );
+        // The nodes and the broadcast address are the same in this test
+        assert!(matches!(receive(&node), ArtCommand::Sync(_)));
+    }
+
+    #[test]
+    fn strict_delivery() {
+        let broadcast: SocketAddr = "127.0.0.1:6454".parse().unwrap();
+        let node: SocketAddr = "127.0.0.2:6454".parse().unwrap();
+        let mut controller = Controller::bind("127.0.0.1:0", broadcast).unwrap();
+        controller.set_strict_delivery(true);
+
+        let output = ArtCommand::Output(Output::new(1.into(), vec![1, 2]));
+        let err = controller.send(&output, broadcast).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = controller
+            .send(&output, "255.255.255.255:6454".parse().unwrap())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = controller.send(&Poll::default().into(), node).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        controller.send(&Poll::default().into(), broadcast).unwrap();
+        controller.set_strict_delivery(false);
+        controller.send(&output, broadcast).unwrap();
+    }
+
+    #[test]
+    fn dmx_broadcast_policy() {
+        let (mut controller, node) = connect();
+        let broadcast = node.local_addr().unwrap();
+        controller.set_dmx_broadcast_policy(DmxBroadcastPolicy::Deny);
+
+        let subscribed = ArtCommand::Output(Output::new(1.into(), vec![1, 2]));
+        let err = controller.send(&subscribed, broadcast).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        // No node subscribed to universe 3
+        let unsubscribed = ArtCommand::Output(Output::new(3.into(), vec![1, 2]));
+        controller.send(&unsubscribed, broadcast).unwrap();
+
+        controller.set_dmx_broadcast_policy(DmxBroadcastPolicy::Warn);
+        controller.send(&subscribed, broadcast).unwrap();
+    }
+
+    #[cfg(feature = "dmx")]
+    #[test]
+    fn program() {
+        let (mut controller, node) = connect();
+        let id = *controller.discovery().nodes().next().unwrap().0;
+
+        controller
+            .program(id, Address::default().program_short_name("Renamed"))
+            .unwrap();
+        match receive(&node) {
+            ArtCommand::Address(address) => {
+                assert_eq!(&address.short_name[..8], b"Renamed\0");
+                assert_eq!(address.bind_index, id.bind_index);
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+
+        let unknown = NodeId {
+            address: [10, 0, 0, 1].into(),
+            bind_index: 1,
+        };
+        assert!(controller.program(unknown, Address::default()).is_err());
+    }
+}
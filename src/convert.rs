@@ -1,32 +1,159 @@
+//! The traits and types that are used to serialize and deserialize the fields of a message.
+//!
+//! These are used by the [data_structure](../macro.data_structure.html) macro, and can be implemented for custom field types.
 use crate::{Error, Result};
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-use std::io::{Cursor, Read};
-use std::net::Ipv4Addr;
+use alloc::vec::Vec;
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use core::net::Ipv4Addr;
 
+/// A minimal read cursor over a byte slice.
+///
+/// This replaces `std::io::Cursor` so that the parser does not depend on `std::io`.
+pub struct Cursor<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    /// Create a cursor at the start of the given data
+    pub fn new(data: &'a [u8]) -> Self {
+        Cursor { data, position: 0 }
+    }
+
+    /// The amount of bytes that have been read
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The bytes that have not been read yet
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.data[self.position.min(self.data.len())..]
+    }
+
+    /// Take the next `len` bytes, advancing the cursor
+    pub fn read_slice(&mut self, len: usize) -> Result<&'a [u8]> {
+        let remaining = self.remaining();
+        if remaining.len() < len {
+            return Err(Error::CursorEof {
+                offset: self.position,
+                needed: len,
+                remaining: remaining.len(),
+            });
+        }
+        self.position += len;
+        Ok(&remaining[..len])
+    }
+
+    /// Fill the buffer with the next bytes, advancing the cursor
+    pub fn read_exact(&mut self, buffer: &mut [u8]) -> Result<()> {
+        let slice = self.read_slice(buffer.len())?;
+        buffer.copy_from_slice(slice);
+        Ok(())
+    }
+
+    /// Read a single byte
+    pub fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_slice(1)?[0])
+    }
+
+    /// Read a little endian `u16`
+    pub fn read_u16_le(&mut self) -> Result<u16> {
+        Ok(LittleEndian::read_u16(self.read_slice(2)?))
+    }
+
+    /// Read a big endian `u16`
+    pub fn read_u16_be(&mut self) -> Result<u16> {
+        Ok(BigEndian::read_u16(self.read_slice(2)?))
+    }
+}
+
+/// The buffer that the fields of a message are appended to.
+///
+/// This is implemented by `Vec<u8>`. With the `heapless` feature, `ArtCommand::write_to_heapless` writes into a `heapless::Vec` through this trait, without allocating.
+pub trait WriteBuffer {
+    /// The amount of bytes in the buffer
+    fn len(&self) -> usize;
+
+    /// Append the given bytes
+    fn extend_from_slice(&mut self, bytes: &[u8]);
+
+    /// Append a byte
+    fn push(&mut self, byte: u8) {
+        self.extend_from_slice(&[byte]);
+    }
+
+    /// If the buffer is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl WriteBuffer for Vec<u8> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn extend_from_slice(&mut self, bytes: &[u8]) {
+        Vec::extend_from_slice(self, bytes);
+    }
+
+    fn push(&mut self, byte: u8) {
+        Vec::push(self, byte);
+    }
+}
+
+/// Writes into a `heapless::Vec`, remembering if a write did not fit instead of failing it, so the fields do not need to check every write
+#[cfg(feature = "heapless")]
+pub(crate) struct HeaplessBuffer<const N: usize> {
+    pub(crate) inner: heapless::Vec<u8, N>,
+    pub(crate) overflowed: bool,
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> WriteBuffer for HeaplessBuffer<N> {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn extend_from_slice(&mut self, bytes: &[u8]) {
+        if self.inner.extend_from_slice(bytes).is_err() {
+            self.overflowed = true;
+        }
+    }
+}
+
+/// A field of a message. `T` is the message that contains the field, which is passed as context when serializing, e.g. to write the length of another field.
 pub trait Convertable<T>: Sized {
-    fn from_cursor(cursor: &mut Cursor<&[u8]>) -> Result<Self>;
-    fn write_to_buffer(&self, buffer: &mut Vec<u8>, context: &T) -> Result<()>;
+    /// Read the field from the cursor
+    fn from_cursor(cursor: &mut Cursor) -> Result<Self>;
+    /// Append the field to the buffer
+    fn write_to_buffer(&self, buffer: &mut dyn WriteBuffer, context: &T) -> Result<()>;
+    /// The amount of bytes that `write_to_buffer` will write
+    fn serialized_len(&self, context: &T) -> usize;
     #[cfg(test)]
+    #[doc(hidden)]
     fn get_test_value() -> Self;
     #[cfg(test)]
+    #[doc(hidden)]
     fn is_equal(&self, other: &Self) -> bool;
 }
 
 impl<T> Convertable<T> for Ipv4Addr {
-    fn from_cursor(cursor: &mut Cursor<&[u8]>) -> Result<Self> {
-        Ok(Ipv4Addr::new(
-            cursor.read_u8().map_err(Error::CursorEof)?,
-            cursor.read_u8().map_err(Error::CursorEof)?,
-            cursor.read_u8().map_err(Error::CursorEof)?,
-            cursor.read_u8().map_err(Error::CursorEof)?,
-        ))
+    fn from_cursor(cursor: &mut Cursor) -> Result<Self> {
+        let mut octets = [0; 4];
+        cursor.read_exact(&mut octets)?;
+        Ok(Ipv4Addr::from(octets))
     }
 
-    fn write_to_buffer(&self, buffer: &mut Vec<u8>, _: &T) -> Result<()> {
+    fn write_to_buffer(&self, buffer: &mut dyn WriteBuffer, _: &T) -> Result<()> {
         buffer.extend_from_slice(&self.octets());
         Ok(())
     }
 
+    fn serialized_len(&self, _: &T) -> usize {
+        4
+    }
+
     #[cfg(test)]
     fn get_test_value() -> Self {
         Ipv4Addr::new(1, 2, 3, 4)
@@ -38,15 +165,17 @@ impl<T> Convertable<T> for Ipv4Addr {
 }
 
 impl<T> Convertable<T> for Vec<u8> {
-    fn from_cursor(cursor: &mut Cursor<&[u8]>) -> Result<Self> {
-        let remaining = cursor.get_ref();
-        Ok(remaining[cursor.position() as usize..].to_vec())
+    fn from_cursor(cursor: &mut Cursor) -> Result<Self> {
+        Ok(cursor.remaining().to_vec())
     }
 
-    fn write_to_buffer(&self, buffer: &mut Vec<u8>, _: &T) -> Result<()> {
+    fn write_to_buffer(&self, buffer: &mut dyn WriteBuffer, _: &T) -> Result<()> {
         buffer.extend_from_slice(&self[..]);
         Ok(())
     }
+    fn serialized_len(&self, _: &T) -> usize {
+        self.len()
+    }
     #[cfg(test)]
     fn get_test_value() -> Self {
         vec![1, 2, 3, 4]
@@ -57,15 +186,41 @@ impl<T> Convertable<T> for Vec<u8> {
     }
 }
 
+#[cfg(feature = "bytes")]
+impl<T> Convertable<T> for bytes::Bytes {
+    fn from_cursor(cursor: &mut Cursor) -> Result<Self> {
+        Ok(bytes::Bytes::copy_from_slice(cursor.remaining()))
+    }
+
+    fn write_to_buffer(&self, buffer: &mut dyn WriteBuffer, _: &T) -> Result<()> {
+        buffer.extend_from_slice(&self[..]);
+        Ok(())
+    }
+    fn serialized_len(&self, _: &T) -> usize {
+        self.len()
+    }
+    #[cfg(test)]
+    fn get_test_value() -> Self {
+        bytes::Bytes::from_static(&[1, 2, 3, 4])
+    }
+    #[cfg(test)]
+    fn is_equal(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
 impl<T> Convertable<T> for u8 {
-    fn from_cursor(cursor: &mut Cursor<&[u8]>) -> Result<Self> {
-        cursor.read_u8().map_err(Error::CursorEof)
+    fn from_cursor(cursor: &mut Cursor) -> Result<Self> {
+        cursor.read_u8()
     }
 
-    fn write_to_buffer(&self, buffer: &mut Vec<u8>, _: &T) -> Result<()> {
+    fn write_to_buffer(&self, buffer: &mut dyn WriteBuffer, _: &T) -> Result<()> {
         buffer.push(*self);
         Ok(())
     }
+    fn serialized_len(&self, _: &T) -> usize {
+        1
+    }
     #[cfg(test)]
     fn get_test_value() -> Self {
         1
@@ -79,17 +234,18 @@ impl<T> Convertable<T> for u8 {
 macro_rules! convert_primitive {
     ([u8; $length:tt]) => {
         impl<T> Convertable<T> for [u8; $length] {
-            fn from_cursor(cursor: &mut Cursor<&[u8]>) -> Result<Self> {
+            fn from_cursor(cursor: &mut Cursor) -> Result<Self> {
                 let mut result = [0; $length];
-                cursor
-                    .read_exact(&mut result[..])
-                    .map_err(Error::CursorEof)?;
+                cursor.read_exact(&mut result[..])?;
                 Ok(result)
             }
-            fn write_to_buffer(&self, buffer: &mut Vec<u8>, _: &T) -> Result<()> {
+            fn write_to_buffer(&self, buffer: &mut dyn WriteBuffer, _: &T) -> Result<()> {
                 buffer.extend_from_slice(&self[..]);
                 Ok(())
             }
+            fn serialized_len(&self, _: &T) -> usize {
+                $length
+            }
             #[cfg(test)]
             fn get_test_value() -> Self {
                 [0; $length]
@@ -100,15 +256,17 @@ macro_rules! convert_primitive {
             }
         }
     };
-    ($ty:ty, $read_fn:tt, $write_fn:tt) => {
+    ($ty:ty, $read_fn:tt) => {
         impl<T> Convertable<T> for $ty {
-            fn from_cursor(cursor: &mut Cursor<&[u8]>) -> Result<Self> {
-                cursor.$read_fn::<LittleEndian>().map_err(Error::CursorEof)
+            fn from_cursor(cursor: &mut Cursor) -> Result<Self> {
+                cursor.$read_fn()
+            }
+            fn write_to_buffer(&self, buffer: &mut dyn WriteBuffer, _: &T) -> Result<()> {
+                buffer.extend_from_slice(&self.to_le_bytes());
+                Ok(())
             }
-            fn write_to_buffer(&self, buffer: &mut Vec<u8>, _: &T) -> Result<()> {
-                buffer
-                    .$write_fn::<LittleEndian>(*self)
-                    .map_err(Error::CursorEof)
+            fn serialized_len(&self, _: &T) -> usize {
+                core::mem::size_of::<$ty>()
             }
             #[cfg(test)]
             fn get_test_value() -> Self {
@@ -122,11 +280,16 @@ macro_rules! convert_primitive {
     };
 }
 
-convert_primitive!(u16, read_u16, write_u16);
+convert_primitive!(u16, read_u16_le);
 convert_primitive!([u8; 2]);
 convert_primitive!([u8; 3]);
 convert_primitive!([u8; 4]);
 convert_primitive!([u8; 6]);
+convert_primitive!([u8; 7]);
+convert_primitive!([u8; 10]);
+convert_primitive!([u8; 14]);
 convert_primitive!([u8; 18]);
+convert_primitive!([u8; 20]);
+convert_primitive!([u8; 21]);
 convert_primitive!([u8; 26]);
 convert_primitive!([u8; 64]);
@@ -0,0 +1,189 @@
+use crate::{ArtCommand, ArtTalkToMe, Poll};
+use std::collections::{HashMap, VecDeque};
+use std::net::Ipv4Addr;
+use std::time::Instant;
+
+/// A diagnostic message of a node, as collected by a [DiagnosticsCollector](struct.DiagnosticsCollector.html)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnosticMessage {
+    /// The IP address of the node that sent the message
+    pub source: Ipv4Addr,
+
+    /// The priority of the message, e.g. `DiagData::PRIORITY_HIGH`
+    pub priority: u8,
+
+    /// The logical port of the node that the message is about
+    pub logical_port: u8,
+
+    /// The text of the message
+    pub text: String,
+
+    /// When the message was received
+    pub received: Instant,
+}
+
+/// Requests diagnostic messages from the nodes, and keeps the recent messages of every node.
+///
+/// Nodes only send ArtDiagData after a poll that enabled diagnostics. Send the poll of `poll` instead of `Poll::default()`, pass every received command to `handle` and show the messages that it returns, e.g. on a monitoring dashboard.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::Instant;
+///
+/// let mut collector = DiagnosticsCollector::new(DiagData::PRIORITY_MED);
+/// let poll = collector.poll();
+/// assert!(poll.talk_to_me.contains(ArtTalkToMe::ENABLE_DIAGNOSTICS));
+///
+/// let node = [10, 0, 0, 5].into();
+/// let low = DiagData::new(DiagData::PRIORITY_LOW, "Booted").into_command();
+/// assert!(collector.handle(&low, node, Instant::now()).is_none());
+/// let high = DiagData::new(DiagData::PRIORITY_HIGH, "Overheating").into_command();
+/// assert!(collector.handle(&high, node, Instant::now()).is_some());
+/// assert_eq!(collector.history(node).count(), 1);
+/// ```
+#[derive(Debug)]
+pub struct DiagnosticsCollector {
+    min_priority: u8,
+    unicast: bool,
+    history_size: usize,
+    history: HashMap<Ipv4Addr, VecDeque<DiagnosticMessage>>,
+}
+
+impl DiagnosticsCollector {
+    /// The number of messages that is kept for every node
+    pub const DEFAULT_HISTORY: usize = 100;
+
+    /// Collect the messages with at least the given priority, e.g. `DiagData::PRIORITY_MED`
+    pub fn new(min_priority: u8) -> DiagnosticsCollector {
+        DiagnosticsCollector {
+            min_priority,
+            unicast: false,
+            history_size: DiagnosticsCollector::DEFAULT_HISTORY,
+            history: HashMap::new(),
+        }
+    }
+
+    /// Ask the nodes to unicast their messages to the controller, instead of broadcasting them
+    pub fn set_unicast(&mut self, unicast: bool) {
+        self.unicast = unicast;
+    }
+
+    /// Change the number of messages that is kept for every node. The oldest messages are dropped first.
+    pub fn set_history_size(&mut self, size: usize) {
+        self.history_size = size;
+        for messages in self.history.values_mut() {
+            while messages.len() > size {
+                messages.pop_front();
+            }
+        }
+    }
+
+    /// The lowest priority that is collected
+    pub fn min_priority(&self) -> u8 {
+        self.min_priority
+    }
+
+    /// A poll that enables diagnostics on the nodes, with the priority threshold of this collector
+    pub fn poll(&self) -> Poll {
+        let mut poll = Poll {
+            diagnostics_priority: self.min_priority,
+            ..Poll::default()
+        };
+        poll.talk_to_me |= ArtTalkToMe::ENABLE_DIAGNOSTICS;
+        if self.unicast {
+            poll.talk_to_me |= ArtTalkToMe::UNICAST_DIAGNOSTICS;
+        }
+        poll
+    }
+
+    /// Handle a command that was received from the given IP address. Returns the message if it is an ArtDiagData with at least the priority of this collector.
+    pub fn handle(
+        &mut self,
+        command: &ArtCommand,
+        source: Ipv4Addr,
+        now: Instant,
+    ) -> Option<DiagnosticMessage> {
+        let diag = match command {
+            ArtCommand::DiagData(diag) if diag.priority >= self.min_priority => diag,
+            _ => return None,
+        };
+        let message = DiagnosticMessage {
+            source,
+            priority: diag.priority,
+            logical_port: diag.logical_port,
+            text: diag.text().into(),
+            received: now,
+        };
+        if self.history_size > 0 {
+            let messages = self.history.entry(source).or_default();
+            if messages.len() == self.history_size {
+                messages.pop_front();
+            }
+            messages.push_back(message.clone());
+        }
+        Some(message)
+    }
+
+    /// The nodes that sent a message
+    pub fn nodes(&self) -> impl Iterator<Item = Ipv4Addr> + '_ {
+        self.history.keys().copied()
+    }
+
+    /// The recent messages of the given node, from old to new
+    pub fn history(&self, source: Ipv4Addr) -> impl Iterator<Item = &DiagnosticMessage> {
+        self.history.get(&source).into_iter().flatten()
+    }
+
+    /// The recent messages of all nodes, from old to new
+    pub fn messages(&self) -> impl Iterator<Item = &DiagnosticMessage> {
+        let mut messages: Vec<_> = self.history.values().flatten().collect();
+        messages.sort_by_key(|message| message.received);
+        messages.into_iter()
+    }
+
+    /// Forget the messages of the given node
+    pub fn clear(&mut self, source: Ipv4Addr) {
+        self.history.remove(&source);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DiagData;
+    use std::time::Duration;
+
+    #[test]
+    fn history() {
+        let mut collector = DiagnosticsCollector::new(DiagData::PRIORITY_LOW);
+        collector.set_unicast(true);
+        collector.set_history_size(2);
+        assert!(collector
+            .poll()
+            .talk_to_me
+            .contains(ArtTalkToMe::UNICAST_DIAGNOSTICS));
+
+        let start = Instant::now();
+        let first: Ipv4Addr = [10, 0, 0, 1].into();
+        let second: Ipv4Addr = [10, 0, 0, 2].into();
+        for (i, text) in ["one", "two", "three"].iter().enumerate() {
+            let command = DiagData::new(DiagData::PRIORITY_LOW, text).into_command();
+            let at = start + Duration::from_millis(i as u64 * 10);
+            collector.handle(&command, first, at);
+        }
+        let command = DiagData::new(DiagData::PRIORITY_CRITICAL, "four").into_command();
+        collector.handle(&command, second, start + Duration::from_millis(15));
+
+        let texts: Vec<_> = collector.history(first).map(|m| m.text.as_str()).collect();
+        assert_eq!(texts, ["two", "three"]);
+        let texts: Vec<_> = collector.messages().map(|m| m.text.as_str()).collect();
+        assert_eq!(texts, ["two", "four", "three"]);
+        assert_eq!(collector.nodes().count(), 2);
+
+        collector.clear(first);
+        assert_eq!(collector.history(first).count(), 0);
+        assert!(collector
+            .handle(&Poll::default().into(), first, start)
+            .is_none());
+    }
+}
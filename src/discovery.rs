@@ -0,0 +1,276 @@
+use crate::transport::drain;
+use crate::{ArtCommand, PollReply, RecvBuffer, Transport};
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::net::Ipv4Addr;
+use std::time::{Duration, Instant};
+
+/// Identifies a node on the network. A single device can contain several nodes with the same IP address, which are told apart by their `bind_index`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeId {
+    /// The IP address of the node, as reported in its `PollReply`
+    pub address: Ipv4Addr,
+
+    /// The bind index of the node, as reported in its `PollReply`
+    pub bind_index: u8,
+}
+
+impl NodeId {
+    /// The id of the node that sent the given reply
+    pub fn of(reply: &PollReply) -> NodeId {
+        NodeId {
+            address: reply.address,
+            bind_index: reply.bind_index,
+        }
+    }
+}
+
+/// A node that replied to a poll
+#[derive(Debug)]
+pub struct DiscoveredNode {
+    /// The most recent reply of the node
+    pub reply: Box<PollReply>,
+
+    /// When the most recent reply was received
+    pub last_seen: Instant,
+}
+
+/// A change in the list of nodes of a [DiscoveryManager](struct.DiscoveryManager.html)
+#[derive(Debug)]
+pub enum DiscoveryEvent {
+    /// A node replied for the first time
+    NodeAdded(NodeId),
+
+    /// A known node sent a reply that differs from its previous one
+    NodeUpdated(NodeId),
+
+    /// A node did not reply to a poll in time, and was removed. Contains its last reply.
+    NodeLost(NodeId, Box<PollReply>),
+}
+
+/// Keeps track of the nodes on the network, based on the `PollReply` messages that they send.
+///
+/// Tell it when a poll is sent with `poll_sent`, pass every received command to `handle`, and call `expire` regularly to find nodes that stopped replying. A node is lost when it did not reply within the timeout, 3 seconds by default, after a poll.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::{Duration, Instant};
+///
+/// let mut discovery = DiscoveryManager::new();
+/// let start = Instant::now();
+/// discovery.poll_sent(start);
+///
+/// let reply = ArtCommand::PollReply(Box::default());
+/// assert!(matches!(discovery.handle(&reply, start), Some(DiscoveryEvent::NodeAdded(_))));
+/// assert_eq!(discovery.nodes().count(), 1);
+///
+/// // The node did not reply to the next poll
+/// discovery.poll_sent(start + Duration::from_millis(2500));
+/// let events = discovery.expire(start + Duration::from_millis(5500));
+/// assert!(matches!(events[..], [DiscoveryEvent::NodeLost(..)]));
+/// ```
+#[derive(Debug)]
+pub struct DiscoveryManager {
+    nodes: HashMap<NodeId, DiscoveredNode>,
+    polls: VecDeque<Instant>,
+    timeout: Duration,
+    /// The buffer of `handle_readable`, created when it is first used. It is taken out while in use, so the manager can be changed at the same time.
+    buffer: Option<Box<RecvBuffer>>,
+}
+
+impl Default for DiscoveryManager {
+    fn default() -> DiscoveryManager {
+        DiscoveryManager::new()
+    }
+}
+
+impl DiscoveryManager {
+    /// The time that a node has to reply to a poll, as given by the Art-Net specification
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+    /// Create a manager with the default timeout
+    pub fn new() -> DiscoveryManager {
+        DiscoveryManager::with_timeout(DiscoveryManager::DEFAULT_TIMEOUT)
+    }
+
+    /// Create a manager with the given timeout. Lossy networks or slow nodes may need more than the default.
+    pub fn with_timeout(timeout: Duration) -> DiscoveryManager {
+        DiscoveryManager {
+            nodes: HashMap::new(),
+            polls: VecDeque::new(),
+            timeout,
+            buffer: None,
+        }
+    }
+
+    /// Record that a poll was sent at the given time
+    pub fn poll_sent(&mut self, now: Instant) {
+        self.polls.push_back(now);
+    }
+
+    /// Handle a received command. Commands other than `PollReply` are ignored.
+    pub fn handle(&mut self, command: &ArtCommand, now: Instant) -> Option<DiscoveryEvent> {
+        let reply = command.as_poll_reply()?;
+        let id = NodeId::of(reply);
+        match self.nodes.get_mut(&id) {
+            Some(node) => {
+                node.last_seen = now;
+                if *node.reply == *reply {
+                    return None;
+                }
+                *node.reply = reply.clone();
+                Some(DiscoveryEvent::NodeUpdated(id))
+            }
+            None => {
+                self.nodes.insert(
+                    id,
+                    DiscoveredNode {
+                        reply: Box::new(reply.clone()),
+                        last_seen: now,
+                    },
+                );
+                Some(DiscoveryEvent::NodeAdded(id))
+            }
+        }
+    }
+
+    /// Read every datagram that is waiting on the transport, and handle the replies. Call this when an event loop, e.g. mio, reports the socket as readable.
+    pub fn handle_readable(
+        &mut self,
+        socket: &impl Transport,
+        now: Instant,
+    ) -> io::Result<Vec<DiscoveryEvent>> {
+        let mut events = Vec::new();
+        let mut buffer = self.buffer.take().unwrap_or_default();
+        let result = drain(socket, &mut buffer, |command, _| {
+            events.extend(self.handle(&command, now));
+            Ok(())
+        });
+        self.buffer = Some(buffer);
+        result?;
+        Ok(events)
+    }
+
+    /// Remove the nodes that did not reply within the timeout of a poll.
+    pub fn expire(&mut self, now: Instant) -> Vec<DiscoveryEvent> {
+        let mut events = Vec::new();
+        while let Some(&sent) = self.polls.front() {
+            if now < sent + self.timeout {
+                break;
+            }
+            self.polls.pop_front();
+
+            let lost: Vec<NodeId> = self
+                .nodes
+                .iter()
+                .filter(|(_, node)| node.last_seen < sent)
+                .map(|(id, _)| *id)
+                .collect();
+            for id in lost {
+                if let Some(node) = self.nodes.remove(&id) {
+                    events.push(DiscoveryEvent::NodeLost(id, node.reply));
+                }
+            }
+        }
+        events
+    }
+
+    /// The node with the given id, if it is known
+    pub fn node(&self, id: &NodeId) -> Option<&DiscoveredNode> {
+        self.nodes.get(id)
+    }
+
+    /// All known nodes, in no particular order
+    pub fn nodes(&self) -> impl Iterator<Item = (&NodeId, &DiscoveredNode)> {
+        self.nodes.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(address: [u8; 4], bind_index: u8) -> ArtCommand {
+        PollReply {
+            address: address.into(),
+            bind_index,
+            ..PollReply::default()
+        }
+        .into()
+    }
+
+    #[test]
+    fn bind_index() {
+        let mut discovery = DiscoveryManager::new();
+        let now = Instant::now();
+        assert!(discovery.handle(&reply([10, 0, 0, 1], 1), now).is_some());
+        assert!(discovery.handle(&reply([10, 0, 0, 1], 2), now).is_some());
+        // The same reply again is not an update
+        assert!(discovery.handle(&reply([10, 0, 0, 1], 2), now).is_none());
+        assert_eq!(discovery.nodes().count(), 2);
+    }
+
+    #[test]
+    fn handle_readable() {
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.set_nonblocking(true).unwrap();
+        let node = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let bytes = reply([10, 0, 0, 1], 1).write_to_buffer().unwrap();
+        node.send_to(&bytes, socket.local_addr().unwrap()).unwrap();
+
+        let mut discovery = DiscoveryManager::new();
+        let start = Instant::now();
+        let mut events = Vec::new();
+        while events.is_empty() && start.elapsed() < Duration::from_secs(5) {
+            events = discovery.handle_readable(&socket, start).unwrap();
+        }
+        assert!(matches!(events[..], [DiscoveryEvent::NodeAdded(_)]));
+    }
+
+    #[test]
+    fn updated() {
+        let mut discovery = DiscoveryManager::new();
+        let now = Instant::now();
+        let mut changed = PollReply::default();
+        changed.short_name[0] = b'A';
+        discovery.handle(&reply([0, 0, 0, 0], 0), now);
+        match discovery.handle(&changed.into(), now) {
+            Some(DiscoveryEvent::NodeUpdated(id)) => {
+                assert_eq!(discovery.node(&id).unwrap().reply.short_name[0], b'A');
+            }
+            e => panic!("Unexpected event {:?}", e),
+        }
+    }
+
+    #[test]
+    fn timeout() {
+        let mut discovery = DiscoveryManager::new();
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+
+        discovery.poll_sent(start);
+        discovery.handle(&reply([10, 0, 0, 1], 1), at(100));
+        discovery.handle(&reply([10, 0, 0, 2], 1), at(200));
+
+        // Both nodes reply to the second poll, one of them late
+        discovery.poll_sent(at(2500));
+        discovery.handle(&reply([10, 0, 0, 1], 1), at(2600));
+        assert!(discovery.expire(at(5000)).is_empty());
+        discovery.handle(&reply([10, 0, 0, 2], 1), at(5400));
+        assert!(discovery.expire(at(5500)).is_empty());
+
+        // Only the first node replies to the third poll
+        discovery.poll_sent(at(5600));
+        discovery.handle(&reply([10, 0, 0, 1], 1), at(5700));
+        assert!(discovery.expire(at(8500)).is_empty());
+        let events = discovery.expire(at(8600));
+        match &events[..] {
+            [DiscoveryEvent::NodeLost(id, _)] => {
+                assert_eq!(id.address, Ipv4Addr::new(10, 0, 0, 2))
+            }
+            e => panic!("Unexpected events {:?}", e),
+        }
+        assert_eq!(discovery.nodes().count(), 1);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::{Output, PortAddress};
+use alloc::vec::Vec;
+
+/// The number of slots in a DMX512 frame
+pub const DMX_SLOTS: usize = 512;
+
+/// Produces the DMX512 frames of universes, e.g. the data that was received from the network.
+///
+/// Together with [DmxSink](trait.DmxSink.html), this lets generic lighting pipelines and other DMX transports exchange data with this crate. It is implemented by the [DmxReceiver](struct.DmxReceiver.html).
+///
+/// ```rust
+/// use artnet_protocol::*;
+///
+/// /// Copy a universe from any source to any sink
+/// fn forward<E>(
+///     source: &mut impl DmxSource,
+///     sink: &mut impl DmxSink<Error = E>,
+///     port_address: PortAddress,
+/// ) -> std::result::Result<bool, E> {
+///     let mut frame = [0; DMX_SLOTS];
+///     if !source.read_frame(port_address, &mut frame) {
+///         return Ok(false);
+///     }
+///     sink.write_frame(port_address, &frame)?;
+///     Ok(true)
+/// }
+/// # #[cfg(feature = "std")]
+/// # {
+/// # let mut receiver = DmxReceiver::new();
+/// # let mut outputs = Vec::new();
+/// # assert_eq!(forward(&mut receiver, &mut outputs, 1.into()), Ok(false));
+/// # }
+/// ```
+pub trait DmxSource {
+    /// Copy the current data of the universe into `frame`. Slots that the source has no data for are set to zero. Returns false if the source has no data for the universe at all, and leaves `frame` unchanged.
+    fn read_frame(&mut self, port_address: PortAddress, frame: &mut [u8; DMX_SLOTS]) -> bool;
+}
+
+/// Consumes the DMX512 frames of universes, e.g. to send them to the network.
+///
+/// It is implemented by the [Controller](struct.Controller.html), which sends the frames as ArtDmx, and by `Vec<Output>`, which collects them as ArtDmx packets for an application that sends them itself.
+pub trait DmxSink {
+    /// The error when a frame could not be written
+    type Error;
+
+    /// Write the data of a universe, from 1 to 512 slots
+    fn write_frame(&mut self, port_address: PortAddress, frame: &[u8]) -> Result<(), Self::Error>;
+}
+
+impl<T: DmxSource + ?Sized> DmxSource for &mut T {
+    fn read_frame(&mut self, port_address: PortAddress, frame: &mut [u8; DMX_SLOTS]) -> bool {
+        (**self).read_frame(port_address, frame)
+    }
+}
+
+impl<T: DmxSink + ?Sized> DmxSink for &mut T {
+    type Error = T::Error;
+
+    fn write_frame(&mut self, port_address: PortAddress, frame: &[u8]) -> Result<(), T::Error> {
+        (**self).write_frame(port_address, frame)
+    }
+}
+
+impl DmxSink for Vec<Output> {
+    type Error = core::convert::Infallible;
+
+    fn write_frame(
+        &mut self,
+        port_address: PortAddress,
+        frame: &[u8],
+    ) -> Result<(), core::convert::Infallible> {
+        self.push(Output::new(port_address, frame.to_vec()));
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl DmxSource for crate::DmxReceiver {
+    fn read_frame(&mut self, port_address: PortAddress, frame: &mut [u8; DMX_SLOTS]) -> bool {
+        match self.universe(port_address) {
+            Some(universe) => {
+                *frame = universe.data;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: crate::Transport> DmxSink for crate::Controller<T> {
+    type Error = std::io::Error;
+
+    fn write_frame(&mut self, port_address: PortAddress, frame: &[u8]) -> std::io::Result<()> {
+        self.send_dmx(port_address, frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn outputs() {
+        let mut outputs = Vec::new();
+        let sink = &mut outputs;
+        sink.write_frame(3.into(), &[1, 2]).unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].port_address, PortAddress::from(3));
+        assert_eq!(outputs[0].data.as_ref(), &vec![1, 2]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn receiver_to_controller() {
+        use crate::{
+            Controller, DmxReceiver, MemoryNetwork, Node, PollReply, RecvBuffer, Transport,
+        };
+        use std::time::Instant;
+
+        let mut receiver = DmxReceiver::new();
+        let source = "10.0.0.1:6454".parse().unwrap();
+        receiver.handle(
+            &Output::new(1.into(), vec![9; 4]).into(),
+            source,
+            Instant::now(),
+        );
+        let mut frame = [1; DMX_SLOTS];
+        assert!(!receiver.read_frame(2.into(), &mut frame));
+        assert_eq!(frame, [1; DMX_SLOTS]);
+        assert!(receiver.read_frame(1.into(), &mut frame));
+        assert_eq!(&frame[..5], &[9, 9, 9, 9, 0]);
+
+        // The controller sends to the nodes that it discovered
+        let network = MemoryNetwork::new();
+        let transport = network.bind("10.0.0.2:6454".parse().unwrap()).unwrap();
+        let mut node = Node::new(PollReply {
+            address: [10, 0, 0, 2].into(),
+            num_ports: [0, 1],
+            port_types: [0x80, 0, 0, 0],
+            swout: [1, 0, 0, 0],
+            ..PollReply::default()
+        });
+        let mut controller = Controller::with_transport(
+            network.bind("10.0.0.3:6454".parse().unwrap()).unwrap(),
+            "255.255.255.255:6454".parse().unwrap(),
+        );
+        controller.update().unwrap();
+        node.handle_readable(&transport).unwrap();
+        controller.update().unwrap();
+
+        controller.write_frame(1.into(), &frame).unwrap();
+        let mut buffer = RecvBuffer::new();
+        let (length, _) = transport.recv_from(&mut buffer).unwrap();
+        let output = buffer.parse(length).unwrap();
+        assert_eq!(output.as_output().unwrap().data.as_ref().len(), DMX_SLOTS);
+    }
+}
@@ -0,0 +1,370 @@
+use crate::{ArtCommand, Output, PortAddress};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// The number of slots in a DMX512 frame
+const SLOTS: usize = 512;
+
+/// How often the data of a fading universe is updated
+const FADE_STEP: Duration = Duration::from_millis(25);
+
+/// What a [DmxReceiver](struct.DmxReceiver.html) does with the data of a universe when no ArtDmx was received for it within the timeout. A [Node](struct.Node.html) applies it to the merged data of its output ports, see `Node::tick`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DataLossPolicy {
+    /// Keep the last received data
+    #[default]
+    Hold,
+
+    /// Set all slots to zero
+    Zero,
+
+    /// Fade all slots to zero over the given time
+    Fade(Duration),
+}
+
+/// The most recent ArtDmx of a universe, as kept by a [DmxReceiver](struct.DmxReceiver.html)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DmxFrame {
+    /// The Port-Address of the universe
+    pub port_address: PortAddress,
+
+    /// The 512 slots of the universe. Slots after the received `length` are zero.
+    pub data: [u8; SLOTS],
+
+    /// The number of slots in the most recent ArtDmx
+    pub length: usize,
+
+    /// The address that sent the most recent ArtDmx
+    pub source: SocketAddr,
+
+    /// The sequence number of the most recent ArtDmx, or 0 if the sender does not use it
+    pub sequence: u8,
+
+    /// When the most recent ArtDmx was received
+    pub last_seen: Instant,
+
+    /// The number of ArtDmx that were received for the universe
+    pub frames: u64,
+
+    /// If no ArtDmx was received within the timeout, and the data loss policy was applied to `data`
+    pub lost: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Universe {
+    frame: DmxFrame,
+    /// The data that was received, before the data loss policy was applied
+    look: [u8; SLOTS],
+    /// When the data loss policy was last applied
+    applied: Option<Instant>,
+}
+
+impl DmxFrame {
+    /// The slots that were received in the most recent ArtDmx
+    pub fn slots(&self) -> &[u8] {
+        &self.data[..self.length]
+    }
+}
+
+/// Keeps the most recent DMX512 data of every universe that ArtDmx was received for, e.g. for a visualizer or a recorder.
+///
+/// Pass every received command to `handle`, together with the address that sent it. Other commands than ArtDmx are ignored. Use `universe` to read a single universe, or `snapshot` to copy all universes, e.g. to hand them to another thread.
+///
+/// When no ArtDmx is received for a universe within the timeout, 10 seconds by default, its [DataLossPolicy](enum.DataLossPolicy.html) is applied. The policy holds the last look by default, and can be changed for all universes or per universe. Call `tick` at `next_deadline` to apply it.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::Instant;
+///
+/// let mut receiver = DmxReceiver::new();
+/// let source = "10.0.0.1:6454".parse().unwrap();
+/// let command = Output::new(1.into(), vec![255, 128]).into_command();
+/// assert_eq!(receiver.handle(&command, source, Instant::now()), Some(1.into()));
+///
+/// let frame = receiver.universe(1.into()).unwrap();
+/// assert_eq!(frame.slots(), &[255, 128]);
+/// assert_eq!(frame.data[2], 0);
+/// assert_eq!(frame.source, source);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DmxReceiver {
+    universes: BTreeMap<PortAddress, Universe>,
+    timeout: Duration,
+    policy: DataLossPolicy,
+    policies: BTreeMap<PortAddress, DataLossPolicy>,
+}
+
+impl Default for DmxReceiver {
+    fn default() -> DmxReceiver {
+        DmxReceiver::new()
+    }
+}
+
+impl DmxReceiver {
+    /// The time without ArtDmx after which the data of a universe is lost, as given by the Art-Net specification
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Create a receiver without any universes, that holds the last look after the default timeout
+    pub fn new() -> DmxReceiver {
+        DmxReceiver::with_timeout(DmxReceiver::DEFAULT_TIMEOUT)
+    }
+
+    /// Create a receiver that applies the data loss policy after the given timeout
+    pub fn with_timeout(timeout: Duration) -> DmxReceiver {
+        DmxReceiver {
+            universes: BTreeMap::new(),
+            timeout,
+            policy: DataLossPolicy::Hold,
+            policies: BTreeMap::new(),
+        }
+    }
+
+    /// Change the data loss policy of the universes without a policy of their own
+    pub fn set_default_policy(&mut self, policy: DataLossPolicy) {
+        self.policy = policy;
+    }
+
+    /// Change the data loss policy of a single universe
+    pub fn set_policy(&mut self, port_address: PortAddress, policy: DataLossPolicy) {
+        self.policies.insert(port_address, policy);
+    }
+
+    /// The data loss policy of the given universe
+    pub fn policy(&self, port_address: PortAddress) -> DataLossPolicy {
+        self.policies
+            .get(&port_address)
+            .copied()
+            .unwrap_or(self.policy)
+    }
+
+    /// Handle a command that was received from the given address. Returns the Port-Address of the universe if it was ArtDmx.
+    pub fn handle(
+        &mut self,
+        command: &ArtCommand,
+        source: SocketAddr,
+        now: Instant,
+    ) -> Option<PortAddress> {
+        match command {
+            ArtCommand::Output(output) => Some(self.handle_output(output, source, now)),
+            _ => None,
+        }
+    }
+
+    /// Keep the data of an ArtDmx that was received from the given address, and return its Port-Address
+    pub(crate) fn handle_output(
+        &mut self,
+        output: &Output,
+        source: SocketAddr,
+        now: Instant,
+    ) -> PortAddress {
+        let data = output.data.as_ref();
+        let length = data.len().min(SLOTS);
+        let universe = self
+            .universes
+            .entry(output.port_address)
+            .or_insert_with(|| Universe {
+                frame: DmxFrame {
+                    port_address: output.port_address,
+                    data: [0; SLOTS],
+                    length: 0,
+                    source,
+                    sequence: 0,
+                    last_seen: now,
+                    frames: 0,
+                    lost: false,
+                },
+                look: [0; SLOTS],
+                applied: None,
+            });
+        universe.look[..length].copy_from_slice(&data[..length]);
+        universe.look[length..].fill(0);
+        let frame = &mut universe.frame;
+        frame.data = universe.look;
+        frame.lost = false;
+        universe.applied = None;
+        frame.length = length;
+        frame.source = source;
+        frame.sequence = output.sequence;
+        frame.last_seen = now;
+        frame.frames += 1;
+        output.port_address
+    }
+
+    /// Apply the data loss policy to the universes that did not receive ArtDmx within the timeout. Returns the universes whose data changed, or that were lost while holding the last look.
+    pub fn tick(&mut self, now: Instant) -> Vec<PortAddress> {
+        let mut changed = Vec::new();
+        for (port_address, universe) in &mut self.universes {
+            let lost_at = universe.frame.last_seen + self.timeout;
+            if now < lost_at {
+                continue;
+            }
+            let policy = self
+                .policies
+                .get(port_address)
+                .copied()
+                .unwrap_or(self.policy);
+            let data = match policy {
+                DataLossPolicy::Hold => universe.look,
+                DataLossPolicy::Zero => [0; SLOTS],
+                DataLossPolicy::Fade(fade) => fade_out(&universe.look, now - lost_at, fade),
+            };
+            universe.applied = Some(now);
+            let frame = &mut universe.frame;
+            if !frame.lost || frame.data != data {
+                frame.lost = true;
+                frame.data = data;
+                changed.push(*port_address);
+            }
+        }
+        changed
+    }
+
+    /// When `tick` should be called next, if any universe is about to lose its data or is fading out
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.universes
+            .iter()
+            .filter_map(|(port_address, universe)| {
+                let lost_at = universe.frame.last_seen + self.timeout;
+                let applied = match universe.applied {
+                    Some(applied) => applied,
+                    None => return Some(lost_at),
+                };
+                match self.policy(*port_address) {
+                    DataLossPolicy::Fade(fade) if applied < lost_at + fade => {
+                        Some((lost_at + fade).min(applied + FADE_STEP))
+                    }
+                    _ => None,
+                }
+            })
+            .min()
+    }
+
+    /// The most recent data of the given universe, if any was received
+    pub fn universe(&self, port_address: PortAddress) -> Option<&DmxFrame> {
+        self.universes
+            .get(&port_address)
+            .map(|universe| &universe.frame)
+    }
+
+    /// The universes that data was received for, ordered by Port-Address
+    pub fn universes(&self) -> impl Iterator<Item = &DmxFrame> {
+        self.universes.values().map(|universe| &universe.frame)
+    }
+
+    /// A copy of every universe, ordered by Port-Address
+    pub fn snapshot(&self) -> Vec<DmxFrame> {
+        self.universes().cloned().collect()
+    }
+
+    /// Forget the given universe, e.g. when its sender stopped
+    pub fn remove(&mut self, port_address: PortAddress) -> Option<DmxFrame> {
+        self.universes
+            .remove(&port_address)
+            .map(|universe| universe.frame)
+    }
+
+    /// Forget every universe
+    pub fn clear(&mut self) {
+        self.universes.clear();
+    }
+}
+
+/// The data, faded out for `elapsed` of a fade of `fade`
+fn fade_out(data: &[u8; SLOTS], elapsed: Duration, fade: Duration) -> [u8; SLOTS] {
+    if elapsed >= fade {
+        return [0; SLOTS];
+    }
+    let remaining = (fade - elapsed).as_micros();
+    let fade = fade.as_micros();
+    let mut faded = [0; SLOTS];
+    for (faded, slot) in faded.iter_mut().zip(data.iter()) {
+        *faded = (u128::from(*slot) * remaining / fade) as u8;
+    }
+    faded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Output, Poll};
+    use std::time::Duration;
+
+    #[test]
+    fn universes() {
+        let mut receiver = DmxReceiver::new();
+        let first = "10.0.0.1:6454".parse().unwrap();
+        let second = "10.0.0.2:6454".parse().unwrap();
+        let start = Instant::now();
+        let later = start + Duration::from_millis(25);
+
+        let mut output = Output::new(2.into(), vec![1; 512]);
+        output.sequence = 7;
+        receiver.handle(&output.into(), first, start);
+        receiver.handle(&Output::new(1.into(), vec![9; 4]).into(), first, start);
+        assert!(receiver
+            .handle(&Poll::default().into(), first, start)
+            .is_none());
+
+        // A shorter frame from another sender replaces the data
+        receiver.handle(&Output::new(2.into(), vec![3, 4]).into(), second, later);
+        let frame = receiver.universe(2.into()).unwrap();
+        assert_eq!(frame.slots(), &[3, 4]);
+        assert!(frame.data[2..].iter().all(|slot| *slot == 0));
+        assert_eq!(frame.source, second);
+        assert_eq!(frame.sequence, 0);
+        assert_eq!(frame.last_seen, later);
+        assert_eq!(frame.frames, 2);
+
+        let snapshot = receiver.snapshot();
+        assert_eq!(
+            snapshot.iter().map(|f| f.port_address).collect::<Vec<_>>(),
+            vec![1.into(), 2.into()]
+        );
+        assert!(receiver.remove(1.into()).is_some());
+        assert_eq!(receiver.universes().count(), 1);
+        receiver.clear();
+        assert!(receiver.universe(2.into()).is_none());
+    }
+
+    #[test]
+    fn data_loss() {
+        let mut receiver = DmxReceiver::new();
+        receiver.set_policy(2.into(), DataLossPolicy::Zero);
+        receiver.set_policy(3.into(), DataLossPolicy::Fade(Duration::from_secs(2)));
+        let source = "10.0.0.1:6454".parse().unwrap();
+        let start = Instant::now();
+        for port_address in 1..=3 {
+            let output = Output::new(port_address.into(), vec![200; 4]);
+            receiver.handle(&output.into(), source, start);
+        }
+        assert_eq!(
+            receiver.next_deadline(),
+            Some(start + DmxReceiver::DEFAULT_TIMEOUT)
+        );
+        assert!(receiver.tick(start + Duration::from_secs(9)).is_empty());
+
+        let lost = start + DmxReceiver::DEFAULT_TIMEOUT;
+        assert_eq!(receiver.tick(lost), vec![1.into(), 2.into(), 3.into()]);
+        let held = receiver.universe(1.into()).unwrap();
+        assert!(held.lost);
+        assert_eq!(held.slots(), &[200; 4]);
+        assert_eq!(receiver.universe(2.into()).unwrap().slots(), &[0; 4]);
+        assert_eq!(receiver.universe(3.into()).unwrap().slots(), &[200; 4]);
+
+        // Only the fading universe changes
+        assert_eq!(receiver.next_deadline(), Some(lost + FADE_STEP));
+        assert_eq!(receiver.tick(lost + Duration::from_secs(1)), vec![3.into()]);
+        assert_eq!(receiver.universe(3.into()).unwrap().slots(), &[100; 4]);
+        assert_eq!(receiver.tick(lost + Duration::from_secs(2)), vec![3.into()]);
+        assert_eq!(receiver.universe(3.into()).unwrap().slots(), &[0; 4]);
+        assert!(receiver.next_deadline().is_none());
+
+        // New data restores the universe
+        let output = Output::new(2.into(), vec![50; 4]);
+        receiver.handle(&output.into(), source, lost);
+        let restored = receiver.universe(2.into()).unwrap();
+        assert!(!restored.lost);
+        assert_eq!(restored.slots(), &[50; 4]);
+    }
+}
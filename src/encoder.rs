@@ -0,0 +1,173 @@
+use crate::{ArtCommand, Output, Result};
+use alloc::vec::Vec;
+
+/// Serializes commands into a buffer that is reused between calls.
+///
+/// After the first few messages the buffer is large enough, and encoding does not allocate anymore. Use `encode_batch` to prepare all the messages of a frame at once.
+///
+/// ```rust
+/// use artnet_protocol::*;
+///
+/// let mut encoder = Encoder::new();
+/// for sequence in 1..=3 {
+///     let command = ArtCommand::Output(Output {
+///         sequence,
+///         data: vec![255; 512].into(),
+///         ..Output::default()
+///     });
+///     let bytes: &[u8] = encoder.encode(&command).unwrap();
+///     // socket.send_to(bytes, addr)
+///     # assert_eq!(bytes.len(), 530);
+/// }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Encoder {
+    buffer: Vec<u8>,
+    /// The end of every message in the buffer, after `encode_batch`
+    ends: Vec<usize>,
+}
+
+impl Encoder {
+    /// Create an encoder with a buffer that fits any ArtDmx message
+    pub fn new() -> Encoder {
+        Encoder::with_capacity(Output::MAX_PACKET_SIZE)
+    }
+
+    /// Create an encoder with a buffer of the given capacity
+    pub fn with_capacity(capacity: usize) -> Encoder {
+        Encoder {
+            buffer: Vec::with_capacity(capacity),
+            ends: Vec::new(),
+        }
+    }
+
+    /// Serialize the command, replacing the previously encoded message. The returned slice is valid until the next call.
+    pub fn encode(&mut self, command: &ArtCommand) -> Result<&[u8]> {
+        self.buffer.clear();
+        if let Err(e) = command.write_to(&mut self.buffer) {
+            // Don't leave a partial message behind
+            self.buffer.clear();
+            return Err(e);
+        }
+        Ok(&self.buffer)
+    }
+
+    /// Serialize all the commands into the buffer, replacing the previously encoded messages, and return the message of every command in order. The slices are valid until the next call.
+    ///
+    /// The buffer grows at most once to fit all the messages, so e.g. the ArtDmx of 64 universes and an ArtSync are prepared with a single allocation, or none when the buffer is large enough already. If a command can not be serialized, none of the messages are returned.
+    ///
+    /// ```rust
+    /// use artnet_protocol::*;
+    ///
+    /// let mut frame: Vec<ArtCommand> = (1..=64)
+    ///     .map(|universe| Output::new(universe.into(), vec![255; 512]).into_command())
+    ///     .collect();
+    /// frame.push(ArtCommand::sync());
+    ///
+    /// let mut encoder = Encoder::new();
+    /// let messages: Vec<&[u8]> = encoder.encode_batch(&frame).unwrap().collect();
+    /// assert_eq!(messages.len(), 65);
+    /// assert_eq!(messages[64].len(), 14);
+    /// // for message in messages { socket.send_to(message, addr) }
+    /// ```
+    pub fn encode_batch(
+        &mut self,
+        commands: &[ArtCommand],
+    ) -> Result<impl Iterator<Item = &[u8]> + '_> {
+        self.buffer.clear();
+        self.ends.clear();
+        self.buffer
+            .reserve(commands.iter().map(ArtCommand::serialized_len).sum());
+        self.ends.reserve(commands.len());
+        for command in commands {
+            if let Err(e) = command.write_to(&mut self.buffer) {
+                // Don't leave partial messages behind
+                self.buffer.clear();
+                self.ends.clear();
+                return Err(e);
+            }
+            self.ends.push(self.buffer.len());
+        }
+        let buffer = &self.buffer;
+        Ok(self.ends.iter().scan(0, move |start, &end| {
+            let message = &buffer[*start..end];
+            *start = end;
+            Some(message)
+        }))
+    }
+
+    /// The current capacity of the internal buffer
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Poll, PollReply};
+
+    #[test]
+    fn reuses_buffer() {
+        let mut encoder = Encoder::new();
+        let output = Output::new(1.into(), vec![0; 512]).into_command();
+        let pointer = encoder.encode(&output).unwrap().as_ptr();
+
+        let poll = ArtCommand::Poll(Poll::default());
+        let bytes = encoder.encode(&poll).unwrap();
+        assert_eq!(bytes.as_ptr(), pointer);
+        assert_eq!(
+            bytes,
+            &ArtCommand::Poll(Poll::default()).write_to_buffer().unwrap()[..]
+        );
+        assert_eq!(encoder.capacity(), Output::MAX_PACKET_SIZE);
+    }
+
+    #[test]
+    fn grows_when_needed() {
+        let mut encoder = Encoder::with_capacity(0);
+        let reply = ArtCommand::PollReply(Box::default());
+        assert_eq!(
+            encoder.encode(&reply).unwrap().len(),
+            PollReply::PACKET_SIZE
+        );
+        assert!(encoder.capacity() >= PollReply::PACKET_SIZE);
+    }
+
+    #[test]
+    fn batch() {
+        let mut frame: Vec<ArtCommand> = (1..=64)
+            .map(|universe| Output::new(universe.into(), vec![universe; 512]).into_command())
+            .collect();
+        frame.push(ArtCommand::sync());
+
+        let mut encoder = Encoder::new();
+        let messages: Vec<Vec<u8>> = encoder
+            .encode_batch(&frame)
+            .unwrap()
+            .map(|message| message.to_vec())
+            .collect();
+        assert_eq!(messages.len(), frame.len());
+        for (message, command) in messages.iter().zip(frame) {
+            assert_eq!(message, &command.write_to_buffer().unwrap());
+        }
+        let capacity = encoder.capacity();
+        assert!(capacity >= 64 * Output::MAX_PACKET_SIZE + 14);
+
+        // The buffer is reused for the next frame
+        let frame = [Poll::default().into_command(), ArtCommand::sync()];
+        assert_eq!(encoder.encode_batch(&frame).unwrap().count(), 2);
+        assert_eq!(encoder.capacity(), capacity);
+
+        let invalid = [ArtCommand::sync(), ArtCommand::Output(Output::default())];
+        assert!(encoder.encode_batch(&invalid).is_err());
+        assert_eq!(encoder.encode_batch(&[]).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn error() {
+        let mut encoder = Encoder::new();
+        let invalid = ArtCommand::Output(Output::default());
+        assert!(encoder.encode(&invalid).is_err());
+    }
+}
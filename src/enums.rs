@@ -1,12 +1,13 @@
-use crate::byteorder::ReadBytesExt;
-use crate::convert::Convertable;
-use crate::{Error, Result};
-use std::io::Cursor;
+use crate::convert::{Convertable, Cursor, WriteBuffer};
+use crate::Result;
 
 bitflags! {
-    #[derive(Debug, PartialEq)]
+    #[derive(Debug, Clone, PartialEq)]
     /// The TalkToMe flag, as to be used in the `Poll` and `PollReply` message
     pub struct ArtTalkToMe: u8 {
+        /// Only the nodes with a Port-Address from `Poll::target_port_bottom` to `Poll::target_port_top` should reply
+        const TARGETED_MODE = 0b0010_0000;
+
         /// Enable VLC transmission if set, disabled otherwise
         const ENABLE_VLC = 0b0001_0000;
 
@@ -24,15 +25,50 @@ bitflags! {
     }
 }
 
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    /// The status of a node, as reported in the `status` of an `IpProgReply`
+    pub struct IpProgStatus: u8 {
+        /// DHCP is enabled on the node
+        const DHCP_ENABLED = 0b0100_0000;
+
+        /// No flags
+        const NONE = 0b0000_0000;
+    }
+}
+
+#[cfg(feature = "dmx")]
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    /// The setting of a single input port in an `Input`
+    pub struct InputDisable: u8 {
+        /// The input port is disabled, and the node does not send the data that it receives on it
+        const DISABLED = 0b0000_0001;
+
+        /// No flags, the input port is enabled
+        const NONE = 0b0000_0000;
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for ArtTalkToMe {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(ArtTalkToMe::from_bits_truncate(u.arbitrary()?))
+    }
+}
+
 impl<T> Convertable<T> for ArtTalkToMe {
-    fn from_cursor(cursor: &mut Cursor<&[u8]>) -> Result<Self> {
-        let b = cursor.read_u8().map_err(Error::CursorEof)?;
+    fn from_cursor(cursor: &mut Cursor) -> Result<Self> {
+        let b = cursor.read_u8()?;
         Ok(ArtTalkToMe::from_bits_truncate(b))
     }
-    fn write_to_buffer(&self, buffer: &mut Vec<u8>, _: &T) -> Result<()> {
+    fn write_to_buffer(&self, buffer: &mut dyn WriteBuffer, _: &T) -> Result<()> {
         buffer.push(self.bits());
         Ok(())
     }
+    fn serialized_len(&self, _: &T) -> usize {
+        1
+    }
     #[cfg(test)]
     fn get_test_value() -> Self {
         ArtTalkToMe::NONE
@@ -42,3 +78,39 @@ impl<T> Convertable<T> for ArtTalkToMe {
         self == other
     }
 }
+
+#[cfg(all(feature = "dmx", feature = "arbitrary"))]
+impl<'a> arbitrary::Arbitrary<'a> for InputDisable {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(InputDisable::from_bits_retain(u.arbitrary()?))
+    }
+}
+
+#[cfg(feature = "dmx")]
+impl<T> Convertable<T> for [InputDisable; 4] {
+    fn from_cursor(cursor: &mut Cursor) -> Result<Self> {
+        let mut bytes = [0; 4];
+        cursor.read_exact(&mut bytes)?;
+        Ok(bytes.map(InputDisable::from_bits_retain))
+    }
+    fn write_to_buffer(&self, buffer: &mut dyn WriteBuffer, _: &T) -> Result<()> {
+        buffer.extend_from_slice(&self.map(|input| input.bits()));
+        Ok(())
+    }
+    fn serialized_len(&self, _: &T) -> usize {
+        4
+    }
+    #[cfg(test)]
+    fn get_test_value() -> Self {
+        [
+            InputDisable::DISABLED,
+            InputDisable::NONE,
+            InputDisable::DISABLED,
+            InputDisable::NONE,
+        ]
+    }
+    #[cfg(test)]
+    fn is_equal(&self, other: &Self) -> bool {
+        self == other
+    }
+}
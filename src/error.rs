@@ -1,24 +1,55 @@
-use std::ops::Range;
+use alloc::boxed::Box;
+use core::ops::Range;
 
 /// The result that this crate uses
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 /// All the possible errors this crate can encounter
-#[derive(Debug)]
+///
+/// Errors can be compared, so tests can assert on a specific failure. New variants may be added in minor releases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Error {
-    /// Could not read or write to the inner curso
-    CursorEof(std::io::Error),
+    /// Tried to read past the end of the message
+    CursorEof {
+        /// The offset at which the read was attempted
+        offset: usize,
 
-    /// Could not serialize an artnet command
-    SerializeError(&'static str, Box<Error>),
+        /// The amount of bytes that were needed
+        needed: usize,
 
-    /// Could not deserialize an artnet command
-    DeserializeError(&'static str, Box<Error>),
+        /// The amount of bytes that were left in the message
+        remaining: usize,
+    },
+
+    /// Could not serialize a field of an artnet command
+    SerializeError {
+        /// The path of the field, e.g. `"Output::data"`
+        field: &'static str,
+
+        /// The offset in the body of the command at which the field would be written
+        offset: usize,
+
+        /// The reason why the field could not be serialized
+        source: Box<Error>,
+    },
+
+    /// Could not deserialize a field of an artnet command
+    DeserializeError {
+        /// The path of the field, e.g. `"PollReply::swout"`
+        field: &'static str,
+
+        /// The offset in the body of the command at which the field starts
+        offset: usize,
+
+        /// The reason why the field could not be deserialized
+        source: Box<Error>,
+    },
 
     /// The given message was too short
     MessageTooShort {
-        /// The message that was being send or received
-        message: Vec<u8>,
+        /// The length of the message that was received
+        len: usize,
 
         /// The minimal length that is supported
         min_len: usize,
@@ -26,51 +57,124 @@ pub enum Error {
 
     /// The given message was too long or too short
     MessageSizeInvalid {
-        /// The message that was being send or received
-        message: Vec<u8>,
+        /// The length of the message or data that was being sent or received
+        len: usize,
 
         /// The size that the artnet protocol expects
         allowed_size: Range<usize>,
     },
 
     /// The artnet header is invalid
-    InvalidArtnetHeader(Vec<u8>),
+    InvalidArtnetHeader {
+        /// The length of the message that was received
+        len: usize,
+    },
+
+    /// Could not parse the body of the given opcode
+    OpcodeError {
+        /// The name of the command, e.g. `"PollReply"`
+        name: &'static str,
+
+        /// The opcode of the command
+        opcode: u16,
 
-    /// Could not parse the given opcode
-    OpcodeError(&'static str, Box<Error>),
+        /// The reason why the command could not be parsed
+        source: Box<Error>,
+    },
 
-    /// Unknown opcode ID
+    /// Unknown opcode ID, or the opcode belongs to a family that is disabled through cargo features
     UnknownOpcode(u16),
 
     /// The Art-Net PortAddress was not from 0 to 32_767
     InvalidPortAddress(i32),
+
+    /// The protocol version of the message is lower than `ParseOptions::min_protocol_version`
+    UnsupportedProtocolVersion {
+        /// The protocol version of the message
+        version: u16,
+
+        /// The minimal protocol version that is accepted
+        min_version: u16,
+    },
+
+    /// The message does not follow the Art-Net specification. Only returned when parsing with `ParseMode::Strict`
+    SpecViolation(&'static str),
+
+    /// The RDM packet in an ArtRdm could not be parsed, e.g. because its checksum is wrong
+    InvalidRdmPacket(&'static str),
+
+    /// An sACN (E1.31) packet could not be converted, e.g. because its root layer is invalid
+    InvalidSacnPacket(&'static str),
+
+    /// A configuration can not be programmed into a node, e.g. because its ports are in different Net and Sub-Nets
+    InvalidConfig(&'static str),
+
+    /// An ArtNzs has the DMX start code 0x00 or the RDM start code 0xCC, which must be sent with ArtDmx and ArtRdm instead. Only returned when parsing or writing with `ParseMode::Strict`
+    InvalidStartCode(u8),
+
+    /// A time code could not be parsed from text, e.g. because it is not in the "HH:MM:SS:FF" notation
+    InvalidTimecode(&'static str),
+}
+
+impl Error {
+    /// The byte offset in the message at which this error occurred, if known.
+    ///
+    /// For errors returned by `ArtCommand::from_buffer` this is counted from the start of the message, including the Art-Net header and opcode. For errors returned by the `from` function of a single command, it is counted from the start of that command's body.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Error::CursorEof { offset, .. } => Some(*offset),
+            Error::SerializeError { offset, .. } => Some(*offset),
+            Error::DeserializeError { offset, source, .. } => {
+                // The source error is more precise, e.g. the exact EOF position in a nested field
+                Some(source.offset().unwrap_or(*offset).max(*offset))
+            }
+            Error::OpcodeError { source, .. } => source
+                .offset()
+                .map(|offset| offset + crate::command::HEADER_LENGTH),
+            _ => None,
+        }
+    }
 }
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl core::fmt::Display for Error {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
         match self {
-            Error::CursorEof(inner) => write!(fmt, "Cursor EOF: {}", inner),
-            Error::SerializeError(message, inner) => write!(fmt, "{}: {}", message, inner),
-            Error::DeserializeError(message, inner) => write!(fmt, "{}: {}", message, inner),
-            Error::MessageTooShort { message, min_len } => write!(
+            Error::CursorEof {
+                offset,
+                needed,
+                remaining,
+            } => write!(
+                fmt,
+                "Unexpected end of message at byte {}, needed {} bytes but only {} were left",
+                offset, needed, remaining
+            ),
+            Error::SerializeError { field, offset, .. } => write!(
+                fmt,
+                "Could not serialize field {} at byte {}",
+                field, offset
+            ),
+            Error::DeserializeError { field, offset, .. } => write!(
+                fmt,
+                "Could not deserialize field {} at byte {}",
+                field, offset
+            ),
+            Error::MessageTooShort { len, min_len } => write!(
                 fmt,
                 "Message too short, it was {} but artnet expects at least {}",
-                message.len(),
-                min_len
+                len, min_len
             ),
-            Error::MessageSizeInvalid {
-                message,
-                allowed_size,
-            } => write!(
+            Error::MessageSizeInvalid { len, allowed_size } => write!(
                 fmt,
                 "Message size invalid, it was {} but artnet expects between {} and {}",
-                message.len(),
-                allowed_size.start,
-                allowed_size.end
+                len, allowed_size.start, allowed_size.end
             ),
-            Error::InvalidArtnetHeader(_) => write!(fmt, "Invalid artnet header"),
-            Error::OpcodeError(opcode, inner) => {
-                write!(fmt, "Could not parse opcode {:?}: {}", opcode, inner)
+            Error::InvalidArtnetHeader { .. } => write!(fmt, "Invalid artnet header"),
+            Error::OpcodeError { name, opcode, .. } => {
+                write!(fmt, "Could not parse opcode {} (0x{:04X})", name, opcode)?;
+                if let Some(offset) = self.offset() {
+                    write!(fmt, " at byte {}", offset)?;
+                }
+                Ok(())
             }
             Error::UnknownOpcode(opcode) => write!(fmt, "Unknown opcode 0x{:X}", opcode),
             Error::InvalidPortAddress(wrong_number) => write!(
@@ -78,8 +182,120 @@ impl std::fmt::Display for Error {
                 "Art-Net PortAddress must be from 0 to 32_767. Got {:?}",
                 wrong_number
             ),
+            Error::UnsupportedProtocolVersion {
+                version,
+                min_version,
+            } => write!(
+                fmt,
+                "Protocol version {} is not supported, expected at least {}",
+                version, min_version
+            ),
+            Error::SpecViolation(reason) => write!(fmt, "Spec violation: {}", reason),
+            Error::InvalidRdmPacket(reason) => write!(fmt, "Invalid RDM packet: {}", reason),
+            Error::InvalidSacnPacket(reason) => write!(fmt, "Invalid sACN packet: {}", reason),
+            Error::InvalidConfig(reason) => write!(fmt, "Invalid configuration: {}", reason),
+            Error::InvalidTimecode(reason) => write!(fmt, "Invalid time code: {}", reason),
+            Error::InvalidStartCode(start_code) => write!(
+                fmt,
+                "Start code 0x{:02X} can not be sent with ArtNzs",
+                start_code
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::SerializeError { source, .. }
+            | Error::DeserializeError { source, .. }
+            | Error::OpcodeError { source, .. } => Some(source.as_ref()),
+            _ => None,
         }
     }
 }
 
-impl std::error::Error for Error {}
+#[cfg(test)]
+mod tests {
+    use crate::{ArtCommand, Error, PollReply};
+    #[cfg(feature = "std")]
+    use std::error::Error as _;
+
+    #[test]
+    fn truncated_field() {
+        let mut message = ArtCommand::PollReply(Box::default())
+            .write_to_buffer()
+            .unwrap();
+        // Cut the message in the middle of `swout`, which starts at byte 190
+        message.truncate(192);
+
+        let err = PollReply::from(&message[10..]).unwrap_err();
+        match &err {
+            Error::DeserializeError { field, offset, .. } => {
+                assert_eq!(*field, "PollReply::swout");
+                assert_eq!(*offset, 180);
+            }
+            _ => panic!("Unexpected error {:?}", err),
+        }
+        #[cfg(feature = "std")]
+        match err.source().and_then(|e| e.downcast_ref::<Error>()) {
+            Some(Error::CursorEof {
+                offset,
+                needed,
+                remaining,
+            }) => {
+                assert_eq!(*offset, 180);
+                assert_eq!(*needed, 4);
+                assert_eq!(*remaining, 2);
+            }
+            e => panic!("Unexpected source {:?}", e),
+        }
+    }
+
+    #[test]
+    fn comparable() {
+        let err = ArtCommand::from_buffer(b"Art-Net\0\x00\x21\x00\x0e\x00\x00").unwrap_err();
+        assert_eq!(
+            err,
+            Error::MessageTooShort {
+                len: 14,
+                min_len: 207,
+            }
+        );
+        assert_ne!(err, Error::UnknownOpcode(0x2100));
+    }
+
+    #[test]
+    fn message_size() {
+        let err = Error::MessageSizeInvalid {
+            len: 600,
+            allowed_size: 2..512,
+        };
+        assert_eq!(
+            err.to_string(),
+            "Message size invalid, it was 600 but artnet expects between 2 and 512"
+        );
+    }
+
+    #[test]
+    fn opcode_offset() {
+        let err = ArtCommand::from_buffer(&[
+            65, 114, 116, 45, 78, 101, 116, 0, 0, 80, 0, 14, 0, 0, 0, 128, 0, 2, 0, 0,
+        ])
+        .unwrap_err();
+        match &err {
+            Error::OpcodeError { name, opcode, .. } => {
+                assert_eq!(*name, "Output");
+                assert_eq!(*opcode, 0x5000);
+            }
+            _ => panic!("Unexpected error {:?}", err),
+        }
+        // The port address starts at byte 14
+        assert_eq!(err.offset(), Some(14));
+        assert_eq!(
+            err.to_string(),
+            "Could not parse opcode Output (0x5000) at byte 14"
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use crate::{Address, ArtCommand, FailsafeState, NodeId, PortAddress};
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::time::{Duration, Instant};
+
+/// A change that was detected by a [FailoverMonitor](struct.FailoverMonitor.html)
+#[derive(Debug)]
+pub enum FailoverEvent {
+    /// The primary source stopped sending ArtDmx for this universe
+    PrimaryLost(PortAddress),
+
+    /// The primary source sends ArtDmx for this universe again
+    PrimaryRestored(PortAddress),
+
+    /// Send this ArtAddress to the node, e.g. with [Controller::program](struct.Controller.html#method.program)
+    Program(NodeId, Address),
+}
+
+/// Watches the ArtDmx of a primary source, and programs the failsafe state of the nodes that support failover when it goes away.
+///
+/// Nodes advertise failover support in `PollReply::status_3`. When the primary source did not send ArtDmx for a universe within the timeout, every failover node that outputs that universe is sent an ArtAddress with the failsafe command of the monitor, e.g. `AddressCommand::FailHold`.
+///
+/// Pass every received command to `handle`, together with the IP address that sent it, and call `poll` regularly.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::{Duration, Instant};
+///
+/// let primary = [10, 0, 0, 1].into();
+/// let mut monitor = FailoverMonitor::new(primary, FailsafeState::Hold);
+/// let start = Instant::now();
+///
+/// let reply = PollReply {
+///     num_ports: [0, 1],
+///     port_types: [0x80, 0, 0, 0],
+///     status_3: PollReply::STATUS_3_FAILOVER,
+///     ..PollReply::default()
+/// };
+/// monitor.handle(&reply.into_command(), [10, 0, 0, 20].into(), start);
+/// monitor.handle(&Output::new(0.into(), vec![0; 2]).into(), primary, start);
+///
+/// let events = monitor.poll(start + Duration::from_secs(5));
+/// assert!(matches!(events[..], [FailoverEvent::PrimaryLost(_), FailoverEvent::Program(..)]));
+/// ```
+#[derive(Debug)]
+pub struct FailoverMonitor {
+    primary: Ipv4Addr,
+    failsafe: FailsafeState,
+    timeout: Duration,
+    /// The universes of the primary source, and when it last sent ArtDmx for them
+    universes: HashMap<PortAddress, Universe>,
+    /// The failover nodes, and the universes that they output
+    nodes: HashMap<NodeId, Vec<PortAddress>>,
+}
+
+#[derive(Debug)]
+struct Universe {
+    last_seen: Instant,
+    lost: bool,
+}
+
+impl FailoverMonitor {
+    /// How long the primary source can be silent before it is considered lost. ArtDmx is re-transmitted at least every 4 seconds.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Monitor the given primary source, and program the failover nodes with the given failsafe state when it is lost
+    pub fn new(primary: Ipv4Addr, failsafe: FailsafeState) -> FailoverMonitor {
+        FailoverMonitor {
+            primary,
+            failsafe,
+            timeout: FailoverMonitor::DEFAULT_TIMEOUT,
+            universes: HashMap::new(),
+            nodes: HashMap::new(),
+        }
+    }
+
+    /// Change how long the primary source can be silent before it is considered lost
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// The nodes that advertise failover support
+    pub fn failover_nodes(&self) -> impl Iterator<Item = &NodeId> {
+        self.nodes.keys()
+    }
+
+    /// If the primary source is lost for the given universe
+    pub fn is_lost(&self, port_address: PortAddress) -> bool {
+        self.universes
+            .get(&port_address)
+            .is_some_and(|universe| universe.lost)
+    }
+
+    /// Forget a node, e.g. because it was lost by the [DiscoveryManager](struct.DiscoveryManager.html)
+    pub fn remove(&mut self, node: &NodeId) {
+        self.nodes.remove(node);
+    }
+
+    /// Handle a command that was received from the given IP address. ArtPollReply updates the failover nodes, and ArtDmx of the primary source keeps its universe alive. Other commands are ignored.
+    pub fn handle(
+        &mut self,
+        command: &ArtCommand,
+        source: Ipv4Addr,
+        now: Instant,
+    ) -> Option<FailoverEvent> {
+        match command {
+            ArtCommand::PollReply(reply) => {
+                let id = NodeId::of(reply);
+                if reply.supports_failover() {
+                    self.nodes
+                        .insert(id, reply.output_port_addresses().collect());
+                } else {
+                    self.nodes.remove(&id);
+                }
+                None
+            }
+            ArtCommand::Output(output) if source == self.primary => {
+                let universe = self
+                    .universes
+                    .entry(output.port_address)
+                    .or_insert(Universe {
+                        last_seen: now,
+                        lost: false,
+                    });
+                universe.last_seen = now;
+                if universe.lost {
+                    universe.lost = false;
+                    Some(FailoverEvent::PrimaryRestored(output.port_address))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Find the universes for which the primary source was lost, and program the failover nodes that output them
+    pub fn poll(&mut self, now: Instant) -> Vec<FailoverEvent> {
+        let mut events = Vec::new();
+        let mut programmed = Vec::new();
+        for (port_address, universe) in &mut self.universes {
+            if universe.lost || now < universe.last_seen + self.timeout {
+                continue;
+            }
+            universe.lost = true;
+            events.push(FailoverEvent::PrimaryLost(*port_address));
+            for (node, port_addresses) in &self.nodes {
+                if port_addresses.contains(port_address) && !programmed.contains(node) {
+                    programmed.push(*node);
+                }
+            }
+        }
+        // Nodes that output several lost universes are only programmed once
+        programmed.sort();
+        for node in programmed {
+            let address = Address::default().with_command(self.failsafe.command());
+            events.push(FailoverEvent::Program(node, address));
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AddressCommand, Output, PollReply};
+
+    fn reply(address: [u8; 4], status_3: u8) -> ArtCommand {
+        PollReply {
+            address: address.into(),
+            num_ports: [0, 2],
+            port_types: [0x80, 0x80, 0, 0],
+            swout: [0, 1, 0, 0],
+            status_3,
+            ..PollReply::default()
+        }
+        .into_command()
+    }
+
+    fn dmx(port_address: u8) -> ArtCommand {
+        Output::new(port_address.into(), vec![0; 2]).into()
+    }
+
+    #[test]
+    fn failover() {
+        let primary = Ipv4Addr::new(10, 0, 0, 1);
+        let mut monitor = FailoverMonitor::new(primary, FailsafeState::Scene);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        monitor.handle(&reply([10, 0, 0, 20], 0x20), primary, start);
+        monitor.handle(&reply([10, 0, 0, 21], 0x00), primary, start);
+        assert_eq!(monitor.failover_nodes().count(), 1);
+
+        monitor.handle(&dmx(0), primary, start);
+        monitor.handle(&dmx(1), primary, start);
+        // ArtDmx of another controller does not keep the primary alive
+        monitor.handle(&dmx(0), Ipv4Addr::new(10, 0, 0, 2), at(3));
+        monitor.handle(&dmx(1), primary, at(3));
+        assert!(monitor.poll(at(4)).is_empty());
+
+        let events = monitor.poll(at(5));
+        assert!(matches!(
+            events[..],
+            [FailoverEvent::PrimaryLost(_), FailoverEvent::Program(..)]
+        ));
+        match &events[1] {
+            FailoverEvent::Program(node, address) => {
+                assert_eq!(node.address, Ipv4Addr::new(10, 0, 0, 20));
+                assert_eq!(address.address_command(), AddressCommand::FailScene);
+            }
+            event => panic!("Unexpected event {:?}", event),
+        }
+        assert!(monitor.is_lost(0.into()));
+        assert!(!monitor.is_lost(1.into()));
+        assert!(monitor.poll(at(6)).is_empty());
+
+        assert!(matches!(
+            monitor.handle(&dmx(0), primary, at(7)),
+            Some(FailoverEvent::PrimaryRestored(_))
+        ));
+        assert!(!monitor.is_lost(0.into()));
+
+        // The node no longer supports failover
+        monitor.handle(&reply([10, 0, 0, 20], 0x00), primary, at(7));
+        let events = monitor.poll(at(12));
+        assert!(matches!(
+            events[..],
+            [FailoverEvent::PrimaryLost(_), FailoverEvent::PrimaryLost(_)]
+        ));
+    }
+}
@@ -0,0 +1,555 @@
+use crate::{ArtCommand, FileFnMaster, FileFnReply, FileTnMaster, FirmwareReply};
+use std::time::{Duration, Instant};
+
+/// A step of a [FileUploader](struct.FileUploader.html) or [FileDownloader](struct.FileDownloader.html)
+#[derive(Debug)]
+pub enum FileTransferEvent {
+    /// Send this message to the node. This is the next block or acknowledgement, or one that is sent again.
+    Send(ArtCommand),
+
+    /// A block was transferred
+    Progress {
+        /// The number of blocks that were transferred
+        transferred: usize,
+
+        /// The total number of blocks
+        total: usize,
+    },
+
+    /// The whole file was transferred
+    Complete,
+
+    /// The transfer failed too often, the node stopped replying, or the node reported that it received the whole file before the last block was sent
+    Failed,
+}
+
+/// The progress of a transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// `start` was not called yet
+    Idle,
+    /// Waiting for the node, since the given time
+    Active(Instant),
+    /// The last block of an upload was acknowledged at the given time, waiting for the node to accept the whole file
+    Verifying(Instant),
+    Complete,
+    Failed,
+}
+
+/// The number of blocks of a file with the given length
+fn blocks(length: usize) -> usize {
+    length.div_ceil(FileTnMaster::BLOCK_SIZE).max(1)
+}
+
+/// Uploads a user file, e.g. a configuration file, to a single node with ArtFileTnMaster.
+///
+/// Non-standard: this uses the layout of [FileTnMaster](struct.FileTnMaster.html) that is specific to this crate, so it only works with nodes that use this crate as well.
+///
+/// Every block must be acknowledged with an ArtFirmwareReply before the next one is sent. When a block is not acknowledged within the timeout, or the node reports a failure, the upload resumes by sending that block again. After too many retries of the same block the upload fails.
+///
+/// The upload is only complete when the node replies with `FIRM_ALL_GOOD` to the last block. If it acknowledges the last block with `FIRM_BLOCK_GOOD`, the uploader keeps waiting for the `FIRM_ALL_GOOD`, and sends the last block again if it does not arrive in time.
+///
+/// Send the messages of `FileTransferEvent::Send` to the node, pass the replies of that node to `handle`, and call `poll` regularly.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::Instant;
+///
+/// let mut uploader = FileUploader::new("config.txt", b"brightness=100");
+/// let now = Instant::now();
+/// assert!(matches!(uploader.start(now), FileTransferEvent::Send(_)));
+///
+/// let reply = FirmwareReply { reply_type: FirmwareReply::FIRM_ALL_GOOD, ..FirmwareReply::default() };
+/// uploader.handle(&reply.into(), now);
+/// assert!(uploader.is_complete());
+/// ```
+#[derive(Debug)]
+pub struct FileUploader {
+    name: String,
+    data: Vec<u8>,
+    /// The block that is waiting for an acknowledgement
+    block: usize,
+    state: State,
+    timeout: Duration,
+    retries: usize,
+    max_retries: usize,
+}
+
+impl FileUploader {
+    /// How long to wait for the node before a block is sent again
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// How often a single block is sent again before the transfer fails
+    pub const DEFAULT_MAX_RETRIES: usize = 3;
+
+    /// Upload the given file. The name is truncated to 13 bytes.
+    pub fn new(name: &str, data: &[u8]) -> FileUploader {
+        FileUploader {
+            name: name.to_string(),
+            data: data.to_vec(),
+            block: 0,
+            state: State::Idle,
+            timeout: FileUploader::DEFAULT_TIMEOUT,
+            retries: 0,
+            max_retries: FileUploader::DEFAULT_MAX_RETRIES,
+        }
+    }
+
+    /// Change how long to wait for the node before a block is sent again
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Change how often a single block is sent again before the transfer fails
+    pub fn set_max_retries(&mut self, max_retries: usize) {
+        self.max_retries = max_retries;
+    }
+
+    /// The total number of blocks
+    pub fn blocks(&self) -> usize {
+        blocks(self.data.len())
+    }
+
+    /// The number of blocks that were acknowledged
+    pub fn acknowledged(&self) -> usize {
+        match self.state {
+            State::Complete => self.blocks(),
+            _ => self.block,
+        }
+    }
+
+    /// If the node received the whole file
+    pub fn is_complete(&self) -> bool {
+        self.state == State::Complete
+    }
+
+    /// Start the upload with the first block
+    pub fn start(&mut self, now: Instant) -> FileTransferEvent {
+        self.block = 0;
+        self.retries = 0;
+        self.send(now)
+    }
+
+    /// Handle an ArtFirmwareReply of the node. Other commands are ignored.
+    pub fn handle(&mut self, command: &ArtCommand, now: Instant) -> Vec<FileTransferEvent> {
+        let reply = match (command, self.state) {
+            (ArtCommand::FirmwareReply(reply), State::Active(_) | State::Verifying(_)) => reply,
+            _ => return Vec::new(),
+        };
+        if let State::Verifying(_) = self.state {
+            return match reply.reply_type {
+                FirmwareReply::FIRM_ALL_GOOD => {
+                    self.state = State::Complete;
+                    vec![FileTransferEvent::Complete]
+                }
+                FirmwareReply::FIRM_FAIL => vec![self.retry_last(now)],
+                // The last block was acknowledged already
+                _ => Vec::new(),
+            };
+        }
+        let progress = FileTransferEvent::Progress {
+            transferred: self.block + 1,
+            total: self.blocks(),
+        };
+        match reply.reply_type {
+            FirmwareReply::FIRM_BLOCK_GOOD => {
+                self.block += 1;
+                self.retries = 0;
+                if self.block < self.blocks() {
+                    vec![progress, self.send(now)]
+                } else {
+                    self.state = State::Verifying(now);
+                    vec![progress]
+                }
+            }
+            FirmwareReply::FIRM_ALL_GOOD if self.block + 1 == self.blocks() => {
+                self.block += 1;
+                self.state = State::Complete;
+                vec![progress, FileTransferEvent::Complete]
+            }
+            FirmwareReply::FIRM_ALL_GOOD => {
+                // The node can not have received the whole file yet
+                self.state = State::Failed;
+                vec![FileTransferEvent::Failed]
+            }
+            FirmwareReply::FIRM_FAIL => vec![self.retry(now)],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Send the current block again if it was not acknowledged in time, or the last block if the node did not accept the whole file in time
+    pub fn poll(&mut self, now: Instant) -> Option<FileTransferEvent> {
+        match self.state {
+            State::Active(sent) if now >= sent + self.timeout => Some(self.retry(now)),
+            State::Verifying(sent) if now >= sent + self.timeout => Some(self.retry_last(now)),
+            _ => None,
+        }
+    }
+
+    /// Send the last block again while waiting for the node to accept the whole file
+    fn retry_last(&mut self, now: Instant) -> FileTransferEvent {
+        self.block = self.blocks() - 1;
+        self.retry(now)
+    }
+
+    fn retry(&mut self, now: Instant) -> FileTransferEvent {
+        if self.retries >= self.max_retries {
+            self.state = State::Failed;
+            return FileTransferEvent::Failed;
+        }
+        self.retries += 1;
+        self.send(now)
+    }
+
+    fn send(&mut self, now: Instant) -> FileTransferEvent {
+        self.state = State::Active(now);
+        let block_type = if self.block + 1 == self.blocks() {
+            FileTnMaster::FILE_LAST
+        } else if self.block == 0 {
+            FileTnMaster::FILE_FIRST
+        } else {
+            FileTnMaster::FILE_CONT
+        };
+        let start = self.block * FileTnMaster::BLOCK_SIZE;
+        let end = (start + FileTnMaster::BLOCK_SIZE).min(self.data.len());
+        FileTransferEvent::Send(
+            FileTnMaster::new(
+                &self.name,
+                block_type,
+                self.block as u8,
+                self.data.len() as u32,
+                self.data[start..end].to_vec(),
+            )
+            .into(),
+        )
+    }
+}
+
+/// Downloads a user file from a single node with ArtFileFnMaster.
+///
+/// Non-standard: this uses the layouts of [FileFnMaster](struct.FileFnMaster.html) and [FileFnReply](struct.FileFnReply.html) that are specific to this crate, so it only works with nodes that use this crate as well.
+///
+/// Every block is verified with its checksum and acknowledged with an ArtFileFnReply. When a block is invalid or out of order, or the node does not send a block within the timeout, the download resumes by acknowledging the last valid block again, so the node sends the rest of the file. After too many retries the download fails.
+///
+/// Send the messages of `FileTransferEvent::Send` to the node, pass the messages of that node to `handle`, and call `poll` regularly.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::Instant;
+///
+/// let mut downloader = FileDownloader::new("config.txt");
+/// let now = Instant::now();
+/// assert!(matches!(downloader.start(now), FileTransferEvent::Send(_)));
+///
+/// let block = FileFnMaster::new("config.txt", FileFnMaster::FILE_LAST, 0, 3, vec![1, 2, 3]);
+/// downloader.handle(&block.into(), now);
+/// assert_eq!(downloader.data(), Some(&[1, 2, 3][..]));
+/// ```
+#[derive(Debug)]
+pub struct FileDownloader {
+    name: String,
+    data: Vec<u8>,
+    /// The length of the file, once the first block was received
+    length: Option<usize>,
+    /// The number of valid blocks that were received
+    received: usize,
+    state: State,
+    timeout: Duration,
+    retries: usize,
+    max_retries: usize,
+}
+
+impl FileDownloader {
+    /// Download the file with the given name. The name is truncated to 13 bytes.
+    pub fn new(name: &str) -> FileDownloader {
+        FileDownloader {
+            name: name.to_string(),
+            data: Vec::new(),
+            length: None,
+            received: 0,
+            state: State::Idle,
+            timeout: FileUploader::DEFAULT_TIMEOUT,
+            retries: 0,
+            max_retries: FileUploader::DEFAULT_MAX_RETRIES,
+        }
+    }
+
+    /// Change how long to wait for the node before the download is resumed
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Change how often the download is resumed after the same block before it fails
+    pub fn set_max_retries(&mut self, max_retries: usize) {
+        self.max_retries = max_retries;
+    }
+
+    /// The number of blocks that were received, and the total number of blocks once the first block was received
+    pub fn progress(&self) -> (usize, Option<usize>) {
+        (self.received, self.length.map(blocks))
+    }
+
+    /// The downloaded file, once it is complete
+    pub fn data(&self) -> Option<&[u8]> {
+        match self.state {
+            State::Complete => Some(&self.data),
+            _ => None,
+        }
+    }
+
+    /// Request the file from the node
+    pub fn start(&mut self, now: Instant) -> FileTransferEvent {
+        self.data.clear();
+        self.length = None;
+        self.received = 0;
+        self.retries = 0;
+        self.state = State::Active(now);
+        FileTransferEvent::Send(FileFnMaster::request(&self.name).into())
+    }
+
+    /// Handle an ArtFileFnMaster of the node. Other commands, and blocks of other files, are ignored.
+    pub fn handle(&mut self, command: &ArtCommand, now: Instant) -> Vec<FileTransferEvent> {
+        let block = match (command, self.state) {
+            (ArtCommand::FileFnMaster(block), State::Active(_))
+                if block.block_type != FileFnMaster::FILE_REQUEST
+                    && block.name == FileFnMaster::request(&self.name).name =>
+            {
+                block
+            }
+            _ => return Vec::new(),
+        };
+
+        let expected = self.received as u8;
+        if self.received > 0 && block.block_id == expected.wrapping_sub(1) {
+            // The node did not receive the acknowledgement, and sent this block again
+            return vec![self.acknowledge(FileFnReply::FILE_BLOCK_GOOD, now)];
+        }
+        let length = block.file_length() as usize;
+        if block.block_id != expected
+            || !block.is_checksum_valid()
+            || self.length.is_some_and(|l| l != length)
+            || self.data.len() + block.data.len() > length
+        {
+            return self.retry(now);
+        }
+
+        self.length = Some(length);
+        self.data.extend_from_slice(&block.data);
+        self.received += 1;
+        self.retries = 0;
+        let progress = FileTransferEvent::Progress {
+            transferred: self.received,
+            total: blocks(length),
+        };
+        if block.block_type != FileFnMaster::FILE_LAST {
+            return vec![
+                progress,
+                self.acknowledge(FileFnReply::FILE_BLOCK_GOOD, now),
+            ];
+        }
+        if self.data.len() != length {
+            self.state = State::Failed;
+            return vec![
+                self.acknowledge(FileFnReply::FILE_FAIL, now),
+                FileTransferEvent::Failed,
+            ];
+        }
+        let reply = self.acknowledge(FileFnReply::FILE_ALL_GOOD, now);
+        self.state = State::Complete;
+        vec![progress, reply, FileTransferEvent::Complete]
+    }
+
+    /// Resume the download if the node did not send a block in time
+    pub fn poll(&mut self, now: Instant) -> Vec<FileTransferEvent> {
+        match self.state {
+            State::Active(last) if now >= last + self.timeout => self.retry(now),
+            _ => Vec::new(),
+        }
+    }
+
+    fn retry(&mut self, now: Instant) -> Vec<FileTransferEvent> {
+        if self.retries >= self.max_retries {
+            let reply = self.acknowledge(FileFnReply::FILE_FAIL, now);
+            self.state = State::Failed;
+            return vec![reply, FileTransferEvent::Failed];
+        }
+        self.retries += 1;
+        if self.received == 0 {
+            self.state = State::Active(now);
+            return vec![FileTransferEvent::Send(
+                FileFnMaster::request(&self.name).into(),
+            )];
+        }
+        vec![self.acknowledge(FileFnReply::FILE_BLOCK_GOOD, now)]
+    }
+
+    /// Acknowledge the last valid block
+    fn acknowledge(&mut self, reply_type: u8, now: Instant) -> FileTransferEvent {
+        self.state = State::Active(now);
+        FileTransferEvent::Send(
+            FileFnReply {
+                reply_type,
+                block_id: (self.received as u8).wrapping_sub(1),
+                ..FileFnReply::default()
+            }
+            .into(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sent(event: &FileTransferEvent) -> &ArtCommand {
+        match event {
+            FileTransferEvent::Send(command) => command,
+            event => panic!("Unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn upload() {
+        let mut uploader = FileUploader::new("show.cfg", &[7; 1500]);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let good = FirmwareReply::default().into();
+        let all_good = FirmwareReply {
+            reply_type: FirmwareReply::FIRM_ALL_GOOD,
+            ..FirmwareReply::default()
+        }
+        .into();
+
+        match sent(&uploader.start(start)) {
+            ArtCommand::FileTnMaster(block) => {
+                assert_eq!(block.block_type, FileTnMaster::FILE_FIRST);
+                assert_eq!(block.name(), "show.cfg");
+                assert!(block.is_checksum_valid());
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+        uploader.handle(&good, start);
+        assert_eq!(uploader.acknowledged(), 1);
+
+        // The second block is resumed, instead of restarting the upload
+        match sent(&uploader.poll(at(5)).unwrap()) {
+            ArtCommand::FileTnMaster(block) => {
+                assert_eq!(block.block_type, FileTnMaster::FILE_LAST);
+                assert_eq!(block.block_id, 1);
+                assert_eq!(block.data.len(), 476);
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+        let events = uploader.handle(&all_good, at(6));
+        assert!(matches!(events[1], FileTransferEvent::Complete));
+    }
+
+    #[test]
+    fn upload_complete_after_last_block() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let good = FirmwareReply::default().into();
+        let all_good = FirmwareReply {
+            reply_type: FirmwareReply::FIRM_ALL_GOOD,
+            ..FirmwareReply::default()
+        }
+        .into();
+
+        // Accepting the whole file before the last block is an error
+        let mut early = FileUploader::new("show.cfg", &[7; 1500]);
+        early.start(start);
+        let events = early.handle(&all_good, start);
+        assert!(matches!(events[..], [FileTransferEvent::Failed]));
+        assert!(!early.is_complete());
+
+        let mut uploader = FileUploader::new("show.cfg", &[7; 1500]);
+        uploader.start(start);
+        uploader.handle(&good, start);
+        let events = uploader.handle(&good, start);
+        assert!(matches!(
+            events[..],
+            [FileTransferEvent::Progress {
+                transferred: 2,
+                total: 2
+            }]
+        ));
+        assert!(!uploader.is_complete());
+        assert!(uploader.handle(&good, start).is_empty());
+
+        // The last block is sent again if the node does not accept the file in time
+        match sent(&uploader.poll(at(5)).unwrap()) {
+            ArtCommand::FileTnMaster(block) => assert_eq!(block.block_id, 1),
+            command => panic!("Unexpected command {:?}", command),
+        }
+        let events = uploader.handle(&all_good, at(6));
+        assert!(matches!(events[..], [_, FileTransferEvent::Complete]));
+        assert!(uploader.is_complete());
+        assert_eq!(uploader.acknowledged(), 2);
+    }
+
+    #[test]
+    fn download() {
+        let mut downloader = FileDownloader::new("show.cfg");
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let block = |block_type, block_id, data: &[u8]| {
+            ArtCommand::from(FileFnMaster::new(
+                "show.cfg",
+                block_type,
+                block_id,
+                1025,
+                data.to_vec(),
+            ))
+        };
+
+        downloader.start(start);
+        let events = downloader.handle(&block(FileFnMaster::FILE_FIRST, 0, &[1; 1024]), start);
+        assert!(matches!(
+            events[0],
+            FileTransferEvent::Progress {
+                transferred: 1,
+                total: 2
+            }
+        ));
+
+        // A corrupted block resumes after the last valid one
+        let mut corrupted =
+            FileFnMaster::new("show.cfg", FileFnMaster::FILE_LAST, 1, 1025, vec![2]);
+        corrupted.checksum = [0, 0];
+        let events = downloader.handle(&corrupted.into(), at(1));
+        match sent(&events[0]) {
+            ArtCommand::FileFnReply(reply) => {
+                assert_eq!(reply.reply_type, FileFnReply::FILE_BLOCK_GOOD);
+                assert_eq!(reply.block_id, 0);
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+        assert_eq!(downloader.data(), None);
+
+        let events = downloader.handle(&block(FileFnMaster::FILE_LAST, 1, &[2]), at(2));
+        assert!(matches!(events[2], FileTransferEvent::Complete));
+        let data = downloader.data().unwrap();
+        assert_eq!(data.len(), 1025);
+        assert_eq!(data[1024], 2);
+    }
+
+    #[test]
+    fn download_timeout() {
+        let mut downloader = FileDownloader::new("show.cfg");
+        downloader.set_max_retries(1);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        downloader.start(start);
+        assert!(downloader.poll(at(4)).is_empty());
+        // The request is sent again
+        assert!(matches!(
+            sent(&downloader.poll(at(5))[0]),
+            ArtCommand::FileFnMaster(_)
+        ));
+        assert!(matches!(
+            downloader.poll(at(10))[..],
+            [FileTransferEvent::Send(_), FileTransferEvent::Failed]
+        ));
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{ArtCommand, FirmwareKind, FirmwareMaster, FirmwareReply};
+use alloc::vec::Vec;
+
+/// An upload that is being received
+#[derive(Debug)]
+struct Upload {
+    kind: FirmwareKind,
+    /// The length of the image in bytes, as announced in the first block
+    length: usize,
+    /// The `block_id` of the last block that was received
+    block_id: u8,
+    image: Vec<u8>,
+}
+
+/// Receives a firmware or UBEA upload on a node, and replies to every ArtFirmwareMaster block with an ArtFirmwareReply.
+///
+/// Blocks must arrive in order. A block that is sent again, e.g. because the acknowledgement was lost, is acknowledged again without changing the image. Any other block out of order fails the upload. When the last block arrives, the callback is called with the complete image. It returns if the image is valid and was installed, which is reported to the controller.
+///
+/// The receiver works without `std`.
+///
+/// ```rust
+/// use artnet_protocol::*;
+///
+/// let mut receiver = FirmwareReceiver::new(64 * 1024, |kind, image: &[u8]| {
+///     // Verify and flash the image
+///     kind == FirmwareKind::Firmware && image.len() == 4
+/// });
+/// let block = FirmwareMaster {
+///     block_type: FirmwareMaster::FIRM_LAST,
+///     firmware_length: 2u32.to_be_bytes(),
+///     data: vec![1, 2, 3, 4].into(),
+///     ..FirmwareMaster::default()
+/// };
+/// match receiver.handle(&block.into()) {
+///     Some(ArtCommand::FirmwareReply(reply)) => {
+///         assert_eq!(reply.reply_type, FirmwareReply::FIRM_ALL_GOOD)
+///     }
+///     _ => unreachable!(),
+/// }
+/// ```
+pub struct FirmwareReceiver<F> {
+    max_length: usize,
+    upload: Option<Upload>,
+    on_complete: F,
+}
+
+impl<F> core::fmt::Debug for FirmwareReceiver<F> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.debug_struct("FirmwareReceiver")
+            .field("max_length", &self.max_length)
+            .field("upload", &self.upload)
+            .finish()
+    }
+}
+
+impl<F: FnMut(FirmwareKind, &[u8]) -> bool> FirmwareReceiver<F> {
+    /// Create a receiver that accepts images of at most `max_length` bytes, and calls `on_complete` with every complete image
+    pub fn new(max_length: usize, on_complete: F) -> FirmwareReceiver<F> {
+        FirmwareReceiver {
+            max_length,
+            upload: None,
+            on_complete,
+        }
+    }
+
+    /// If an upload is in progress
+    pub fn is_receiving(&self) -> bool {
+        self.upload.is_some()
+    }
+
+    /// The number of bytes that were received of the current upload, and the length of the image
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        self.upload
+            .as_ref()
+            .map(|upload| (upload.image.len(), upload.length))
+    }
+
+    /// Stop the current upload, e.g. because the controller did not send a block for 30 seconds
+    pub fn reset(&mut self) {
+        self.upload = None;
+    }
+
+    /// Handle a received command. Returns the ArtFirmwareReply that should be sent to the controller, or `None` if the command is not an ArtFirmwareMaster.
+    pub fn handle(&mut self, command: &ArtCommand) -> Option<ArtCommand> {
+        match command {
+            ArtCommand::FirmwareMaster(block) => {
+                let reply_type = self.receive(block);
+                Some(
+                    FirmwareReply {
+                        reply_type,
+                        ..FirmwareReply::default()
+                    }
+                    .into(),
+                )
+            }
+            _ => None,
+        }
+    }
+
+    fn receive(&mut self, block: &FirmwareMaster) -> u8 {
+        let kind = match block.kind() {
+            Some(kind) => kind,
+            None => return self.fail(),
+        };
+        // An image that fits in a single block is only sent as the last block
+        if block.is_first() || (block.is_last() && block.block_id == 0) {
+            let length = block.firmware_length() as usize * 2;
+            if block.block_id != 0 || length > self.max_length {
+                return self.fail();
+            }
+            self.upload = Some(Upload {
+                kind,
+                length,
+                block_id: 0,
+                image: Vec::with_capacity(length),
+            });
+        } else {
+            let upload = match &self.upload {
+                Some(upload) if upload.kind == kind => upload,
+                _ => return self.fail(),
+            };
+            if block.block_id == upload.block_id {
+                // The controller did not receive the acknowledgement, and sent this block again
+                return FirmwareReply::FIRM_BLOCK_GOOD;
+            }
+            if block.block_id != upload.block_id.wrapping_add(1) {
+                return self.fail();
+            }
+        }
+
+        let upload = match &mut self.upload {
+            Some(upload) => upload,
+            None => return self.fail(),
+        };
+        upload.block_id = block.block_id;
+        // The last block is padded to 1 kB
+        let remaining = upload.length - upload.image.len();
+        upload
+            .image
+            .extend_from_slice(&block.data[..block.data.len().min(remaining)]);
+
+        if !block.is_last() {
+            return FirmwareReply::FIRM_BLOCK_GOOD;
+        }
+        let upload = self.upload.take();
+        match upload {
+            Some(upload)
+                if upload.image.len() == upload.length
+                    && (self.on_complete)(upload.kind, &upload.image) =>
+            {
+                FirmwareReply::FIRM_ALL_GOOD
+            }
+            _ => FirmwareReply::FIRM_FAIL,
+        }
+    }
+
+    fn fail(&mut self) -> u8 {
+        self.upload = None;
+        FirmwareReply::FIRM_FAIL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArtSync;
+    use alloc::vec;
+
+    fn block(block_type: u8, block_id: u8, data: Vec<u8>) -> ArtCommand {
+        FirmwareMaster {
+            block_type,
+            block_id,
+            // 2 kB and 2 bytes
+            firmware_length: 1025u32.to_be_bytes(),
+            data: data.into(),
+            ..FirmwareMaster::default()
+        }
+        .into()
+    }
+
+    fn reply_type(reply: Option<ArtCommand>) -> u8 {
+        match reply {
+            Some(ArtCommand::FirmwareReply(reply)) => reply.reply_type,
+            reply => panic!("Unexpected reply {:?}", reply),
+        }
+    }
+
+    #[test]
+    fn reassemble() {
+        let mut images = Vec::new();
+        let mut receiver = FirmwareReceiver::new(4096, |kind, image: &[u8]| {
+            images.push((kind, image.to_vec()));
+            true
+        });
+
+        let first = block(FirmwareMaster::UBEA_FIRST, 0, vec![1; 1024]);
+        assert_eq!(
+            reply_type(receiver.handle(&first)),
+            FirmwareReply::FIRM_BLOCK_GOOD
+        );
+        let cont = block(FirmwareMaster::UBEA_CONT, 1, vec![2; 1024]);
+        assert_eq!(
+            reply_type(receiver.handle(&cont)),
+            FirmwareReply::FIRM_BLOCK_GOOD
+        );
+        // Sent again, because the acknowledgement was lost
+        assert_eq!(
+            reply_type(receiver.handle(&cont)),
+            FirmwareReply::FIRM_BLOCK_GOOD
+        );
+        assert_eq!(receiver.progress(), Some((2048, 2050)));
+
+        let last = block(FirmwareMaster::UBEA_LAST, 2, vec![3; 1024]);
+        assert_eq!(
+            reply_type(receiver.handle(&last)),
+            FirmwareReply::FIRM_ALL_GOOD
+        );
+        assert!(!receiver.is_receiving());
+        drop(receiver);
+
+        let (kind, image) = &images[0];
+        assert_eq!(*kind, FirmwareKind::Ubea);
+        assert_eq!(image.len(), 2050);
+        assert_eq!(&image[2046..], &[2, 2, 3, 3]);
+    }
+
+    #[test]
+    fn invalid() {
+        let mut receiver = FirmwareReceiver::new(2048, |_, _: &[u8]| true);
+
+        // Too large
+        let first = block(FirmwareMaster::FIRM_FIRST, 0, vec![1; 1024]);
+        assert_eq!(
+            reply_type(receiver.handle(&first)),
+            FirmwareReply::FIRM_FAIL
+        );
+
+        // Without a first block
+        let cont = block(FirmwareMaster::FIRM_CONT, 1, vec![1; 1024]);
+        assert_eq!(reply_type(receiver.handle(&cont)), FirmwareReply::FIRM_FAIL);
+
+        // Missing a block
+        let mut receiver = FirmwareReceiver::new(4096, |_, _: &[u8]| true);
+        receiver.handle(&first);
+        let last = block(FirmwareMaster::FIRM_LAST, 2, vec![1; 1024]);
+        assert_eq!(reply_type(receiver.handle(&last)), FirmwareReply::FIRM_FAIL);
+
+        // Rejected by the application
+        let mut receiver = FirmwareReceiver::new(4096, |_, _: &[u8]| false);
+        receiver.handle(&first);
+        receiver.handle(&cont);
+        let last = block(FirmwareMaster::FIRM_LAST, 2, vec![1; 1024]);
+        assert_eq!(reply_type(receiver.handle(&last)), FirmwareReply::FIRM_FAIL);
+        assert!(receiver.handle(&ArtSync::default().into()).is_none());
+    }
+}
@@ -0,0 +1,342 @@
+use crate::{ArtCommand, FirmwareBlockType, FirmwareKind, FirmwareMaster, FirmwareReplyStatus};
+use std::time::{Duration, Instant};
+
+/// A step of a [FirmwareUploader](struct.FirmwareUploader.html)
+#[derive(Debug)]
+pub enum FirmwareUploadEvent {
+    /// Send this ArtFirmwareMaster to the node. This is the next block, or a block that is sent again.
+    Send(ArtCommand),
+
+    /// The node acknowledged a block
+    Progress {
+        /// The number of blocks that were acknowledged
+        acknowledged: usize,
+
+        /// The total number of blocks
+        total: usize,
+    },
+
+    /// The node received and accepted the whole image
+    Complete,
+
+    /// The upload failed too often, the node stopped replying, or it reported that it received the whole image before the last block was sent
+    Failed,
+}
+
+/// The state of a [FirmwareUploader](struct.FirmwareUploader.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// `start` was not called yet
+    Idle,
+    /// Waiting for the node to acknowledge a block
+    Waiting {
+        block: usize,
+        sent: Instant,
+    },
+    /// The last block was acknowledged, waiting for the node to accept the whole image
+    Verifying {
+        sent: Instant,
+    },
+    Complete,
+    Failed,
+}
+
+/// Uploads a firmware or UBEA image to a single node, by splitting it in ArtFirmwareMaster blocks of 1 kB.
+///
+/// Every block must be acknowledged with an ArtFirmwareReply before the next one is sent. A block that is not acknowledged within the timeout is sent again. When the node reports that the upload failed, the upload restarts from the first block. After too many retries the upload fails.
+///
+/// The upload is only complete when the node replies with `FirmAllGood` to the last block. If it acknowledges the last block with `FirmBlockGood`, the uploader keeps waiting for the `FirmAllGood`, and sends the last block again if it does not arrive in time.
+///
+/// Send the messages of `FirmwareUploadEvent::Send` to the node, pass the ArtFirmwareReply messages of that node to `handle`, and call `poll` regularly. ArtFirmwareReply does not identify the upload, so the replies of other nodes must be filtered out by their address.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::Instant;
+///
+/// let mut uploader = FirmwareUploader::new(&[0xAB; 2048]);
+/// let now = Instant::now();
+/// assert!(matches!(uploader.start(now), FirmwareUploadEvent::Send(_)));
+///
+/// let events = uploader.handle(&FirmwareReply::default().into(), now);
+/// assert!(matches!(events[..], [FirmwareUploadEvent::Progress { acknowledged: 1, total: 2 }, FirmwareUploadEvent::Send(_)]));
+/// ```
+#[derive(Debug)]
+pub struct FirmwareUploader {
+    image: Vec<u8>,
+    ubea: bool,
+    state: State,
+    timeout: Duration,
+    retries: usize,
+    max_retries: usize,
+}
+
+impl FirmwareUploader {
+    /// How long to wait for an acknowledgement before a block is sent again
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// How often a block is sent again, or the upload is restarted, before it fails
+    pub const DEFAULT_MAX_RETRIES: usize = 3;
+
+    /// Upload a firmware image. An image with an odd length is padded with a zero, because its length is sent in 16 bit words.
+    pub fn new(image: &[u8]) -> FirmwareUploader {
+        let mut image = image.to_vec();
+        if !image.len().is_multiple_of(2) {
+            image.push(0);
+        }
+        FirmwareUploader {
+            image,
+            ubea: false,
+            state: State::Idle,
+            timeout: FirmwareUploader::DEFAULT_TIMEOUT,
+            retries: 0,
+            max_retries: FirmwareUploader::DEFAULT_MAX_RETRIES,
+        }
+    }
+
+    /// Upload a User Bios Extension Area (UBEA) image instead of a firmware image
+    pub fn ubea(image: &[u8]) -> FirmwareUploader {
+        FirmwareUploader {
+            ubea: true,
+            ..FirmwareUploader::new(image)
+        }
+    }
+
+    /// Change how long to wait for an acknowledgement before a block is sent again
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Change how often a block is sent again, or the upload is restarted, before it fails
+    pub fn set_max_retries(&mut self, max_retries: usize) {
+        self.max_retries = max_retries;
+    }
+
+    /// The total number of blocks
+    pub fn blocks(&self) -> usize {
+        self.image.len().div_ceil(FirmwareMaster::BLOCK_SIZE).max(1)
+    }
+
+    /// The number of blocks that were acknowledged
+    pub fn acknowledged(&self) -> usize {
+        match self.state {
+            State::Idle | State::Failed => 0,
+            State::Waiting { block, .. } => block,
+            State::Verifying { .. } | State::Complete => self.blocks(),
+        }
+    }
+
+    /// If the node accepted the whole image
+    pub fn is_complete(&self) -> bool {
+        self.state == State::Complete
+    }
+
+    /// Start, or restart, the upload with the first block
+    pub fn start(&mut self, now: Instant) -> FirmwareUploadEvent {
+        self.retries = 0;
+        self.send(0, now)
+    }
+
+    /// Handle an ArtFirmwareReply of the node. Other commands are ignored.
+    pub fn handle(&mut self, command: &ArtCommand, now: Instant) -> Vec<FirmwareUploadEvent> {
+        let reply = match command {
+            ArtCommand::FirmwareReply(reply) => reply,
+            _ => return Vec::new(),
+        };
+        let block = match self.state {
+            State::Waiting { block, .. } => block,
+            State::Verifying { .. } => match reply.status() {
+                FirmwareReplyStatus::FirmAllGood => {
+                    self.state = State::Complete;
+                    return vec![FirmwareUploadEvent::Complete];
+                }
+                FirmwareReplyStatus::FirmFail => return vec![self.retry(0, now)],
+                // The last block was acknowledged already
+                _ => return Vec::new(),
+            },
+            _ => return Vec::new(),
+        };
+        let acknowledged = block + 1;
+        let progress = FirmwareUploadEvent::Progress {
+            acknowledged,
+            total: self.blocks(),
+        };
+        match reply.status() {
+            FirmwareReplyStatus::FirmBlockGood => {
+                self.retries = 0;
+                if acknowledged < self.blocks() {
+                    vec![progress, self.send(acknowledged, now)]
+                } else {
+                    self.state = State::Verifying { sent: now };
+                    vec![progress]
+                }
+            }
+            FirmwareReplyStatus::FirmAllGood if acknowledged == self.blocks() => {
+                self.state = State::Complete;
+                vec![progress, FirmwareUploadEvent::Complete]
+            }
+            FirmwareReplyStatus::FirmAllGood => {
+                // The node can not have received the whole image yet
+                self.state = State::Failed;
+                vec![FirmwareUploadEvent::Failed]
+            }
+            FirmwareReplyStatus::FirmFail => vec![self.retry(0, now)],
+            FirmwareReplyStatus::Other(_) => Vec::new(),
+        }
+    }
+
+    /// Send the current block again if it was not acknowledged in time, or fail the upload if it was sent too often
+    pub fn poll(&mut self, now: Instant) -> Option<FirmwareUploadEvent> {
+        match self.state {
+            State::Waiting { block, sent } if now >= sent + self.timeout => {
+                Some(self.retry(block, now))
+            }
+            State::Verifying { sent } if now >= sent + self.timeout => {
+                Some(self.retry(self.blocks() - 1, now))
+            }
+            _ => None,
+        }
+    }
+
+    fn retry(&mut self, block: usize, now: Instant) -> FirmwareUploadEvent {
+        if self.retries >= self.max_retries {
+            self.state = State::Failed;
+            return FirmwareUploadEvent::Failed;
+        }
+        self.retries += 1;
+        self.send(block, now)
+    }
+
+    fn send(&mut self, block: usize, now: Instant) -> FirmwareUploadEvent {
+        self.state = State::Waiting { block, sent: now };
+
+        let kind = if self.ubea {
+            FirmwareKind::Ubea
+        } else {
+            FirmwareKind::Firmware
+        };
+        let block_type = FirmwareBlockType::new(kind, block == 0, block + 1 == self.blocks());
+        let start = block * FirmwareMaster::BLOCK_SIZE;
+        let end = (start + FirmwareMaster::BLOCK_SIZE).min(self.image.len());
+        let mut data = self.image[start..end].to_vec();
+        // Every block contains 1 kB, the length of the image is sent separately
+        data.resize(FirmwareMaster::BLOCK_SIZE, 0);
+
+        FirmwareUploadEvent::Send(
+            FirmwareMaster::new(block_type, block as u8, (self.image.len() / 2) as u32, data)
+                .into(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FirmwareReply;
+
+    fn reply(status: FirmwareReplyStatus) -> ArtCommand {
+        FirmwareReply::new(status).into()
+    }
+
+    fn sent(event: &FirmwareUploadEvent) -> &FirmwareMaster {
+        match event {
+            FirmwareUploadEvent::Send(ArtCommand::FirmwareMaster(master)) => master,
+            event => panic!("Unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn blocks() {
+        let mut uploader = FirmwareUploader::new(&[1; 2049]);
+        let now = Instant::now();
+        assert_eq!(uploader.blocks(), 3);
+
+        let first = uploader.start(now);
+        assert_eq!(sent(&first).block_type, FirmwareMaster::FIRM_FIRST);
+        assert_eq!(sent(&first).firmware_length(), 1025);
+
+        let events = uploader.handle(&reply(FirmwareReplyStatus::FirmBlockGood), now);
+        assert_eq!(sent(&events[1]).block_type, FirmwareMaster::FIRM_CONT);
+        let events = uploader.handle(&reply(FirmwareReplyStatus::FirmBlockGood), now);
+        let last = sent(&events[1]);
+        assert_eq!(last.block_type, FirmwareMaster::FIRM_LAST);
+        assert_eq!(last.block_id, 2);
+        assert_eq!(&last.data[..3], &[1, 0, 0]);
+        assert_eq!(last.data.len(), FirmwareMaster::BLOCK_SIZE);
+
+        let events = uploader.handle(&reply(FirmwareReplyStatus::FirmAllGood), now);
+        assert!(matches!(
+            events[..],
+            [
+                FirmwareUploadEvent::Progress {
+                    acknowledged: 3,
+                    total: 3
+                },
+                FirmwareUploadEvent::Complete
+            ]
+        ));
+        assert!(uploader.is_complete());
+    }
+
+    #[test]
+    fn complete_after_last_block() {
+        let mut uploader = FirmwareUploader::new(&[1; 2048]);
+        let start = Instant::now();
+        uploader.start(start);
+
+        // Accepting the whole image before the last block is an error
+        let mut early = FirmwareUploader::new(&[1; 2048]);
+        early.start(start);
+        let events = early.handle(&reply(FirmwareReplyStatus::FirmAllGood), start);
+        assert!(matches!(events[..], [FirmwareUploadEvent::Failed]));
+
+        uploader.handle(&reply(FirmwareReplyStatus::FirmBlockGood), start);
+        let events = uploader.handle(&reply(FirmwareReplyStatus::FirmBlockGood), start);
+        assert!(matches!(
+            events[..],
+            [FirmwareUploadEvent::Progress {
+                acknowledged: 2,
+                total: 2
+            }]
+        ));
+        assert!(!uploader.is_complete());
+        assert!(uploader
+            .handle(&reply(FirmwareReplyStatus::FirmBlockGood), start)
+            .is_empty());
+
+        // The last block is sent again if the node does not accept the image in time
+        let resent = uploader.poll(start + Duration::from_secs(5)).unwrap();
+        assert_eq!(sent(&resent).block_id, 1);
+        let events = uploader.handle(&reply(FirmwareReplyStatus::FirmAllGood), start);
+        assert!(matches!(events[..], [_, FirmwareUploadEvent::Complete]));
+        assert!(uploader.is_complete());
+    }
+
+    #[test]
+    fn retries() {
+        let mut uploader = FirmwareUploader::ubea(&[1; 4096]);
+        uploader.set_max_retries(2);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert_eq!(
+            sent(&uploader.start(start)).block_type,
+            FirmwareMaster::UBEA_FIRST
+        );
+        uploader.handle(&reply(FirmwareReplyStatus::FirmBlockGood), start);
+        assert!(uploader.poll(at(4)).is_none());
+
+        // Not acknowledged in time, the block is sent again
+        assert_eq!(sent(&uploader.poll(at(5)).unwrap()).block_id, 1);
+        assert_eq!(uploader.acknowledged(), 1);
+
+        // The node failed, so the upload restarts
+        let events = uploader.handle(&reply(FirmwareReplyStatus::FirmFail), at(6));
+        assert_eq!(sent(&events[0]).block_id, 0);
+
+        assert!(matches!(
+            uploader.poll(at(11)),
+            Some(FirmwareUploadEvent::Failed)
+        ));
+        assert_eq!(uploader.acknowledged(), 0);
+    }
+}
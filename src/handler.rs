@@ -0,0 +1,207 @@
+#[cfg(feature = "std")]
+use crate::RecvBuffer;
+use crate::{ArtCommand, ArtSync, Output, Poll, PollReply};
+#[cfg(feature = "media")]
+use crate::{Timecode, Trigger};
+use core::net::SocketAddr;
+
+/// Callbacks for the received commands, as called by a [Dispatcher](struct.Dispatcher.html).
+///
+/// Every method does nothing by default, so an application only implements the commands that it cares about. Commands without a method of their own are passed to `on_unknown`.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::net::SocketAddr;
+///
+/// #[derive(Default)]
+/// struct Frames(usize);
+///
+/// impl Handler for Frames {
+///     fn on_dmx(&mut self, _output: &Output, _source: SocketAddr) {
+///         self.0 += 1;
+///     }
+/// }
+///
+/// let mut dispatcher = Dispatcher::new(Frames::default());
+/// let source = "10.0.0.1:6454".parse().unwrap();
+/// let bytes = Output::new(1.into(), vec![255; 4]).into_command().write_to_buffer().unwrap();
+/// dispatcher.dispatch_datagram(&bytes, source).unwrap();
+/// dispatcher.dispatch(&Poll::default().into(), source);
+/// assert_eq!(dispatcher.handler().0, 1);
+/// ```
+pub trait Handler {
+    /// An ArtPoll was received
+    fn on_poll(&mut self, _poll: &Poll, _source: SocketAddr) {}
+
+    /// An ArtPollReply was received
+    fn on_poll_reply(&mut self, _reply: &PollReply, _source: SocketAddr) {}
+
+    /// An ArtDmx was received
+    fn on_dmx(&mut self, _output: &Output, _source: SocketAddr) {}
+
+    /// An ArtSync was received
+    fn on_sync(&mut self, _sync: &ArtSync, _source: SocketAddr) {}
+
+    /// An ArtTimeCode was received
+    #[cfg(feature = "media")]
+    fn on_timecode(&mut self, _timecode: &Timecode, _source: SocketAddr) {}
+
+    /// An ArtTrigger was received
+    #[cfg(feature = "media")]
+    fn on_trigger(&mut self, _trigger: &Trigger, _source: SocketAddr) {}
+
+    /// A command was received that has no method of its own
+    fn on_unknown(&mut self, _command: &ArtCommand, _source: SocketAddr) {}
+}
+
+/// Parses received datagrams and passes the commands to the methods of a [Handler](trait.Handler.html).
+///
+/// Datagrams can be passed in with `dispatch_datagram`, e.g. from an async runtime. With the `std` feature, `handle_readable` reads them from a non-blocking socket, and `run` from a blocking socket.
+#[derive(Debug, Default)]
+pub struct Dispatcher<H> {
+    handler: H,
+    #[cfg(feature = "std")]
+    buffer: RecvBuffer,
+}
+
+impl<H: Handler> Dispatcher<H> {
+    /// Create a dispatcher that calls the given handler
+    pub fn new(handler: H) -> Dispatcher<H> {
+        Dispatcher {
+            handler,
+            #[cfg(feature = "std")]
+            buffer: RecvBuffer::new(),
+        }
+    }
+
+    /// The handler of this dispatcher
+    pub fn handler(&self) -> &H {
+        &self.handler
+    }
+
+    /// The handler of this dispatcher, e.g. to change its configuration
+    pub fn handler_mut(&mut self) -> &mut H {
+        &mut self.handler
+    }
+
+    /// Stop dispatching, and return the handler
+    pub fn into_handler(self) -> H {
+        self.handler
+    }
+
+    /// Pass a command that was received from `source` to the handler
+    pub fn dispatch(&mut self, command: &ArtCommand, source: SocketAddr) {
+        dispatch(&mut self.handler, command, source);
+    }
+
+    /// Parse a datagram that was received from `source`, and pass it to the handler. Datagrams that are not a valid Art-Net message return the error, without calling the handler.
+    pub fn dispatch_datagram(&mut self, data: &[u8], source: SocketAddr) -> crate::Result<()> {
+        let command = ArtCommand::from_buffer(data)?;
+        self.dispatch(&command, source);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<H: Handler> Dispatcher<H> {
+    /// Handle every datagram that is waiting on the non-blocking socket. Call this when an event loop, e.g. mio, reports the socket as readable. Datagrams that are not a valid Art-Net message are ignored.
+    pub fn handle_readable(&mut self, socket: &impl crate::Transport) -> std::io::Result<()> {
+        let handler = &mut self.handler;
+        crate::transport::drain(socket, &mut self.buffer, |command, source| {
+            dispatch(handler, &command, source);
+            Ok(())
+        })
+    }
+
+    /// Receive datagrams from the blocking socket and pass them to the handler, until receiving fails. Datagrams that are not a valid Art-Net message are ignored.
+    ///
+    /// A read timeout on the socket makes this return an error of kind `WouldBlock` or `TimedOut` when nothing was received in time, after which it can be called again.
+    pub fn run(&mut self, socket: &std::net::UdpSocket) -> std::io::Result<()> {
+        loop {
+            let (length, source) = socket.recv_from(&mut self.buffer)?;
+            if let Ok(command) = self.buffer.parse(length) {
+                self.dispatch(&command, source);
+            }
+        }
+    }
+}
+
+fn dispatch(handler: &mut impl Handler, command: &ArtCommand, source: SocketAddr) {
+    match command {
+        ArtCommand::Poll(poll) => handler.on_poll(poll, source),
+        ArtCommand::PollReply(reply) => handler.on_poll_reply(reply, source),
+        ArtCommand::Output(output) => handler.on_dmx(output, source),
+        ArtCommand::Sync(sync) => handler.on_sync(sync, source),
+        #[cfg(feature = "media")]
+        ArtCommand::OpTimeCode(timecode) => handler.on_timecode(timecode, source),
+        #[cfg(feature = "media")]
+        ArtCommand::OpTrigger(trigger) => handler.on_trigger(trigger, source),
+        // Every command has a method of its own when the optional opcode families are disabled
+        #[allow(unreachable_patterns)]
+        command => handler.on_unknown(command, source),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[derive(Default)]
+    struct Recorder(Vec<&'static str>);
+
+    impl Handler for Recorder {
+        fn on_poll(&mut self, _: &Poll, _: SocketAddr) {
+            self.0.push("poll");
+        }
+
+        fn on_sync(&mut self, _: &ArtSync, _: SocketAddr) {
+            self.0.push("sync");
+        }
+
+        fn on_unknown(&mut self, command: &ArtCommand, _: SocketAddr) {
+            self.0.push(command.name());
+        }
+    }
+
+    #[test]
+    fn dispatch() {
+        let mut dispatcher = Dispatcher::new(Recorder::default());
+        let source = "10.0.0.1:6454".parse().unwrap();
+        dispatcher.dispatch(&Poll::default().into(), source);
+        dispatcher.dispatch(&ArtSync::default().into(), source);
+        // Commands with a default method are not passed to on_unknown
+        dispatcher.dispatch(&Output::default().into(), source);
+        dispatcher.dispatch(&PollReply::default().into(), source);
+        assert!(dispatcher.dispatch_datagram(b"Art-Net", source).is_err());
+        assert_eq!(dispatcher.into_handler().0, vec!["poll", "sync"]);
+    }
+
+    #[cfg(all(feature = "std", feature = "dmx"))]
+    #[test]
+    fn handle_readable() {
+        use std::net::UdpSocket;
+
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.set_nonblocking(true).unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let bytes = ArtCommand::from(crate::TextCommand::default())
+            .write_to_buffer()
+            .unwrap();
+        sender
+            .send_to(&bytes, socket.local_addr().unwrap())
+            .unwrap();
+        sender
+            .send_to(b"garbage", socket.local_addr().unwrap())
+            .unwrap();
+
+        let mut dispatcher = Dispatcher::new(Recorder::default());
+        let start = std::time::Instant::now();
+        while dispatcher.handler().0.is_empty()
+            && start.elapsed() < std::time::Duration::from_secs(5)
+        {
+            dispatcher.handle_readable(&socket).unwrap();
+        }
+        assert_eq!(dispatcher.handler().0, vec!["ArtCommand"]);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{Output, PortAddress};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// The last frame that was sent to a universe
+#[derive(Debug)]
+struct Frame {
+    data: Vec<u8>,
+    sequence: u8,
+    last_sent: Instant,
+}
+
+/// Keeps the last frame of every universe, and re-sends it when no new data was sent for a while.
+///
+/// Nodes hold or drop their output when they stop receiving ArtDmx, so the Art-Net specification asks senders to re-transmit the last frame every 800 to 1000 milliseconds. The scheduler also numbers the frames of each universe.
+///
+/// Call `send` for every new frame and `tick` regularly, and send the returned `Output`s to the nodes. Alternatively, `spawn` calls `tick` from a background thread.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::{Duration, Instant};
+///
+/// let mut scheduler = KeepaliveScheduler::new();
+/// let start = Instant::now();
+/// let output = scheduler.send(1.into(), &[255; 512], start);
+/// assert_eq!(output.sequence, 1);
+///
+/// assert!(scheduler.tick(start + Duration::from_millis(500)).is_empty());
+/// let outputs = scheduler.tick(start + Duration::from_millis(1000));
+/// assert_eq!(outputs[0].sequence, 2);
+/// ```
+#[derive(Debug)]
+pub struct KeepaliveScheduler {
+    frames: HashMap<PortAddress, Frame>,
+    interval: Duration,
+}
+
+impl Default for KeepaliveScheduler {
+    fn default() -> KeepaliveScheduler {
+        KeepaliveScheduler::new()
+    }
+}
+
+impl KeepaliveScheduler {
+    /// How often unchanged data is re-transmitted. The Art-Net specification recommends every 800 to 1000 milliseconds.
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(1000);
+
+    /// Create a scheduler with the default interval
+    pub fn new() -> KeepaliveScheduler {
+        KeepaliveScheduler::with_interval(KeepaliveScheduler::DEFAULT_INTERVAL)
+    }
+
+    /// Create a scheduler with the given interval
+    pub fn with_interval(interval: Duration) -> KeepaliveScheduler {
+        KeepaliveScheduler {
+            frames: HashMap::new(),
+            interval,
+        }
+    }
+
+    /// Change the interval at which unchanged data is re-transmitted
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Store a new frame for the given universe, and return the `Output` that should be sent now
+    pub fn send(&mut self, port_address: PortAddress, data: &[u8], now: Instant) -> Output {
+        let frame = self.frames.entry(port_address).or_insert(Frame {
+            data: Vec::new(),
+            sequence: 0,
+            last_sent: now,
+        });
+        frame.data.clear();
+        frame.data.extend_from_slice(data);
+        next_output(port_address, frame, now)
+    }
+
+    /// The `Output`s of the universes that should be re-transmitted, because no new data was sent within the interval
+    pub fn tick(&mut self, now: Instant) -> Vec<Output> {
+        let interval = self.interval;
+        self.frames
+            .iter_mut()
+            .filter(|(_, frame)| now >= frame.last_sent + interval)
+            .map(|(port_address, frame)| next_output(*port_address, frame, now))
+            .collect()
+    }
+
+    /// When `tick` should be called next, or `None` if there are no universes
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.frames
+            .values()
+            .map(|frame| frame.last_sent + self.interval)
+            .min()
+    }
+
+    /// Stop re-transmitting the given universe
+    pub fn remove(&mut self, port_address: PortAddress) {
+        self.frames.remove(&port_address);
+    }
+
+    /// Call `tick` from a background thread, and pass every `Output` that should be re-transmitted to `send`.
+    ///
+    /// The thread stops when every other `Arc` of the scheduler is dropped.
+    pub fn spawn<F>(scheduler: &Arc<Mutex<KeepaliveScheduler>>, mut send: F) -> JoinHandle<()>
+    where
+        F: FnMut(Output) + Send + 'static,
+    {
+        let scheduler = Arc::downgrade(scheduler);
+        thread::spawn(move || {
+            while let Some(scheduler) = scheduler.upgrade() {
+                let now = Instant::now();
+                let (outputs, deadline) = {
+                    let mut scheduler = match scheduler.lock() {
+                        Ok(scheduler) => scheduler,
+                        Err(_) => return,
+                    };
+                    let outputs = scheduler.tick(now);
+                    let deadline = scheduler.next_deadline();
+                    (outputs, deadline.unwrap_or(now + scheduler.interval))
+                };
+                // Don't keep the scheduler alive while sleeping
+                drop(scheduler);
+
+                for output in outputs {
+                    send(output);
+                }
+                thread::sleep(deadline.saturating_duration_since(Instant::now()));
+            }
+        })
+    }
+}
+
+fn next_output(port_address: PortAddress, frame: &mut Frame, now: Instant) -> Output {
+    // The sequence is increased from 1 to 255, 0 disables sequencing
+    frame.sequence = frame.sequence.checked_add(1).unwrap_or(1);
+    frame.last_sent = now;
+    Output {
+        sequence: frame.sequence,
+        ..Output::new(port_address, frame.data.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn sequence() {
+        let mut scheduler = KeepaliveScheduler::new();
+        let now = Instant::now();
+        for sequence in 1..=255 {
+            assert_eq!(scheduler.send(1.into(), &[0, 0], now).sequence, sequence);
+        }
+        // 0 disables sequencing, so it is skipped
+        assert_eq!(scheduler.send(1.into(), &[0, 0], now).sequence, 1);
+        assert_eq!(scheduler.send(2.into(), &[0, 0], now).sequence, 1);
+    }
+
+    #[test]
+    fn new_data_delays_keepalive() {
+        let mut scheduler = KeepaliveScheduler::new();
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+
+        scheduler.send(1.into(), &[1, 2], start);
+        scheduler.send(2.into(), &[3, 4], start);
+        scheduler.send(1.into(), &[5, 6], at(600));
+        assert_eq!(scheduler.next_deadline(), Some(at(1000)));
+
+        let outputs = scheduler.tick(at(1000));
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(u16::from(outputs[0].port_address), 2);
+
+        let outputs = scheduler.tick(at(1600));
+        assert_eq!(outputs[0].data.as_ref(), &vec![5, 6]);
+
+        scheduler.remove(1.into());
+        scheduler.remove(2.into());
+        assert_eq!(scheduler.next_deadline(), None);
+    }
+
+    #[test]
+    fn spawn() {
+        let scheduler = Arc::new(Mutex::new(KeepaliveScheduler::with_interval(
+            Duration::from_millis(10),
+        )));
+        let (sender, receiver) = mpsc::channel();
+        let thread = KeepaliveScheduler::spawn(&scheduler, move |output| {
+            let _ = sender.send(output.sequence);
+        });
+
+        scheduler
+            .lock()
+            .unwrap()
+            .send(1.into(), &[1, 2], Instant::now());
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(5)), Ok(2));
+
+        drop(scheduler);
+        thread.join().unwrap();
+    }
+}
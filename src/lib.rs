@@ -8,32 +8,75 @@
 //! let broadcast_addr = ("255.255.255.255", 6454).to_socket_addrs().unwrap().next().unwrap();
 //! socket.set_broadcast(true).unwrap();
 //! let buff = ArtCommand::Poll(Poll::default()).write_to_buffer().unwrap();
-//! socket.send_to(&buff, &broadcast_addr).unwrap();
+//! socket.send_to(&buff, broadcast_addr).unwrap();
 //!
 //! loop {
-//!     let mut buffer = [0u8; 1024];
+//!     let mut buffer = RecvBuffer::new();
 //!     let (length, addr) = socket.recv_from(&mut buffer).unwrap();
-//!     let command = ArtCommand::from_buffer(&buffer[..length]).unwrap();
+//!     let command = buffer.parse(length).unwrap();
 //!     
 //!     println!("Received {:?}", command);
 //!     match command {
-//!         ArtCommand::Poll(poll) => {
+//!         ArtCommand::Poll(_poll) => {
 //!             // This will most likely be our own poll request, as this is broadcast to all devices on the network
 //!         },
-//!         ArtCommand::PollReply(reply) => {
+//!         ArtCommand::PollReply(_reply) => {
 //!             // This is an ArtNet node on the network. We can send commands to it like this:
 //!             let command = ArtCommand::Output(Output {
 //!                 data: vec![1, 2, 3, 4, 5].into(), // The data we're sending to the node
 //!                 ..Output::default()
 //!             });
 //!             let bytes = command.write_to_buffer().unwrap();
-//!             socket.send_to(&bytes, &addr).unwrap();
+//!             socket.send_to(&bytes, addr).unwrap();
 //!         },
 //!         _ => {}
 //!     }
 //! }
 //! ```
+//!
+//! # Sans-io helpers
+//!
+//! The helpers for controllers and nodes, e.g. `Node`, `DiscoveryManager`, `Merger` and `FirmwareUploader`, are state machines that do not do any IO themselves. Pass them the commands that were received, and the current time where they keep track of timeouts, and send the commands that they return with any socket or runtime. `Controller` and the `handle_readable` functions drive them over a [Transport](trait.Transport.html) for applications that use a plain or mio UDP socket.
+//!
+//! # `no_std` support
+//!
+//! The `std` feature is enabled by default. Disable default features to use this crate in a `no_std` environment, e.g. on an ESP32 or STM32 running an Art-Net node. An allocator is still required.
+//!
+//! To keep flash usage down, the opcode families can be disabled individually. `Poll`, `PollReply`, `Output` and `Sync` are always available, the `dmx`, `rdm`, `media`, `firmware` and `video` features enable the rest. All of them are enabled by default.
+//!
+//! The `nonstandard-file-transfer` feature enables ArtFileTnMaster, ArtFileFnMaster and ArtFileFnReply, with the `FileUploader` and `FileDownloader`. The Art-Net specification only names these packets, so their layout is specific to this crate and does not interoperate with other implementations. It is not enabled by default.
+//!
+//! With the `heapless` feature, commands can be written into a fixed-capacity [heapless::Vec](https://docs.rs/heapless) and output data can be created from one.
+//!
+//! With the `smallvec` feature, short variable length data, e.g. the text of an ArtDiagData or ArtCommand, is stored inline in a [Payload](struct.Payload.html), so parsing a stream of diagnostics does not allocate for every message.
+//!
+//! With the `bytes` feature, the data of ArtDmx, ArtRdm and ArtFirmwareMaster is stored in a `bytes::Bytes`, `ArtCommand::from_bytes` parses these messages without copying the data, and `ArtCommand::write_to_bytes` returns a message that can be cloned cheaply and handed to e.g. tokio.
+//!
+//! With the `tokio`, `async-std` or `smol` feature, the `aio` module contains an async socket, controller and node for that runtime. With the `tokio` feature, the `codec` module contains a codec for `UdpFramed`.
+//!
+//! With the `tracing` feature, parse failures, unknown opcodes, truncated messages and serialization errors are reported as [tracing](https://docs.rs/tracing) events, so gateways can diagnose misbehaving devices from their logs.
+//!
+//! With the `mio` feature, `mio::net::UdpSocket` implements [Transport](trait.Transport.html), so the `handle_readable` functions can be driven from a mio event loop.
+//!
+//! With the `interfaces` feature, `Interface::list` and `Interface::find` look up the IPv4 interfaces of the host on Unix, to broadcast to the directed broadcast address of a local network instead of 255.255.255.255, which many managed switches drop.
+//!
+//! With the `chrono` feature, an ArtTimeSync converts to and from `chrono::NaiveDateTime`.
+//!
+//! With the `sacn` feature, the `sacn` module converts ArtDmx and ArtSync to and from sACN (E1.31) packets, for gateways between the two protocols.
+//!
+//! With the `serde` feature, a [NetworkSnapshot](struct.NetworkSnapshot.html) of the discovered nodes can be serialized, e.g. to JSON, for inventory and monitoring tools, and a [NodeConfig](struct.NodeConfig.html) can be loaded from a configuration file.
+//!
+//! With the `pcap` feature, the `pcap` module reads the Art-Net messages out of pcap and pcapng network captures, e.g. from Wireshark, to replay or analyse them.
+//!
+//! The `monitor` feature builds the `artnet-monitor` binary, which prints the Art-Net traffic on the network with the [pretty](pretty/index.html) reports, and a summary of the nodes and the frames per second of every universe. Install it with `cargo install artnet_protocol --features monitor`.
+//!
+//! The `scan` feature builds the `artnet-scan` binary, which polls the network and prints a table of the nodes that replied, with their IP and MAC address, name, style, universes and firmware version.
+//!
+//! The `test-utils` feature adds the `test_vectors` module, with hand-built messages and the commands that they decode to. Together with `std`, it adds the `mock` module with a node emulator for integration tests.
 #![deny(missing_docs)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 /// Re-export of the bitflags crate that this library uses
 #[macro_use]
@@ -43,13 +86,159 @@ pub extern crate byteorder;
 
 #[macro_use]
 mod macros;
+mod addressing;
+#[cfg(any(feature = "tokio", feature = "async-std", feature = "smol"))]
+pub mod aio;
+#[cfg(feature = "tokio")]
+pub mod codec;
 mod command;
-mod convert;
+#[cfg(feature = "std")]
+mod conflict;
+#[cfg(feature = "std")]
+mod controller;
+pub mod convert;
+#[cfg(all(feature = "std", feature = "dmx"))]
+mod diagnostics;
+#[cfg(feature = "std")]
+mod discovery;
+mod dmx_io;
+#[cfg(feature = "std")]
+mod dmx_receiver;
+mod encoder;
 mod enums;
 mod error;
+#[cfg(all(feature = "std", feature = "dmx"))]
+mod failover;
+#[cfg(all(feature = "std", feature = "nonstandard-file-transfer"))]
+mod file_transfer;
+#[cfg(feature = "firmware")]
+mod firmware_receiver;
+#[cfg(all(feature = "std", feature = "firmware"))]
+mod firmware_upload;
+mod handler;
+#[cfg(feature = "std")]
+mod keepalive;
+#[cfg(all(feature = "std", feature = "dmx"))]
+mod merger;
+#[cfg(all(feature = "test-utils", feature = "std"))]
+pub mod mock;
+mod node;
+#[cfg(feature = "dmx")]
+mod node_config;
+mod options;
+#[cfg(feature = "pcap")]
+pub mod pcap;
+mod pixel_mapper;
+#[cfg(feature = "std")]
+mod poll_scheduler;
 mod port_address;
+pub mod pretty;
+#[cfg(feature = "std")]
+mod rate_limiter;
+#[cfg(feature = "rdm")]
+mod rdm;
+#[cfg(feature = "rdm")]
+mod rdm_discovery;
+#[cfg(feature = "std")]
+mod recorder;
+mod recv_buffer;
+mod router;
+#[cfg(feature = "sacn")]
+pub mod sacn;
+#[cfg(feature = "std")]
+mod sequence_tracker;
+#[cfg(feature = "serde")]
+mod snapshot;
+#[cfg(feature = "std")]
+mod socket;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+#[cfg(feature = "std")]
+mod sync_receiver;
+#[cfg(feature = "test-utils")]
+pub mod test_vectors;
+#[cfg(all(feature = "std", feature = "media"))]
+mod timecode_sender;
+#[cfg(feature = "std")]
+mod transport;
+#[cfg(feature = "media")]
+mod trigger_dispatcher;
+mod uid;
 
+pub use crate::addressing::{directed_broadcast, ArtNetNetwork};
 pub use crate::command::*;
-pub use crate::enums::ArtTalkToMe;
+#[cfg(feature = "std")]
+pub use crate::conflict::{ConflictDetector, ConflictEvent};
+#[cfg(feature = "std")]
+pub use crate::controller::{Controller, DmxBroadcastPolicy};
+#[cfg(all(feature = "std", feature = "dmx"))]
+pub use crate::diagnostics::{DiagnosticMessage, DiagnosticsCollector};
+#[cfg(feature = "std")]
+pub use crate::discovery::{DiscoveredNode, DiscoveryEvent, DiscoveryManager, NodeId};
+pub use crate::dmx_io::{DmxSink, DmxSource, DMX_SLOTS};
+#[cfg(feature = "std")]
+pub use crate::dmx_receiver::{DataLossPolicy, DmxFrame, DmxReceiver};
+pub use crate::encoder::Encoder;
+#[cfg(feature = "dmx")]
+pub use crate::enums::InputDisable;
+pub use crate::enums::{ArtTalkToMe, IpProgStatus};
 pub use crate::error::*;
+#[cfg(all(feature = "std", feature = "dmx"))]
+pub use crate::failover::{FailoverEvent, FailoverMonitor};
+#[cfg(all(feature = "std", feature = "nonstandard-file-transfer"))]
+pub use crate::file_transfer::{FileDownloader, FileTransferEvent, FileUploader};
+#[cfg(feature = "firmware")]
+pub use crate::firmware_receiver::FirmwareReceiver;
+#[cfg(all(feature = "std", feature = "firmware"))]
+pub use crate::firmware_upload::{FirmwareUploadEvent, FirmwareUploader};
+pub use crate::handler::{Dispatcher, Handler};
+#[cfg(feature = "std")]
+pub use crate::keepalive::KeepaliveScheduler;
+#[cfg(all(feature = "std", feature = "dmx"))]
+pub use crate::merger::Merger;
+pub use crate::node::{Node, NodeEvent};
+#[cfg(all(feature = "dmx", feature = "firmware"))]
+pub use crate::node_config::IpConfig;
+#[cfg(feature = "dmx")]
+pub use crate::node_config::{MergeMode, NodeConfig, PortConfig};
+pub use crate::options::{ParseMode, ParseOptions};
+pub use crate::pixel_mapper::{ColorOrder, PixelMapper};
+#[cfg(feature = "std")]
+pub use poll_scheduler::PollScheduler;
 pub use port_address::PortAddress;
+#[cfg(feature = "std")]
+pub use rate_limiter::RateLimiter;
+#[cfg(feature = "rdm")]
+pub use rdm::{RdmFrame, RdmMessage};
+#[cfg(feature = "rdm")]
+pub use rdm_discovery::{RdmDiscovery, RdmDiscoveryEvent, TodAssembler};
+#[cfg(feature = "std")]
+pub use recorder::{RecordedCommand, Recorder, Replayer};
+pub use recv_buffer::RecvBuffer;
+pub use router::{Route, Router, Universe};
+#[cfg(feature = "std")]
+pub use sequence_tracker::{SequenceStats, SequenceTracker};
+#[cfg(feature = "serde")]
+pub use snapshot::{NetworkSnapshot, NodeSnapshot, PortSnapshot, UniverseSnapshot};
+#[cfg(feature = "std")]
+pub use socket::{ArtNetSocket, Interface};
+#[cfg(feature = "std")]
+pub use stats::{OpcodeStats, Stats, UniverseStats};
+#[cfg(feature = "std")]
+pub use sync_receiver::SyncReceiver;
+#[cfg(all(feature = "std", feature = "media"))]
+pub use timecode_sender::TimecodeSender;
+#[cfg(feature = "std")]
+pub use transport::{MemoryNetwork, MemoryTransport, Transport};
+#[cfg(feature = "media")]
+pub use trigger_dispatcher::{TriggerDispatcher, TriggerRoute};
+pub use uid::Uid;
+
+#[doc(hidden)]
+pub mod __private {
+    //! Used by the `data_structure` macro, so downstream crates don't need `extern crate alloc`
+    pub use alloc::boxed::Box;
+    pub use alloc::vec::Vec;
+}
@@ -1,4 +1,74 @@
+/// Define an Art-Net message as a struct, whose fields are serialized in order with the [Convertable](convert/trait.Convertable.html) trait.
+///
+/// This generates `to_bytes`, `write_to`, `serialized_len`, `field_layout` and `from` functions, the same way as the commands in this crate are defined. Every field type must implement `Convertable<YourStruct>`. It can be used to define manufacturer-specific messages:
+///
+/// ```rust
+/// use artnet_protocol::{data_structure, ARTNET_HEADER};
+///
+/// data_structure! {
+///     #[derive(Debug)]
+///     #[doc = "A manufacturer-specific message"]
+///     pub struct VendorCommand {
+///         #[doc = "The protocol version"]
+///         pub version: [u8; 2],
+///         #[doc = "The command to execute"]
+///         pub command: u8,
+///         #[doc = "The parameters, until the end of the message"]
+///         pub parameters: Vec<u8>,
+///     }
+/// }
+///
+/// let command = VendorCommand {
+///     version: [0, 14],
+///     command: 3,
+///     parameters: vec![1, 2, 3],
+/// };
+/// let mut message = ARTNET_HEADER.to_vec();
+/// message.extend_from_slice(&0x8000u16.to_le_bytes());
+/// command.write_to(&mut message).unwrap();
+///
+/// let parsed = VendorCommand::from(&message[10..]).unwrap();
+/// assert_eq!(parsed.parameters, vec![1, 2, 3]);
+/// ```
+///
+/// The `@test` form is used by this crate to also generate an encode/decode test, and is not usable outside of it.
+#[macro_export]
 macro_rules! data_structure {
+    (
+        @test
+        $(#[$outer:meta])*
+        pub struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                pub $field:ident : $ty:ty,
+            )*
+        }
+    ) => {
+        $crate::data_structure! {
+            $(#[$outer])*
+            pub struct $name {
+                $(
+                    $(#[$field_meta])*
+                    pub $field: $ty,
+                )*
+            }
+        }
+
+        #[test]
+        fn test_encode_decode() {
+            let start = $name {
+                $(
+                    $field: $crate::convert::Convertable::<$name>::get_test_value(),
+                )*
+            };
+            let bytes = start.to_bytes().expect("Could not serialize");
+            assert_eq!(bytes.len(), start.serialized_len());
+            let end = $name::from(&bytes).expect("Could not deserialize");
+            $(
+                assert!($crate::convert::Convertable::<$name>::is_equal(&start.$field, &end.$field));
+            )*
+        }
+    };
     (
         $(#[$outer:meta])*
         pub struct $name:ident {
@@ -18,47 +88,73 @@ macro_rules! data_structure {
 
         impl $name {
             /// Convert this struct to a byte array.
-            pub fn to_bytes(&self) -> crate::Result<Vec<u8>> {
-                use crate::convert::Convertable;
-                use crate::Error;
+            pub fn to_bytes(&self) -> $crate::Result<$crate::__private::Vec<u8>> {
+                let mut result = $crate::__private::Vec::new();
+                self.write_to(&mut result)?;
+                Ok(result)
+            }
 
-                let mut result = Vec::new();
+            /// The amount of bytes that `to_bytes` and `write_to` produce.
+            pub fn serialized_len(&self) -> usize {
+                use $crate::convert::Convertable;
+
+                0 $(+ Convertable::<$name>::serialized_len(&self.$field, self))*
+            }
+
+            /// The name and byte range of every field in the output of `to_bytes`, in order.
+            pub fn field_layout(&self) -> $crate::__private::Vec<(&'static str, ::core::ops::Range<usize>)> {
+                use $crate::convert::Convertable;
+
+                let mut layout = $crate::__private::Vec::new();
                 $(
-                    self.$field.write_to_buffer(&mut result, &self)
-                        .map_err(|e| Error::SerializeError(concat!("Could not serialize field ", stringify!($name), "::", stringify!($field)), Box::new(e)))?;
+                    let start = layout.last().map_or(0, |(_, range): &(&str, ::core::ops::Range<usize>)| range.end);
+                    let len = Convertable::<$name>::serialized_len(&self.$field, self);
+                    layout.push((concat!(stringify!($name), "::", stringify!($field)), start..start + len));
                 )*
-                Ok(result)
+                layout
+            }
+
+            /// Append the bytes of this struct to the given buffer.
+            ///
+            /// If an error occurs, the buffer may contain some of the fields that were already written.
+            pub fn write_to(&self, buffer: &mut dyn $crate::convert::WriteBuffer) -> $crate::Result<()> {
+                use $crate::convert::Convertable;
+                use $crate::Error;
+                use $crate::__private::Box;
+
+                let start = buffer.len();
+                $(
+                    let offset = buffer.len() - start;
+                    self.$field.write_to_buffer(buffer, &self)
+                        .map_err(|e| Error::SerializeError {
+                            field: concat!(stringify!($name), "::", stringify!($field)),
+                            offset,
+                            source: Box::new(e),
+                        })?;
+                )*
+                Ok(())
             }
 
             /// Convert a byte array to an instance of this struct.
-            pub fn from(data: &[u8]) -> crate::Result<$name> {
-                use crate::convert::Convertable;
-                use crate::Error;
+            pub fn from(data: &[u8]) -> $crate::Result<$name> {
+                use $crate::convert::Convertable;
+                use $crate::Error;
+                use $crate::__private::Box;
 
-                let mut cursor = ::std::io::Cursor::new(data);
+                let mut cursor = $crate::convert::Cursor::new(data);
                 $(
+                    let offset = cursor.position();
                     let $field: $ty = Convertable::<$name>::from_cursor(&mut cursor)
-                        .map_err(|e| Error::DeserializeError(concat!("Could not deserialize field ", stringify!($name), "::", stringify!($field)), Box::new(e)))?;
+                        .map_err(|e| Error::DeserializeError {
+                            field: concat!(stringify!($name), "::", stringify!($field)),
+                            offset,
+                            source: Box::new(e),
+                        })?;
                 )*
                 Ok($name {
                     $($field, )*
                 })
             }
         }
-
-
-        #[test]
-        fn test_encode_decode() {
-            let start = $name {
-                $(
-                    $field: crate::convert::Convertable::<$name>::get_test_value(),
-                )*
-            };
-            let bytes = start.to_bytes().expect("Could not serialize");
-            let end = $name::from(&bytes).expect("Could not deserialize");
-            $(
-                assert!(crate::convert::Convertable::<$name>::is_equal(&start.$field, &end.$field));
-            )*
-        }
     };
 }
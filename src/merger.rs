@@ -0,0 +1,265 @@
+use crate::MergeMode;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// A controller that sends ArtDmx to a [Merger](struct.Merger.html)
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Source {
+    addr: SocketAddr,
+    priority: u8,
+    data: Vec<u8>,
+    last_seen: Instant,
+}
+
+/// Merges the ArtDmx of two controllers that send to the same output port, as described by the Art-Net specification.
+///
+/// The first two controllers that send data are merged, either highest takes precedence or latest takes precedence. Data of a third controller is ignored until one of them times out. An ArtAddress with `AddressCommand::CancelMerge` ends the merge: `cancel_merge` drops the controller that started sending last.
+///
+/// Controllers can send with a priority, see `handle_with_priority`. Only the controllers with the highest priority are merged, the data of the others is ignored until the controllers with a higher priority time out. A controller with a higher priority replaces the controller with the lowest priority when two controllers are merged already. A [Node](struct.Node.html) merges ArtDmx at the `acn_priority` that it was programmed with, and sACN data at its own `priority`, see `Node::handle_with_priority`.
+///
+/// Pass the ArtDmx of the port to `handle`, and output the returned data. A [Node](struct.Node.html) merges the data of its output ports with a merger each, see `Node::handle_at`.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::Instant;
+///
+/// let mut merger = Merger::new(MergeMode::Htp);
+/// let now = Instant::now();
+/// merger.handle(&[255, 0, 10], "10.0.0.1:6454".parse().unwrap(), now);
+/// let merged = merger.handle(&[0, 255], "10.0.0.2:6454".parse().unwrap(), now);
+/// assert_eq!(merged, Some(&[255, 255, 10][..]));
+/// assert!(merger.is_merging());
+///
+/// merger.cancel_merge();
+/// assert!(!merger.is_merging());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Merger {
+    mode: MergeMode,
+    timeout: Duration,
+    /// The controllers that are merged, the one that started sending first at the front
+    sources: Vec<Source>,
+    /// If the merge was cancelled, so other controllers are ignored until the remaining one times out
+    cancelled: bool,
+    merged: Vec<u8>,
+}
+
+impl Default for Merger {
+    fn default() -> Merger {
+        Merger::new(MergeMode::Htp)
+    }
+}
+
+impl Merger {
+    /// The time without ArtDmx after which a controller is no longer merged, as given by the Art-Net specification
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// The priority of the data that is passed to `handle`, the default priority of sACN
+    pub const DEFAULT_PRIORITY: u8 = 100;
+
+    /// Create a merger with the given mode and the default timeout
+    pub fn new(mode: MergeMode) -> Merger {
+        Merger {
+            mode,
+            timeout: Merger::DEFAULT_TIMEOUT,
+            sources: Vec::new(),
+            cancelled: false,
+            merged: Vec::new(),
+        }
+    }
+
+    /// Stop merging a controller when it sent no ArtDmx for the given time
+    pub fn with_timeout(mut self, timeout: Duration) -> Merger {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The merge mode
+    pub fn mode(&self) -> MergeMode {
+        self.mode
+    }
+
+    /// Change the merge mode, e.g. after an ArtAddress with `AddressCommand::MergeLtp`. This takes effect with the next ArtDmx.
+    pub fn set_mode(&mut self, mode: MergeMode) {
+        self.mode = mode;
+    }
+
+    /// If the data of two controllers with the same priority is merged
+    pub fn is_merging(&self) -> bool {
+        let priority = self.priority();
+        self.sources
+            .iter()
+            .filter(|source| Some(source.priority) == priority)
+            .count()
+            > 1
+    }
+
+    /// The priority of the data that is output, or `None` if no controller is sending
+    pub fn priority(&self) -> Option<u8> {
+        self.sources.iter().map(|source| source.priority).max()
+    }
+
+    /// The addresses of the controllers that are merged, the one that started sending first at the front
+    pub fn sources(&self) -> impl Iterator<Item = SocketAddr> + '_ {
+        self.sources.iter().map(|source| source.addr)
+    }
+
+    /// Handle the ArtDmx data of a controller that was received at `now`, with the default priority, and return the merged data. Returns `None` if the data of this controller is ignored, because two other controllers are merged or the merge was cancelled.
+    pub fn handle(&mut self, data: &[u8], addr: SocketAddr, now: Instant) -> Option<&[u8]> {
+        self.handle_with_priority(data, addr, Merger::DEFAULT_PRIORITY, now)
+    }
+
+    /// Handle the data of a controller that sends with the given priority, and return the merged data. Returns `None` if the data of this controller is ignored, because two other controllers with at least the same priority are merged or the merge was cancelled.
+    pub fn handle_with_priority(
+        &mut self,
+        data: &[u8],
+        addr: SocketAddr,
+        priority: u8,
+        now: Instant,
+    ) -> Option<&[u8]> {
+        let timeout = self.timeout;
+        self.sources.retain(|source| {
+            source.addr == addr || now.saturating_duration_since(source.last_seen) < timeout
+        });
+        if self.sources.is_empty() {
+            self.cancelled = false;
+        }
+        let limit = if self.cancelled { 1 } else { 2 };
+        let index = match self.sources.iter().position(|source| source.addr == addr) {
+            Some(index) => index,
+            None => {
+                if self.sources.len() >= limit {
+                    // Replace the controller with the lowest priority that started sending last
+                    let lowest = (0..self.sources.len())
+                        .rev()
+                        .min_by_key(|index| self.sources[*index].priority)?;
+                    if self.sources[lowest].priority >= priority {
+                        return None;
+                    }
+                    self.sources.remove(lowest);
+                }
+                self.sources.push(Source {
+                    addr,
+                    priority,
+                    data: Vec::new(),
+                    last_seen: now,
+                });
+                self.sources.len() - 1
+            }
+        };
+        let source = &mut self.sources[index];
+        source.priority = priority;
+        source.data.clear();
+        source.data.extend_from_slice(data);
+        source.last_seen = now;
+
+        let highest = self.priority().unwrap_or(priority);
+        let sources = self
+            .sources
+            .iter()
+            .filter(|source| source.priority == highest);
+        self.merged.clear();
+        match self.mode {
+            MergeMode::Htp => {
+                for source in sources {
+                    if self.merged.len() < source.data.len() {
+                        self.merged.resize(source.data.len(), 0);
+                    }
+                    for (merged, value) in self.merged.iter_mut().zip(&source.data) {
+                        *merged = (*merged).max(*value);
+                    }
+                }
+            }
+            // The controller that sent last takes precedence
+            MergeMode::Ltp if priority == highest => self.merged.extend_from_slice(data),
+            MergeMode::Ltp => {
+                if let Some(latest) = sources.max_by_key(|source| source.last_seen) {
+                    self.merged.extend_from_slice(&latest.data);
+                }
+            }
+        }
+        Some(&self.merged)
+    }
+
+    /// Cancel the merge, as asked by an ArtAddress with `AddressCommand::CancelMerge`. The controller that started sending last is dropped immediately. Other controllers are ignored until the remaining one times out.
+    pub fn cancel_merge(&mut self) {
+        self.sources.truncate(1);
+        self.cancelled = !self.sources.is_empty();
+    }
+
+    /// Forget every controller
+    pub fn clear(&mut self) {
+        self.sources.clear();
+        self.cancelled = false;
+        self.merged.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge() {
+        let first: SocketAddr = "10.0.0.1:6454".parse().unwrap();
+        let second: SocketAddr = "10.0.0.2:6454".parse().unwrap();
+        let third: SocketAddr = "10.0.0.3:6454".parse().unwrap();
+        let start = Instant::now();
+        let mut merger = Merger::new(MergeMode::Ltp);
+        merger.handle(&[1, 2], first, start);
+        assert_eq!(merger.handle(&[3], second, start), Some(&[3][..]));
+        assert_eq!(merger.handle(&[4, 4], third, start), None);
+        assert_eq!(merger.sources().collect::<Vec<_>>(), vec![first, second]);
+
+        // The first controller times out
+        merger.set_mode(MergeMode::Htp);
+        let later = start + Merger::DEFAULT_TIMEOUT;
+        merger.handle(&[5], second, later - Duration::from_secs(1));
+        assert_eq!(merger.handle(&[4, 4], third, later), Some(&[5, 4][..]));
+        assert_eq!(merger.sources().collect::<Vec<_>>(), vec![second, third]);
+
+        merger.cancel_merge();
+        assert_eq!(merger.sources().collect::<Vec<_>>(), vec![second]);
+        assert_eq!(merger.handle(&[4, 4], third, later), None);
+        assert_eq!(merger.handle(&[1, 1], second, later), Some(&[1, 1][..]));
+        let timed_out = later + Merger::DEFAULT_TIMEOUT;
+        assert_eq!(merger.handle(&[4, 4], third, timed_out), Some(&[4, 4][..]));
+        assert!(!merger.is_merging());
+    }
+
+    #[test]
+    fn priority() {
+        let low: SocketAddr = "10.0.0.1:6454".parse().unwrap();
+        let high: SocketAddr = "10.0.0.2:6454".parse().unwrap();
+        let higher: SocketAddr = "10.0.0.3:6454".parse().unwrap();
+        let now = Instant::now();
+        let mut merger = Merger::new(MergeMode::Ltp);
+        merger.handle_with_priority(&[1], low, 50, now);
+        assert_eq!(
+            merger.handle_with_priority(&[2], high, 150, now),
+            Some(&[2][..])
+        );
+        // The data of a controller with a lower priority is not output
+        assert_eq!(
+            merger.handle_with_priority(&[3], low, 50, now),
+            Some(&[2][..])
+        );
+        assert!(!merger.is_merging());
+        assert_eq!(merger.priority(), Some(150));
+
+        // A higher priority replaces the lowest one
+        assert_eq!(
+            merger.handle_with_priority(&[4], higher, 200, now),
+            Some(&[4][..])
+        );
+        assert_eq!(merger.sources().collect::<Vec<_>>(), vec![high, higher]);
+        assert_eq!(merger.handle_with_priority(&[5], low, 50, now), None);
+
+        merger.set_mode(MergeMode::Htp);
+        assert_eq!(
+            merger.handle_with_priority(&[6, 6], high, 200, now),
+            Some(&[6, 6][..])
+        );
+        assert!(merger.is_merging());
+    }
+}
@@ -0,0 +1,218 @@
+//! A node emulator for integration tests, so applications can test their Art-Net code without hardware.
+//!
+//! ```rust
+//! use artnet_protocol::mock::MockNode;
+//! use artnet_protocol::*;
+//! use std::time::Duration;
+//!
+//! let node = MockNode::bind().unwrap();
+//! let mut controller = Controller::bind("127.0.0.1:0", node.addr()).unwrap();
+//!
+//! // Wait until the controller discovered the node
+//! while controller.discovery().nodes().next().is_none() {
+//!     controller.update().unwrap();
+//! }
+//! controller.send_dmx(1.into(), &[1, 2, 3, 4]).unwrap();
+//!
+//! let frames = node.wait_for_frames(1, Duration::from_secs(5));
+//! assert_eq!(frames[0].data.as_ref(), &vec![1, 2, 3, 4]);
+//! ```
+use crate::{ArtCommand, Output, PollReply, RecvBuffer};
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct State {
+    reply: PollReply,
+    frames: Vec<Output>,
+    polls: usize,
+    invalid: usize,
+}
+
+/// A node on a loopback socket, that answers polls from a background thread and records the ArtDmx that it receives.
+///
+/// By default the node has a single output port for Port-Address 1. Change the reply with `set_reply` to emulate a different node. The thread stops when the node is dropped.
+#[derive(Debug)]
+pub struct MockNode {
+    socket: UdpSocket,
+    state: Arc<Mutex<State>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockNode {
+    /// Bind a node to a random port on 127.0.0.1
+    pub fn bind() -> io::Result<MockNode> {
+        let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))?;
+        socket.set_read_timeout(Some(Duration::from_millis(10)))?;
+        let state = Arc::new(Mutex::new(State {
+            reply: PollReply {
+                num_ports: [0, 1],
+                port_types: [0x80, 0, 0, 0],
+                swout: [1, 0, 0, 0],
+                ..PollReply::default()
+            },
+            frames: Vec::new(),
+            polls: 0,
+            invalid: 0,
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let socket = socket.try_clone()?;
+            let state = state.clone();
+            let stop = stop.clone();
+            std::thread::spawn(move || run(socket, state, stop))
+        };
+        let node = MockNode {
+            socket,
+            state,
+            stop,
+            thread: Some(thread),
+        };
+        let reply = node.state().reply.clone();
+        node.set_reply(reply);
+        Ok(node)
+    }
+
+    /// The address that the node listens on
+    pub fn addr(&self) -> SocketAddr {
+        self.socket
+            .local_addr()
+            .expect("The socket of a mock node is bound")
+    }
+
+    /// Change the reply to polls. The address and port are replaced by the ones of this node.
+    pub fn set_reply(&self, reply: PollReply) {
+        let addr = self.addr();
+        self.state().reply = PollReply {
+            address: Ipv4Addr::LOCALHOST,
+            port: addr.port(),
+            ..reply
+        };
+    }
+
+    /// The number of polls that were answered
+    pub fn polls(&self) -> usize {
+        self.state().polls
+    }
+
+    /// The number of received messages that could not be parsed
+    pub fn invalid(&self) -> usize {
+        self.state().invalid
+    }
+
+    /// Take the ArtDmx that was received since the last call
+    pub fn take_frames(&self) -> Vec<Output> {
+        std::mem::take(&mut self.state().frames)
+    }
+
+    /// Wait until at least `count` ArtDmx messages were received or the timeout passed, and take them
+    pub fn wait_for_frames(&self, count: usize, timeout: Duration) -> Vec<Output> {
+        let start = Instant::now();
+        while self.state().frames.len() < count && start.elapsed() < timeout {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        self.take_frames()
+    }
+
+    /// Send raw bytes from the socket of this node, e.g. a malformed packet from [malformed_packets](fn.malformed_packets.html)
+    pub fn inject(&self, bytes: &[u8], addr: SocketAddr) -> io::Result<()> {
+        self.socket.send_to(bytes, addr)?;
+        Ok(())
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for MockNode {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run(socket: UdpSocket, state: Arc<Mutex<State>>, stop: Arc<AtomicBool>) {
+    let mut buffer = RecvBuffer::new();
+    while !stop.load(Ordering::Relaxed) {
+        let (length, addr) = match socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(_) => continue,
+        };
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        match buffer.parse(length) {
+            Ok(ArtCommand::Poll(_)) => {
+                state.polls += 1;
+                let reply = ArtCommand::PollReply(Box::new(state.reply.clone()));
+                if let Ok(bytes) = reply.write_to_buffer() {
+                    let _ = socket.send_to(&bytes, addr);
+                }
+            }
+            Ok(ArtCommand::Output(output)) => state.frames.push(output),
+            Ok(_) => {}
+            Err(_) => state.invalid += 1,
+        }
+    }
+}
+
+/// Packets that a robust Art-Net implementation should reject without panicking: a wrong header, an unknown opcode, a truncated ArtPollReply and an ArtPoll without a protocol version.
+pub fn malformed_packets() -> Vec<Vec<u8>> {
+    let mut truncated_reply = ArtCommand::PollReply(Box::default())
+        .write_to_buffer()
+        .expect("The default reply is valid");
+    truncated_reply.truncate(100);
+    vec![
+        b"Not-Art\0\x00\x20\x00\x0e\x00\x00".to_vec(),
+        b"Art-Net\0\x00\x01\x00\x0e".to_vec(),
+        truncated_reply,
+        b"Art-Net\0\x00\x20\x00".to_vec(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poll_and_inject() {
+        let node = MockNode::bind().unwrap();
+        let controller = UdpSocket::bind("127.0.0.1:0").unwrap();
+        controller
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+
+        let poll = ArtCommand::Poll(Default::default())
+            .write_to_buffer()
+            .unwrap();
+        controller.send_to(&poll, node.addr()).unwrap();
+        let mut buffer = RecvBuffer::new();
+        let (length, _) = controller.recv_from(&mut buffer).unwrap();
+        match buffer.parse(length).unwrap() {
+            ArtCommand::PollReply(reply) => assert_eq!(reply.port, node.addr().port()),
+            command => panic!("Unexpected command {:?}", command),
+        }
+        assert_eq!(node.polls(), 1);
+
+        for packet in malformed_packets() {
+            assert!(ArtCommand::from_buffer(&packet).is_err());
+            node.inject(&packet, controller.local_addr().unwrap())
+                .unwrap();
+            let (length, _) = controller.recv_from(&mut buffer).unwrap();
+            assert!(buffer.parse(length).is_err());
+            controller.send_to(&packet, node.addr()).unwrap();
+        }
+        let start = Instant::now();
+        while node.invalid() < 4 && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(node.invalid(), 4);
+    }
+}
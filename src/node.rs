@@ -0,0 +1,851 @@
+#[cfg(feature = "std")]
+use crate::Transport;
+#[cfg(feature = "dmx")]
+use crate::{Address, AddressCommand, AddressSwitch};
+use crate::{ArtCommand, ArtTalkToMe, Output, Poll, PollReply};
+#[cfg(any(feature = "std", feature = "dmx"))]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::net::SocketAddr;
+
+/// Indicator state in bits 7-6 of `PollReply::status_1`
+#[cfg(feature = "dmx")]
+const INDICATOR_MASK: u8 = 0b1100_0000;
+
+/// What the application should do after a [Node](struct.Node.html) handled a command
+#[derive(Debug)]
+pub enum NodeEvent {
+    /// A controller polled the network. Send this reply to the controller.
+    Reply(ArtCommand),
+
+    /// A controller programmed this node with an ArtAddress. The state of the node was updated, send the reply to the controller.
+    ///
+    /// Commands that the node can not execute by itself, e.g. failover modes, are left to the application.
+    #[cfg(feature = "dmx")]
+    Programmed {
+        /// The received message
+        address: Box<Address>,
+
+        /// The reply with the new state, to be sent to the controller
+        reply: ArtCommand,
+    },
+
+    /// ArtDmx was received for one of the output ports of this node
+    Dmx {
+        /// The output port, from 0 to 3
+        port: usize,
+
+        /// The received data
+        output: Output,
+    },
+}
+
+impl NodeEvent {
+    /// The reply that must be sent to the controller that sent the handled command
+    #[cfg(feature = "std")]
+    pub(crate) fn reply(&self) -> Option<&ArtCommand> {
+        match self {
+            NodeEvent::Reply(reply) => Some(reply),
+            #[cfg(feature = "dmx")]
+            NodeEvent::Programmed { reply, .. } => Some(reply),
+            _ => None,
+        }
+    }
+}
+
+/// The state of an Art-Net node, which answers polls and receives data for its output ports.
+///
+/// Pass every received command to `handle` and act on the returned event. It works without `std`, so it can be used on microcontrollers.
+///
+/// ```rust
+/// use artnet_protocol::*;
+///
+/// let mut node = Node::new(PollReply {
+///     num_ports: [0, 1],
+///     port_types: [0x80, 0, 0, 0],
+///     swout: [1, 0, 0, 0],
+///     ..PollReply::default()
+/// });
+///
+/// match node.handle(Output::new(1.into(), vec![255; 512]).into()) {
+///     Some(NodeEvent::Dmx { port, output }) => {
+///         // Write output.data to the DMX port
+///         # assert_eq!(port, 0);
+///     }
+///     Some(NodeEvent::Reply(reply)) => {
+///         // socket.send_to(&reply.write_to_buffer().unwrap(), controller)
+///     }
+///     _ => {}
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Node {
+    reply: PollReply,
+    /// The physical switch settings, that an ArtAddress can reset the node to
+    #[cfg(feature = "dmx")]
+    defaults: PollReply,
+    /// The controllers that polled with `ArtTalkToMe::EMIT_CHANGES`, the oldest first
+    subscribers: Vec<SocketAddr>,
+    /// The state that the subscribers were last told about
+    reported: PollReply,
+    /// The minimum time between two replies to the polls of a controller
+    #[cfg(feature = "std")]
+    reply_interval: std::time::Duration,
+    /// The controllers whose polls were answered within the last `reply_interval`, and when
+    #[cfg(feature = "std")]
+    replied: Vec<(SocketAddr, std::time::Instant)>,
+    /// Merges the ArtDmx of the controllers that send to each output port
+    #[cfg(all(feature = "std", feature = "dmx"))]
+    mergers: Vec<crate::Merger>,
+    /// The sACN priority that an ArtAddress programmed
+    #[cfg(feature = "dmx")]
+    acn_priority: u8,
+    /// The merged data of the output ports, to apply the data loss policy to
+    #[cfg(all(feature = "std", feature = "dmx"))]
+    merged: crate::DmxReceiver,
+    /// The buffers of `handle_readable` and `send_changes`, created when they are first used. They are taken out while in use, so the node can be changed at the same time.
+    #[cfg(feature = "std")]
+    buffers: Option<Box<Buffers>>,
+}
+
+/// The buffers that a node reuses to receive and send messages
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+struct Buffers {
+    recv: crate::RecvBuffer,
+    encoder: crate::Encoder,
+}
+
+impl Node {
+    /// The number of controllers that are told about changes. When another controller asks for them, the controller that asked first is forgotten.
+    pub const MAX_SUBSCRIBERS: usize = 8;
+
+    /// The priority of ArtDmx until an ArtAddress programs the sACN priority, the default priority of sACN
+    pub const DEFAULT_ACN_PRIORITY: u8 = 100;
+
+    /// Create a node with the given reply. The Port-Addresses in the reply are its physical switch settings, which an ArtAddress can reset the node to.
+    pub fn new(reply: PollReply) -> Node {
+        Node {
+            #[cfg(feature = "dmx")]
+            defaults: reply.clone(),
+            subscribers: Vec::new(),
+            reported: reply.clone(),
+            #[cfg(feature = "std")]
+            reply_interval: Node::DEFAULT_REPLY_INTERVAL,
+            #[cfg(feature = "std")]
+            replied: Vec::new(),
+            #[cfg(all(feature = "std", feature = "dmx"))]
+            mergers: (0..4).map(|_| crate::Merger::default()).collect(),
+            #[cfg(feature = "dmx")]
+            acn_priority: Node::DEFAULT_ACN_PRIORITY,
+            #[cfg(all(feature = "std", feature = "dmx"))]
+            merged: crate::DmxReceiver::new(),
+            #[cfg(feature = "std")]
+            buffers: None,
+            reply,
+        }
+    }
+
+    /// The current state of the node
+    pub fn reply(&self) -> &PollReply {
+        &self.reply
+    }
+
+    /// Change the state of the node, e.g. to update the node report
+    pub fn reply_mut(&mut self) -> &mut PollReply {
+        &mut self.reply
+    }
+
+    /// The sACN priority that the node was programmed with, by the `acn_priority` of an ArtAddress. This is `DEFAULT_ACN_PRIORITY` until the node is programmed, or when it does not support sACN.
+    #[cfg(feature = "dmx")]
+    pub fn acn_priority(&self) -> u8 {
+        self.acn_priority
+    }
+
+    /// Handle a command that was received from `source`. This is the same as `handle`, but remembers the controllers that polled with `ArtTalkToMe::EMIT_CHANGES`, so `changed_reply` can tell them about changes.
+    ///
+    /// A controller that polls without `ArtTalkToMe::EMIT_CHANGES` is no longer told about changes.
+    pub fn handle_from(&mut self, command: ArtCommand, source: SocketAddr) -> Option<NodeEvent> {
+        let emit_changes = match &command {
+            ArtCommand::Poll(poll) => Some(poll.talk_to_me.contains(ArtTalkToMe::EMIT_CHANGES)),
+            _ => None,
+        };
+        let event = self.handle(command);
+        if let (Some(emit_changes), Some(NodeEvent::Reply(_))) = (emit_changes, &event) {
+            self.subscribe(source, emit_changes);
+        }
+        event
+    }
+
+    /// Remember or forget a controller that polled this node, depending on if it asked for changes
+    fn subscribe(&mut self, source: SocketAddr, emit_changes: bool) {
+        self.subscribers.retain(|subscriber| *subscriber != source);
+        if emit_changes {
+            if self.subscribers.len() == Node::MAX_SUBSCRIBERS {
+                self.subscribers.remove(0);
+            }
+            self.subscribers.push(source);
+        }
+    }
+
+    /// If this node answers the given poll, which is not the case for a targeted poll outside its Port-Addresses
+    fn answers(&self, poll: &Poll) -> bool {
+        poll.target().is_none()
+            || (0..4)
+                .flat_map(|port| {
+                    self.reply
+                        .output_port_address(port)
+                        .into_iter()
+                        .chain(self.reply.input_port_address(port))
+                })
+                .any(|port_address| poll.targets(port_address))
+    }
+
+    /// The controllers that asked to be told about changes of this node
+    pub fn subscribers(&self) -> &[SocketAddr] {
+        &self.subscribers
+    }
+
+    /// The reply to send to every controller in `subscribers`, if the state of the node changed since they were last told. Call this after changing the state with `reply_mut`, and after handling a command.
+    pub fn changed_reply(&mut self) -> Option<ArtCommand> {
+        if self.reply == self.reported {
+            return None;
+        }
+        self.reported = self.reply.clone();
+        if self.subscribers.is_empty() {
+            return None;
+        }
+        Some(self.reply.clone().into())
+    }
+
+    /// Handle a received command. Returns `None` if the command is not meant for this node.
+    ///
+    /// A poll in targeted mode is only answered when one of the input or output ports of the node has a Port-Address in its target range.
+    pub fn handle(&mut self, command: ArtCommand) -> Option<NodeEvent> {
+        match command {
+            ArtCommand::Poll(poll) => {
+                if !self.answers(&poll) {
+                    return None;
+                }
+                Some(NodeEvent::Reply(self.reply.clone().into()))
+            }
+            #[cfg(feature = "dmx")]
+            ArtCommand::Address(address) => {
+                if address.bind_index.max(1) != self.reply.bind_index.max(1) {
+                    return None;
+                }
+                self.program(&address);
+                Some(NodeEvent::Programmed {
+                    address,
+                    reply: self.reply.clone().into(),
+                })
+            }
+            ArtCommand::Output(output) => {
+                let port = (0..4).find(|&port| {
+                    self.reply.output_port_address(port) == Some(output.port_address)
+                })?;
+                Some(NodeEvent::Dmx { port, output })
+            }
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "dmx")]
+    fn program(&mut self, address: &Address) {
+        let reply = &mut self.reply;
+        let defaults = &self.defaults;
+
+        program(
+            address.net_switch,
+            0x7F,
+            &mut reply.port_address[0],
+            defaults.port_address[0],
+        );
+        program(
+            address.sub_switch,
+            0x0F,
+            &mut reply.port_address[1],
+            defaults.port_address[1],
+        );
+        for port in 0..4 {
+            program(
+                address.swin[port],
+                0x0F,
+                &mut reply.swin[port],
+                defaults.swin[port],
+            );
+            program(
+                address.swout[port],
+                0x0F,
+                &mut reply.swout[port],
+                defaults.swout[port],
+            );
+        }
+        program_name(&address.short_name, &mut reply.short_name);
+        program_name(&address.long_name, &mut reply.long_name);
+        if let Some(priority) = address.acn_priority().filter(|_| reply.supports_sacn()) {
+            self.acn_priority = priority;
+        }
+
+        match address.address_command() {
+            AddressCommand::LedNormal => reply.status_1 |= INDICATOR_MASK,
+            AddressCommand::LedMute => {
+                reply.status_1 = (reply.status_1 & !INDICATOR_MASK) | 0b1000_0000
+            }
+            AddressCommand::LedLocate => {
+                reply.status_1 = (reply.status_1 & !INDICATOR_MASK) | 0b0100_0000
+            }
+            // Bit 3 of GoodOutput is set while merging
+            AddressCommand::CancelMerge => {
+                for good_output in &mut reply.good_output {
+                    *good_output &= !0x08;
+                }
+            }
+            // Bit 1 of GoodOutput is set for LTP
+            AddressCommand::MergeLtp(port) => reply.good_output[usize::from(port)] |= 0x02,
+            AddressCommand::MergeHtp(port) => reply.good_output[usize::from(port)] &= !0x02,
+            // Bit 0 of GoodOutput is set when the port transmits sACN
+            AddressCommand::ArtNetSel(port) => reply.good_output[usize::from(port)] &= !0x01,
+            AddressCommand::AcnSel(port) if reply.supports_sacn() => {
+                reply.good_output[usize::from(port)] |= 0x01
+            }
+            AddressCommand::BackgroundQueuePolicy(policy) if reply.supports_background_queue() => {
+                reply.background_queue_policy = policy.into()
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Node {
+    /// The default minimum time between two replies to the polls of the same controller. Controllers should not poll more often than every 2.5 seconds, so this only drops repeated polls.
+    pub const DEFAULT_REPLY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(1000);
+
+    /// The number of controllers whose polls are answered within one reply interval. Polls of other controllers are ignored until the interval passed, so a flood of polls does not make the node send more replies than this.
+    pub const MAX_REPLIES_PER_INTERVAL: usize = 16;
+
+    /// Wait at least the given interval between two replies to the polls of the same controller
+    pub fn with_reply_interval(mut self, interval: std::time::Duration) -> Node {
+        self.reply_interval = interval;
+        self
+    }
+
+    /// Change the minimum time between two replies to the polls of the same controller
+    pub fn set_reply_interval(&mut self, interval: std::time::Duration) {
+        self.reply_interval = interval;
+    }
+
+    /// The minimum time between two replies to the polls of the same controller
+    pub fn reply_interval(&self) -> std::time::Duration {
+        self.reply_interval
+    }
+
+    /// Handle a command that was received from `source` at `now`. This is the same as `handle_from`, but rate limits the replies to polls.
+    ///
+    /// A poll is ignored when the poll of the same controller was answered less than `reply_interval` ago, or when `MAX_REPLIES_PER_INTERVAL` controllers were answered within the interval.
+    ///
+    /// With the `dmx` feature, the ArtDmx of two controllers that send to the same output port is merged with a [Merger](struct.Merger.html), in the merge mode of the port, and the event contains the merged data. ArtDmx of a third controller is ignored. While a port is merging, bit 3 of its `good_output` is set. An ArtAddress with `AddressCommand::CancelMerge` drops the controller that started sending last from every port.
+    ///
+    /// ArtDmx is merged at the `acn_priority` of the node. See `handle_with_priority` for data with a priority of its own, e.g. sACN. When the merged data of a port is lost, `tick` applies the data loss policy of the node to it.
+    pub fn handle_at(
+        &mut self,
+        command: ArtCommand,
+        source: SocketAddr,
+        now: std::time::Instant,
+    ) -> Option<NodeEvent> {
+        #[cfg(feature = "dmx")]
+        let priority = self.acn_priority;
+        #[cfg(not(feature = "dmx"))]
+        let priority = Node::DEFAULT_ACN_PRIORITY;
+        self.handle_with_priority(command, source, priority, now)
+    }
+
+    /// Handle a command like `handle_at`, but merge its ArtDmx at the given priority, e.g. the `priority` of an sACN data packet that was converted with `sacn::Converter::to_art_net`. Only the controllers with the highest priority are merged, see `Merger::handle_with_priority`.
+    pub fn handle_with_priority(
+        &mut self,
+        command: ArtCommand,
+        source: SocketAddr,
+        priority: u8,
+        now: std::time::Instant,
+    ) -> Option<NodeEvent> {
+        if let ArtCommand::Poll(poll) = &command {
+            let interval = self.reply_interval;
+            self.replied
+                .retain(|(_, replied)| now.saturating_duration_since(*replied) < interval);
+            if self.replied.len() >= Node::MAX_REPLIES_PER_INTERVAL
+                || self.replied.iter().any(|(addr, _)| *addr == source)
+            {
+                // A controller that keeps polling is still told about changes, even if it is not answered
+                if self.answers(poll) {
+                    self.subscribe(source, poll.talk_to_me.contains(ArtTalkToMe::EMIT_CHANGES));
+                }
+                return None;
+            }
+            let event = self.handle_from(command, source);
+            if event.is_some() {
+                self.replied.push((source, now));
+            }
+            return event;
+        }
+        let event = self.handle_from(command, source);
+        #[cfg(feature = "dmx")]
+        let event = self.merge(event?, source, priority, now);
+        #[cfg(not(feature = "dmx"))]
+        let _ = priority;
+        event
+    }
+
+    /// Merge the ArtDmx of an output port with the data of other controllers, and cancel the merges when asked to
+    #[cfg(feature = "dmx")]
+    fn merge(
+        &mut self,
+        event: NodeEvent,
+        source: SocketAddr,
+        priority: u8,
+        now: std::time::Instant,
+    ) -> Option<NodeEvent> {
+        match event {
+            NodeEvent::Dmx { port, mut output } => {
+                let good_output = &mut self.reply.good_output[port];
+                let merger = &mut self.mergers[port];
+                merger.set_mode(if *good_output & 0x02 != 0 {
+                    crate::MergeMode::Ltp
+                } else {
+                    crate::MergeMode::Htp
+                });
+                output.data = merger
+                    .handle_with_priority(output.data.as_ref(), source, priority, now)?
+                    .to_vec()
+                    .into();
+                if merger.is_merging() {
+                    *good_output |= 0x08;
+                } else {
+                    *good_output &= !0x08;
+                }
+                self.merged.handle_output(&output, source, now);
+                Some(NodeEvent::Dmx { port, output })
+            }
+            NodeEvent::Programmed { address, reply } => {
+                if let AddressCommand::CancelMerge = address.address_command() {
+                    for merger in &mut self.mergers {
+                        merger.cancel_merge();
+                    }
+                }
+                Some(NodeEvent::Programmed { address, reply })
+            }
+            event => Some(event),
+        }
+    }
+
+    /// What the output ports do with their data when no ArtDmx was received within 10 seconds. The last look is held by default.
+    #[cfg(feature = "dmx")]
+    pub fn set_data_loss_policy(&mut self, policy: crate::DataLossPolicy) {
+        self.merged.set_default_policy(policy);
+    }
+
+    /// Apply the data loss policy to the output ports that did not receive ArtDmx within the timeout. Returns an event with the new data of every port whose data changed, or that was lost while holding the last look. Call this at `next_deadline`.
+    #[cfg(feature = "dmx")]
+    pub fn tick(&mut self, now: std::time::Instant) -> Vec<NodeEvent> {
+        let reply = &self.reply;
+        let merged = &mut self.merged;
+        merged
+            .tick(now)
+            .into_iter()
+            .filter_map(|port_address| {
+                let port =
+                    (0..4).find(|&port| reply.output_port_address(port) == Some(port_address))?;
+                let frame = merged.universe(port_address)?;
+                let output = Output::new(port_address, frame.data[..frame.length].to_vec());
+                Some(NodeEvent::Dmx { port, output })
+            })
+            .collect()
+    }
+
+    /// When `tick` should be called next, if an output port is about to lose its data or is fading out
+    #[cfg(feature = "dmx")]
+    pub fn next_deadline(&self) -> Option<std::time::Instant> {
+        self.merged.next_deadline()
+    }
+
+    /// Handle every datagram that is waiting on the non-blocking socket, and send the replies to polls and ArtAddress messages to the controller that sent them. Call this when an event loop, e.g. mio, reports the socket as readable.
+    ///
+    /// The replies to polls are rate limited, see `handle_at`. When the state of the node changed, the controllers that asked for changes are told as well, see `send_changes`.
+    ///
+    /// Returns the events of the handled commands, including the replies that were sent.
+    pub fn handle_readable<T: Transport>(&mut self, socket: &T) -> std::io::Result<Vec<NodeEvent>> {
+        let mut events = Vec::new();
+        let mut buffers = self.buffers.take().unwrap_or_default();
+        let Buffers { recv, encoder } = &mut *buffers;
+        let result = crate::transport::drain(socket, recv, |command, addr| {
+            let event = match self.handle_at(command, addr, std::time::Instant::now()) {
+                Some(event) => event,
+                None => return Ok(()),
+            };
+            if let Some(reply) = event.reply() {
+                let bytes = encoder
+                    .encode(reply)
+                    .map_err(crate::controller::invalid_data)?;
+                socket.send_to(bytes, addr)?;
+            }
+            events.push(event);
+            Ok(())
+        });
+        self.buffers = Some(buffers);
+        result?;
+        self.send_changes(socket)?;
+        Ok(events)
+    }
+
+    /// Send the `changed_reply` to every controller that asked for changes. Returns false if nothing changed.
+    pub fn send_changes<T: Transport>(&mut self, socket: &T) -> std::io::Result<bool> {
+        let reply = match self.changed_reply() {
+            Some(reply) => reply,
+            None => return Ok(false),
+        };
+        let buffers = self.buffers.get_or_insert_with(Default::default);
+        let bytes = buffers
+            .encoder
+            .encode(&reply)
+            .map_err(crate::controller::invalid_data)?;
+        for subscriber in &self.subscribers {
+            socket.send_to(bytes, *subscriber)?;
+        }
+        Ok(true)
+    }
+}
+
+/// Program a switch setting from an ArtAddress
+#[cfg(feature = "dmx")]
+fn program(value: u8, mask: u8, current: &mut u8, default: u8) {
+    match AddressSwitch::from(value) {
+        AddressSwitch::Program(value) => *current = value & mask,
+        AddressSwitch::Reset => *current = default,
+        AddressSwitch::NoChange => {}
+    }
+}
+
+/// Program a name from an ArtAddress, if it is not empty
+#[cfg(feature = "dmx")]
+fn program_name(value: &[u8], current: &mut [u8]) {
+    if value[0] != 0 {
+        current.copy_from_slice(value);
+        // Always keep the name null terminated
+        if let Some(last) = current.last_mut() {
+            *last = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node() -> Node {
+        Node::new(PollReply {
+            num_ports: [0, 2],
+            port_types: [0x80, 0x80, 0, 0],
+            port_address: [0, 1],
+            swout: [0, 1, 0, 0],
+            bind_index: 1,
+            ..PollReply::default()
+        })
+    }
+
+    #[test]
+    fn poll() {
+        let mut node = node();
+        match node.handle(Poll::default().into()) {
+            Some(NodeEvent::Reply(ArtCommand::PollReply(reply))) => {
+                assert_eq!(reply.swout, [0, 1, 0, 0])
+            }
+            e => panic!("Unexpected event {:?}", e),
+        }
+    }
+
+    #[test]
+    fn targeted_poll() {
+        let mut node = node();
+        let poll = |bottom: u8, top: u8| Poll::default().with_target(bottom.into()..=top.into());
+        assert!(node.handle(poll(0x11, 0x20).into()).is_some());
+        assert!(node.handle(poll(0x00, 0x10).into()).is_some());
+        assert!(node.handle(poll(0x12, 0xFF).into()).is_none());
+        assert!(node.handle(poll(0x00, 0x0F).into()).is_none());
+    }
+
+    #[test]
+    fn emit_changes() {
+        let mut node = node();
+        let first: SocketAddr = "10.0.0.1:6454".parse().unwrap();
+        let second: SocketAddr = "10.0.0.2:6454".parse().unwrap();
+        let mut poll = Poll::default();
+        node.handle_from(poll.clone().into(), first);
+        node.reply_mut().short_name[0] = b'A';
+        // Nobody asked for changes
+        assert!(node.changed_reply().is_none());
+
+        poll.talk_to_me |= ArtTalkToMe::EMIT_CHANGES;
+        node.handle_from(poll.clone().into(), first);
+        node.handle_from(poll.into(), second);
+        assert_eq!(node.subscribers(), &[first, second]);
+        assert!(node.changed_reply().is_none());
+        node.reply_mut().good_output[0] = 0x80;
+        assert!(node.changed_reply().unwrap().as_poll_reply().is_some());
+        assert!(node.changed_reply().is_none());
+
+        node.handle_from(Poll::default().into(), first);
+        assert_eq!(node.subscribers(), &[second]);
+    }
+
+    #[test]
+    fn dmx() {
+        let mut node = node();
+        match node.handle(Output::new(0x11.into(), vec![1, 2]).into()) {
+            Some(NodeEvent::Dmx { port, .. }) => assert_eq!(port, 1),
+            e => panic!("Unexpected event {:?}", e),
+        }
+        assert!(node
+            .handle(Output::new(0x12.into(), vec![1, 2]).into())
+            .is_none());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn reply_interval() {
+        use std::time::{Duration, Instant};
+
+        let mut node = node().with_reply_interval(Duration::from_secs(1));
+        let start = Instant::now();
+        let controller: SocketAddr = "10.0.0.1:6454".parse().unwrap();
+        let poll = || ArtCommand::from(Poll::default());
+        assert!(node.handle_at(poll(), controller, start).is_some());
+        assert!(node
+            .handle_at(poll(), controller, start + Duration::from_millis(500))
+            .is_none());
+        assert!(node
+            .handle_at(poll(), controller, start + Duration::from_secs(1))
+            .is_some());
+
+        // A flood from many addresses is capped
+        let replies = (0..100u16)
+            .filter_map(|port| {
+                let source = SocketAddr::from(([10, 0, 0, 2], port));
+                node.handle_at(poll(), source, start + Duration::from_secs(1))
+            })
+            .count();
+        assert_eq!(replies, Node::MAX_REPLIES_PER_INTERVAL - 1);
+
+        // A poll that is not answered still changes if the controller is told about changes
+        let emit_changes = ArtCommand::from(Poll::with_flags(
+            ArtTalkToMe::EMIT_CHANGES,
+            crate::DiagPriority::DpHigh,
+        ));
+        assert!(node
+            .handle_at(emit_changes, controller, start + Duration::from_secs(1))
+            .is_none());
+        assert_eq!(node.subscribers(), &[controller]);
+        assert!(node
+            .handle_at(poll(), controller, start + Duration::from_secs(1))
+            .is_none());
+        assert!(node.subscribers().is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn handle_readable() {
+        use std::net::UdpSocket;
+
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.set_nonblocking(true).unwrap();
+        let controller = UdpSocket::bind("127.0.0.1:0").unwrap();
+        controller
+            .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+            .unwrap();
+        let addr = socket.local_addr().unwrap();
+
+        let mut node = node();
+        assert!(node.handle_readable(&socket).unwrap().is_empty());
+
+        let poll = ArtCommand::from(Poll::default()).write_to_buffer().unwrap();
+        controller.send_to(&poll, addr).unwrap();
+        controller.send_to(b"Not Art-Net", addr).unwrap();
+        let output = ArtCommand::from(Output::new(0x11.into(), vec![1, 2]));
+        controller
+            .send_to(&output.write_to_buffer().unwrap(), addr)
+            .unwrap();
+
+        let mut events = Vec::new();
+        while events.len() < 2 {
+            events.extend(node.handle_readable(&socket).unwrap());
+        }
+        assert!(matches!(
+            events[..],
+            [NodeEvent::Reply(_), NodeEvent::Dmx { port: 1, .. }]
+        ));
+        let mut buffer = crate::RecvBuffer::new();
+        let (length, _) = controller.recv_from(&mut buffer).unwrap();
+        assert!(buffer.parse(length).unwrap().as_poll_reply().is_some());
+    }
+
+    #[cfg(feature = "dmx")]
+    #[test]
+    fn address() {
+        let mut node = node();
+        let mut address = Address {
+            swout: [Address::NO_CHANGE, 0x85, 0, 0],
+            sub_switch: Address::PROGRAM | 0x02,
+            ..Address::default()
+        }
+        .with_command(AddressCommand::LedLocate);
+        address.short_name[..5].copy_from_slice(b"Stage");
+        assert!(node.handle(address.into()).is_some());
+
+        let reply = node.reply();
+        assert_eq!(&reply.short_name[..6], b"Stage\0");
+        assert_eq!(reply.port_address, [0, 2]);
+        assert_eq!(reply.swout[..2], [0, 5]);
+        assert_eq!(reply.status_1 & INDICATOR_MASK, 0b0100_0000);
+        assert_eq!(reply.output_port_address(1).map(u16::from), Some(0x25));
+
+        // Reset to the physical switch settings
+        let address = Address {
+            sub_switch: 0,
+            ..Address::default()
+        };
+        node.handle(address.into());
+        assert_eq!(node.reply().port_address, [0, 1]);
+
+        // Addressed to another bound node
+        let address = Address {
+            bind_index: 2,
+            ..Address::default()
+        };
+        assert!(node.handle(address.into()).is_none());
+    }
+
+    #[cfg(all(feature = "std", feature = "dmx"))]
+    #[test]
+    fn merge() {
+        let now = std::time::Instant::now();
+        let first: SocketAddr = "10.0.0.1:6454".parse().unwrap();
+        let second: SocketAddr = "10.0.0.2:6454".parse().unwrap();
+        let mut node = node();
+        let dmx = |node: &mut Node, data: Vec<u8>, source| match node.handle_at(
+            Output::new(0x11.into(), data).into(),
+            source,
+            now,
+        ) {
+            Some(NodeEvent::Dmx { output, .. }) => Some(output.data.to_vec()),
+            _ => None,
+        };
+        assert_eq!(dmx(&mut node, vec![10, 0], first), Some(vec![10, 0]));
+        assert_eq!(dmx(&mut node, vec![0, 20], second), Some(vec![10, 20]));
+        assert_eq!(node.reply().good_output[1] & 0x08, 0x08);
+
+        let cancel = Address::default().with_command(AddressCommand::CancelMerge);
+        assert!(node.handle_at(cancel.into(), first, now).is_some());
+        assert_eq!(node.reply().good_output[1] & 0x08, 0);
+        assert_eq!(dmx(&mut node, vec![0, 20], second), None);
+        assert_eq!(dmx(&mut node, vec![1, 2], first), Some(vec![1, 2]));
+    }
+
+    #[cfg(all(feature = "std", feature = "dmx"))]
+    #[test]
+    fn data_loss() {
+        use std::time::Duration;
+
+        let now = std::time::Instant::now();
+        let source: SocketAddr = "10.0.0.1:6454".parse().unwrap();
+        let mut node = node();
+        node.set_data_loss_policy(crate::DataLossPolicy::Zero);
+        node.handle_at(Output::new(0x11.into(), vec![10, 20]).into(), source, now);
+        assert!(node.tick(now + Duration::from_secs(1)).is_empty());
+
+        let lost = now + Duration::from_secs(10);
+        assert_eq!(node.next_deadline(), Some(lost));
+        match &node.tick(lost)[..] {
+            [NodeEvent::Dmx { port: 1, output }] => assert_eq!(output.data.as_ref(), &[0, 0]),
+            events => panic!("Unexpected events {:?}", events),
+        }
+        assert!(node.tick(lost + Duration::from_secs(1)).is_empty());
+    }
+
+    #[cfg(all(feature = "std", feature = "dmx"))]
+    #[test]
+    fn merge_priority() {
+        let now = std::time::Instant::now();
+        let art_net: SocketAddr = "10.0.0.1:6454".parse().unwrap();
+        let sacn: SocketAddr = "10.0.0.2:5568".parse().unwrap();
+        let mut node = node();
+        node.reply_mut().status_2 |= PollReply::STATUS_2_SACN;
+        let dmx = |node: &mut Node, data: Vec<u8>, source, priority: Option<u8>| {
+            let command = Output::new(0x11.into(), data).into();
+            let event = match priority {
+                Some(priority) => node.handle_with_priority(command, source, priority, now),
+                None => node.handle_at(command, source, now),
+            };
+            match event {
+                Some(NodeEvent::Dmx { output, .. }) => Some(output.data.to_vec()),
+                _ => None,
+            }
+        };
+        assert_eq!(
+            dmx(&mut node, vec![10, 0], art_net, None),
+            Some(vec![10, 0])
+        );
+        // sACN with a higher priority than the Art-Net data takes precedence
+        assert_eq!(
+            dmx(&mut node, vec![0, 20], sacn, Some(150)),
+            Some(vec![0, 20])
+        );
+        assert_eq!(
+            dmx(&mut node, vec![30, 0], art_net, None),
+            Some(vec![0, 20])
+        );
+
+        // Art-Net data with the programmed priority is merged with the sACN data
+        node.handle_at(
+            Address::default().program_acn_priority(150).into(),
+            art_net,
+            now,
+        );
+        assert_eq!(node.acn_priority(), 150);
+        assert_eq!(
+            dmx(&mut node, vec![30, 0], art_net, None),
+            Some(vec![30, 20])
+        );
+    }
+
+    #[cfg(feature = "dmx")]
+    #[test]
+    fn sacn() {
+        let mut node = node();
+        node.reply_mut().status_2 |= PollReply::STATUS_2_SACN;
+        node.reply_mut().status_3 |= PollReply::STATUS_3_BACKGROUND_QUEUE;
+
+        let warning = crate::BackgroundQueuePolicy::Warning;
+        let address = Address::default()
+            .program_acn_priority(150)
+            .with_command(AddressCommand::BackgroundQueuePolicy(warning));
+        match node.handle(address.into()) {
+            Some(NodeEvent::Programmed { address, .. }) => {
+                assert_eq!(address.acn_priority(), Some(150))
+            }
+            e => panic!("Unexpected event {:?}", e),
+        }
+        assert_eq!(node.reply().background_queue_policy, 2);
+
+        node.handle(
+            Address::default()
+                .with_command(AddressCommand::AcnSel(1))
+                .into(),
+        );
+        assert_eq!(node.reply().good_output[1] & 0x01, 0x01);
+        node.handle(
+            Address::default()
+                .with_command(AddressCommand::ArtNetSel(1))
+                .into(),
+        );
+        assert_eq!(node.reply().good_output[1] & 0x01, 0x00);
+    }
+}
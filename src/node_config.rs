@@ -0,0 +1,407 @@
+use crate::command::null_terminated;
+#[cfg(feature = "firmware")]
+use crate::IpProg;
+use crate::{
+    Address, AddressCommand, ArtCommand, Error, FailsafeState, PollReply, PortAddress, Result,
+};
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "firmware")]
+use core::net::Ipv4Addr;
+
+/// How an output port combines the data of two controllers that send to its universe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MergeMode {
+    /// Highest Takes Precedence, the default of a node
+    Htp,
+    /// Latest Takes Precedence
+    Ltp,
+}
+
+/// The desired state of a single port of a node, as part of a [NodeConfig](struct.NodeConfig.html). Settings that are `None` are left as they are.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PortConfig {
+    /// The universe that the port outputs
+    pub output: Option<PortAddress>,
+
+    /// The universe that the port sends its input to
+    pub input: Option<PortAddress>,
+
+    /// How the port merges the data of two controllers
+    pub merge_mode: Option<MergeMode>,
+}
+
+/// How a node gets its IP address, as part of a [NodeConfig](struct.NodeConfig.html)
+#[cfg(feature = "firmware")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IpConfig {
+    /// The node gets its address from a DHCP server
+    Dhcp,
+
+    /// The node uses a static address
+    Static {
+        /// The IP address of the node
+        address: Ipv4Addr,
+        /// The subnet mask of the node
+        subnet_mask: Ipv4Addr,
+        /// The default gateway of the node
+        gateway: Ipv4Addr,
+    },
+}
+
+/// The desired configuration of a node, e.g. loaded from a configuration file of a show. Settings that are `None` are left as they are.
+///
+/// `reconcile` compares the configuration to the ArtPollReply of the node, and returns the packets that program the settings that differ. After the node executed them, its next reply matches the configuration and `reconcile` returns nothing.
+///
+/// ```rust
+/// use artnet_protocol::*;
+///
+/// let config = NodeConfig {
+///     short_name: Some("Dimmer 1".into()),
+///     ports: vec![PortConfig {
+///         output: Some(PortAddress::from(3)),
+///         merge_mode: Some(MergeMode::Ltp),
+///         ..PortConfig::default()
+///     }],
+///     ..NodeConfig::default()
+/// };
+/// let mut node = Node::new(PollReply {
+///     num_ports: [0, 1],
+///     port_types: [0x80, 0, 0, 0],
+///     ..PollReply::default()
+/// });
+///
+/// let commands = config.reconcile(node.reply()).unwrap();
+/// // The name, universe and merge mode fit in a single ArtAddress
+/// assert_eq!(commands.len(), 1);
+/// for command in commands {
+///     // socket.send_to(&command.write_to_buffer().unwrap(), node_address)
+///     node.handle(command);
+/// }
+/// assert!(config.reconcile(node.reply()).unwrap().is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct NodeConfig {
+    /// The short name of the node, at most 17 bytes
+    pub short_name: Option<String>,
+
+    /// The long name of the node, at most 63 bytes
+    pub long_name: Option<String>,
+
+    /// The ports of the node, from port 0 up to port 3. The universes of all ports must be in the same Net and Sub-Net, because a node only has a single Net and Sub-Net switch.
+    pub ports: Vec<PortConfig>,
+
+    /// What the outputs do when the data stops. Only programmed when the node supports failover.
+    pub failsafe: Option<FailsafeState>,
+
+    /// How the node gets its IP address. A static address is programmed when the address of the node differs, the subnet mask and gateway are not reported in an ArtPollReply.
+    #[cfg(feature = "firmware")]
+    pub ip: Option<IpConfig>,
+}
+
+impl NodeConfig {
+    /// The current configuration of the node that sent the given reply
+    pub fn from_reply(reply: &PollReply) -> NodeConfig {
+        let ports = usize::from(reply.num_ports[1]).min(4);
+        NodeConfig {
+            short_name: Some(null_terminated(&reply.short_name).into_owned()),
+            long_name: Some(null_terminated(&reply.long_name).into_owned()),
+            ports: (0..ports)
+                .map(|port| PortConfig {
+                    output: reply.output_port_address(port),
+                    input: reply.input_port_address(port),
+                    merge_mode: Some(merge_mode(reply, port)),
+                })
+                .collect(),
+            failsafe: Some(reply.failsafe_state()).filter(|_| reply.supports_failover()),
+            #[cfg(feature = "firmware")]
+            ip: Some(if reply.dhcp_enabled() {
+                IpConfig::Dhcp
+            } else {
+                IpConfig::Static {
+                    address: reply.address,
+                    subnet_mask: Ipv4Addr::UNSPECIFIED,
+                    gateway: Ipv4Addr::UNSPECIFIED,
+                }
+            }),
+        }
+    }
+
+    /// The packets that program the node that sent the given reply with this configuration, in the order in which they should be sent. Returns nothing if the node already matches it.
+    ///
+    /// An ArtAddress only carries a single command, so the names and universes are programmed with the first ArtAddress, together with the first merge or failsafe command, and every further command gets an ArtAddress of its own. With the `firmware` feature, an ArtIpProg comes last, because the node no longer receives packets on its old address once it executed it.
+    ///
+    /// Returns `Error::InvalidConfig` if the configuration has more than 4 ports, or if the universes of the ports are in different Net and Sub-Nets.
+    pub fn reconcile(&self, reply: &PollReply) -> Result<Vec<ArtCommand>> {
+        if self.ports.len() > 4 {
+            return Err(Error::InvalidConfig("A node has at most 4 ports"));
+        }
+        let mut addresses = self
+            .ports
+            .iter()
+            .flat_map(|port| port.output.into_iter().chain(port.input))
+            .map(|port_address| (port_address.net(), port_address.sub_uni() >> 4));
+        let net_sub_net = addresses.next();
+        if addresses.any(|address| Some(address) != net_sub_net) {
+            return Err(Error::InvalidConfig(
+                "The ports of a node must be in the same Net and Sub-Net",
+            ));
+        }
+
+        let bind_index = reply.bind_index.max(1);
+        let mut address = Address::default().for_bind_index(bind_index);
+        let mut changed = false;
+        if let Some(name) = &self.short_name {
+            let programmed = Address::default().program_short_name(name);
+            if null_terminated(&programmed.short_name) != null_terminated(&reply.short_name) {
+                address.short_name = programmed.short_name;
+                changed = true;
+            }
+        }
+        if let Some(name) = &self.long_name {
+            let programmed = Address::default().program_long_name(name);
+            if null_terminated(&programmed.long_name) != null_terminated(&reply.long_name) {
+                address.long_name = programmed.long_name;
+                changed = true;
+            }
+        }
+        if let Some((net, sub_net)) = net_sub_net {
+            if reply.port_address[0] & 0x7F != net {
+                address = address.program_net(net);
+                changed = true;
+            }
+            if reply.port_address[1] & 0x0F != sub_net {
+                address = address.program_sub_net(sub_net);
+                changed = true;
+            }
+        }
+
+        let mut commands = Vec::new();
+        for (port, config) in self.ports.iter().enumerate() {
+            if let Some(output) = config.output {
+                let universe = output.sub_uni() & 0x0F;
+                if reply.swout[port] & 0x0F != universe {
+                    address = address.program_swout(port, universe)?;
+                    changed = true;
+                }
+            }
+            if let Some(input) = config.input {
+                let universe = input.sub_uni() & 0x0F;
+                if reply.swin[port] & 0x0F != universe {
+                    address = address.program_swin(port, universe)?;
+                    changed = true;
+                }
+            }
+            match config.merge_mode {
+                Some(mode) if mode != merge_mode(reply, port) => commands.push(match mode {
+                    MergeMode::Htp => AddressCommand::MergeHtp(port as u8),
+                    MergeMode::Ltp => AddressCommand::MergeLtp(port as u8),
+                }),
+                _ => {}
+            }
+        }
+        match self.failsafe {
+            Some(failsafe) if reply.supports_failover() && failsafe != reply.failsafe_state() => {
+                commands.push(failsafe.command())
+            }
+            _ => {}
+        }
+
+        let mut packets = Vec::new();
+        let mut commands = commands.into_iter();
+        if changed {
+            if let Some(command) = commands.next() {
+                address = address.with_command(command);
+            }
+            packets.push(address.into_command());
+        }
+        packets.extend(commands.map(|command| {
+            Address::default()
+                .for_bind_index(bind_index)
+                .with_command(command)
+                .into_command()
+        }));
+
+        #[cfg(feature = "firmware")]
+        match self.ip {
+            Some(IpConfig::Dhcp) if !reply.dhcp_enabled() => {
+                packets.push(IpProg::default().enable_dhcp().into_command())
+            }
+            Some(IpConfig::Static {
+                address,
+                subnet_mask,
+                gateway,
+            }) if reply.dhcp_enabled() || reply.address != address => packets.push(
+                IpProg::default()
+                    .program_ip(address)
+                    .program_subnet_mask(subnet_mask)
+                    .program_gateway(gateway)
+                    .into_command(),
+            ),
+            _ => {}
+        }
+        Ok(packets)
+    }
+}
+
+/// The merge mode of the given port, from bit 1 of `good_output`
+fn merge_mode(reply: &PollReply, port: usize) -> MergeMode {
+    if reply.good_output[port] & 0x02 != 0 {
+        MergeMode::Ltp
+    } else {
+        MergeMode::Htp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Node;
+
+    fn node() -> Node {
+        Node::new(PollReply {
+            num_ports: [0, 2],
+            port_types: [0x80, 0xC0, 0, 0],
+            status_3: PollReply::STATUS_3_FAILOVER,
+            ..PollReply::default()
+        })
+    }
+
+    #[test]
+    fn reconcile() {
+        let config = NodeConfig {
+            short_name: Some("Stage left".into()),
+            long_name: None,
+            ports: vec![
+                PortConfig {
+                    output: Some(PortAddress::from_parts(1, 0x23)),
+                    merge_mode: Some(MergeMode::Ltp),
+                    ..PortConfig::default()
+                },
+                PortConfig {
+                    output: Some(PortAddress::from_parts(1, 0x24)),
+                    input: Some(PortAddress::from_parts(1, 0x25)),
+                    merge_mode: Some(MergeMode::Ltp),
+                },
+            ],
+            failsafe: Some(FailsafeState::Zero),
+            #[cfg(feature = "firmware")]
+            ip: None,
+        };
+        let mut node = node();
+        let commands = config.reconcile(node.reply()).unwrap();
+        assert_eq!(commands.len(), 3);
+        match &commands[0] {
+            ArtCommand::Address(address) => {
+                assert_eq!(address.net_switch, 0x81);
+                assert_eq!(address.sub_switch, 0x82);
+                assert_eq!(address.swout, [0x83, 0x84, 0x7F, 0x7F]);
+                assert_eq!(address.swin, [0x7F, 0x85, 0x7F, 0x7F]);
+                assert_eq!(address.long_name, [0; 64]);
+                assert_eq!(address.address_command(), AddressCommand::MergeLtp(0));
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+        for command in commands {
+            node.handle(command);
+        }
+        // The failsafe state is left to the application of a node
+        node.reply_mut().status_3 |= 0x40;
+        assert!(config.reconcile(node.reply()).unwrap().is_empty());
+        assert!(NodeConfig::from_reply(node.reply())
+            .reconcile(node.reply())
+            .unwrap()
+            .is_empty());
+
+        // Only the settings that changed are programmed
+        let mut config = config;
+        config.ports[1].output = Some(PortAddress::from_parts(1, 0x26));
+        match &config.reconcile(node.reply()).unwrap()[..] {
+            [ArtCommand::Address(address)] => {
+                assert_eq!(address.net_switch, Address::NO_CHANGE);
+                assert_eq!(address.short_name, [0; 18]);
+                assert_eq!(address.swout, [0x7F, 0x86, 0x7F, 0x7F]);
+                assert_eq!(address.address_command(), AddressCommand::None);
+            }
+            commands => panic!("Unexpected commands {:?}", commands),
+        }
+    }
+
+    #[test]
+    fn invalid_config() {
+        let config = NodeConfig {
+            ports: vec![
+                PortConfig {
+                    output: Some(PortAddress::from_parts(0, 0x01)),
+                    ..PortConfig::default()
+                },
+                PortConfig {
+                    output: Some(PortAddress::from_parts(0, 0x11)),
+                    ..PortConfig::default()
+                },
+            ],
+            ..NodeConfig::default()
+        };
+        assert!(matches!(
+            config.reconcile(node().reply()),
+            Err(Error::InvalidConfig(_))
+        ));
+        let config = NodeConfig {
+            ports: vec![PortConfig::default(); 5],
+            ..NodeConfig::default()
+        };
+        assert!(config.reconcile(node().reply()).is_err());
+    }
+
+    #[cfg(feature = "firmware")]
+    #[test]
+    fn ip() {
+        let mut reply = node().reply().clone();
+        reply.address = [2, 0, 0, 1].into();
+        let config = NodeConfig {
+            ip: Some(IpConfig::Static {
+                address: [2, 0, 0, 2].into(),
+                subnet_mask: [255, 0, 0, 0].into(),
+                gateway: [2, 0, 0, 254].into(),
+            }),
+            ..NodeConfig::default()
+        };
+        match &config.reconcile(&reply).unwrap()[..] {
+            [ArtCommand::OpIpProg(ip_prog)] => {
+                assert_eq!(ip_prog.command, 0x80 | 0x10 | 0x04 | 0x02);
+                assert_eq!(ip_prog.ip, Ipv4Addr::new(2, 0, 0, 2));
+            }
+            commands => panic!("Unexpected commands {:?}", commands),
+        }
+        reply.address = [2, 0, 0, 2].into();
+        assert!(config.reconcile(&reply).unwrap().is_empty());
+
+        let config = NodeConfig {
+            ip: Some(IpConfig::Dhcp),
+            ..NodeConfig::default()
+        };
+        assert_eq!(config.reconcile(&reply).unwrap().len(), 1);
+        reply.status_2 |= PollReply::STATUS_2_DHCP;
+        assert!(config.reconcile(&reply).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let config: NodeConfig = serde_json::from_str(
+            r#"{"short_name": "Dimmer", "ports": [{"output": 3, "merge_mode": "Ltp"}], "failsafe": "Zero"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.ports[0].output, Some(PortAddress::from(3)));
+        assert_eq!(config.failsafe, Some(FailsafeState::Zero));
+        assert!(serde_json::from_str::<PortConfig>(r#"{"output": 40000}"#).is_err());
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(serde_json::from_str::<NodeConfig>(&json).unwrap(), config);
+    }
+}
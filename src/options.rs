@@ -0,0 +1,63 @@
+/// Options that determine how strictly incoming messages are checked against the Art-Net specification.
+///
+/// Used by [ArtCommand::from_buffer_with](enum.ArtCommand.html#method.from_buffer_with). `ArtCommand::from_buffer` uses `ParseOptions::default()`, which is lenient and returns the messages as they were received. [ArtCommand::write_to_with](enum.ArtCommand.html#method.write_to_with) uses the same options to check outgoing messages.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ParseOptions {
+    /// Whether violations of the specification are rejected or tolerated
+    pub mode: ParseMode,
+
+    /// Reject commands whose protocol version (see `ArtCommand::protocol_version`) is lower than this. Commands without a protocol version are always accepted.
+    pub min_protocol_version: Option<u16>,
+
+    /// In lenient mode, truncate the data of ArtDmx and ArtNzs to their length field. Off by default.
+    pub normalize: bool,
+}
+
+impl ParseOptions {
+    /// Reject any message that does not follow the specification, including messages with a protocol version below 14
+    pub fn strict() -> ParseOptions {
+        ParseOptions {
+            mode: ParseMode::Strict,
+            min_protocol_version: Some(14),
+            normalize: false,
+        }
+    }
+
+    /// Accept messages from devices that do not follow the specification to the letter, as long as they can be understood
+    pub fn lenient() -> ParseOptions {
+        ParseOptions {
+            mode: ParseMode::Lenient,
+            min_protocol_version: None,
+            normalize: false,
+        }
+    }
+
+    /// Accept messages like `lenient`, and normalize them: trailing bytes after the data of an ArtDmx or ArtNzs are dropped
+    pub fn normalizing() -> ParseOptions {
+        ParseOptions {
+            normalize: true,
+            ..ParseOptions::lenient()
+        }
+    }
+
+    /// Returns true if the mode is `ParseMode::Strict`
+    pub fn is_strict(&self) -> bool {
+        self.mode == ParseMode::Strict
+    }
+
+    /// If messages are normalized, see `normalize`
+    pub(crate) fn normalizes(&self) -> bool {
+        self.normalize && !self.is_strict()
+    }
+}
+
+/// How spec violations are handled while parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Reserved fields must be zero, length fields must match the data and values must be in range. Any violation results in `Error::SpecViolation`.
+    Strict,
+
+    /// Violations are tolerated as long as the message can be understood. With `ParseOptions::normalize`, trailing bytes after the data are ignored.
+    #[default]
+    Lenient,
+}
@@ -0,0 +1,550 @@
+//! Read the Art-Net messages out of a network capture, e.g. one recorded with Wireshark or tcpdump, to replay or analyse it.
+//!
+//! Both the classic pcap format and pcapng are supported. Captures of Ethernet, Linux cooked (SLL and SLL2), BSD loopback and raw IP links are decoded. Every UDP datagram over IPv4 or IPv6 that starts with the Art-Net header is returned, other traffic is skipped. Fragmented IP packets are skipped as well.
+//!
+//! ```rust,no_run
+//! use artnet_protocol::pcap::PcapReader;
+//! use artnet_protocol::ArtCommand;
+//!
+//! for packet in PcapReader::open("show.pcapng").unwrap() {
+//!     let packet = packet.unwrap();
+//!     if let Ok(ArtCommand::Output(output)) = &packet.command {
+//!         println!("{:?} {} -> {:?}", packet.timestamp, packet.source, output.port_address);
+//!     }
+//! }
+//! ```
+use crate::{ArtCommand, Result, ARTNET_HEADER};
+use std::convert::TryInto;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const PCAP_MAGIC_MICROS: u32 = 0xA1B2_C3D4;
+const PCAP_MAGIC_NANOS: u32 = 0xA1B2_3C4D;
+const PCAPNG_SECTION_HEADER: u32 = 0x0A0D_0D0A;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+const PCAPNG_INTERFACE_DESCRIPTION: u32 = 0x0000_0001;
+const PCAPNG_ENHANCED_PACKET: u32 = 0x0000_0006;
+const PCAPNG_OPTION_TSRESOL: u16 = 9;
+
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LOOP: u32 = 108;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_IPV4: u32 = 228;
+const LINKTYPE_IPV6: u32 = 229;
+const LINKTYPE_LINUX_SLL2: u32 = 276;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86DD;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const ETHERTYPE_QINQ: u16 = 0x88A8;
+const IP_PROTOCOL_UDP: u8 = 17;
+
+/// Larger frames and blocks are rejected, so a corrupt length does not allocate all memory
+const MAX_LENGTH: usize = 16 * 1024 * 1024;
+
+/// An Art-Net message in a capture
+#[derive(Debug)]
+pub struct CapturedPacket {
+    /// When the message was captured
+    pub timestamp: SystemTime,
+
+    /// The address that sent the message
+    pub source: SocketAddr,
+
+    /// The address that the message was sent to
+    pub destination: SocketAddr,
+
+    /// The UDP payload, to replay the message as it was captured
+    pub payload: Vec<u8>,
+
+    /// The payload parsed with `ArtCommand::from_buffer`. Malformed messages are kept, so they can be analysed.
+    pub command: Result<ArtCommand>,
+}
+
+#[derive(Debug)]
+struct Interface {
+    link_type: u32,
+    /// The number of timestamp units in a second
+    units_per_second: u64,
+}
+
+#[derive(Debug)]
+enum Format {
+    Pcap {
+        big_endian: bool,
+        nanos: bool,
+        link_type: u32,
+    },
+    PcapNg {
+        big_endian: bool,
+        interfaces: Vec<Interface>,
+    },
+}
+
+/// Iterates over the Art-Net messages in a pcap or pcapng capture.
+///
+/// The reader is read in small pieces, so wrap a `File` in a `BufReader`, or use `PcapReader::open`. An error in the capture itself, e.g. a truncated file, ends the iteration after it is returned.
+#[derive(Debug)]
+pub struct PcapReader<R> {
+    reader: R,
+    format: Format,
+    failed: bool,
+}
+
+impl PcapReader<BufReader<File>> {
+    /// Open the capture at the given path
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<PcapReader<BufReader<File>>> {
+        PcapReader::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> PcapReader<R> {
+    /// Read the header of the capture. Returns an error with `io::ErrorKind::InvalidData` if it is not a pcap or pcapng capture.
+    pub fn new(mut reader: R) -> io::Result<PcapReader<R>> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if u32::from_le_bytes(magic) == PCAPNG_SECTION_HEADER {
+            let mut reader = PcapReader {
+                reader,
+                format: Format::PcapNg {
+                    big_endian: false,
+                    interfaces: Vec::new(),
+                },
+                failed: false,
+            };
+            reader.read_section_header()?;
+            return Ok(reader);
+        }
+
+        let (big_endian, nanos) = match (u32::from_le_bytes(magic), u32::from_be_bytes(magic)) {
+            (PCAP_MAGIC_MICROS, _) => (false, false),
+            (PCAP_MAGIC_NANOS, _) => (false, true),
+            (_, PCAP_MAGIC_MICROS) => (true, false),
+            (_, PCAP_MAGIC_NANOS) => (true, true),
+            _ => return Err(invalid("Not a pcap or pcapng capture")),
+        };
+        let mut header = [0; 20];
+        reader.read_exact(&mut header)?;
+        Ok(PcapReader {
+            reader,
+            format: Format::Pcap {
+                big_endian,
+                nanos,
+                link_type: read_u32(&header[16..], big_endian),
+            },
+            failed: false,
+        })
+    }
+
+    /// Read the rest of a pcapng section header, after its block type. This starts a new section with its own byte order and interfaces.
+    fn read_section_header(&mut self) -> io::Result<()> {
+        let mut header = [0; 8];
+        self.reader.read_exact(&mut header)?;
+        let big_endian = match u32::from_le_bytes(header[4..].try_into().unwrap()) {
+            PCAPNG_BYTE_ORDER_MAGIC => false,
+            magic if magic.swap_bytes() == PCAPNG_BYTE_ORDER_MAGIC => true,
+            _ => return Err(invalid("Invalid pcapng byte order magic")),
+        };
+        let length = read_u32(&header, big_endian) as usize;
+        if length < 28 || !length.is_multiple_of(4) || length > MAX_LENGTH {
+            return Err(invalid("Invalid pcapng section header length"));
+        }
+        // The version, section length, options and trailing length are not needed
+        io::copy(
+            &mut (&mut self.reader).take(length as u64 - 12),
+            &mut io::sink(),
+        )?;
+        self.format = Format::PcapNg {
+            big_endian,
+            interfaces: Vec::new(),
+        };
+        Ok(())
+    }
+
+    /// Read the next captured frame with the link type of its interface, or `None` at the end of the capture
+    fn next_frame(&mut self) -> io::Result<Option<(SystemTime, u32, Vec<u8>)>> {
+        match self.format {
+            Format::Pcap {
+                big_endian,
+                nanos,
+                link_type,
+            } => {
+                let mut header = [0; 16];
+                if !read_or_eof(&mut self.reader, &mut header)? {
+                    return Ok(None);
+                }
+                let seconds = read_u32(&header, big_endian);
+                let fraction = read_u32(&header[4..], big_endian);
+                let length = read_u32(&header[8..], big_endian) as usize;
+                if length > MAX_LENGTH {
+                    return Err(invalid("Invalid pcap frame length"));
+                }
+                let mut data = vec![0; length];
+                self.reader.read_exact(&mut data)?;
+                let fraction = if nanos {
+                    fraction
+                } else {
+                    fraction.saturating_mul(1000)
+                };
+                let timestamp = UNIX_EPOCH
+                    .checked_add(Duration::new(u64::from(seconds), fraction))
+                    .ok_or_else(|| invalid("Invalid pcap timestamp"))?;
+                Ok(Some((timestamp, link_type, data)))
+            }
+            Format::PcapNg { .. } => loop {
+                let mut header = [0; 4];
+                if !read_or_eof(&mut self.reader, &mut header)? {
+                    return Ok(None);
+                }
+                if u32::from_le_bytes(header) == PCAPNG_SECTION_HEADER {
+                    self.read_section_header()?;
+                    continue;
+                }
+                let (big_endian, interfaces) = match &mut self.format {
+                    Format::PcapNg {
+                        big_endian,
+                        interfaces,
+                    } => (*big_endian, interfaces),
+                    Format::Pcap { .. } => unreachable!(),
+                };
+                let block_type = read_u32(&header, big_endian);
+                self.reader.read_exact(&mut header)?;
+                let length = read_u32(&header, big_endian) as usize;
+                if length < 12 || !length.is_multiple_of(4) || length > MAX_LENGTH {
+                    return Err(invalid("Invalid pcapng block length"));
+                }
+                let mut body = vec![0; length - 8];
+                self.reader.read_exact(&mut body)?;
+                // Drop the trailing length
+                body.truncate(length - 12);
+
+                match block_type {
+                    PCAPNG_INTERFACE_DESCRIPTION => {
+                        if body.len() < 8 {
+                            return Err(invalid("Truncated pcapng interface description"));
+                        }
+                        interfaces.push(Interface {
+                            link_type: u32::from(read_u16(&body, big_endian)),
+                            units_per_second: tsresol(&body[8..], big_endian),
+                        });
+                    }
+                    PCAPNG_ENHANCED_PACKET => {
+                        if body.len() < 20 {
+                            return Err(invalid("Truncated pcapng enhanced packet"));
+                        }
+                        let interface = interfaces
+                            .get(read_u32(&body, big_endian) as usize)
+                            .ok_or_else(|| invalid("Packet of an unknown pcapng interface"))?;
+                        let units = u64::from(read_u32(&body[4..], big_endian)) << 32
+                            | u64::from(read_u32(&body[8..], big_endian));
+                        let length = read_u32(&body[12..], big_endian) as usize;
+                        let data = body
+                            .get(20..20 + length)
+                            .ok_or_else(|| invalid("Truncated pcapng enhanced packet"))?;
+                        let per_second = interface.units_per_second;
+                        let nanos =
+                            u128::from(units % per_second) * 1_000_000_000 / u128::from(per_second);
+                        let timestamp = UNIX_EPOCH
+                            .checked_add(Duration::new(units / per_second, nanos as u32))
+                            .ok_or_else(|| invalid("Invalid pcapng timestamp"))?;
+                        return Ok(Some((timestamp, interface.link_type, data.to_vec())));
+                    }
+                    // Simple packet blocks have no timestamp, they are skipped with the other blocks
+                    _ => {}
+                }
+            },
+        }
+    }
+}
+
+impl<R: Read> Iterator for PcapReader<R> {
+    type Item = io::Result<CapturedPacket>;
+
+    fn next(&mut self) -> Option<io::Result<CapturedPacket>> {
+        if self.failed {
+            return None;
+        }
+        loop {
+            let (timestamp, link_type, frame) = match self.next_frame() {
+                Ok(Some(frame)) => frame,
+                Ok(None) => return None,
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+            };
+            let (source, destination, payload) = match udp_payload(link_type, &frame) {
+                Some(udp) if udp.2.starts_with(ARTNET_HEADER) => udp,
+                _ => continue,
+            };
+            return Some(Ok(CapturedPacket {
+                timestamp,
+                source,
+                destination,
+                payload: payload.to_vec(),
+                command: ArtCommand::from_buffer(payload),
+            }));
+        }
+    }
+}
+
+fn invalid(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Fill the buffer, returning `false` if the reader is at its end before the first byte
+fn read_or_eof<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<bool> {
+    let mut read = 0;
+    while read < buffer.len() {
+        match reader.read(&mut buffer[read..]) {
+            Ok(0) if read == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(length) => read += length,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+fn read_u16(data: &[u8], big_endian: bool) -> u16 {
+    let bytes = data[..2].try_into().unwrap();
+    if big_endian {
+        u16::from_be_bytes(bytes)
+    } else {
+        u16::from_le_bytes(bytes)
+    }
+}
+
+fn read_u32(data: &[u8], big_endian: bool) -> u32 {
+    let bytes = data[..4].try_into().unwrap();
+    if big_endian {
+        u32::from_be_bytes(bytes)
+    } else {
+        u32::from_le_bytes(bytes)
+    }
+}
+
+/// The timestamp resolution in the options of an interface description, microseconds by default
+fn tsresol(mut options: &[u8], big_endian: bool) -> u64 {
+    while options.len() >= 4 {
+        let code = read_u16(options, big_endian);
+        let length = usize::from(read_u16(&options[2..], big_endian));
+        if code == PCAPNG_OPTION_TSRESOL && length == 1 && options.len() > 4 {
+            let resolution = options[4];
+            let exponent = u32::from(resolution & 0x7F);
+            // Larger resolutions do not fit in a u64
+            return if resolution & 0x80 == 0 {
+                10u64.saturating_pow(exponent.min(19))
+            } else {
+                1 << exponent.min(63)
+            };
+        }
+        // Options are padded to 4 bytes
+        options = options.get(4 + length.div_ceil(4) * 4..).unwrap_or(&[]);
+    }
+    1_000_000
+}
+
+/// The addresses and payload of a UDP datagram in a captured frame
+fn udp_payload(link_type: u32, frame: &[u8]) -> Option<(SocketAddr, SocketAddr, &[u8])> {
+    let packet = match link_type {
+        LINKTYPE_ETHERNET => {
+            let mut offset = 12;
+            let mut ether_type =
+                u16::from_be_bytes(frame.get(offset..offset + 2)?.try_into().ok()?);
+            while ether_type == ETHERTYPE_VLAN || ether_type == ETHERTYPE_QINQ {
+                offset += 4;
+                ether_type = u16::from_be_bytes(frame.get(offset..offset + 2)?.try_into().ok()?);
+            }
+            if ether_type != ETHERTYPE_IPV4 && ether_type != ETHERTYPE_IPV6 {
+                return None;
+            }
+            frame.get(offset + 2..)?
+        }
+        LINKTYPE_NULL | LINKTYPE_LOOP => frame.get(4..)?,
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => frame,
+        LINKTYPE_LINUX_SLL => frame.get(16..)?,
+        LINKTYPE_LINUX_SLL2 => frame.get(20..)?,
+        _ => return None,
+    };
+
+    let (source, destination, datagram): (IpAddr, IpAddr, _) = match packet.first()? >> 4 {
+        4 => {
+            let header_length = usize::from(packet[0] & 0x0F) * 4;
+            let total_length = usize::from(u16::from_be_bytes(packet.get(2..4)?.try_into().ok()?));
+            let fragment = u16::from_be_bytes(packet.get(6..8)?.try_into().ok()?);
+            // Skip fragments; the "more fragments" flag or an offset is set
+            if *packet.get(9)? != IP_PROTOCOL_UDP || fragment & 0x3FFF != 0 {
+                return None;
+            }
+            let source: [u8; 4] = packet.get(12..16)?.try_into().ok()?;
+            let destination: [u8; 4] = packet.get(16..20)?.try_into().ok()?;
+            (
+                Ipv4Addr::from(source).into(),
+                Ipv4Addr::from(destination).into(),
+                packet.get(header_length..total_length.min(packet.len()))?,
+            )
+        }
+        6 => {
+            // Extension headers are not followed
+            if *packet.get(6)? != IP_PROTOCOL_UDP {
+                return None;
+            }
+            let payload_length =
+                usize::from(u16::from_be_bytes(packet.get(4..6)?.try_into().ok()?));
+            let source: [u8; 16] = packet.get(8..24)?.try_into().ok()?;
+            let destination: [u8; 16] = packet.get(24..40)?.try_into().ok()?;
+            (
+                Ipv6Addr::from(source).into(),
+                Ipv6Addr::from(destination).into(),
+                packet.get(40..(40 + payload_length).min(packet.len()))?,
+            )
+        }
+        _ => return None,
+    };
+
+    let source_port = u16::from_be_bytes(datagram.get(0..2)?.try_into().ok()?);
+    let destination_port = u16::from_be_bytes(datagram.get(2..4)?.try_into().ok()?);
+    let length = usize::from(u16::from_be_bytes(datagram.get(4..6)?.try_into().ok()?));
+    let payload = datagram.get(8..length.min(datagram.len()))?;
+    Some((
+        SocketAddr::new(source, source_port),
+        SocketAddr::new(destination, destination_port),
+        payload,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Output, Poll};
+
+    /// An Ethernet frame with an IPv4 UDP datagram from 10.0.0.1:6454 to 10.0.0.255:6454
+    fn ethernet_frame(payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0xFF; 6];
+        frame.extend_from_slice(&[2, 0, 0, 0, 0, 1]);
+        frame.extend_from_slice(&ETHERTYPE_IPV4.to_be_bytes());
+        frame.extend_from_slice(&[0x45, 0]);
+        frame.extend_from_slice(&(28 + payload.len() as u16).to_be_bytes());
+        frame.extend_from_slice(&[0, 0, 0, 0, 64, IP_PROTOCOL_UDP, 0, 0]);
+        frame.extend_from_slice(&[10, 0, 0, 1, 10, 0, 0, 255]);
+        frame.extend_from_slice(&6454u16.to_be_bytes());
+        frame.extend_from_slice(&6454u16.to_be_bytes());
+        frame.extend_from_slice(&(8 + payload.len() as u16).to_be_bytes());
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    #[test]
+    fn pcap() {
+        let dmx = ArtCommand::Output(Output::new(3.into(), vec![1, 2, 3, 4]))
+            .write_to_buffer()
+            .unwrap();
+        let mut capture = Vec::new();
+        capture.extend_from_slice(&PCAP_MAGIC_MICROS.to_be_bytes());
+        capture.extend_from_slice(&[0, 2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF]);
+        capture.extend_from_slice(&LINKTYPE_ETHERNET.to_be_bytes());
+        for (i, frame) in [ethernet_frame(&dmx), ethernet_frame(b"Not Art-Net")]
+            .iter()
+            .enumerate()
+        {
+            capture.extend_from_slice(&(1_700_000_000 + i as u32).to_be_bytes());
+            capture.extend_from_slice(&250_000u32.to_be_bytes());
+            capture.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+            capture.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+            capture.extend_from_slice(frame);
+        }
+
+        let packets: Vec<_> = PcapReader::new(&capture[..])
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(packets.len(), 1);
+        assert_eq!(
+            packets[0].timestamp,
+            UNIX_EPOCH + Duration::new(1_700_000_000, 250_000_000)
+        );
+        assert_eq!(packets[0].source, "10.0.0.1:6454".parse().unwrap());
+        assert_eq!(packets[0].destination, "10.0.0.255:6454".parse().unwrap());
+        assert_eq!(packets[0].payload, dmx);
+        match &packets[0].command {
+            Ok(ArtCommand::Output(output)) => assert_eq!(output.port_address, 3.into()),
+            command => panic!("Unexpected command {:?}", command),
+        }
+
+        // A truncated capture ends with an error
+        let mut reader = PcapReader::new(&capture[..capture.len() - 20]).unwrap();
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+        assert!(PcapReader::new(&b"Not a capture"[..]).is_err());
+    }
+
+    #[test]
+    fn pcapng() {
+        let poll = ArtCommand::Poll(Poll::default()).write_to_buffer().unwrap();
+        let block = |block_type: u32, body: &[u8]| {
+            let mut block = block_type.to_le_bytes().to_vec();
+            let padded = body.len().div_ceil(4) * 4;
+            block.extend_from_slice(&(padded as u32 + 12).to_le_bytes());
+            block.extend_from_slice(body);
+            block.resize(8 + padded, 0);
+            block.extend_from_slice(&(padded as u32 + 12).to_le_bytes());
+            block
+        };
+
+        let mut section = PCAPNG_BYTE_ORDER_MAGIC.to_le_bytes().to_vec();
+        section.extend_from_slice(&[1, 0, 0, 0]);
+        section.extend_from_slice(&u64::MAX.to_le_bytes());
+        let mut capture = block(PCAPNG_SECTION_HEADER, &section);
+
+        // Nanosecond timestamps on an Ethernet interface
+        let mut interface = vec![1, 0, 0, 0, 0, 0, 0, 0];
+        interface.extend_from_slice(&[9, 0, 1, 0, 9, 0, 0, 0, 0, 0, 0, 0]);
+        capture.extend(block(PCAPNG_INTERFACE_DESCRIPTION, &interface));
+
+        let frame = ethernet_frame(&poll);
+        let units: u64 = 1_700_000_000_123_456_789;
+        let mut packet = 0u32.to_le_bytes().to_vec();
+        packet.extend_from_slice(&((units >> 32) as u32).to_le_bytes());
+        packet.extend_from_slice(&(units as u32).to_le_bytes());
+        packet.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        packet.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        packet.extend_from_slice(&frame);
+        capture.extend(block(PCAPNG_ENHANCED_PACKET, &packet));
+
+        let packets: Vec<_> = PcapReader::new(&capture[..])
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(packets.len(), 1);
+        assert_eq!(
+            packets[0].timestamp,
+            UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789)
+        );
+        assert!(matches!(packets[0].command, Ok(ArtCommand::Poll(_))));
+
+        // A timestamp in seconds that does not fit in a SystemTime
+        let mut interface = vec![1, 0, 0, 0, 0, 0, 0, 0];
+        interface.extend_from_slice(&[9, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        capture.extend(block(PCAPNG_INTERFACE_DESCRIPTION, &interface));
+        let mut packet = 1u32.to_le_bytes().to_vec();
+        packet.extend_from_slice(&u64::MAX.to_le_bytes());
+        packet.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        packet.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        packet.extend_from_slice(&frame);
+        capture.extend(block(PCAPNG_ENHANCED_PACKET, &packet));
+
+        let mut reader = PcapReader::new(&capture[..]).unwrap();
+        assert!(reader.next().unwrap().is_ok());
+        let error = reader.next().unwrap().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
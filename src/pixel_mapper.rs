@@ -0,0 +1,180 @@
+use crate::{Output, PortAddress, Result};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// The order in which the LEDs of a pixel expect their color channels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorOrder {
+    /// Red, green, blue
+    Rgb,
+    /// Red, blue, green
+    Rbg,
+    /// Green, red, blue, e.g. WS2812 LEDs
+    Grb,
+    /// Green, blue, red
+    Gbr,
+    /// Blue, red, green
+    Brg,
+    /// Blue, green, red
+    Bgr,
+    /// Red, green, blue, white
+    Rgbw,
+    /// Green, red, blue, white, e.g. SK6812 RGBW LEDs
+    Grbw,
+}
+
+impl ColorOrder {
+    /// The number of DMX channels of a single pixel
+    pub fn channels(self) -> usize {
+        self.order().len()
+    }
+
+    /// For every channel of a pixel, the index of its color in an RGB(W) pixel
+    fn order(self) -> &'static [usize] {
+        match self {
+            ColorOrder::Rgb => &[0, 1, 2],
+            ColorOrder::Rbg => &[0, 2, 1],
+            ColorOrder::Grb => &[1, 0, 2],
+            ColorOrder::Gbr => &[1, 2, 0],
+            ColorOrder::Brg => &[2, 0, 1],
+            ColorOrder::Bgr => &[2, 1, 0],
+            ColorOrder::Rgbw => &[0, 1, 2, 3],
+            ColorOrder::Grbw => &[1, 0, 2, 3],
+        }
+    }
+}
+
+/// Splits a buffer of RGB or RGBW pixels over consecutive universes, and creates the ArtDmx packets for them.
+///
+/// The pixels are passed in as red, green, blue and, for the 4 channel orders, white, and are written in the color order of the LEDs. A pixel is never split over two universes, so by default a universe holds 170 RGB or 128 RGBW pixels.
+///
+/// ```rust
+/// use artnet_protocol::*;
+///
+/// let mapper = PixelMapper::new(ColorOrder::Grb, PortAddress::from(1));
+/// // 200 pixels of red
+/// let pixels = [255, 0, 0].repeat(200);
+/// let outputs = mapper.map(&pixels).unwrap();
+/// assert_eq!(outputs.len(), 2);
+/// assert_eq!(outputs[1].port_address, PortAddress::from(2));
+/// assert_eq!(&outputs[0].data.as_ref()[..3], &[0, 255, 0]);
+/// assert_eq!(outputs[1].data.as_ref().len(), 30 * 3);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PixelMapper {
+    order: ColorOrder,
+    start: PortAddress,
+    pixels_per_universe: usize,
+}
+
+impl PixelMapper {
+    /// Create a mapper that starts at the given Port-Address, and fills every universe with as many pixels as fit in it
+    pub fn new(order: ColorOrder, start: PortAddress) -> PixelMapper {
+        PixelMapper {
+            order,
+            start,
+            pixels_per_universe: 512 / order.channels(),
+        }
+    }
+
+    /// Put at most the given number of pixels in every universe, e.g. to match the strips of a controller. It is clamped from 1 up to as many pixels as fit in a universe.
+    pub fn with_pixels_per_universe(mut self, pixels: usize) -> PixelMapper {
+        self.pixels_per_universe = pixels.clamp(1, 512 / self.order.channels());
+        self
+    }
+
+    /// The color order of the LEDs
+    pub fn order(&self) -> ColorOrder {
+        self.order
+    }
+
+    /// The Port-Address of the first universe
+    pub fn start(&self) -> PortAddress {
+        self.start
+    }
+
+    /// The number of pixels in every universe, except maybe the last one
+    pub fn pixels_per_universe(&self) -> usize {
+        self.pixels_per_universe
+    }
+
+    /// The number of universes that the given number of pixels is split over
+    pub fn universes(&self, pixels: usize) -> usize {
+        pixels.div_ceil(self.pixels_per_universe)
+    }
+
+    /// The universe and the first DMX channel, from 0, of the given pixel
+    pub fn locate(&self, pixel: usize) -> Result<(PortAddress, usize)> {
+        let universe = pixel / self.pixels_per_universe;
+        let channel = pixel % self.pixels_per_universe * self.order.channels();
+        Ok((self.port_address(universe)?, channel))
+    }
+
+    /// Create an ArtDmx packet for every universe of the given pixels. A trailing partial pixel is ignored.
+    ///
+    /// Returns `Error::InvalidPortAddress` if the pixels do not fit below the highest Port-Address.
+    pub fn map(&self, pixels: &[u8]) -> Result<Vec<Output>> {
+        let channels = self.order.channels();
+        let pixels = &pixels[..pixels.len() - pixels.len() % channels];
+        pixels
+            .chunks(self.pixels_per_universe * channels)
+            .enumerate()
+            .map(|(universe, pixels)| {
+                let mut data = Vec::with_capacity(pixels.len());
+                for pixel in pixels.chunks_exact(channels) {
+                    data.extend(self.order.order().iter().map(|color| pixel[*color]));
+                }
+                Ok(Output::new(self.port_address(universe)?, data))
+            })
+            .collect()
+    }
+
+    fn port_address(&self, universe: usize) -> Result<PortAddress> {
+        let universe = i32::try_from(universe).unwrap_or(i32::MAX);
+        PortAddress::try_from(i32::from(u16::from(self.start)).saturating_add(universe))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn map() {
+        let mapper =
+            PixelMapper::new(ColorOrder::Rgbw, PortAddress::from(10)).with_pixels_per_universe(2);
+        let pixels = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13];
+        let outputs = mapper.map(&pixels).unwrap();
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0].data.as_ref(), &vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        // The partial pixel at the end is ignored
+        assert_eq!(outputs[1].data.as_ref(), &vec![9, 10, 11, 12]);
+        assert_eq!(outputs[1].port_address, PortAddress::from(11));
+        assert_eq!(mapper.universes(5), 3);
+        assert_eq!(mapper.locate(3).unwrap(), (PortAddress::from(11), 4));
+
+        let mapper = PixelMapper::new(ColorOrder::Bgr, PortAddress::from(0));
+        assert_eq!(mapper.pixels_per_universe(), 170);
+        assert_eq!(
+            mapper.map(&[1, 2, 3]).unwrap()[0].data.as_ref(),
+            &vec![3, 2, 1]
+        );
+        assert!(mapper.map(&[]).unwrap().is_empty());
+        assert_eq!(
+            mapper.with_pixels_per_universe(1000).pixels_per_universe(),
+            170
+        );
+    }
+
+    #[test]
+    fn last_port_address() {
+        let start = PortAddress::try_from(32_767u16).unwrap();
+        let mapper = PixelMapper::new(ColorOrder::Rgb, start).with_pixels_per_universe(1);
+        assert_eq!(mapper.map(&[0; 3]).unwrap().len(), 1);
+        assert_eq!(
+            mapper.map(&[0; 6]).unwrap_err(),
+            Error::InvalidPortAddress(32_768)
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use crate::{DiscoveryManager, Poll};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Decides when the network should be polled.
+///
+/// The Art-Net specification asks controllers to send an ArtPoll every 2.5 to 3 seconds. The interval is picked at random within that range for every poll, so controllers that started at the same time do not keep polling at the same moment.
+///
+/// Call `tick` regularly, or at `next_deadline`, and broadcast the returned `Poll`. `tick_discovery` also sends the poll, and records it in a [DiscoveryManager](struct.DiscoveryManager.html) once it was sent, so it can detect the nodes that did not reply in time. Alternatively, `spawn` calls `tick` from a background thread.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::{Duration, Instant};
+///
+/// let mut scheduler = PollScheduler::new();
+/// let mut discovery = DiscoveryManager::new();
+/// let start = Instant::now();
+///
+/// // The first poll is sent immediately
+/// let sent = scheduler.tick_discovery(&mut discovery, start, |_poll| {
+///     // Broadcast the poll here
+///     Ok::<(), std::io::Error>(())
+/// });
+/// assert!(sent.unwrap());
+/// assert!(scheduler.tick(start + Duration::from_millis(2000)).is_none());
+///
+/// let deadline = scheduler.next_deadline(start);
+/// assert!(deadline >= start + Duration::from_millis(2500));
+/// assert!(deadline <= start + Duration::from_millis(3000));
+/// assert!(scheduler.tick(deadline).is_some());
+/// ```
+#[derive(Debug)]
+pub struct PollScheduler {
+    poll: Poll,
+    min_interval: Duration,
+    max_interval: Duration,
+    next: Option<Instant>,
+    /// The state of the xorshift generator of the intervals
+    random: u64,
+}
+
+impl Default for PollScheduler {
+    fn default() -> PollScheduler {
+        PollScheduler::new()
+    }
+}
+
+impl PollScheduler {
+    /// The shortest time between two polls, as given by the Art-Net specification
+    pub const DEFAULT_MIN_INTERVAL: Duration = Duration::from_millis(2500);
+
+    /// The longest time between two polls, as given by the Art-Net specification
+    pub const DEFAULT_MAX_INTERVAL: Duration = Duration::from_millis(3000);
+
+    /// Create a scheduler with the interval of the Art-Net specification, that sends `Poll::default()`
+    pub fn new() -> PollScheduler {
+        PollScheduler::with_interval(
+            PollScheduler::DEFAULT_MIN_INTERVAL,
+            PollScheduler::DEFAULT_MAX_INTERVAL,
+        )
+    }
+
+    /// Create a scheduler that waits between `min` and `max` after every poll. Use the same value twice for a fixed interval.
+    pub fn with_interval(min: Duration, max: Duration) -> PollScheduler {
+        let seed = RandomState::new().build_hasher().finish();
+        PollScheduler {
+            poll: Poll::default(),
+            min_interval: min,
+            max_interval: max.max(min),
+            next: None,
+            // Xorshift can not start at zero
+            random: seed | 1,
+        }
+    }
+
+    /// Change the interval. This applies after the next poll.
+    pub fn set_interval(&mut self, min: Duration, max: Duration) {
+        self.min_interval = min;
+        self.max_interval = max.max(min);
+    }
+
+    /// Change the poll that is sent, e.g. the poll of a [DiagnosticsCollector](struct.DiagnosticsCollector.html)
+    pub fn set_poll(&mut self, poll: Poll) {
+        self.poll = poll;
+    }
+
+    /// Send the next poll at the next `tick`, e.g. to refresh the list of nodes after a network change
+    pub fn poll_now(&mut self) {
+        self.next = None;
+    }
+
+    /// Return the poll that should be sent now, if the interval passed
+    pub fn tick(&mut self, now: Instant) -> Option<Poll> {
+        if self.next.is_some_and(|next| now < next) {
+            return None;
+        }
+        let interval = self.next_interval();
+        self.next = Some(now + interval);
+        Some(self.poll.clone())
+    }
+
+    /// Like `tick`, but passes the poll that should be sent to `send`, and records it in the discovery manager when `send` succeeds. A poll that could not be sent is not recorded, so the nodes do not expire because they did not reply to it.
+    ///
+    /// Returns if a poll was sent.
+    pub fn tick_discovery<E>(
+        &mut self,
+        discovery: &mut DiscoveryManager,
+        now: Instant,
+        send: impl FnOnce(Poll) -> Result<(), E>,
+    ) -> Result<bool, E> {
+        let poll = match self.tick(now) {
+            Some(poll) => poll,
+            None => return Ok(false),
+        };
+        send(poll)?;
+        discovery.poll_sent(now);
+        Ok(true)
+    }
+
+    /// When `tick` should be called next. This is `now` if no poll was sent yet.
+    pub fn next_deadline(&self, now: Instant) -> Instant {
+        self.next.unwrap_or(now)
+    }
+
+    /// Call `tick` from a background thread, and pass every poll that should be sent to `send`.
+    ///
+    /// The thread stops when every other `Arc` of the scheduler is dropped.
+    pub fn spawn<F>(scheduler: &Arc<Mutex<PollScheduler>>, mut send: F) -> JoinHandle<()>
+    where
+        F: FnMut(Poll) + Send + 'static,
+    {
+        let scheduler = Arc::downgrade(scheduler);
+        thread::spawn(move || {
+            while let Some(scheduler) = scheduler.upgrade() {
+                let now = Instant::now();
+                let (poll, deadline) = {
+                    let mut scheduler = match scheduler.lock() {
+                        Ok(scheduler) => scheduler,
+                        Err(_) => return,
+                    };
+                    let poll = scheduler.tick(now);
+                    (poll, scheduler.next_deadline(now))
+                };
+                // Don't keep the scheduler alive while sleeping
+                drop(scheduler);
+
+                if let Some(poll) = poll {
+                    send(poll);
+                }
+                thread::sleep(deadline.saturating_duration_since(Instant::now()));
+            }
+        })
+    }
+
+    /// A random interval between the minimum and maximum interval
+    fn next_interval(&mut self) -> Duration {
+        self.random ^= self.random << 13;
+        self.random ^= self.random >> 7;
+        self.random ^= self.random << 17;
+        let range = (self.max_interval - self.min_interval).as_nanos() as u64;
+        let jitter = self.random % range.saturating_add(1);
+        self.min_interval + Duration::from_nanos(jitter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArtTalkToMe;
+    use std::sync::mpsc;
+
+    #[test]
+    fn jitter() {
+        let mut scheduler = PollScheduler::new();
+        let mut now = Instant::now();
+        let mut intervals = Vec::new();
+        assert!(scheduler.tick(now).is_some());
+        for _ in 0..100 {
+            let next = scheduler.next_deadline(now);
+            intervals.push(next - now);
+            assert!(scheduler.tick(next - Duration::from_millis(1)).is_none());
+            assert!(scheduler.tick(next).is_some());
+            now = next;
+        }
+        assert!(intervals
+            .iter()
+            .all(|i| *i >= PollScheduler::DEFAULT_MIN_INTERVAL
+                && *i <= PollScheduler::DEFAULT_MAX_INTERVAL));
+        assert!(intervals.iter().any(|i| *i != intervals[0]));
+
+        scheduler.set_interval(Duration::from_secs(1), Duration::from_secs(1));
+        scheduler.poll_now();
+        let mut poll = Poll::default();
+        poll.talk_to_me |= ArtTalkToMe::EMIT_CHANGES;
+        scheduler.set_poll(poll);
+        let sent = scheduler.tick(now).unwrap();
+        assert!(sent.talk_to_me.contains(ArtTalkToMe::EMIT_CHANGES));
+        assert_eq!(scheduler.next_deadline(now), now + Duration::from_secs(1));
+    }
+
+    #[test]
+    fn tick_discovery() {
+        let mut scheduler = PollScheduler::new();
+        let mut discovery = DiscoveryManager::new();
+        let start = Instant::now();
+        discovery.handle(&crate::PollReply::default().into(), start);
+
+        // A poll that could not be sent does not expire the node
+        let failed = scheduler.tick_discovery(&mut discovery, start, |_| Err(()));
+        assert_eq!(failed, Err(()));
+        assert!(discovery.expire(start + Duration::from_secs(10)).is_empty());
+
+        let next = scheduler.next_deadline(start);
+        assert_eq!(
+            scheduler.tick_discovery(&mut discovery, next, |_| Ok::<(), ()>(())),
+            Ok(true)
+        );
+        assert_eq!(
+            scheduler.tick_discovery(&mut discovery, next, |_| Ok::<(), ()>(())),
+            Ok(false)
+        );
+        assert_eq!(discovery.expire(next + Duration::from_secs(10)).len(), 1);
+    }
+
+    #[test]
+    fn spawn() {
+        let scheduler = Arc::new(Mutex::new(PollScheduler::with_interval(
+            Duration::from_millis(10),
+            Duration::from_millis(20),
+        )));
+        let (sender, receiver) = mpsc::channel();
+        let thread = PollScheduler::spawn(&scheduler, move |poll| {
+            let _ = sender.send(poll);
+        });
+        for _ in 0..3 {
+            assert!(receiver.recv_timeout(Duration::from_secs(5)).is_ok());
+        }
+        drop(scheduler);
+        thread.join().unwrap();
+    }
+}
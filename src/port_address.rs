@@ -1,9 +1,9 @@
-use std::convert::TryFrom;
-use std::io::Cursor;
+use core::convert::TryFrom;
 
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-
-use crate::{convert::Convertable, Error, Result};
+use crate::{
+    convert::{Convertable, Cursor, WriteBuffer},
+    Error, Result,
+};
 
 /// A `PortAddress` is an unsigned integer from 0 to 32_767 (15-bit).
 ///
@@ -19,8 +19,30 @@ use crate::{convert::Convertable, Error, Result};
 /// let better_not = PortAddress::from(0);
 /// ```
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "u16", into = "u16")
+)]
 pub struct PortAddress(u16);
 
+impl PortAddress {
+    /// Combine the Net (bits 14-8) and the Sub-Net and Universe (bits 7-0) into a Port-Address. Bit 7 of `net` is ignored.
+    pub fn from_parts(net: u8, sub_uni: u8) -> PortAddress {
+        PortAddress((u16::from(net & 0x7F) << 8) | u16::from(sub_uni))
+    }
+
+    /// Bits 14-8 of the Port-Address
+    pub fn net(self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    /// Bits 7-0 of the Port-Address, the Sub-Net and Universe
+    pub fn sub_uni(self) -> u8 {
+        self.0 as u8
+    }
+}
+
 // basic support for u8 literals
 impl From<u8> for PortAddress {
     fn from(value: u8) -> Self {
@@ -52,6 +74,13 @@ impl TryFrom<i32> for PortAddress {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for PortAddress {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(PortAddress(u.int_in_range(0..=32_767)?))
+    }
+}
+
 impl From<PortAddress> for u16 {
     fn from(value: PortAddress) -> Self {
         value.0
@@ -59,17 +88,18 @@ impl From<PortAddress> for u16 {
 }
 
 impl<T> Convertable<T> for PortAddress {
-    fn from_cursor(cursor: &mut Cursor<&[u8]>) -> Result<Self> {
-        let number = cursor
-            .read_u16::<LittleEndian>()
-            .map_err(Error::CursorEof)?;
+    fn from_cursor(cursor: &mut Cursor) -> Result<Self> {
+        let number = cursor.read_u16_le()?;
         PortAddress::try_from(number)
     }
 
-    fn write_to_buffer(&self, buffer: &mut Vec<u8>, _context: &T) -> Result<()> {
-        buffer
-            .write_u16::<LittleEndian>(self.0)
-            .map_err(Error::CursorEof)
+    fn write_to_buffer(&self, buffer: &mut dyn WriteBuffer, _context: &T) -> Result<()> {
+        buffer.extend_from_slice(&self.0.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialized_len(&self, _context: &T) -> usize {
+        2
     }
 
     #[cfg(test)]
@@ -114,4 +144,12 @@ mod tests {
         let _f: PortAddress = 256.try_into().unwrap();
         let _f: PortAddress = 32_767u16.try_into().unwrap();
     }
+
+    #[test]
+    fn parts() {
+        let address = PortAddress::from_parts(0x85, 0x21);
+        assert_eq!(u16::from(address), 0x0521);
+        assert_eq!(address.net(), 0x05);
+        assert_eq!(address.sub_uni(), 0x21);
+    }
 }
@@ -0,0 +1,844 @@
+//! Multi-line, human-readable reports of commands, for CLI monitors and bug reports.
+//!
+//! Where `Debug` shows the raw fields and `Display` a one-line summary, a [Report](struct.Report.html) shows every field on its own line and decodes it: status bits are listed by name, Port-Addresses are split into their net, sub-net and universe, and names are shown as text.
+//!
+//! ```rust
+//! use artnet_protocol::pretty::Report;
+//! use artnet_protocol::*;
+//! use std::convert::TryFrom;
+//!
+//! let port_address = PortAddress::try_from(0x123u16).unwrap();
+//! let command = ArtCommand::Output(Output::new(port_address, vec![255, 0, 128]));
+//! let report = Report(&command).to_string();
+//! assert!(report.starts_with("ArtDmx (0x5000) to Port-Address 291"));
+//! assert!(report.contains("port address:         291 (net 1, sub-net 2, universe 3)"));
+//! assert!(report.contains("001  ff 00 80"));
+//! ```
+use crate::{ArtCommand, ArtTalkToMe, PollReply, PortAddress};
+use alloc::string::String;
+use core::fmt::{self, Display, Formatter, Write};
+
+/// The column at which the values start
+const VALUE_COLUMN: usize = 22;
+
+/// The amount of bytes that are shown on a single line
+const BYTES_PER_LINE: usize = 16;
+
+/// A report of the given command. Write it with `{}`, or call `to_string`.
+///
+/// The first line is the `Display` summary of the command, every field follows on its own indented line. Commands that are not decoded by this module show the raw bytes of their fields.
+#[derive(Debug, Clone, Copy)]
+pub struct Report<'a>(pub &'a ArtCommand);
+
+impl Display for Report<'_> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        writeln!(fmt, "{}", self.0)?;
+        let mut report = Fields { fmt };
+        match self.0 {
+            ArtCommand::Poll(poll) => {
+                report.version(poll.version)?;
+                report.field("talk to me", TalkToMe(poll.talk_to_me.bits()))?;
+                report.field("diagnostics priority", Priority(poll.diagnostics_priority))
+            }
+            ArtCommand::PollReply(reply) => poll_reply(&mut report, reply),
+            ArtCommand::Output(output) => {
+                report.version(output.version)?;
+                report.field("sequence", output.sequence)?;
+                report.field("physical port", output.physical)?;
+                report.field("port address", Universe(output.port_address))?;
+                report.data(output.data.as_ref())
+            }
+            ArtCommand::Sync(sync) => {
+                report.version(sync.version)?;
+                report.field("aux", Hex(&[sync.aux1, sync.aux2]))
+            }
+            #[cfg(feature = "dmx")]
+            ArtCommand::Nzs(nzs) => {
+                report.version(nzs.version)?;
+                report.field("sequence", nzs.sequence)?;
+                report.field("start code", format_args!("0x{:02X}", nzs.start_code))?;
+                report.field("port address", Universe(nzs.port_address))?;
+                report.data(nzs.data.as_ref())
+            }
+            #[cfg(feature = "dmx")]
+            ArtCommand::Input(input) => {
+                report.version(input.version)?;
+                report.field("bind index", input.bind_index)?;
+                report.field("ports", input.num_ports())?;
+                for port in 0..input.input.len() {
+                    report.field(
+                        format_args!("port {}", port + 1),
+                        if input.is_disabled(port) {
+                            "disabled"
+                        } else {
+                            "enabled"
+                        },
+                    )?;
+                }
+                Ok(())
+            }
+            #[cfg(feature = "dmx")]
+            ArtCommand::Address(address) => {
+                use crate::AddressSwitch;
+
+                report.version(address.version)?;
+                report.field("bind index", address.bind_index)?;
+                report.field(
+                    "net",
+                    format_args!("{:?}", AddressSwitch::from(address.net_switch)),
+                )?;
+                report.field(
+                    "sub-net",
+                    format_args!("{:?}", AddressSwitch::from(address.sub_switch)),
+                )?;
+                for (port, (swin, swout)) in address.swin.iter().zip(&address.swout).enumerate() {
+                    report.field(
+                        format_args!("port {}", port + 1),
+                        format_args!(
+                            "input {:?}, output {:?}",
+                            AddressSwitch::from(*swin),
+                            AddressSwitch::from(*swout)
+                        ),
+                    )?;
+                }
+                report.name("short name", &address.short_name)?;
+                report.name("long name", &address.long_name)?;
+                match address.acn_priority() {
+                    Some(priority) => report.field("sACN priority", priority)?,
+                    None => report.field("sACN priority", "no change")?,
+                }
+                report.field("command", format_args!("{:?}", address.address_command()))
+            }
+            #[cfg(feature = "dmx")]
+            ArtCommand::DiagData(diag) => {
+                report.version(diag.version)?;
+                report.field("priority", Priority(diag.priority))?;
+                report.field("logical port", diag.logical_port)?;
+                report.field("text", format_args!("{:?}", diag.text()))
+            }
+            #[cfg(feature = "dmx")]
+            ArtCommand::Command(command) => {
+                report.version(command.version)?;
+                report.field(
+                    "manufacturer",
+                    Esta(command.esta_manufacturer(), crate::TextCommand::ESTA_ANY),
+                )?;
+                for entry in command.entries() {
+                    report.field(entry.key(), format_args!("{:?}", entry.value()))?;
+                }
+                Ok(())
+            }
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodRequest(request) => {
+                report.version(request.version)?;
+                report.field("command", command_name(request.command, &[(0, "TodFull")]))?;
+                for port_address in request.port_addresses() {
+                    report.field("port address", Universe(port_address))?;
+                }
+                Ok(())
+            }
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodData(tod) => {
+                report.version(tod.version)?;
+                report.field("RDM version", tod.rdm_version)?;
+                report.field("physical port", tod.port)?;
+                report.field("bind index", tod.bind_index)?;
+                report.field("port address", Universe(tod.port_address()))?;
+                report.field(
+                    "response",
+                    command_name(tod.command_response, &[(0x00, "TodFull"), (0xFF, "TodNak")]),
+                )?;
+                report.field(
+                    "devices",
+                    format_args!(
+                        "{} in total, block {} has {}",
+                        tod.uid_total(),
+                        tod.block_count,
+                        tod.uids.len()
+                    ),
+                )?;
+                for uid in &tod.uids {
+                    report.field("uid", uid)?;
+                }
+                Ok(())
+            }
+            #[cfg(feature = "rdm")]
+            ArtCommand::TodControl(control) => {
+                report.version(control.version)?;
+                report.field("port address", Universe(control.port_address()))?;
+                report.field(
+                    "command",
+                    command_name(
+                        control.command,
+                        &[
+                            (0x00, "None"),
+                            (0x01, "Flush"),
+                            (0x02, "End"),
+                            (0x03, "IncOn"),
+                            (0x04, "IncOff"),
+                        ],
+                    ),
+                )
+            }
+            #[cfg(feature = "rdm")]
+            ArtCommand::Rdm(rdm) => {
+                use crate::RdmFrame;
+
+                report.version(rdm.version)?;
+                report.field("RDM version", rdm.rdm_version)?;
+                report.field("port address", Universe(rdm.port_address()))?;
+                report.field("command", command_name(rdm.command, &[(0, "ArProcess")]))?;
+                match rdm.message::<RdmFrame>() {
+                    Ok(frame) => {
+                        report.field("destination", frame.destination)?;
+                        report.field("source", frame.source)?;
+                        report.field("transaction", frame.transaction)?;
+                        let port = if frame.is_response() {
+                            "response type"
+                        } else {
+                            "port id"
+                        };
+                        report.field(port, frame.port_id)?;
+                        report.field("message count", frame.message_count)?;
+                        report.field("sub-device", frame.sub_device)?;
+                        report.field(
+                            "command class",
+                            command_name(
+                                frame.command_class,
+                                &[
+                                    (0x10, "Discovery"),
+                                    (0x11, "DiscoveryResponse"),
+                                    (0x20, "Get"),
+                                    (0x21, "GetResponse"),
+                                    (0x30, "Set"),
+                                    (0x31, "SetResponse"),
+                                ],
+                            ),
+                        )?;
+                        report
+                            .field("parameter id", format_args!("0x{:04X}", frame.parameter_id))?;
+                        report.bytes("parameter data", &frame.parameter_data)
+                    }
+                    Err(e) => {
+                        report.field("packet", format_args!("invalid: {}", e))?;
+                        report.bytes("packet", &rdm.packet)
+                    }
+                }
+            }
+            #[cfg(feature = "firmware")]
+            ArtCommand::FirmwareMaster(master) => {
+                report.version(master.version)?;
+                report.field(
+                    "block type",
+                    command_name(
+                        master.block_type,
+                        &[
+                            (0x00, "FirmFirst"),
+                            (0x01, "FirmCont"),
+                            (0x02, "FirmLast"),
+                            (0x03, "UbeaFirst"),
+                            (0x04, "UbeaCont"),
+                            (0x05, "UbeaLast"),
+                        ],
+                    ),
+                )?;
+                report.field("block id", master.block_id)?;
+                report.field(
+                    "firmware length",
+                    format_args!("{} words", master.firmware_length()),
+                )?;
+                report.field("data", format_args!("{} bytes", master.data.len()))
+            }
+            #[cfg(feature = "firmware")]
+            ArtCommand::FirmwareReply(reply) => {
+                report.version(reply.version)?;
+                report.field(
+                    "reply",
+                    command_name(
+                        reply.reply_type,
+                        &[
+                            (0x00, "FirmBlockGood"),
+                            (0x01, "FirmAllGood"),
+                            (0xFF, "FirmFail"),
+                        ],
+                    ),
+                )
+            }
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileTnMaster(master) => {
+                report.version(master.version)?;
+                report.field(
+                    "block type",
+                    command_name(
+                        master.block_type,
+                        &[(0x00, "FileFirst"), (0x01, "FileCont"), (0x02, "FileLast")],
+                    ),
+                )?;
+                report.field("block id", master.block_id)?;
+                report.field("name", format_args!("{:?}", master.name()))?;
+                report.field(
+                    "file length",
+                    format_args!("{} bytes", master.file_length()),
+                )?;
+                report.field(
+                    "checksum",
+                    if master.is_checksum_valid() {
+                        "valid"
+                    } else {
+                        "invalid"
+                    },
+                )?;
+                report.field("data", format_args!("{} bytes", master.data.len()))
+            }
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileFnMaster(master) => {
+                report.version(master.version)?;
+                report.field(
+                    "block type",
+                    command_name(
+                        master.block_type,
+                        &[
+                            (0x00, "FileFirst"),
+                            (0x01, "FileCont"),
+                            (0x02, "FileLast"),
+                            (0x03, "FileRequest"),
+                        ],
+                    ),
+                )?;
+                report.field("block id", master.block_id)?;
+                report.field("name", format_args!("{:?}", master.name()))?;
+                report.field(
+                    "file length",
+                    format_args!("{} bytes", master.file_length()),
+                )?;
+                report.field(
+                    "checksum",
+                    if master.is_checksum_valid() {
+                        "valid"
+                    } else {
+                        "invalid"
+                    },
+                )?;
+                report.field("data", format_args!("{} bytes", master.data.len()))
+            }
+            #[cfg(feature = "nonstandard-file-transfer")]
+            ArtCommand::FileFnReply(reply) => {
+                report.version(reply.version)?;
+                report.field(
+                    "reply",
+                    command_name(
+                        reply.reply_type,
+                        &[
+                            (0x00, "FileBlockGood"),
+                            (0x01, "FileAllGood"),
+                            (0xFF, "FileFail"),
+                        ],
+                    ),
+                )?;
+                report.field("block id", reply.block_id)
+            }
+            #[cfg(feature = "media")]
+            ArtCommand::OpTimeCode(time_code) => {
+                report.version(time_code.version)?;
+                report.field("stream", time_code.stream_id)?;
+                report.field("time", time_code)?;
+                match time_code.frame_rate() {
+                    Some(rate) => report.field(
+                        "frame rate",
+                        format_args!("{:?}, {} fps", rate, rate.frames_per_second()),
+                    ),
+                    None => {
+                        report.field("frame rate", format_args!("unknown ({})", time_code.kind))
+                    }
+                }
+            }
+            #[cfg(feature = "media")]
+            ArtCommand::OpTimeSync(time_sync) => {
+                const WEEKDAYS: [&str; 7] = [
+                    "Sunday",
+                    "Monday",
+                    "Tuesday",
+                    "Wednesday",
+                    "Thursday",
+                    "Friday",
+                    "Saturday",
+                ];
+                report.version(time_sync.version)?;
+                report.field(
+                    "date",
+                    format_args!(
+                        "{:04}-{:02}-{:02}, {}",
+                        1900 + u32::from(u16::from_be_bytes(time_sync.year)),
+                        u32::from(time_sync.month) + 1,
+                        time_sync.day,
+                        WEEKDAYS
+                            .get(usize::from(time_sync.weekday))
+                            .unwrap_or(&"unknown weekday")
+                    ),
+                )?;
+                report.field(
+                    "time",
+                    format_args!(
+                        "{:02}:{:02}:{:02}",
+                        time_sync.hours, time_sync.minutes, time_sync.seconds
+                    ),
+                )?;
+                report.field(
+                    "daylight saving",
+                    if time_sync.is_dst() { "yes" } else { "no" },
+                )
+            }
+            // Show the raw fields of the other commands
+            #[allow(unreachable_patterns)]
+            command => {
+                let mut buffer = alloc::vec::Vec::new();
+                if command.write_to(&mut buffer).is_err() {
+                    return report.field("fields", "could not be serialized");
+                }
+                for (name, range) in command.field_layout() {
+                    let name = name.rsplit("::").next().unwrap_or(name);
+                    let start = range.start + crate::command::HEADER_LENGTH;
+                    let end = range.end + crate::command::HEADER_LENGTH;
+                    report.bytes(name, &buffer[start..end])?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn poll_reply(report: &mut Fields, reply: &PollReply) -> fmt::Result {
+    report.field("address", format_args!("{}:{}", reply.address, reply.port))?;
+    report.field("firmware version", u16::from_be_bytes(reply.version))?;
+    report.field(
+        "net, sub-net",
+        format_args!(
+            "{}, {}",
+            reply.port_address[0] & 0x7F,
+            reply.port_address[1] & 0x0F
+        ),
+    )?;
+    report.field(
+        "oem",
+        format_args!("0x{:04X}", u16::from_be_bytes(reply.oem)),
+    )?;
+    report.field("ubea version", reply.ubea_version)?;
+    report.field("manufacturer", Esta(reply.esta_code, 0))?;
+    report.name("short name", &reply.short_name)?;
+    report.name("long name", &reply.long_name)?;
+    report.name("node report", &reply.node_report)?;
+    report.field("style", CommandName(reply.style, reply.style_name()))?;
+    report.field("mac", Mac(reply.mac))?;
+    report.field(
+        "bind",
+        format_args!(
+            "{}, index {}",
+            core::net::Ipv4Addr::from(reply.bind_ip),
+            reply.bind_index
+        ),
+    )?;
+
+    // Bits 7-6 are the indicator state, bits 5-4 who programmed the Port-Address
+    let indicators = ["unknown", "locate", "mute", "normal"][usize::from(reply.status_1 >> 6)];
+    let authority =
+        ["unknown", "front panel", "network", "not used"][usize::from(reply.status_1 >> 4 & 0x03)];
+    report.field(
+        "status 1",
+        format_args!(
+            "indicators {}, Port-Address set by {}, {}",
+            indicators,
+            authority,
+            Flags(
+                reply.status_1 & 0x07,
+                &[
+                    (0x04, "booted from ROM"),
+                    (0x02, "RDM"),
+                    (0x01, "UBEA present"),
+                ]
+            )
+        ),
+    )?;
+    report.field(
+        "status 2",
+        Flags(
+            reply.status_2,
+            &[
+                (0x80, "RDM over ArtCommand"),
+                (0x40, "switchable output style"),
+                (0x20, "squawking"),
+                (0x10, "switchable to sACN"),
+                (0x08, "15-bit Port-Address"),
+                (0x04, "DHCP capable"),
+                (0x02, "configured by DHCP"),
+                (0x01, "web configuration"),
+            ],
+        ),
+    )?;
+    report.field(
+        "status 3",
+        format_args!(
+            "failsafe {:?}, {}",
+            reply.failsafe_state(),
+            Flags(
+                reply.status_3 & 0x3F,
+                &[
+                    (0x20, "failover"),
+                    (0x10, "LLRP"),
+                    (0x08, "switchable input and output"),
+                    (0x04, "RDMnet"),
+                    (0x02, "background queue"),
+                    (0x01, "background discovery control"),
+                ]
+            )
+        ),
+    )?;
+    report.field("default responder", reply.default_responder)?;
+    match u16::from_be_bytes(reply.refresh_rate) {
+        0 => report.field("refresh rate", "up to 44 Hz")?,
+        rate => report.field("refresh rate", format_args!("{} Hz", rate))?,
+    }
+    report.field(
+        "background queue",
+        format_args!(
+            "{:?}",
+            crate::BackgroundQueuePolicy::from(reply.background_queue_policy)
+        ),
+    )?;
+
+    let ports = usize::from(reply.num_ports[1]).min(4);
+    for port in 0..ports {
+        let port_type = reply.port_types[port];
+        let protocol = command_name(
+            port_type & 0x3F,
+            &[
+                (0, "DMX512"),
+                (1, "MIDI"),
+                (2, "Avab"),
+                (3, "Colortran CMX"),
+                (4, "ADB 62.5"),
+                (5, "Art-Net"),
+                (6, "DALI"),
+            ],
+        );
+        report.field(format_args!("port {}", port + 1), protocol)?;
+        if let Some(address) = reply.input_port_address(port) {
+            report.field(
+                "  input",
+                format_args!(
+                    "{}, {}",
+                    Universe(address),
+                    Flags(
+                        reply.good_input[port],
+                        &[
+                            (0x80, "data received"),
+                            (0x40, "test packets"),
+                            (0x20, "SIPs"),
+                            (0x10, "text packets"),
+                            (0x08, "disabled"),
+                            (0x04, "errors"),
+                            (0x01, "sent as sACN"),
+                        ]
+                    )
+                ),
+            )?;
+        }
+        if let Some(address) = reply.output_port_address(port) {
+            report.field(
+                "  output",
+                format_args!(
+                    "{}, {}",
+                    Universe(address),
+                    Flags(
+                        reply.good_output[port],
+                        &[
+                            (0x80, "data transmitted"),
+                            (0x40, "test packets"),
+                            (0x20, "SIPs"),
+                            (0x10, "text packets"),
+                            (0x08, "merging"),
+                            (0x04, "short circuit"),
+                            (0x02, "LTP merge"),
+                            (0x01, "sACN"),
+                        ]
+                    )
+                ),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the indented fields of a report
+struct Fields<'a, 'b> {
+    fmt: &'a mut Formatter<'b>,
+}
+
+impl Fields<'_, '_> {
+    fn field(&mut self, name: impl Display, value: impl Display) -> fmt::Result {
+        let mut label = String::new();
+        let _ = write!(label, "{}:", name);
+        writeln!(
+            self.fmt,
+            "  {:<width$}{}",
+            label,
+            value,
+            width = VALUE_COLUMN
+        )
+    }
+
+    fn version(&mut self, version: [u8; 2]) -> fmt::Result {
+        self.field("version", u16::from_be_bytes(version))
+    }
+
+    fn name(&mut self, name: &str, bytes: &[u8]) -> fmt::Result {
+        self.field(
+            name,
+            format_args!("{:?}", crate::command::null_terminated(bytes)),
+        )
+    }
+
+    /// A short field of raw bytes
+    fn bytes(&mut self, name: &str, bytes: &[u8]) -> fmt::Result {
+        if bytes.len() <= BYTES_PER_LINE {
+            self.field(name, Hex(bytes))
+        } else {
+            self.field(name, format_args!("{} bytes", bytes.len()))?;
+            self.rows(bytes, 0)
+        }
+    }
+
+    /// The DMX512 data of a universe, with the channel number of the first byte on every line
+    fn data(&mut self, data: &[u8]) -> fmt::Result {
+        let active = data.iter().filter(|b| **b != 0).count();
+        self.field(
+            "data",
+            format_args!("{} channels, {} non-zero", data.len(), active),
+        )?;
+        self.rows(data, 1)
+    }
+
+    fn rows(&mut self, bytes: &[u8], first: usize) -> fmt::Result {
+        for (row, chunk) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+            writeln!(
+                self.fmt,
+                "    {:03}  {}",
+                first + row * BYTES_PER_LINE,
+                Hex(chunk)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+struct Hex<'a>(&'a [u8]);
+
+impl Display for Hex<'_> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if i > 0 {
+                fmt.write_char(' ')?;
+            }
+            write!(fmt, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// The names of the bits that are set, or `none`
+struct Flags<'a>(u8, &'a [(u8, &'a str)]);
+
+impl Display for Flags<'_> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        let mut first = true;
+        for (bit, name) in self.1 {
+            if self.0 & bit != 0 {
+                if !first {
+                    fmt.write_str(", ")?;
+                }
+                fmt.write_str(name)?;
+                first = false;
+            }
+        }
+        if first {
+            fmt.write_str("none")?;
+        }
+        Ok(())
+    }
+}
+
+/// A Port-Address with its net, sub-net and universe
+struct Universe(PortAddress);
+
+impl Display for Universe {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "{} (net {}, sub-net {}, universe {})",
+            u16::from(self.0),
+            self.0.net(),
+            self.0.sub_uni() >> 4,
+            self.0.sub_uni() & 0x0F
+        )
+    }
+}
+
+struct TalkToMe(u8);
+
+impl Display for TalkToMe {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        Flags(
+            self.0,
+            &[
+                (ArtTalkToMe::ENABLE_VLC.bits(), "VLC"),
+                (
+                    ArtTalkToMe::UNICAST_DIAGNOSTICS.bits(),
+                    "unicast diagnostics",
+                ),
+                (ArtTalkToMe::ENABLE_DIAGNOSTICS.bits(), "diagnostics"),
+                (ArtTalkToMe::EMIT_CHANGES.bits(), "reply on change"),
+            ],
+        )
+        .fmt(fmt)
+    }
+}
+
+/// A diagnostics priority with its name
+struct Priority(u8);
+
+impl Display for Priority {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        let name = match self.0 {
+            0x10 => "low",
+            0x40 => "medium",
+            0x80 => "high",
+            0xE0 => "critical",
+            0xF0 => "volatile",
+            _ => "unknown",
+        };
+        write!(fmt, "0x{:02X} ({})", self.0, name)
+    }
+}
+
+/// An ESTA manufacturer code, with its initials if they are printable
+struct Esta(u16, u16);
+
+impl Display for Esta {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "0x{:04X}", self.0)?;
+        let [high, low] = self.0.to_be_bytes();
+        if self.0 == self.1 && self.1 != 0 {
+            fmt.write_str(" (any)")
+        } else if high.is_ascii_graphic() && low.is_ascii_graphic() {
+            write!(fmt, " ({}{})", char::from(high), char::from(low))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+struct Mac([u8; 6]);
+
+impl Display for Mac {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if i > 0 {
+                fmt.write_char(':')?;
+            }
+            write!(fmt, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// The name of a command value, or the value itself if it is not known
+fn command_name<'a>(value: u8, names: &'a [(u8, &'a str)]) -> CommandName<'a> {
+    CommandName(
+        value,
+        names.iter().find(|(v, _)| *v == value).map(|(_, n)| *n),
+    )
+}
+
+struct CommandName<'a>(u8, Option<&'a str>);
+
+impl Display for CommandName<'_> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        match self.1 {
+            Some(name) => write!(fmt, "{} (0x{:02X})", name, self.0),
+            None => write!(fmt, "unknown (0x{:02X})", self.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Poll, PollReply};
+    use alloc::boxed::Box;
+
+    #[test]
+    fn poll() {
+        let mut poll = Poll::default();
+        poll.talk_to_me |= ArtTalkToMe::ENABLE_DIAGNOSTICS | ArtTalkToMe::EMIT_CHANGES;
+        assert_eq!(
+            Report(&poll.into()).to_string(),
+            "ArtPoll (0x2000), talk to me ArtTalkToMe(ENABLE_DIAGNOSTICS | EMIT_CHANGES), diagnostics priority 0x80
+  version:              14
+  talk to me:           diagnostics, reply on change
+  diagnostics priority: 0x80 (high)
+"
+        );
+    }
+
+    #[test]
+    fn poll_reply() {
+        let mut reply = PollReply {
+            address: [10, 0, 0, 5].into(),
+            port_address: [1, 2],
+            esta_code: 0x414C,
+            num_ports: [0, 2],
+            port_types: [0x80, 0xC5, 0, 0],
+            good_output: [0x80, 0x0C, 0, 0],
+            swin: [0, 4, 0, 0],
+            swout: [3, 4, 0, 0],
+            status_1: 0xE2,
+            ..PollReply::default()
+        };
+        reply.short_name[..4].copy_from_slice(b"Node");
+        let report = Report(&ArtCommand::PollReply(Box::new(reply))).to_string();
+        for line in [
+            "  manufacturer:         0x414C (AL)",
+            "  short name:           \"Node\"",
+            "  status 1:             indicators normal, Port-Address set by network, RDM",
+            "  port 1:               DMX512 (0x00)",
+            "    output:             291 (net 1, sub-net 2, universe 3), data transmitted",
+            "  port 2:               Art-Net (0x05)",
+            "    input:              292 (net 1, sub-net 2, universe 4), none",
+            "    output:             292 (net 1, sub-net 2, universe 4), merging, short circuit",
+        ] {
+            assert!(report.lines().any(|l| l == line), "{} in {}", line, report);
+        }
+    }
+
+    #[test]
+    fn output() {
+        let mut data = alloc::vec![0; 20];
+        data[16] = 0xFF;
+        let command = ArtCommand::Output(crate::Output::new(1.into(), data));
+        let report = Report(&command).to_string();
+        assert!(report.contains(
+            "  data:                 20 channels, 1 non-zero
+    001  00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+    017  ff 00 00 00
+"
+        ));
+    }
+
+    #[cfg(feature = "video")]
+    #[test]
+    fn raw() {
+        assert_eq!(
+            Report(&ArtCommand::VideoSetup).to_string(),
+            "ArtVideoSetup (0xA010)\n"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{Output, PortAddress};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// The state of a single universe of a [RateLimiter](struct.RateLimiter.html)
+#[derive(Debug)]
+struct Universe {
+    last_sent: Instant,
+    /// The newest frame that was held back, which replaces any older one
+    pending: Option<Output>,
+}
+
+/// Caps the rate at which ArtDmx is sent to each universe.
+///
+/// A frame that arrives too soon after the previous one is held back, and sent by `tick` when the universe may be sent again. A newer frame replaces a frame that is held back, so the nodes always get the latest data without being flooded. The Art-Net specification recommends at most 44 frames per second, which is the maximum refresh rate of DMX512.
+///
+/// Pass every frame to `limit`, send the frames that it returns, and call `tick` at `next_deadline`.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::{Duration, Instant};
+///
+/// let mut limiter = RateLimiter::new();
+/// let start = Instant::now();
+/// assert!(limiter.limit(Output::new(1.into(), vec![1, 2]), start).is_some());
+///
+/// // Too soon, these are coalesced into the last frame
+/// assert!(limiter.limit(Output::new(1.into(), vec![3, 4]), start).is_none());
+/// assert!(limiter.limit(Output::new(1.into(), vec![5, 6]), start).is_none());
+///
+/// let outputs = limiter.tick(limiter.next_deadline().unwrap());
+/// assert_eq!(outputs[0].data.as_ref(), &vec![5, 6]);
+/// ```
+#[derive(Debug)]
+pub struct RateLimiter {
+    universes: HashMap<PortAddress, Universe>,
+    interval: Duration,
+}
+
+impl Default for RateLimiter {
+    fn default() -> RateLimiter {
+        RateLimiter::new()
+    }
+}
+
+impl RateLimiter {
+    /// The maximum number of frames per second per universe, as recommended by the Art-Net specification
+    pub const DEFAULT_RATE: u32 = 44;
+
+    /// Create a limiter with the default rate
+    pub fn new() -> RateLimiter {
+        RateLimiter::with_rate(RateLimiter::DEFAULT_RATE)
+    }
+
+    /// Create a limiter that sends at most the given number of frames per second to each universe
+    pub fn with_rate(rate: u32) -> RateLimiter {
+        RateLimiter::with_interval(Duration::from_secs(1) / rate.max(1))
+    }
+
+    /// Create a limiter that waits at least the given interval between two frames of a universe
+    pub fn with_interval(interval: Duration) -> RateLimiter {
+        RateLimiter {
+            universes: HashMap::new(),
+            interval,
+        }
+    }
+
+    /// The minimum time between two frames of a universe
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Return the frame if it can be sent now. Otherwise it is held back until `tick`, replacing the frame that was held back before.
+    pub fn limit(&mut self, output: Output, now: Instant) -> Option<Output> {
+        let interval = self.interval;
+        match self.universes.get_mut(&output.port_address) {
+            Some(universe) if now < universe.last_sent + interval => {
+                universe.pending = Some(output);
+                None
+            }
+            Some(universe) => {
+                universe.last_sent = now;
+                universe.pending = None;
+                Some(output)
+            }
+            None => {
+                self.universes.insert(
+                    output.port_address,
+                    Universe {
+                        last_sent: now,
+                        pending: None,
+                    },
+                );
+                Some(output)
+            }
+        }
+    }
+
+    /// The frames that were held back, and can be sent now
+    pub fn tick(&mut self, now: Instant) -> Vec<Output> {
+        let interval = self.interval;
+        self.universes
+            .values_mut()
+            .filter(|universe| universe.pending.is_some() && now >= universe.last_sent + interval)
+            .filter_map(|universe| {
+                universe.last_sent = now;
+                universe.pending.take()
+            })
+            .collect()
+    }
+
+    /// When `tick` should be called next, or `None` if no frames are held back
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.universes
+            .values()
+            .filter(|universe| universe.pending.is_some())
+            .map(|universe| universe.last_sent + self.interval)
+            .min()
+    }
+
+    /// Forget the given universe, and drop the frame that is held back for it
+    pub fn remove(&mut self, port_address: PortAddress) {
+        self.universes.remove(&port_address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coalesce() {
+        let mut limiter = RateLimiter::with_rate(50);
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let output = |port_address: u8, value| Output::new(port_address.into(), vec![value; 2]);
+
+        assert!(limiter.limit(output(1, 1), start).is_some());
+        assert!(limiter.limit(output(2, 1), at(5)).is_some());
+        assert!(limiter.limit(output(1, 2), at(10)).is_none());
+        assert!(limiter.limit(output(1, 3), at(15)).is_none());
+        assert_eq!(limiter.next_deadline(), Some(at(20)));
+        assert!(limiter.tick(at(19)).is_empty());
+
+        let outputs = limiter.tick(at(20));
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].data.as_ref(), &vec![3, 3]);
+        assert_eq!(limiter.next_deadline(), None);
+
+        // The frame of the tick counts for the rate
+        assert!(limiter.limit(output(1, 4), at(30)).is_none());
+        assert!(limiter.limit(output(1, 5), at(40)).is_some());
+        assert!(limiter.tick(at(60)).is_empty());
+    }
+}
@@ -0,0 +1,265 @@
+use crate::{Error, Result, Uid};
+use alloc::vec::Vec;
+
+/// A message that can be sent in the RDM packet of an [Rdm](struct.Rdm.html).
+///
+/// This is implemented by the built-in [RdmFrame](struct.RdmFrame.html), and by `Vec<u8>` for raw packets. It can be implemented for (a wrapper around) the message type of an RDM crate, so its messages can be sent over Art-Net directly.
+///
+/// The packet starts at the sub start code, the DMX start code is not sent over Art-Net.
+pub trait RdmMessage: Sized {
+    /// Append the RDM packet to the buffer, without the DMX start code
+    fn write_rdm(&self, buffer: &mut Vec<u8>) -> Result<()>;
+
+    /// Parse an RDM packet, which does not contain the DMX start code
+    fn parse_rdm(packet: &[u8]) -> Result<Self>;
+}
+
+impl RdmMessage for Vec<u8> {
+    fn write_rdm(&self, buffer: &mut Vec<u8>) -> Result<()> {
+        buffer.extend_from_slice(self);
+        Ok(())
+    }
+
+    fn parse_rdm(packet: &[u8]) -> Result<Vec<u8>> {
+        Ok(packet.to_vec())
+    }
+}
+
+/// An RDM message as defined by ANSI E1.20, e.g. a GET or SET of a parameter.
+///
+/// The message length and checksum are calculated when the frame is written, and verified when it is parsed.
+///
+/// ```rust
+/// use artnet_protocol::*;
+///
+/// // GET DEVICE_INFO
+/// let frame = RdmFrame::get(Uid::new(0x7FF0, 1), Uid::new(0x7FF0, 2), 0x0060);
+/// let rdm = Rdm::new(1.into(), &frame).unwrap();
+/// assert_eq!(rdm.message::<RdmFrame>().unwrap(), frame);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct RdmFrame {
+    /// The device that should handle the message, or [Uid::BROADCAST](struct.Uid.html#associatedconstant.BROADCAST)
+    pub destination: Uid,
+
+    /// The device that sent the message
+    pub source: Uid,
+
+    /// Increased by the controller for every request, and copied into the response
+    pub transaction: u8,
+
+    /// The port of the controller in a request, or the response type (e.g. `RdmFrame::RESPONSE_ACK`) in a response
+    pub port_id: u8,
+
+    /// The number of queued messages of the responder, 0 in requests
+    pub message_count: u8,
+
+    /// The sub-device, 0 for the root device
+    pub sub_device: u16,
+
+    /// The command class, e.g. `RdmFrame::GET_COMMAND`
+    pub command_class: u8,
+
+    /// The parameter id (PID), e.g. 0x0060 for DEVICE_INFO
+    pub parameter_id: u16,
+
+    /// The parameter data, at most 231 bytes
+    pub parameter_data: Vec<u8>,
+}
+
+impl RdmFrame {
+    /// The DMX start code of RDM messages, which is not sent over Art-Net
+    pub const START_CODE: u8 = 0xCC;
+
+    /// The first byte of the RDM packet
+    pub const SUB_START_CODE: u8 = 0x01;
+
+    /// The maximum length of `parameter_data`
+    pub const MAX_PARAMETER_DATA: usize = 231;
+
+    /// Command class of discovery requests
+    pub const DISCOVERY_COMMAND: u8 = 0x10;
+
+    /// Command class of discovery responses
+    pub const DISCOVERY_COMMAND_RESPONSE: u8 = 0x11;
+
+    /// Command class of GET requests
+    pub const GET_COMMAND: u8 = 0x20;
+
+    /// Command class of GET responses
+    pub const GET_COMMAND_RESPONSE: u8 = 0x21;
+
+    /// Command class of SET requests
+    pub const SET_COMMAND: u8 = 0x30;
+
+    /// Command class of SET responses
+    pub const SET_COMMAND_RESPONSE: u8 = 0x31;
+
+    /// Response type of a successful response
+    pub const RESPONSE_ACK: u8 = 0x00;
+
+    /// Response type when the response is not available yet
+    pub const RESPONSE_ACK_TIMER: u8 = 0x01;
+
+    /// Response type when the request could not be handled, the reason is in the parameter data
+    pub const RESPONSE_NACK_REASON: u8 = 0x02;
+
+    /// Response type when the response does not fit in a single message
+    pub const RESPONSE_ACK_OVERFLOW: u8 = 0x03;
+
+    /// The length of a frame without parameter data, including the DMX start code
+    const HEADER_LENGTH: usize = 24;
+
+    /// A GET request for the given parameter of the root device
+    pub fn get(destination: Uid, source: Uid, parameter_id: u16) -> RdmFrame {
+        RdmFrame {
+            destination,
+            source,
+            transaction: 0,
+            port_id: 1,
+            message_count: 0,
+            sub_device: 0,
+            command_class: RdmFrame::GET_COMMAND,
+            parameter_id,
+            parameter_data: Vec::new(),
+        }
+    }
+
+    /// A SET request for the given parameter of the root device
+    pub fn set(
+        destination: Uid,
+        source: Uid,
+        parameter_id: u16,
+        parameter_data: Vec<u8>,
+    ) -> RdmFrame {
+        RdmFrame {
+            command_class: RdmFrame::SET_COMMAND,
+            parameter_data,
+            ..RdmFrame::get(destination, source, parameter_id)
+        }
+    }
+
+    /// If this frame is a response to a request
+    pub fn is_response(&self) -> bool {
+        self.command_class & 0x01 != 0
+    }
+}
+
+impl RdmMessage for RdmFrame {
+    fn write_rdm(&self, buffer: &mut Vec<u8>) -> Result<()> {
+        if self.parameter_data.len() > RdmFrame::MAX_PARAMETER_DATA {
+            return Err(Error::MessageSizeInvalid {
+                len: self.parameter_data.len(),
+                allowed_size: 0..RdmFrame::MAX_PARAMETER_DATA + 1,
+            });
+        }
+        let start = buffer.len();
+        buffer.push(RdmFrame::SUB_START_CODE);
+        buffer.push((RdmFrame::HEADER_LENGTH + self.parameter_data.len()) as u8);
+        buffer.extend_from_slice(&self.destination.0);
+        buffer.extend_from_slice(&self.source.0);
+        buffer.push(self.transaction);
+        buffer.push(self.port_id);
+        buffer.push(self.message_count);
+        buffer.extend_from_slice(&self.sub_device.to_be_bytes());
+        buffer.push(self.command_class);
+        buffer.extend_from_slice(&self.parameter_id.to_be_bytes());
+        buffer.push(self.parameter_data.len() as u8);
+        buffer.extend_from_slice(&self.parameter_data);
+        let checksum = checksum(&buffer[start..]);
+        buffer.extend_from_slice(&checksum.to_be_bytes());
+        Ok(())
+    }
+
+    fn parse_rdm(packet: &[u8]) -> Result<RdmFrame> {
+        // Without the start code and checksum
+        let header_length = RdmFrame::HEADER_LENGTH - 1;
+        if packet.len() < header_length + 2 {
+            return Err(Error::InvalidRdmPacket("The packet is too short"));
+        }
+        if packet[0] != RdmFrame::SUB_START_CODE {
+            return Err(Error::InvalidRdmPacket("Unknown sub start code"));
+        }
+        let data_length = usize::from(packet[22]);
+        let length = header_length + data_length;
+        if usize::from(packet[1]) != length + 1 || packet.len() < length + 2 {
+            return Err(Error::InvalidRdmPacket(
+                "The message length does not match the parameter data length",
+            ));
+        }
+        let expected = u16::from_be_bytes([packet[length], packet[length + 1]]);
+        if checksum(&packet[..length]) != expected {
+            return Err(Error::InvalidRdmPacket("Invalid checksum"));
+        }
+
+        let uid = |offset: usize| {
+            let mut uid = [0; 6];
+            uid.copy_from_slice(&packet[offset..offset + 6]);
+            Uid(uid)
+        };
+        Ok(RdmFrame {
+            destination: uid(2),
+            source: uid(8),
+            transaction: packet[14],
+            port_id: packet[15],
+            message_count: packet[16],
+            sub_device: u16::from_be_bytes([packet[17], packet[18]]),
+            command_class: packet[19],
+            parameter_id: u16::from_be_bytes([packet[20], packet[21]]),
+            parameter_data: packet[header_length..length].to_vec(),
+        })
+    }
+}
+
+/// The checksum of an RDM packet without its start code, which is included in the sum anyway
+fn checksum(packet: &[u8]) -> u16 {
+    packet
+        .iter()
+        .fold(u16::from(RdmFrame::START_CODE), |sum, byte| {
+            sum.wrapping_add(u16::from(*byte))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_device_info() {
+        let frame = RdmFrame {
+            transaction: 3,
+            ..RdmFrame::get(
+                Uid::new(0x1234, 0x5678_9ABC),
+                Uid::new(0xCBA9, 0x8765_4321),
+                0x0060,
+            )
+        };
+        let mut packet = Vec::new();
+        frame.write_rdm(&mut packet).unwrap();
+        assert_eq!(
+            packet,
+            vec![
+                0x01, 0x18, 0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xCB, 0xA9, 0x87, 0x65, 0x43, 0x21,
+                0x03, 0x01, 0x00, 0x00, 0x00, 0x20, 0x00, 0x60, 0x00, 0x06, 0x97,
+            ]
+        );
+        assert_eq!(RdmFrame::parse_rdm(&packet).unwrap(), frame);
+    }
+
+    #[test]
+    fn invalid() {
+        let frame = RdmFrame::set(Uid::BROADCAST, Uid::new(1, 2), 0x1000, vec![1]);
+        let mut packet = Vec::new();
+        frame.write_rdm(&mut packet).unwrap();
+        assert!(!frame.is_response());
+
+        let mut wrong_checksum = packet.clone();
+        *wrong_checksum.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            RdmFrame::parse_rdm(&wrong_checksum),
+            Err(Error::InvalidRdmPacket("Invalid checksum"))
+        );
+        assert!(RdmFrame::parse_rdm(&packet[..packet.len() - 3]).is_err());
+    }
+}
@@ -0,0 +1,354 @@
+use crate::{ArtCommand, PortAddress, TodControl, TodData, TodRequest, Uid};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+
+/// A change in the devices of a [RdmDiscovery](struct.RdmDiscovery.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RdmDiscoveryEvent {
+    /// A device was found on the given Port-Address
+    DeviceAdded(PortAddress, Uid),
+
+    /// A device is no longer in the table of the given Port-Address
+    DeviceRemoved(PortAddress, Uid),
+}
+
+/// The Table of Devices of a single Port-Address
+#[derive(Debug, Default)]
+struct Table {
+    /// The devices of the last complete table
+    devices: BTreeSet<Uid>,
+    /// The devices of the blocks that were received so far, while a table is being collected
+    pending: Option<BTreeSet<Uid>>,
+    /// If a complete table was received since the last flush
+    complete: bool,
+}
+
+/// Keeps track of the RDM devices on every Port-Address, based on the ArtTodData messages that nodes send.
+///
+/// Send the commands returned by `flush` and `request` to the nodes, and pass every received command to `handle`. Tables of more than 200 devices are sent in several blocks, which are collected until the table is complete. Nodes that do incremental discovery send a new table when a device is added or removed, which is reported as a change as well.
+///
+/// ```rust
+/// use artnet_protocol::*;
+///
+/// let mut discovery = RdmDiscovery::new();
+/// let requests = discovery.request(&[1.into()]);
+/// // Send the requests, and pass the replies to `handle`
+/// # let reply = TodData { address: 1, uid_total: [0, 1], uids: vec![Uid::new(0x7FF0, 1)], ..TodData::default() };
+/// # assert_eq!(requests.len(), 1);
+/// let events = discovery.handle(&reply.into());
+/// assert_eq!(events, vec![RdmDiscoveryEvent::DeviceAdded(1.into(), Uid::new(0x7FF0, 1))]);
+/// assert!(discovery.is_complete(1.into()));
+/// ```
+#[derive(Debug, Default)]
+pub struct RdmDiscovery {
+    tables: BTreeMap<PortAddress, Table>,
+}
+
+impl RdmDiscovery {
+    /// Create a discovery without any devices
+    pub fn new() -> RdmDiscovery {
+        RdmDiscovery::default()
+    }
+
+    /// The ArtTodControl that makes the nodes flush the table of the given Port-Address and start a full discovery.
+    ///
+    /// The table is incomplete until the nodes send the new table. The known devices are kept until then, so only the devices that actually changed are reported.
+    pub fn flush(&mut self, port_address: PortAddress) -> ArtCommand {
+        let table = self.tables.entry(port_address).or_default();
+        table.pending = None;
+        table.complete = false;
+        TodControl::flush(port_address).into()
+    }
+
+    /// The ArtTodRequest messages that request the tables of the given Port-Addresses. One message can only request Port-Addresses of the same net, up to 32 at a time.
+    pub fn request(&mut self, port_addresses: &[PortAddress]) -> Vec<ArtCommand> {
+        let mut nets: BTreeMap<u8, BTreeSet<u8>> = BTreeMap::new();
+        for port_address in port_addresses {
+            self.tables.entry(*port_address).or_default();
+            nets.entry(port_address.net())
+                .or_default()
+                .insert(port_address.sub_uni());
+        }
+
+        let mut requests = Vec::new();
+        for (net, addresses) in nets {
+            let addresses: Vec<u8> = addresses.into_iter().collect();
+            for chunk in addresses.chunks(TodRequest::MAX_ADDRESSES) {
+                requests.push(
+                    TodRequest {
+                        net,
+                        addresses: chunk.to_vec(),
+                        ..TodRequest::default()
+                    }
+                    .into(),
+                );
+            }
+        }
+        requests
+    }
+
+    /// Handle a received command, and return the devices that were added or removed. Commands other than ArtTodData are ignored.
+    pub fn handle(&mut self, command: &ArtCommand) -> Vec<RdmDiscoveryEvent> {
+        match command {
+            ArtCommand::TodData(tod) => self.handle_tod(tod),
+            _ => Vec::new(),
+        }
+    }
+
+    fn handle_tod(&mut self, tod: &TodData) -> Vec<RdmDiscoveryEvent> {
+        // The node does not have a table (yet), e.g. because it is still discovering
+        if tod.command_response != TodData::TOD_FULL {
+            return Vec::new();
+        }
+        let port_address = tod.port_address();
+        let table = self.tables.entry(port_address).or_default();
+
+        // The first block starts a new table. Other blocks are ignored if the first one was missed.
+        if tod.block_count == 0 {
+            table.pending = Some(BTreeSet::new());
+        }
+        let pending = match &mut table.pending {
+            Some(pending) => pending,
+            None => return Vec::new(),
+        };
+        pending.extend(tod.uids.iter().copied());
+        if pending.len() < usize::from(tod.uid_total()) {
+            return Vec::new();
+        }
+
+        let devices = table.pending.take().unwrap_or_default();
+        let mut events: Vec<RdmDiscoveryEvent> = table
+            .devices
+            .difference(&devices)
+            .map(|uid| RdmDiscoveryEvent::DeviceRemoved(port_address, *uid))
+            .collect();
+        events.extend(
+            devices
+                .difference(&table.devices)
+                .map(|uid| RdmDiscoveryEvent::DeviceAdded(port_address, *uid)),
+        );
+        table.devices = devices;
+        table.complete = true;
+        events
+    }
+
+    /// The devices of the last complete table of the given Port-Address
+    pub fn devices(&self, port_address: PortAddress) -> impl Iterator<Item = Uid> + '_ {
+        self.tables
+            .get(&port_address)
+            .into_iter()
+            .flat_map(|table| table.devices.iter().copied())
+    }
+
+    /// The Port-Addresses that were flushed, requested or reported by a node
+    pub fn port_addresses(&self) -> impl Iterator<Item = PortAddress> + '_ {
+        self.tables.keys().copied()
+    }
+
+    /// If a complete table was received for the given Port-Address since it was last flushed
+    pub fn is_complete(&self, port_address: PortAddress) -> bool {
+        self.tables
+            .get(&port_address)
+            .is_some_and(|table| table.complete)
+    }
+}
+
+/// Reassembles the blocks of ArtTodData of a single Port-Address into the complete Table of Devices.
+///
+/// Blocks can be received in any order and more than once. When a block of another Port-Address or with another `uid_total` is added, the table that was being collected is dropped and a new one is started. The table is complete when every block up to the highest block number was received, and together they contain `uid_total` devices. [TodData::blocks](struct.TodData.html#method.blocks) does the reverse.
+///
+/// ```rust
+/// use artnet_protocol::*;
+///
+/// let uids: Vec<Uid> = (0..250).map(|device| Uid::new(0x7FF0, device)).collect();
+/// let blocks = TodData::blocks(1.into(), &uids).unwrap();
+/// let mut assembler = TodAssembler::new();
+/// assert!(!assembler.add(&blocks[1]));
+/// assert_eq!(assembler.missing_blocks(), vec![0]);
+/// assert!(assembler.add(&blocks[0]));
+/// assert_eq!(assembler.uids().count(), 250);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TodAssembler {
+    port_address: Option<PortAddress>,
+    uid_total: u16,
+    blocks: BTreeMap<u8, Vec<Uid>>,
+}
+
+impl TodAssembler {
+    /// Create an assembler without any blocks
+    pub fn new() -> TodAssembler {
+        TodAssembler::default()
+    }
+
+    /// Add a received block, and return if the table is complete. Responses of a node without a table (`TodData::TOD_NAK`) are ignored.
+    pub fn add(&mut self, tod: &TodData) -> bool {
+        if tod.command_response != TodData::TOD_FULL {
+            return self.is_complete();
+        }
+        let port_address = tod.port_address();
+        if self.port_address != Some(port_address) || self.uid_total != tod.uid_total() {
+            self.blocks.clear();
+            self.port_address = Some(port_address);
+            self.uid_total = tod.uid_total();
+        }
+        self.blocks.insert(tod.block_count, tod.uids.clone());
+        self.is_complete()
+    }
+
+    /// The Port-Address of the table that is being collected, or `None` if no block was added yet
+    pub fn port_address(&self) -> Option<PortAddress> {
+        self.port_address
+    }
+
+    /// The total number of devices in the table, as reported by the node
+    pub fn uid_total(&self) -> u16 {
+        self.uid_total
+    }
+
+    /// If every block was received
+    pub fn is_complete(&self) -> bool {
+        let count: usize = self.blocks.values().map(Vec::len).sum();
+        !self.blocks.is_empty()
+            && self.missing_blocks().is_empty()
+            && count == usize::from(self.uid_total)
+    }
+
+    /// The numbers of the blocks below the highest block number that were not received. Blocks after the highest one can not be known, so this is empty if the table is incomplete but no gap was found.
+    pub fn missing_blocks(&self) -> Vec<u8> {
+        let highest = match self.blocks.keys().next_back() {
+            Some(highest) => *highest,
+            None => return Vec::new(),
+        };
+        (0..highest)
+            .filter(|block| !self.blocks.contains_key(block))
+            .collect()
+    }
+
+    /// The devices of the blocks that were received, in the order of the blocks
+    pub fn uids(&self) -> impl Iterator<Item = Uid> + '_ {
+        self.blocks.values().flat_map(|uids| uids.iter().copied())
+    }
+
+    /// Drop the blocks that were received
+    pub fn clear(&mut self) {
+        *self = TodAssembler::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tod(total: u16, block: u8, uids: &[u32]) -> ArtCommand {
+        TodData {
+            net: 1,
+            address: 0x23,
+            uid_total: total.to_be_bytes(),
+            block_count: block,
+            uids: uids
+                .iter()
+                .map(|device| Uid::new(0x7FF0, *device))
+                .collect(),
+            ..TodData::default()
+        }
+        .into()
+    }
+
+    #[test]
+    fn request() {
+        let mut discovery = RdmDiscovery::new();
+        let port_addresses: Vec<PortAddress> = (0..40)
+            .map(|address| PortAddress::from_parts(0, address))
+            .chain(Some(PortAddress::from_parts(1, 0)))
+            .collect();
+        let requests = discovery.request(&port_addresses);
+        let counts: Vec<(u8, usize)> = requests
+            .iter()
+            .map(|request| match request {
+                ArtCommand::TodRequest(request) => (request.net, request.addresses.len()),
+                command => panic!("Unexpected command {:?}", command),
+            })
+            .collect();
+        assert_eq!(counts, vec![(0, 32), (0, 8), (1, 1)]);
+        assert_eq!(discovery.port_addresses().count(), 41);
+    }
+
+    #[test]
+    fn blocks() {
+        let mut discovery = RdmDiscovery::new();
+        let port_address = PortAddress::from_parts(1, 0x23);
+
+        // A block without the first one is ignored
+        assert!(discovery.handle(&tod(3, 1, &[3])).is_empty());
+        assert!(discovery.handle(&tod(3, 0, &[1, 2])).is_empty());
+        assert!(!discovery.is_complete(port_address));
+        assert_eq!(discovery.handle(&tod(3, 1, &[3])).len(), 3);
+        assert!(discovery.is_complete(port_address));
+        assert_eq!(discovery.devices(port_address).count(), 3);
+    }
+
+    #[test]
+    fn assembler() {
+        let uids: Vec<Uid> = (0..401).map(|device| Uid::new(0x7FF0, device)).collect();
+        let port_address = PortAddress::from_parts(1, 0x23);
+        let blocks = TodData::blocks(port_address, &uids).unwrap();
+        assert_eq!(blocks.len(), 3);
+        assert!(blocks
+            .iter()
+            .all(|block| block.port_address() == port_address && block.uid_total() == 401));
+
+        let mut assembler = TodAssembler::new();
+        assert!(!assembler.add(&blocks[2]));
+        assert!(!assembler.add(&blocks[0]));
+        assert_eq!(assembler.missing_blocks(), vec![1]);
+        assert!(assembler.add(&blocks[1]));
+        assert!(assembler.add(&blocks[1]));
+        assert_eq!(assembler.uids().collect::<Vec<_>>(), uids);
+
+        // A new table with another total starts over
+        let blocks = TodData::blocks(port_address, &uids[..10]).unwrap();
+        assert!(assembler.add(&blocks[0]));
+        assert_eq!(assembler.uids().count(), 10);
+
+        let empty = TodData::blocks(port_address, &[]).unwrap();
+        assert_eq!(empty.len(), 1);
+        assert!(assembler.add(&empty[0]));
+        assert_eq!(assembler.uids().count(), 0);
+
+        let uids = vec![Uid::new(0, 0); TodData::MAX_TABLE_UIDS + 1];
+        assert!(TodData::blocks(port_address, &uids).is_err());
+    }
+
+    #[test]
+    fn incremental() {
+        let mut discovery = RdmDiscovery::new();
+        let port_address = PortAddress::from_parts(1, 0x23);
+        discovery.handle(&tod(2, 0, &[1, 2]));
+
+        let flush = discovery.flush(port_address);
+        assert!(
+            matches!(flush, ArtCommand::TodControl(control) if control.port_address() == port_address)
+        );
+        assert!(!discovery.is_complete(port_address));
+        assert_eq!(discovery.devices(port_address).count(), 2);
+
+        assert_eq!(
+            discovery.handle(&tod(2, 0, &[2, 3])),
+            vec![
+                RdmDiscoveryEvent::DeviceRemoved(port_address, Uid::new(0x7FF0, 1)),
+                RdmDiscoveryEvent::DeviceAdded(port_address, Uid::new(0x7FF0, 3)),
+            ]
+        );
+
+        // A node without a table keeps the known devices
+        let nak = TodData {
+            net: 1,
+            address: 0x23,
+            command_response: TodData::TOD_NAK,
+            ..TodData::default()
+        };
+        assert!(discovery.handle(&nak.into()).is_empty());
+        assert_eq!(discovery.devices(port_address).count(), 2);
+    }
+}
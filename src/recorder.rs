@@ -0,0 +1,264 @@
+use crate::controller::invalid_data;
+use crate::{ArtCommand, Encoder};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::net::{SocketAddr, UdpSocket};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The first bytes of a recording, with the version of the format
+const MAGIC: &[u8; 8] = b"ArtRec\0\x01";
+
+/// A command in a recording, as returned by a [Replayer](struct.Replayer.html)
+#[derive(Debug)]
+pub struct RecordedCommand {
+    /// The time since the first command of the recording
+    pub offset: Duration,
+
+    /// The recorded command
+    pub command: ArtCommand,
+}
+
+/// Writes ArtDmx, ArtSync and ArtTimeCode traffic to a file with its timing, to play it back with a [Replayer](struct.Replayer.html).
+///
+/// A recording starts with a short header. Every command is stored as the microseconds since the previous command, as a 32 bit little endian number, followed by the length of the Art-Net message as a 16 bit little endian number and the message itself. Pauses of more than about 71 minutes are shortened to that.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::{Duration, Instant};
+///
+/// let mut recorder = Recorder::new(Vec::new()).unwrap();
+/// let start = Instant::now();
+/// recorder.record(&Output::new(1.into(), vec![255; 4]).into(), start).unwrap();
+/// recorder.record(&ArtSync::default().into(), start + Duration::from_millis(25)).unwrap();
+/// // Other commands are not recorded
+/// assert!(!recorder.record(&Poll::default().into(), start).unwrap());
+///
+/// let file = recorder.into_inner().unwrap();
+/// let commands: Vec<_> = Replayer::new(&file[..]).unwrap().map(|c| c.unwrap()).collect();
+/// assert_eq!(commands.len(), 2);
+/// assert_eq!(commands[1].offset, Duration::from_millis(25));
+/// ```
+#[derive(Debug)]
+pub struct Recorder<W: Write> {
+    writer: W,
+    encoder: Encoder,
+    last: Option<Instant>,
+}
+
+impl Recorder<BufWriter<File>> {
+    /// Create a recording at the given path, replacing the file if it exists
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Recorder<BufWriter<File>>> {
+        Recorder::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Start a recording in the given writer
+    pub fn new(mut writer: W) -> io::Result<Recorder<W>> {
+        writer.write_all(MAGIC)?;
+        Ok(Recorder {
+            writer,
+            encoder: Encoder::new(),
+            last: None,
+        })
+    }
+
+    /// Record a command that was received or sent at `now`. Returns false if the command is not ArtDmx, ArtSync or ArtTimeCode, which are not recorded.
+    pub fn record(&mut self, command: &ArtCommand, now: Instant) -> io::Result<bool> {
+        match command {
+            ArtCommand::Output(_) | ArtCommand::Sync(_) => {}
+            #[cfg(feature = "media")]
+            ArtCommand::OpTimeCode(_) => {}
+            _ => return Ok(false),
+        }
+        let bytes = self.encoder.encode(command).map_err(invalid_data)?;
+        let delay = match self.last {
+            Some(last) => now.saturating_duration_since(last),
+            None => Duration::from_secs(0),
+        };
+        self.last = Some(now);
+        let delay = u32::try_from(delay.as_micros()).unwrap_or(u32::MAX);
+        self.writer.write_u32::<LittleEndian>(delay)?;
+        self.writer.write_u16::<LittleEndian>(bytes.len() as u16)?;
+        self.writer.write_all(bytes)?;
+        Ok(true)
+    }
+
+    /// Write the buffered commands to the underlying writer
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Finish the recording, and return the underlying writer
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads a recording of a [Recorder](struct.Recorder.html), and plays it back with its original timing.
+///
+/// The replayer is an iterator over the recorded commands. It stops at the end of the recording, or after the first error.
+///
+/// ```rust,no_run
+/// use artnet_protocol::*;
+///
+/// let socket = ArtNetSocket::bind_default().unwrap();
+/// let replayer = Replayer::open("show.artrec").unwrap();
+/// replayer.play(socket.socket(), "2.255.255.255:6454".parse().unwrap()).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Replayer<R: Read> {
+    reader: R,
+    offset: Duration,
+    buffer: Vec<u8>,
+    done: bool,
+}
+
+impl Replayer<BufReader<File>> {
+    /// Open the recording at the given path
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Replayer<BufReader<File>>> {
+        Replayer::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> Replayer<R> {
+    /// Read a recording from the given reader. Returns an error of kind `InvalidData` if it does not start with the header of a recording.
+    pub fn new(mut reader: R) -> io::Result<Replayer<R>> {
+        let mut magic = [0; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Not an Art-Net recording",
+            ));
+        }
+        Ok(Replayer {
+            reader,
+            offset: Duration::from_secs(0),
+            buffer: Vec::new(),
+            done: false,
+        })
+    }
+
+    /// Send every recorded command to the given address, with the original time between them. This blocks until the recording is done.
+    pub fn play(self, socket: &UdpSocket, addr: SocketAddr) -> io::Result<()> {
+        let start = Instant::now();
+        let mut encoder = Encoder::new();
+        for recorded in self {
+            let recorded = recorded?;
+            thread::sleep((start + recorded.offset).saturating_duration_since(Instant::now()));
+            let bytes = encoder.encode(&recorded.command).map_err(invalid_data)?;
+            socket.send_to(bytes, addr)?;
+        }
+        Ok(())
+    }
+
+    fn read_command(&mut self) -> io::Result<Option<RecordedCommand>> {
+        // The recording ends cleanly before a command, a delay that is cut off is an error
+        let mut delay = [0; 4];
+        let read = loop {
+            match self.reader.read(&mut delay[..1]) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                read => break read?,
+            }
+        };
+        if read == 0 {
+            return Ok(None);
+        }
+        self.reader.read_exact(&mut delay[1..])?;
+        let delay = u32::from_le_bytes(delay);
+        let length = self.reader.read_u16::<LittleEndian>()?;
+        self.buffer.resize(usize::from(length), 0);
+        self.reader.read_exact(&mut self.buffer)?;
+        let command = ArtCommand::from_buffer(&self.buffer).map_err(invalid_data)?;
+        self.offset += Duration::from_micros(u64::from(delay));
+        Ok(Some(RecordedCommand {
+            offset: self.offset,
+            command,
+        }))
+    }
+}
+
+impl<R: Read> Iterator for Replayer<R> {
+    type Item = io::Result<RecordedCommand>;
+
+    fn next(&mut self) -> Option<io::Result<RecordedCommand>> {
+        if self.done {
+            return None;
+        }
+        let result = self.read_command().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArtSync, Output, RecvBuffer};
+
+    #[test]
+    fn round_trip() {
+        let mut recorder = Recorder::new(Vec::new()).unwrap();
+        let start = Instant::now();
+        for frame in 0..3u8 {
+            let mut output = Output::new(1.into(), vec![frame; 8]);
+            output.sequence = frame + 1;
+            let now = start + Duration::from_millis(u64::from(frame) * 10);
+            assert!(recorder.record(&output.into(), now).unwrap());
+        }
+        recorder
+            .record(
+                &ArtSync::default().into(),
+                start + Duration::from_millis(25),
+            )
+            .unwrap();
+        let mut file = recorder.into_inner().unwrap();
+
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let begin = Instant::now();
+        Replayer::new(&file[..])
+            .unwrap()
+            .play(&socket, receiver.local_addr().unwrap())
+            .unwrap();
+        assert!(begin.elapsed() >= Duration::from_millis(25));
+        let mut buffer = RecvBuffer::new();
+        for sequence in 1..=3 {
+            let (length, _) = receiver.recv_from(&mut buffer).unwrap();
+            match buffer.parse(length).unwrap() {
+                ArtCommand::Output(output) => assert_eq!(output.sequence, sequence),
+                command => panic!("Unexpected command {:?}", command),
+            }
+        }
+        let (length, _) = receiver.recv_from(&mut buffer).unwrap();
+        assert!(matches!(buffer.parse(length), Ok(ArtCommand::Sync(_))));
+
+        // A truncated recording returns an error, and stops
+        file.truncate(file.len() - 1);
+        let results: Vec<_> = Replayer::new(&file[..]).unwrap().collect();
+        assert_eq!(results.len(), 4);
+        assert!(results[3].is_err());
+
+        // So does a recording that ends within the header of a command
+        let mut file = Recorder::new(Vec::new()).unwrap().into_inner().unwrap();
+        file.extend_from_slice(&[1, 0]);
+        let results: Vec<_> = Replayer::new(&file[..]).unwrap().collect();
+        match &results[..] {
+            [Err(e)] => assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof),
+            results => panic!("Unexpected results {:?}", results),
+        }
+        let empty = Recorder::new(Vec::new()).unwrap().into_inner().unwrap();
+        assert_eq!(Replayer::new(&empty[..]).unwrap().count(), 0);
+
+        let err = Replayer::new(&b"Not a recording"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{ArtCommand, ParseOptions, Result, MAX_PACKET_SIZE};
+use core::ops::{Deref, DerefMut};
+
+/// A buffer that is large enough to receive any Art-Net message.
+///
+/// ```rust,no_run
+/// use artnet_protocol::*;
+/// use std::net::UdpSocket;
+///
+/// let socket = UdpSocket::bind(("0.0.0.0", 6454)).unwrap();
+/// let mut buffer = RecvBuffer::new();
+/// let (length, addr) = socket.recv_from(&mut buffer).unwrap();
+/// let command = buffer.parse(length).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct RecvBuffer {
+    inner: [u8; MAX_PACKET_SIZE],
+}
+
+impl RecvBuffer {
+    /// Create a new, zeroed buffer
+    pub fn new() -> RecvBuffer {
+        RecvBuffer {
+            inner: [0; MAX_PACKET_SIZE],
+        }
+    }
+
+    /// Parse the first `length` bytes of the buffer, e.g. the length returned by `UdpSocket::recv_from`
+    pub fn parse(&self, length: usize) -> Result<ArtCommand> {
+        self.parse_with(length, &ParseOptions::default())
+    }
+
+    /// Parse the first `length` bytes of the buffer with the given options
+    pub fn parse_with(&self, length: usize, options: &ParseOptions) -> Result<ArtCommand> {
+        ArtCommand::from_buffer_with(&self.inner[..length.min(MAX_PACKET_SIZE)], options)
+    }
+}
+
+impl Default for RecvBuffer {
+    fn default() -> RecvBuffer {
+        RecvBuffer::new()
+    }
+}
+
+impl core::fmt::Debug for RecvBuffer {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.debug_struct("RecvBuffer")
+            .field("capacity", &MAX_PACKET_SIZE)
+            .finish()
+    }
+}
+
+impl Deref for RecvBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.inner
+    }
+}
+
+impl DerefMut for RecvBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Output, Poll, PollReply};
+
+    #[test]
+    fn max_sizes() {
+        let output = ArtCommand::Output(Output {
+            data: vec![0; 512].into(),
+            ..Output::default()
+        });
+        assert_eq!(
+            output.write_to_buffer().unwrap().len(),
+            Output::MAX_PACKET_SIZE
+        );
+        let reply = ArtCommand::PollReply(Box::default());
+        assert_eq!(
+            reply.write_to_buffer().unwrap().len(),
+            PollReply::PACKET_SIZE
+        );
+        const _: () = assert!(Poll::MAX_PACKET_SIZE <= MAX_PACKET_SIZE);
+        const _: () = assert!(Output::MAX_PACKET_SIZE <= MAX_PACKET_SIZE);
+        #[cfg(feature = "rdm")]
+        const _: () = assert!(crate::TodData::MAX_PACKET_SIZE == MAX_PACKET_SIZE);
+    }
+
+    #[test]
+    fn parse() {
+        let bytes = ArtCommand::Poll(Poll::default()).write_to_buffer().unwrap();
+        let mut buffer = RecvBuffer::new();
+        buffer[..bytes.len()].copy_from_slice(&bytes);
+
+        assert_eq!(buffer.parse(bytes.len()).unwrap().name(), "ArtPoll");
+        assert!(buffer.parse(4).is_err());
+        // Lengths larger than the buffer are clamped
+        assert!(buffer.parse(usize::MAX).is_ok());
+    }
+}
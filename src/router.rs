@@ -0,0 +1,192 @@
+use crate::{ArtCommand, PortAddress};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+
+/// The Port-Addresses that a handler of a [Router](struct.Router.html) is registered for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Route {
+    /// A single Port-Address
+    Exact(PortAddress),
+
+    /// All Port-Addresses in the range, inclusive
+    Range(RangeInclusive<PortAddress>),
+
+    /// Every Port-Address
+    Any,
+}
+
+impl Route {
+    /// If this route contains the given Port-Address
+    pub fn contains(&self, port_address: PortAddress) -> bool {
+        match self {
+            Route::Exact(exact) => *exact == port_address,
+            Route::Range(range) => range.contains(&port_address),
+            Route::Any => true,
+        }
+    }
+}
+
+impl From<PortAddress> for Route {
+    fn from(port_address: PortAddress) -> Route {
+        Route::Exact(port_address)
+    }
+}
+
+impl From<RangeInclusive<PortAddress>> for Route {
+    fn from(range: RangeInclusive<PortAddress>) -> Route {
+        Route::Range(range)
+    }
+}
+
+/// The data of an ArtDmx or ArtNzs packet, as passed to the handlers of a [Router](struct.Router.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Universe<'a> {
+    /// The Port-Address that the data is destined to
+    pub port_address: PortAddress,
+
+    /// The DMX512 start code, 0 for an ArtDmx packet
+    pub start_code: u8,
+
+    /// The sequence number of the packet, or 0 if it is disabled
+    pub sequence: u8,
+
+    /// The DMX512 data
+    pub data: &'a [u8],
+}
+
+type Handler<'a> = Box<dyn FnMut(&Universe) + 'a>;
+
+/// Dispatches received ArtDmx and ArtNzs packets to the handlers that are registered for their Port-Address.
+///
+/// Every handler whose route contains the Port-Address is called, in the order in which they were registered.
+///
+/// ```rust
+/// use artnet_protocol::*;
+///
+/// let mut received = Vec::new();
+/// let mut router = Router::new();
+/// router.route(PortAddress::from(1), |universe: &Universe| received.push(universe.data.to_vec()));
+/// router.route(Route::Any, |universe: &Universe| println!("{:?}", universe));
+///
+/// let command = Output::new(1.into(), vec![1, 2]).into_command();
+/// assert_eq!(router.dispatch(&command), 2);
+/// drop(router);
+/// assert_eq!(received, vec![vec![1, 2]]);
+/// ```
+#[derive(Default)]
+pub struct Router<'a> {
+    routes: Vec<(Route, Handler<'a>)>,
+}
+
+impl core::fmt::Debug for Router<'_> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.debug_list()
+            .entries(self.routes.iter().map(|(route, _)| route))
+            .finish()
+    }
+}
+
+impl<'a> Router<'a> {
+    /// Create a router without any handlers
+    pub fn new() -> Router<'a> {
+        Router::default()
+    }
+
+    /// Register a handler for the given Port-Address, range of Port-Addresses or `Route::Any`
+    pub fn route(
+        &mut self,
+        route: impl Into<Route>,
+        handler: impl FnMut(&Universe) + 'a,
+    ) -> &mut Router<'a> {
+        self.routes.push((route.into(), Box::new(handler)));
+        self
+    }
+
+    /// Call the handlers that are registered for the Port-Address of this ArtDmx or ArtNzs packet. Other commands are ignored.
+    ///
+    /// Returns the number of handlers that were called.
+    pub fn dispatch(&mut self, command: &ArtCommand) -> usize {
+        let universe = match command {
+            ArtCommand::Output(output) => Universe {
+                port_address: output.port_address,
+                start_code: 0,
+                sequence: output.sequence,
+                data: output.data.as_ref(),
+            },
+            #[cfg(feature = "dmx")]
+            ArtCommand::Nzs(nzs) => Universe {
+                port_address: nzs.port_address,
+                start_code: nzs.start_code,
+                sequence: nzs.sequence,
+                data: nzs.data.as_ref(),
+            },
+            _ => return 0,
+        };
+        let mut called = 0;
+        for (route, handler) in &mut self.routes {
+            if route.contains(universe.port_address) {
+                handler(&universe);
+                called += 1;
+            }
+        }
+        called
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArtSync, Output};
+    use alloc::vec;
+
+    #[test]
+    fn routes() {
+        let mut exact = Vec::new();
+        let mut range = Vec::new();
+        let mut any = 0;
+        let mut router = Router::new();
+        router
+            .route(PortAddress::from(2), |universe: &Universe| {
+                exact.push(universe.data.to_vec())
+            })
+            .route(
+                PortAddress::from(1)..=PortAddress::from(3),
+                |universe: &Universe| range.push(universe.port_address),
+            )
+            .route(Route::Any, |_: &Universe| any += 1);
+
+        assert_eq!(
+            router.dispatch(&Output::new(2.into(), vec![1, 2]).into()),
+            3
+        );
+        assert_eq!(
+            router.dispatch(&Output::new(3.into(), vec![3, 4]).into()),
+            2
+        );
+        assert_eq!(
+            router.dispatch(&Output::new(4.into(), vec![5, 6]).into()),
+            1
+        );
+        assert_eq!(router.dispatch(&ArtSync::default().into()), 0);
+        drop(router);
+
+        assert_eq!(exact, vec![vec![1, 2]]);
+        assert_eq!(range, vec![PortAddress::from(2), PortAddress::from(3)]);
+        assert_eq!(any, 3);
+    }
+
+    #[cfg(feature = "dmx")]
+    #[test]
+    fn nzs() {
+        let mut start_codes = Vec::new();
+        let mut router = Router::new();
+        router.route(Route::Any, |universe: &Universe| {
+            start_codes.push(universe.start_code)
+        });
+        router.dispatch(&crate::Nzs::new(1.into(), crate::Nzs::TEXT_PACKET, vec![0; 2]).into());
+        router.dispatch(&Output::new(1.into(), vec![0; 2]).into());
+        drop(router);
+        assert_eq!(start_codes, vec![crate::Nzs::TEXT_PACKET, 0]);
+    }
+}
@@ -0,0 +1,528 @@
+//! Conversion between Art-Net and sACN (ANSI E1.31), for gateways that bridge the two protocols.
+//!
+//! [DataPacket](struct.DataPacket.html) and [SyncPacket](struct.SyncPacket.html) are the E1.31 messages that carry DMX512 data and synchronize outputs, which correspond to ArtDmx and ArtSync. A [Converter](struct.Converter.html) translates between the two, keeping track of the sACN sequence numbers, CID and priority of the gateway.
+//!
+//! ```rust
+//! use artnet_protocol::sacn::{Converter, Packet};
+//! use artnet_protocol::{ArtCommand, Output};
+//!
+//! let mut converter = Converter::new([1; 16], "Gateway");
+//! let output = ArtCommand::Output(Output::new(0.into(), vec![255; 512]));
+//! let packet = converter.to_sacn(&output).unwrap().unwrap();
+//! let bytes = packet.to_bytes().unwrap();
+//!
+//! // Port-Address 0 is sACN universe 1
+//! match Packet::parse(&bytes).unwrap() {
+//!     Packet::Data(data) => assert_eq!(data.universe, 1),
+//!     packet => panic!("Unexpected packet {:?}", packet),
+//! }
+//! ```
+use crate::{ArtCommand, ArtSync, Error, Output, PortAddress, Result};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// The UDP port of sACN
+pub const PORT: u16 = 5568;
+
+/// The ACN packet identifier at the start of the root layer
+const ACN_PACKET_IDENTIFIER: [u8; 12] = *b"ASC-E1.17\0\0\0";
+
+const VECTOR_ROOT_E131_DATA: u32 = 0x0000_0004;
+const VECTOR_ROOT_E131_EXTENDED: u32 = 0x0000_0008;
+const VECTOR_E131_DATA_PACKET: u32 = 0x0000_0002;
+const VECTOR_E131_EXTENDED_SYNCHRONIZATION: u32 = 0x0000_0001;
+const VECTOR_DMP_SET_PROPERTY: u8 = 0x02;
+const DMP_ADDRESS_AND_DATA_TYPE: u8 = 0xA1;
+
+/// The length of the root layer
+const ROOT_LENGTH: usize = 38;
+
+/// The length of a data packet without any slots, including the start code
+const DATA_HEADER_LENGTH: usize = 126;
+
+/// The length of a sync packet
+const SYNC_LENGTH: usize = 49;
+
+/// The length of the source name, including the terminating zero
+const SOURCE_NAME_LENGTH: usize = 64;
+
+/// An E1.31 data packet, which carries the DMX512 data of a single universe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataPacket {
+    /// The unique id of the source
+    pub cid: [u8; 16],
+
+    /// The name of the source, at most 63 bytes are sent
+    pub source_name: String,
+
+    /// The priority of the data, from 0 to `DataPacket::MAX_PRIORITY`. Receivers use the data of the source with the highest priority.
+    pub priority: u8,
+
+    /// The universe of the sync packet that makes receivers output this data, or 0 to output it immediately
+    pub sync_address: u16,
+
+    /// Increased for every packet of the universe
+    pub sequence: u8,
+
+    /// A combination of the `DataPacket::OPTION_*` flags
+    pub options: u8,
+
+    /// The universe, from 1 to `DataPacket::MAX_UNIVERSE`
+    pub universe: u16,
+
+    /// The DMX start code, 0 for dimmer data
+    pub start_code: u8,
+
+    /// The slots of the universe, at most 512
+    pub data: Vec<u8>,
+}
+
+impl DataPacket {
+    /// The priority used by sources that don't have a specific priority
+    pub const DEFAULT_PRIORITY: u8 = 100;
+
+    /// The highest priority
+    pub const MAX_PRIORITY: u8 = 200;
+
+    /// The highest universe that can carry data
+    pub const MAX_UNIVERSE: u16 = 63999;
+
+    /// The data is meant for visualizers, and should not be output to fixtures
+    pub const OPTION_PREVIEW: u8 = 0x80;
+
+    /// The source stops sending this universe
+    pub const OPTION_STREAM_TERMINATED: u8 = 0x40;
+
+    /// Receivers keep synchronizing the universe when the sync packets stop
+    pub const OPTION_FORCE_SYNC: u8 = 0x20;
+
+    /// Whether the given `DataPacket::OPTION_*` flag is set
+    pub fn has_option(&self, option: u8) -> bool {
+        self.options & option != 0
+    }
+
+    /// Append the packet to the buffer
+    pub fn write_to(&self, buffer: &mut Vec<u8>) -> Result<()> {
+        if self.data.len() > 512 {
+            return Err(Error::MessageSizeInvalid {
+                len: self.data.len(),
+                allowed_size: 0..513,
+            });
+        }
+        if !(1..=DataPacket::MAX_UNIVERSE).contains(&self.universe) {
+            return Err(Error::InvalidSacnPacket(
+                "The universe must be from 1 to 63999",
+            ));
+        }
+        let start = buffer.len();
+        let length = DATA_HEADER_LENGTH + self.data.len();
+        write_root_layer(buffer, length, VECTOR_ROOT_E131_DATA, &self.cid);
+
+        // Framing layer
+        buffer.extend_from_slice(&flags_and_length(length - ROOT_LENGTH));
+        buffer.extend_from_slice(&VECTOR_E131_DATA_PACKET.to_be_bytes());
+        let mut source_name = [0; SOURCE_NAME_LENGTH];
+        let name = self.source_name.as_bytes();
+        let name_length = name.len().min(SOURCE_NAME_LENGTH - 1);
+        source_name[..name_length].copy_from_slice(&name[..name_length]);
+        buffer.extend_from_slice(&source_name);
+        buffer.push(self.priority);
+        buffer.extend_from_slice(&self.sync_address.to_be_bytes());
+        buffer.push(self.sequence);
+        buffer.push(self.options);
+        buffer.extend_from_slice(&self.universe.to_be_bytes());
+
+        // DMP layer
+        buffer.extend_from_slice(&flags_and_length(length - (buffer.len() - start)));
+        buffer.push(VECTOR_DMP_SET_PROPERTY);
+        buffer.push(DMP_ADDRESS_AND_DATA_TYPE);
+        buffer.extend_from_slice(&0u16.to_be_bytes());
+        buffer.extend_from_slice(&1u16.to_be_bytes());
+        buffer.extend_from_slice(&(self.data.len() as u16 + 1).to_be_bytes());
+        buffer.push(self.start_code);
+        buffer.extend_from_slice(&self.data);
+        Ok(())
+    }
+
+    fn parse(packet: &[u8]) -> Result<DataPacket> {
+        if packet.len() < DATA_HEADER_LENGTH {
+            return Err(Error::InvalidSacnPacket("The data packet is too short"));
+        }
+        if read_u32(packet, 40) != VECTOR_E131_DATA_PACKET {
+            return Err(Error::InvalidSacnPacket("Unknown framing layer vector"));
+        }
+        if packet[117] != VECTOR_DMP_SET_PROPERTY
+            || packet[118] != DMP_ADDRESS_AND_DATA_TYPE
+            || read_u16(packet, 119) != 0
+            || read_u16(packet, 121) != 1
+        {
+            return Err(Error::InvalidSacnPacket("Invalid DMP layer"));
+        }
+        let count = usize::from(read_u16(packet, 123));
+        if count == 0 || count > 513 || packet.len() < DATA_HEADER_LENGTH - 1 + count {
+            return Err(Error::InvalidSacnPacket(
+                "The property value count does not match the length of the packet",
+            ));
+        }
+        let universe = read_u16(packet, 113);
+        if !(1..=DataPacket::MAX_UNIVERSE).contains(&universe) {
+            return Err(Error::InvalidSacnPacket(
+                "The universe must be from 1 to 63999",
+            ));
+        }
+
+        let source_name = &packet[44..44 + SOURCE_NAME_LENGTH];
+        let name_length = source_name
+            .iter()
+            .position(|b| *b == 0)
+            .unwrap_or(SOURCE_NAME_LENGTH);
+        let mut cid = [0; 16];
+        cid.copy_from_slice(&packet[22..ROOT_LENGTH]);
+        Ok(DataPacket {
+            cid,
+            source_name: String::from_utf8_lossy(&source_name[..name_length]).into(),
+            priority: packet[108],
+            sync_address: read_u16(packet, 109),
+            sequence: packet[111],
+            options: packet[112],
+            universe,
+            start_code: packet[125],
+            data: packet[DATA_HEADER_LENGTH..DATA_HEADER_LENGTH - 1 + count].to_vec(),
+        })
+    }
+}
+
+/// An E1.31 synchronization packet, which makes the receivers output the data of the universes with the same sync address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncPacket {
+    /// The unique id of the source
+    pub cid: [u8; 16],
+
+    /// Increased for every sync packet
+    pub sequence: u8,
+
+    /// The universe that the sync packet is sent on, which the data packets refer to
+    pub sync_address: u16,
+}
+
+impl SyncPacket {
+    /// Append the packet to the buffer
+    pub fn write_to(&self, buffer: &mut Vec<u8>) -> Result<()> {
+        if !(1..=DataPacket::MAX_UNIVERSE).contains(&self.sync_address) {
+            return Err(Error::InvalidSacnPacket(
+                "The sync address must be from 1 to 63999",
+            ));
+        }
+        write_root_layer(buffer, SYNC_LENGTH, VECTOR_ROOT_E131_EXTENDED, &self.cid);
+        buffer.extend_from_slice(&flags_and_length(SYNC_LENGTH - ROOT_LENGTH));
+        buffer.extend_from_slice(&VECTOR_E131_EXTENDED_SYNCHRONIZATION.to_be_bytes());
+        buffer.push(self.sequence);
+        buffer.extend_from_slice(&self.sync_address.to_be_bytes());
+        // Reserved
+        buffer.extend_from_slice(&[0, 0]);
+        Ok(())
+    }
+
+    fn parse(packet: &[u8]) -> Result<SyncPacket> {
+        if packet.len() < SYNC_LENGTH {
+            return Err(Error::InvalidSacnPacket("The sync packet is too short"));
+        }
+        if read_u32(packet, 40) != VECTOR_E131_EXTENDED_SYNCHRONIZATION {
+            return Err(Error::InvalidSacnPacket("Unknown framing layer vector"));
+        }
+        let mut cid = [0; 16];
+        cid.copy_from_slice(&packet[22..ROOT_LENGTH]);
+        Ok(SyncPacket {
+            cid,
+            sequence: packet[44],
+            sync_address: read_u16(packet, 45),
+        })
+    }
+}
+
+/// An E1.31 packet that can be converted to or from Art-Net
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    /// The data of a single universe
+    Data(DataPacket),
+    /// A synchronization packet
+    Sync(SyncPacket),
+}
+
+impl Packet {
+    /// Parse a received E1.31 packet. Universe discovery packets are returned as an error.
+    pub fn parse(packet: &[u8]) -> Result<Packet> {
+        if packet.len() < ROOT_LENGTH {
+            return Err(Error::InvalidSacnPacket("The packet is too short"));
+        }
+        if read_u16(packet, 0) != 0x0010
+            || read_u16(packet, 2) != 0
+            || packet[4..16] != ACN_PACKET_IDENTIFIER
+        {
+            return Err(Error::InvalidSacnPacket("Invalid ACN root layer"));
+        }
+        match read_u32(packet, 18) {
+            VECTOR_ROOT_E131_DATA => DataPacket::parse(packet).map(Packet::Data),
+            VECTOR_ROOT_E131_EXTENDED => SyncPacket::parse(packet).map(Packet::Sync),
+            _ => Err(Error::InvalidSacnPacket("Unknown root layer vector")),
+        }
+    }
+
+    /// Append the packet to the buffer
+    pub fn write_to(&self, buffer: &mut Vec<u8>) -> Result<()> {
+        match self {
+            Packet::Data(data) => data.write_to(buffer),
+            Packet::Sync(sync) => sync.write_to(buffer),
+        }
+    }
+
+    /// Write the packet to a new buffer
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.write_to(&mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+/// Converts ArtDmx and ArtSync to sACN packets of a single source, and sACN packets back to Art-Net.
+///
+/// sACN universes start at 1, so by default Port-Address 0 is mapped to universe 1. The converter keeps a sequence number per universe, because sACN receivers drop packets that are out of order.
+#[derive(Debug)]
+pub struct Converter {
+    cid: [u8; 16],
+    source_name: String,
+    priority: u8,
+    sync_address: u16,
+    universe_offset: u16,
+    sequences: BTreeMap<u16, u8>,
+    sync_sequence: u8,
+}
+
+impl Converter {
+    /// Create a converter for the source with the given CID and name, which sends at the default priority without synchronization
+    pub fn new(cid: [u8; 16], source_name: &str) -> Converter {
+        Converter {
+            cid,
+            source_name: source_name.into(),
+            priority: DataPacket::DEFAULT_PRIORITY,
+            sync_address: 0,
+            universe_offset: 1,
+            sequences: BTreeMap::new(),
+            sync_sequence: 0,
+        }
+    }
+
+    /// Change the priority of the data packets. Values above `DataPacket::MAX_PRIORITY` are clamped.
+    pub fn set_priority(&mut self, priority: u8) {
+        self.priority = priority.min(DataPacket::MAX_PRIORITY);
+    }
+
+    /// Change the universe that ArtSync is sent on. ArtSync is not converted when this is 0, which is the default.
+    pub fn set_sync_address(&mut self, sync_address: u16) {
+        self.sync_address = sync_address;
+    }
+
+    /// Change the sACN universe of Port-Address 0
+    pub fn set_universe_offset(&mut self, offset: u16) {
+        self.universe_offset = offset;
+    }
+
+    /// The sACN universe of the given Port-Address
+    pub fn universe(&self, port_address: PortAddress) -> Result<u16> {
+        let universe = u32::from(u16::from(port_address)) + u32::from(self.universe_offset);
+        match u16::try_from(universe) {
+            Ok(universe) if (1..=DataPacket::MAX_UNIVERSE).contains(&universe) => Ok(universe),
+            _ => Err(Error::InvalidSacnPacket(
+                "The Port-Address does not map to a valid universe",
+            )),
+        }
+    }
+
+    /// The Port-Address of the given sACN universe
+    pub fn port_address(&self, universe: u16) -> Result<PortAddress> {
+        PortAddress::try_from(i32::from(universe) - i32::from(self.universe_offset))
+    }
+
+    /// Convert ArtDmx to a data packet and ArtSync to a sync packet. Other commands, and ArtSync without a sync address, return `None`.
+    pub fn to_sacn(&mut self, command: &ArtCommand) -> Result<Option<Packet>> {
+        match command {
+            ArtCommand::Output(output) => Ok(Some(Packet::Data(self.output_to_sacn(output)?))),
+            ArtCommand::Sync(_) if self.sync_address != 0 => {
+                self.sync_sequence = self.sync_sequence.wrapping_add(1);
+                Ok(Some(Packet::Sync(SyncPacket {
+                    cid: self.cid,
+                    sequence: self.sync_sequence,
+                    sync_address: self.sync_address,
+                })))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Convert ArtDmx to a data packet
+    pub fn output_to_sacn(&mut self, output: &Output) -> Result<DataPacket> {
+        let universe = self.universe(output.port_address)?;
+        let sequence = self.sequences.entry(universe).or_insert(0);
+        *sequence = sequence.wrapping_add(1);
+        Ok(DataPacket {
+            cid: self.cid,
+            source_name: self.source_name.clone(),
+            priority: self.priority,
+            sync_address: self.sync_address,
+            sequence: *sequence,
+            options: 0,
+            universe,
+            start_code: 0,
+            data: output.data.to_vec(),
+        })
+    }
+
+    /// Convert a data packet to ArtDmx and a sync packet to ArtSync. Preview data, terminated streams and alternate start codes return `None`, because they have no ArtDmx equivalent.
+    pub fn to_art_net(&self, packet: &Packet) -> Result<Option<ArtCommand>> {
+        match packet {
+            Packet::Data(data) => {
+                if data.start_code != 0
+                    || data.has_option(DataPacket::OPTION_PREVIEW)
+                    || data.has_option(DataPacket::OPTION_STREAM_TERMINATED)
+                {
+                    return Ok(None);
+                }
+                let output = Output {
+                    sequence: data.sequence,
+                    ..Output::new(self.port_address(data.universe)?, data.data.clone())
+                };
+                Ok(Some(output.into()))
+            }
+            Packet::Sync(_) => Ok(Some(ArtSync::default().into())),
+        }
+    }
+}
+
+fn write_root_layer(buffer: &mut Vec<u8>, length: usize, vector: u32, cid: &[u8; 16]) {
+    buffer.extend_from_slice(&0x0010u16.to_be_bytes());
+    buffer.extend_from_slice(&0u16.to_be_bytes());
+    buffer.extend_from_slice(&ACN_PACKET_IDENTIFIER);
+    buffer.extend_from_slice(&flags_and_length(length - 16));
+    buffer.extend_from_slice(&vector.to_be_bytes());
+    buffer.extend_from_slice(cid);
+}
+
+/// The flags and length field of a PDU, which starts every layer
+fn flags_and_length(length: usize) -> [u8; 2] {
+    (0x7000 | length as u16).to_be_bytes()
+}
+
+fn read_u16(packet: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([packet[offset], packet[offset + 1]])
+}
+
+fn read_u32(packet: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        packet[offset],
+        packet[offset + 1],
+        packet[offset + 2],
+        packet[offset + 3],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_packet() {
+        let mut converter = Converter::new([0xAB; 16], "Bridge");
+        converter.set_priority(250);
+        converter.set_sync_address(7);
+
+        let output = ArtCommand::Output(Output::new(2.into(), vec![1, 2, 3]));
+        let packet = converter.to_sacn(&output).unwrap().unwrap();
+        let bytes = packet.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 129);
+        assert_eq!(&bytes[4..16], b"ASC-E1.17\0\0\0");
+        // The flags and length of the root, framing and DMP layer
+        assert_eq!(&bytes[16..18], &[0x70, 113]);
+        assert_eq!(&bytes[38..40], &[0x70, 91]);
+        assert_eq!(&bytes[115..117], &[0x70, 14]);
+        assert_eq!(&bytes[44..51], b"Bridge\0");
+        assert_eq!(&bytes[123..129], &[0, 4, 0, 1, 2, 3]);
+
+        let data = match Packet::parse(&bytes).unwrap() {
+            Packet::Data(data) => data,
+            packet => panic!("Unexpected packet {:?}", packet),
+        };
+        assert_eq!(data.universe, 3);
+        assert_eq!(data.priority, DataPacket::MAX_PRIORITY);
+        assert_eq!(data.sync_address, 7);
+        assert_eq!(data.sequence, 1);
+        assert_eq!(data.source_name, "Bridge");
+        assert_eq!(Packet::Data(data.clone()), packet);
+
+        match converter.to_art_net(&Packet::Data(data.clone())).unwrap() {
+            Some(ArtCommand::Output(output)) => {
+                assert_eq!(u16::from(output.port_address), 2);
+                assert_eq!(output.data.as_ref(), &vec![1, 2, 3]);
+            }
+            command => panic!("Unexpected command {:?}", command),
+        }
+
+        let preview = Packet::Data(DataPacket {
+            options: DataPacket::OPTION_PREVIEW,
+            ..data
+        });
+        assert!(converter.to_art_net(&preview).unwrap().is_none());
+
+        // Every universe has its own sequence
+        let packet = converter.to_sacn(&output).unwrap().unwrap();
+        assert!(matches!(
+            packet,
+            Packet::Data(DataPacket { sequence: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn sync_packet() {
+        let mut converter = Converter::new([1; 16], "Bridge");
+        let sync = ArtSync::default().into_command();
+        assert_eq!(converter.to_sacn(&sync).unwrap(), None);
+
+        converter.set_sync_address(100);
+        let packet = converter.to_sacn(&sync).unwrap().unwrap();
+        let bytes = packet.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 49);
+        assert_eq!(Packet::parse(&bytes).unwrap(), packet);
+        assert!(matches!(
+            converter.to_art_net(&packet),
+            Ok(Some(ArtCommand::Sync(_)))
+        ));
+    }
+
+    #[test]
+    fn invalid() {
+        let converter = Converter::new([1; 16], "Bridge");
+        assert!(converter
+            .universe(PortAddress::try_from(32767u16).unwrap())
+            .is_ok());
+        assert!(converter.port_address(0).is_err());
+        assert_eq!(u16::from(converter.port_address(1).unwrap()), 0);
+
+        assert_eq!(
+            Packet::parse(b"Art-Net\0\x00\x50"),
+            Err(Error::InvalidSacnPacket("The packet is too short"))
+        );
+        let mut bytes = Packet::Sync(SyncPacket {
+            cid: [1; 16],
+            sequence: 0,
+            sync_address: 1,
+        })
+        .to_bytes()
+        .unwrap();
+        bytes[21] = 0x09;
+        assert_eq!(
+            Packet::parse(&bytes),
+            Err(Error::InvalidSacnPacket("Unknown root layer vector"))
+        );
+    }
+}
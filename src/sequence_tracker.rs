@@ -0,0 +1,290 @@
+use crate::{Output, PortAddress};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// A frame that is at most this many sequence numbers behind the last released frame arrived late. A frame that is further behind is treated as the start of a new stream, e.g. because the sender restarted.
+const LATE_LIMIT: u8 = 32;
+
+/// The number of sequence numbers in a cycle. 0 is not part of it, as it disables the sequence.
+const CYCLE: u16 = 255;
+
+/// The counters of a single universe of a [SequenceTracker](struct.SequenceTracker.html)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SequenceStats {
+    /// The number of frames with a sequence number that were received
+    pub received: u64,
+
+    /// The number of sequence numbers that were skipped, because their frames never arrived in time
+    pub lost: u64,
+
+    /// The number of frames that were received more than once, which are dropped
+    pub duplicates: u64,
+
+    /// The number of frames that arrived after a later frame, and were put back in order by the reorder window
+    pub reordered: u64,
+
+    /// The number of frames that arrived after a later frame was already released, which are dropped
+    pub late: u64,
+}
+
+#[derive(Debug, Default)]
+struct Universe {
+    last: Option<u8>,
+    /// The frames that arrived after a gap, with the time that they arrived
+    held: Vec<(Output, Instant)>,
+    stats: SequenceStats,
+}
+
+/// How far sequence number `to` is ahead of `from`, from 0 to 254
+fn distance(from: u8, to: u8) -> u8 {
+    ((u16::from(to) + CYCLE - u16::from(from)) % CYCLE) as u8
+}
+
+impl Universe {
+    /// Release the frame and the held frames that directly follow it
+    fn release(&mut self, output: Output, released: &mut Vec<Output>) {
+        let mut last = output.sequence;
+        released.push(output);
+        while let Some(index) = self
+            .held
+            .iter()
+            .position(|(held, _)| distance(last, held.sequence) == 1)
+        {
+            let (held, _) = self.held.remove(index);
+            last = held.sequence;
+            released.push(held);
+        }
+        self.last = Some(last);
+    }
+
+    /// Give up on the gap before the first held frame, and release it
+    fn skip_gap(&mut self, released: &mut Vec<Output>) {
+        let last = match self.last {
+            Some(last) => last,
+            None => return,
+        };
+        let first = self
+            .held
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, (held, _))| distance(last, held.sequence))
+            .map(|(index, _)| index);
+        if let Some(index) = first {
+            let (output, _) = self.held.remove(index);
+            self.stats.lost += u64::from(distance(last, output.sequence) - 1);
+            self.release(output, released);
+        }
+    }
+}
+
+/// Detects lost, duplicated and out-of-order ArtDmx frames with their `sequence`, and puts reordered frames back in order.
+///
+/// Art-Net is sent over UDP, so frames can get lost or arrive out of order, especially over WiFi or a WAN. Every universe is tracked separately. Frames with sequence 0 do not use the sequence, and are always passed on.
+///
+/// Without a reorder window, frames are released immediately and frames that arrive after a later frame are dropped. With a window, frames after a gap are held until the missing frame arrives, the window is full or the timeout passed. Pass every received frame to `push`, handle the frames that it returns, and call `tick` at `next_deadline`.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::{Duration, Instant};
+///
+/// let frame = |sequence| Output {
+///     sequence,
+///     ..Output::new(1.into(), vec![sequence])
+/// };
+/// let mut tracker = SequenceTracker::with_reorder(4, Duration::from_millis(20));
+/// let now = Instant::now();
+/// assert_eq!(tracker.push(frame(1), now).len(), 1);
+/// // 3 arrives before 2, and is held until 2 arrives
+/// assert!(tracker.push(frame(3), now).is_empty());
+/// let released = tracker.push(frame(2), now);
+/// assert_eq!(released.iter().map(|o| o.sequence).collect::<Vec<_>>(), vec![2, 3]);
+/// assert_eq!(tracker.stats(1.into()).unwrap().reordered, 1);
+/// ```
+#[derive(Debug)]
+pub struct SequenceTracker {
+    universes: HashMap<PortAddress, Universe>,
+    window: usize,
+    timeout: Duration,
+}
+
+impl Default for SequenceTracker {
+    fn default() -> SequenceTracker {
+        SequenceTracker::new()
+    }
+}
+
+impl SequenceTracker {
+    /// Create a tracker without a reorder window, which only detects gaps and drops duplicated and late frames
+    pub fn new() -> SequenceTracker {
+        SequenceTracker::with_reorder(0, Duration::from_secs(0))
+    }
+
+    /// Create a tracker that holds at most `window` frames per universe after a gap, for at most `timeout`
+    pub fn with_reorder(window: usize, timeout: Duration) -> SequenceTracker {
+        SequenceTracker {
+            universes: HashMap::new(),
+            window,
+            timeout,
+        }
+    }
+
+    /// Handle a received frame. Returns the frames that can be released, in order.
+    pub fn push(&mut self, output: Output, now: Instant) -> Vec<Output> {
+        let sequence = output.sequence;
+        if sequence == 0 {
+            return vec![output];
+        }
+        let universe = self.universes.entry(output.port_address).or_default();
+        universe.stats.received += 1;
+        let mut released = Vec::new();
+        let last = match universe.last {
+            Some(last) => last,
+            None => {
+                universe.release(output, &mut released);
+                return released;
+            }
+        };
+
+        let ahead = distance(last, sequence);
+        if ahead == 0 || universe.held.iter().any(|(h, _)| h.sequence == sequence) {
+            universe.stats.duplicates += 1;
+        } else if ahead >= (CYCLE as u8) - LATE_LIMIT {
+            universe.stats.late += 1;
+        } else if ahead == 1 {
+            if !universe.held.is_empty() {
+                universe.stats.reordered += 1;
+            }
+            universe.release(output, &mut released);
+        } else if self.window == 0 {
+            universe.stats.lost += u64::from(ahead - 1);
+            universe.release(output, &mut released);
+        } else {
+            // A frame that fills part of a gap arrived after a later frame
+            let last_held = universe
+                .held
+                .iter()
+                .map(|(h, _)| distance(last, h.sequence))
+                .max();
+            if last_held.is_some_and(|held| held > ahead) {
+                universe.stats.reordered += 1;
+            }
+            universe.held.push((output, now));
+            while universe.held.len() > self.window {
+                universe.skip_gap(&mut released);
+            }
+        }
+        released
+    }
+
+    /// Release the frames that were held for longer than the timeout, skipping the gaps before them
+    pub fn tick(&mut self, now: Instant) -> Vec<Output> {
+        let timeout = self.timeout;
+        let mut released = Vec::new();
+        for universe in self.universes.values_mut() {
+            while universe
+                .held
+                .iter()
+                .any(|(_, arrived)| now >= *arrived + timeout)
+            {
+                universe.skip_gap(&mut released);
+            }
+        }
+        released
+    }
+
+    /// When `tick` should be called next, if any frames are held
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.universes
+            .values()
+            .flat_map(|universe| universe.held.iter())
+            .map(|(_, arrived)| *arrived + self.timeout)
+            .min()
+    }
+
+    /// The counters of the given universe, if any frames with a sequence were received for it
+    pub fn stats(&self, port_address: PortAddress) -> Option<&SequenceStats> {
+        self.universes
+            .get(&port_address)
+            .map(|universe| &universe.stats)
+    }
+
+    /// The universes that frames with a sequence were received for, with their counters
+    pub fn universes(&self) -> impl Iterator<Item = (PortAddress, &SequenceStats)> {
+        self.universes
+            .iter()
+            .map(|(port_address, universe)| (*port_address, &universe.stats))
+    }
+
+    /// Forget the sequence and counters of the given universe, e.g. when its sender changed
+    pub fn reset(&mut self, port_address: PortAddress) {
+        self.universes.remove(&port_address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(sequence: u8) -> Output {
+        Output {
+            sequence,
+            ..Output::new(1.into(), vec![sequence])
+        }
+    }
+
+    fn sequences(outputs: Vec<Output>) -> Vec<u8> {
+        outputs.iter().map(|output| output.sequence).collect()
+    }
+
+    #[test]
+    fn without_window() {
+        let mut tracker = SequenceTracker::new();
+        let now = Instant::now();
+        let mut released = Vec::new();
+        for sequence in [254, 255, 1, 1, 4, 3, 0, 5] {
+            released.extend(tracker.push(frame(sequence), now));
+        }
+        // The late 3 and the duplicated 1 are dropped
+        assert_eq!(sequences(released), vec![254, 255, 1, 4, 0, 5]);
+        assert_eq!(
+            *tracker.stats(1.into()).unwrap(),
+            SequenceStats {
+                received: 7,
+                lost: 2,
+                duplicates: 1,
+                reordered: 0,
+                late: 1,
+            }
+        );
+        assert!(tracker.next_deadline().is_none());
+
+        // A sender that restarts is not late
+        assert_eq!(sequences(tracker.push(frame(100), now)), vec![100]);
+        tracker.reset(1.into());
+        assert!(tracker.stats(1.into()).is_none());
+    }
+
+    #[test]
+    fn reorder() {
+        let mut tracker = SequenceTracker::with_reorder(2, Duration::from_millis(20));
+        let start = Instant::now();
+        assert_eq!(sequences(tracker.push(frame(10), start)), vec![10]);
+        assert!(tracker.push(frame(13), start).is_empty());
+        assert!(tracker.push(frame(12), start).is_empty());
+        // 11 fills the gap, 12 and 13 follow
+        assert_eq!(sequences(tracker.push(frame(11), start)), vec![11, 12, 13]);
+        assert_eq!(tracker.stats(1.into()).unwrap().reordered, 2);
+
+        // 14 never arrives, the window overflows
+        assert!(tracker.push(frame(15), start).is_empty());
+        assert!(tracker.push(frame(17), start).is_empty());
+        assert_eq!(sequences(tracker.push(frame(18), start)), vec![15]);
+
+        // 16 never arrives either, the timeout passes
+        let deadline = tracker.next_deadline().unwrap();
+        assert_eq!(deadline, start + Duration::from_millis(20));
+        assert_eq!(sequences(tracker.tick(deadline)), vec![17, 18]);
+        assert_eq!(tracker.stats(1.into()).unwrap().lost, 2);
+        assert!(tracker.next_deadline().is_none());
+    }
+}
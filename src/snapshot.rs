@@ -0,0 +1,287 @@
+use crate::command::null_terminated;
+use crate::{DiscoveredNode, DiscoveryManager, NodeId};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::Ipv4Addr;
+use std::time::{Instant, SystemTime};
+
+/// The state of the Art-Net network at a moment in time, as seen by a [DiscoveryManager](struct.DiscoveryManager.html).
+///
+/// The snapshot only contains owned, plain data, so it can be serialized with any serde format, e.g. JSON or TOML, by inventory and monitoring tools. The nodes are ordered by their id and the universes by their Port-Address, so two snapshots of the same network are equal.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::Instant;
+///
+/// let mut discovery = DiscoveryManager::new();
+/// let mut reply = PollReply::default();
+/// reply.num_ports = [0, 1];
+/// reply.port_types[0] = 0x80;
+/// discovery.handle(&reply.into(), Instant::now());
+///
+/// let snapshot = NetworkSnapshot::of(&discovery);
+/// assert_eq!(snapshot.nodes.len(), 1);
+/// assert_eq!(snapshot.universes[0].outputs, vec![snapshot.nodes[0].id]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkSnapshot {
+    /// When the snapshot was taken
+    pub taken_at: SystemTime,
+
+    /// The discovered nodes
+    pub nodes: Vec<NodeSnapshot>,
+
+    /// The universes that are used by at least one port of a node
+    pub universes: Vec<UniverseSnapshot>,
+}
+
+/// A discovered node in a [NetworkSnapshot](struct.NetworkSnapshot.html), from its most recent `PollReply`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeSnapshot {
+    /// The IP address and bind index of the node
+    pub id: NodeId,
+
+    /// The UDP port that the node listens on
+    pub port: u16,
+
+    /// The MAC address of the node, e.g. `"02:00:00:00:00:01"`
+    pub mac: String,
+
+    /// The short name of the node
+    pub short_name: String,
+
+    /// The long name of the node
+    pub long_name: String,
+
+    /// The most recent status report of the node
+    pub node_report: String,
+
+    /// The firmware version of the node
+    pub firmware_version: u16,
+
+    /// The OEM code of the node
+    pub oem: u16,
+
+    /// The ESTA manufacturer code of the node
+    pub esta_code: u16,
+
+    /// The ports of the node
+    pub ports: Vec<PortSnapshot>,
+
+    /// When the most recent reply of the node was received
+    pub last_seen: SystemTime,
+}
+
+/// A port of a node in a [NetworkSnapshot](struct.NetworkSnapshot.html)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortSnapshot {
+    /// The index of the port on the node, from 0 to 3
+    pub index: u8,
+
+    /// The protocol of the port, from bits 5-0 of its port type. 0 is DMX512, 5 is Art-Net.
+    pub protocol: u8,
+
+    /// The Port-Address that the port sends to the network, if it is an input
+    pub input: Option<u16>,
+
+    /// The Port-Address that the port receives from the network, if it is an output
+    pub output: Option<u16>,
+}
+
+/// A universe in a [NetworkSnapshot](struct.NetworkSnapshot.html), with the nodes that use it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UniverseSnapshot {
+    /// The 15 bit Port-Address of the universe
+    pub port_address: u16,
+
+    /// The nodes with an input port for this universe
+    pub inputs: Vec<NodeId>,
+
+    /// The nodes with an output port for this universe
+    pub outputs: Vec<NodeId>,
+}
+
+impl NetworkSnapshot {
+    /// Take a snapshot of the nodes that the discovery manager currently knows
+    pub fn of(discovery: &DiscoveryManager) -> NetworkSnapshot {
+        NetworkSnapshot::at(discovery, Instant::now(), SystemTime::now())
+    }
+
+    /// Take a snapshot, where `now` and `taken_at` are the same moment. This converts the times that nodes were last seen to `SystemTime`.
+    pub fn at(discovery: &DiscoveryManager, now: Instant, taken_at: SystemTime) -> NetworkSnapshot {
+        let mut nodes: Vec<NodeSnapshot> = discovery
+            .nodes()
+            .map(|(id, node)| NodeSnapshot::new(*id, node, now, taken_at))
+            .collect();
+        nodes.sort_by_key(|node| node.id);
+
+        let mut universes: BTreeMap<u16, UniverseSnapshot> = BTreeMap::new();
+        for node in &nodes {
+            for port in &node.ports {
+                if let Some(input) = port.input {
+                    universe(&mut universes, input).inputs.push(node.id);
+                }
+                if let Some(output) = port.output {
+                    universe(&mut universes, output).outputs.push(node.id);
+                }
+            }
+        }
+        // A node with several ports for the same universe is listed once
+        for universe in universes.values_mut() {
+            universe.inputs.dedup();
+            universe.outputs.dedup();
+        }
+
+        NetworkSnapshot {
+            taken_at,
+            nodes,
+            universes: universes.into_values().collect(),
+        }
+    }
+
+    /// The node with the given id, if it was discovered
+    pub fn node(&self, id: NodeId) -> Option<&NodeSnapshot> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// The nodes with the given IP address, e.g. the nodes of a single device
+    pub fn nodes_at(&self, address: Ipv4Addr) -> impl Iterator<Item = &NodeSnapshot> {
+        self.nodes
+            .iter()
+            .filter(move |node| node.id.address == address)
+    }
+}
+
+fn universe(
+    universes: &mut BTreeMap<u16, UniverseSnapshot>,
+    port_address: u16,
+) -> &mut UniverseSnapshot {
+    universes
+        .entry(port_address)
+        .or_insert_with(|| UniverseSnapshot {
+            port_address,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        })
+}
+
+impl NodeSnapshot {
+    fn new(id: NodeId, node: &DiscoveredNode, now: Instant, taken_at: SystemTime) -> NodeSnapshot {
+        let reply = &node.reply;
+        let ports = (0..usize::from(reply.num_ports[1]).min(4))
+            .map(|port| PortSnapshot {
+                index: port as u8,
+                protocol: reply.port_types[port] & 0x3F,
+                input: reply.input_port_address(port).map(u16::from),
+                output: reply.output_port_address(port).map(u16::from),
+            })
+            .collect();
+        let mac = reply
+            .mac
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<_>>()
+            .join(":");
+        let age = now.saturating_duration_since(node.last_seen);
+        NodeSnapshot {
+            id,
+            port: reply.port,
+            mac,
+            short_name: null_terminated(&reply.short_name).into_owned(),
+            long_name: null_terminated(&reply.long_name).into_owned(),
+            node_report: null_terminated(&reply.node_report).into_owned(),
+            firmware_version: u16::from_be_bytes(reply.version),
+            oem: u16::from_be_bytes(reply.oem),
+            esta_code: reply.esta_code,
+            ports,
+            last_seen: taken_at.checked_sub(age).unwrap_or(taken_at),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PollReply;
+    use std::time::Duration;
+
+    #[test]
+    fn snapshot() {
+        let mut discovery = DiscoveryManager::new();
+        let now = Instant::now();
+        let taken_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+
+        let mut reply = PollReply {
+            address: [10, 0, 0, 2].into(),
+            version: [1, 2],
+            mac: [2, 0, 0, 0, 0, 0xAB],
+            num_ports: [0, 2],
+            port_types: [0xC0, 0x80, 0, 0],
+            port_address: [0, 1],
+            swin: [3, 0, 0, 0],
+            swout: [4, 4, 0, 0],
+            ..PollReply::default()
+        };
+        reply.short_name[..4].copy_from_slice(b"Dimm");
+        discovery.handle(&reply.into(), now - Duration::from_secs(2));
+        let other = PollReply {
+            address: [10, 0, 0, 1].into(),
+            num_ports: [0, 1],
+            port_types: [0x80, 0, 0, 0],
+            port_address: [0, 1],
+            swout: [4, 0, 0, 0],
+            ..PollReply::default()
+        };
+        discovery.handle(&other.into(), now);
+
+        let snapshot = NetworkSnapshot::at(&discovery, now, taken_at);
+        let first = NodeId {
+            address: [10, 0, 0, 1].into(),
+            bind_index: 0,
+        };
+        let second = NodeId {
+            address: [10, 0, 0, 2].into(),
+            bind_index: 0,
+        };
+        assert_eq!(
+            snapshot.nodes.iter().map(|n| n.id).collect::<Vec<_>>(),
+            vec![first, second]
+        );
+        let node = snapshot.node(second).unwrap();
+        assert_eq!(node.mac, "02:00:00:00:00:ab");
+        assert_eq!(node.short_name, "Dimm");
+        assert_eq!(node.firmware_version, 0x0102);
+        assert_eq!(node.last_seen, taken_at - Duration::from_secs(2));
+        assert_eq!(
+            node.ports[0],
+            PortSnapshot {
+                index: 0,
+                protocol: 0,
+                input: Some(0x13),
+                output: Some(0x14),
+            }
+        );
+        assert_eq!(snapshot.nodes_at(second.address).count(), 1);
+
+        assert_eq!(
+            snapshot.universes,
+            vec![
+                UniverseSnapshot {
+                    port_address: 0x13,
+                    inputs: vec![second],
+                    outputs: vec![],
+                },
+                UniverseSnapshot {
+                    port_address: 0x14,
+                    inputs: vec![],
+                    outputs: vec![first, second],
+                },
+            ]
+        );
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert!(json.contains("\"address\":\"10.0.0.2\""));
+        let parsed: NetworkSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, snapshot);
+    }
+}
@@ -0,0 +1,349 @@
+use crate::controller::invalid_data;
+use crate::{ArtCommand, Encoder, ParseOptions, RecvBuffer};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, ToSocketAddrs, UdpSocket};
+
+/// The IPv4 address and netmask of a network interface, to keep Art-Net traffic on a single network of a host with several network cards.
+///
+/// ```rust
+/// use artnet_protocol::*;
+///
+/// let interface = Interface::new([2, 0, 0, 10].into(), [255, 0, 0, 0].into());
+/// assert_eq!(interface.broadcast_address(), std::net::Ipv4Addr::new(2, 255, 255, 255));
+/// assert!(interface.contains([2, 1, 2, 3].into()));
+/// assert!(!interface.contains([10, 0, 0, 1].into()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Interface {
+    /// The address of the host on the interface
+    pub address: Ipv4Addr,
+
+    /// The netmask of the network of the interface, e.g. 255.0.0.0 for the 2.x.x.x network of Art-Net
+    pub netmask: Ipv4Addr,
+}
+
+impl Interface {
+    /// An interface with the given address and netmask
+    pub fn new(address: Ipv4Addr, netmask: Ipv4Addr) -> Interface {
+        Interface { address, netmask }
+    }
+
+    /// An interface with the given address and prefix length, e.g. 8 for 2.0.0.10/8
+    pub fn with_prefix(address: Ipv4Addr, prefix: u8) -> Interface {
+        let netmask = u32::MAX
+            .checked_shl(32 - u32::from(prefix.min(32)))
+            .unwrap_or(0);
+        Interface::new(address, netmask.into())
+    }
+
+    /// The directed broadcast address of the network of the interface
+    pub fn broadcast_address(&self) -> Ipv4Addr {
+        crate::directed_broadcast(self.address, self.netmask)
+    }
+
+    /// If the given address is on the network of the interface
+    pub fn contains(&self, address: Ipv4Addr) -> bool {
+        let netmask = u32::from(self.netmask);
+        u32::from(address) & netmask == u32::from(self.address) & netmask
+    }
+}
+
+#[cfg(feature = "interfaces")]
+impl Interface {
+    /// The IPv4 interfaces of this host, with the names of their network devices, e.g. `"eth1"` for `ArtNetSocket::bind_device`.
+    ///
+    /// This is only supported on Unix. Other platforms return an error of kind `Unsupported`.
+    pub fn list() -> io::Result<Vec<(String, Interface)>> {
+        list_interfaces()
+    }
+
+    /// The interface of this host with the given address, to find the netmask and directed broadcast address of a local address
+    ///
+    /// ```rust,no_run
+    /// use artnet_protocol::*;
+    ///
+    /// let interface = Interface::find([2, 0, 0, 10].into()).unwrap().expect("No such interface");
+    /// let socket = ArtNetSocket::bind_interface(interface).unwrap();
+    /// ```
+    pub fn find(address: Ipv4Addr) -> io::Result<Option<Interface>> {
+        Ok(Interface::list()?
+            .into_iter()
+            .map(|(_, interface)| interface)
+            .find(|interface| interface.address == address))
+    }
+}
+
+/// A blocking UDP socket that is set up for Art-Net, and sends and receives `ArtCommand`s. The buffers for encoding and receiving are reused for every message.
+///
+/// The socket allows broadcasting, and binds with `SO_REUSEADDR`, so several applications on the same machine can use port 6454. Errors of this crate are returned as an `std::io::Error` of kind `InvalidData`, with the `artnet_protocol::Error` as its inner error.
+///
+/// See [aio::ArtNetSocket](aio/struct.ArtNetSocket.html) for an async socket.
+///
+/// ```rust,no_run
+/// use artnet_protocol::*;
+///
+/// let mut socket = ArtNetSocket::bind_default().unwrap();
+/// socket.broadcast(&Poll::default().into()).unwrap();
+/// loop {
+///     match socket.recv_command() {
+///         Ok((command, addr)) => println!("Received {:?} from {}", command, addr),
+///         Err(e) => println!("Received an invalid message: {}", e),
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ArtNetSocket {
+    socket: UdpSocket,
+    broadcast_address: SocketAddr,
+    options: ParseOptions,
+    encoder: Encoder,
+    buffer: RecvBuffer,
+}
+
+impl ArtNetSocket {
+    /// The UDP port of Art-Net
+    pub const PORT: u16 = 6454;
+
+    /// Bind to port 6454 on all interfaces, and broadcast to 255.255.255.255
+    pub fn bind_default() -> io::Result<ArtNetSocket> {
+        ArtNetSocket::bind((Ipv4Addr::UNSPECIFIED, ArtNetSocket::PORT))
+    }
+
+    /// Bind to port 6454 on the interface with the given address.
+    ///
+    /// On Linux, a socket that is bound to the address of an interface does not receive broadcasts. Use `bind_default` to receive the polls of controllers.
+    pub fn bind_on(interface: Ipv4Addr) -> io::Result<ArtNetSocket> {
+        ArtNetSocket::bind((interface, ArtNetSocket::PORT))
+    }
+
+    /// Bind to port 6454 on the given interface, and broadcast to the directed broadcast address of its network. Traffic that is sent leaves through this interface.
+    ///
+    /// On Linux, a socket that is bound to the address of an interface does not receive broadcasts. Use `bind_device` to receive them as well.
+    pub fn bind_interface(interface: Interface) -> io::Result<ArtNetSocket> {
+        Ok(ArtNetSocket::bind((interface.address, ArtNetSocket::PORT))?
+            .with_broadcast_address(broadcast_address(interface)))
+    }
+
+    /// Bind to port 6454 on all addresses of the network device with the given name, e.g. `"eth1"`, and broadcast to the directed broadcast address of the given interface. Unlike `bind_interface`, this receives broadcasts.
+    ///
+    /// This uses `SO_BINDTODEVICE`, which usually needs the `CAP_NET_RAW` capability.
+    #[cfg(target_os = "linux")]
+    pub fn bind_device(device: &str, interface: Interface) -> io::Result<ArtNetSocket> {
+        let addr = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, ArtNetSocket::PORT).into();
+        let socket = reusable_socket(addr)?;
+        socket.bind_device(Some(device.as_bytes()))?;
+        socket.bind(&addr.into())?;
+        Ok(ArtNetSocket::new(socket.into()).with_broadcast_address(broadcast_address(interface)))
+    }
+
+    /// Bind to the given address, and broadcast to 255.255.255.255
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<ArtNetSocket> {
+        let addr = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No address to bind to"))?;
+        let socket = reusable_socket(addr)?;
+        socket.bind(&addr.into())?;
+        Ok(ArtNetSocket::new(socket.into()))
+    }
+
+    /// Send and receive on an existing socket. Broadcasting is not enabled on it.
+    pub fn new(socket: UdpSocket) -> ArtNetSocket {
+        ArtNetSocket {
+            socket,
+            broadcast_address: SocketAddrV4::new(Ipv4Addr::BROADCAST, ArtNetSocket::PORT).into(),
+            options: ParseOptions::default(),
+            encoder: Encoder::new(),
+            buffer: RecvBuffer::new(),
+        }
+    }
+
+    /// Broadcast to the given address, e.g. the directed broadcast address of a network such as 2.255.255.255
+    pub fn with_broadcast_address(mut self, broadcast_address: SocketAddr) -> ArtNetSocket {
+        self.broadcast_address = broadcast_address;
+        self
+    }
+
+    /// Decode received messages with the given options
+    pub fn with_options(mut self, options: ParseOptions) -> ArtNetSocket {
+        self.options = options;
+        self
+    }
+
+    /// The address that `broadcast` sends to
+    pub fn broadcast_address(&self) -> SocketAddr {
+        self.broadcast_address
+    }
+
+    /// The underlying socket, e.g. to set a read timeout
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Stop using Art-Net, and return the underlying socket
+    pub fn into_inner(self) -> UdpSocket {
+        self.socket
+    }
+
+    /// Send a command to the given address
+    pub fn send_command(&mut self, command: &ArtCommand, addr: SocketAddr) -> io::Result<()> {
+        let bytes = self.encoder.encode(command).map_err(invalid_data)?;
+        self.socket.send_to(bytes, addr)?;
+        Ok(())
+    }
+
+    /// Send a command to the broadcast address
+    pub fn broadcast(&mut self, command: &ArtCommand) -> io::Result<()> {
+        self.send_command(command, self.broadcast_address)
+    }
+
+    /// Receive the next message, and the address that sent it. A message that can not be parsed is returned as an error, the next call receives the next message.
+    pub fn recv_command(&mut self) -> io::Result<(ArtCommand, SocketAddr)> {
+        let (length, addr) = self.socket.recv_from(&mut self.buffer)?;
+        let command = self
+            .buffer
+            .parse_with(length, &self.options)
+            .map_err(invalid_data)?;
+        Ok((command, addr))
+    }
+}
+
+/// An unbound UDP socket that allows broadcasting and reusing the address
+fn reusable_socket(addr: SocketAddr) -> io::Result<Socket> {
+    let socket = Socket::new(Domain::for_address(addr), Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.set_broadcast(true)?;
+    Ok(socket)
+}
+
+#[cfg(all(feature = "interfaces", unix))]
+fn list_interfaces() -> io::Result<Vec<(String, Interface)>> {
+    let mut addrs = core::ptr::null_mut();
+    // SAFETY: getifaddrs allocates a linked list, which is freed below
+    if unsafe { libc::getifaddrs(&mut addrs) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut interfaces = Vec::new();
+    let mut current = addrs;
+    while !current.is_null() {
+        // SAFETY: every entry in the list is valid until it is freed
+        let entry = unsafe { &*current };
+        current = entry.ifa_next;
+        if entry.ifa_addr.is_null() || entry.ifa_netmask.is_null() {
+            continue;
+        }
+        // SAFETY: the addresses are valid, and are a sockaddr_in for AF_INET
+        let (address, netmask) = unsafe {
+            if i32::from((*entry.ifa_addr).sa_family) != libc::AF_INET {
+                continue;
+            }
+            let address = &*(entry.ifa_addr as *const libc::sockaddr_in);
+            let netmask = &*(entry.ifa_netmask as *const libc::sockaddr_in);
+            (address.sin_addr.s_addr, netmask.sin_addr.s_addr)
+        };
+        // SAFETY: the name is a null terminated string
+        let name = unsafe { std::ffi::CStr::from_ptr(entry.ifa_name) };
+        interfaces.push((
+            name.to_string_lossy().into_owned(),
+            Interface::new(u32::from_be(address).into(), u32::from_be(netmask).into()),
+        ));
+    }
+    // SAFETY: the list was allocated by getifaddrs, and is not used after this
+    unsafe { libc::freeifaddrs(addrs) };
+    Ok(interfaces)
+}
+
+#[cfg(all(feature = "interfaces", not(unix)))]
+fn list_interfaces() -> io::Result<Vec<(String, Interface)>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Listing the interfaces is only supported on Unix",
+    ))
+}
+
+/// The directed broadcast address of the network of the interface, on the Art-Net port
+fn broadcast_address(interface: Interface) -> SocketAddr {
+    SocketAddrV4::new(interface.broadcast_address(), ArtNetSocket::PORT).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Output;
+
+    #[test]
+    fn send_recv() {
+        let mut receiver = ArtNetSocket::bind("127.0.0.1:0").unwrap();
+        let addr = receiver.socket().local_addr().unwrap();
+        let mut sender = ArtNetSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .with_broadcast_address(addr);
+        assert!(sender.socket().broadcast().unwrap());
+
+        let output = Output::new(3.into(), vec![1, 2, 3, 4]);
+        sender.broadcast(&output.into()).unwrap();
+        match receiver.recv_command().unwrap() {
+            (ArtCommand::Output(output), from) => {
+                assert_eq!(output.data.as_ref(), &vec![1, 2, 3, 4]);
+                assert_eq!(from, sender.socket().local_addr().unwrap());
+            }
+            (command, _) => panic!("Unexpected command {:?}", command),
+        }
+
+        sender
+            .socket()
+            .send_to(b"Not Art-Net at all", addr)
+            .unwrap();
+        let err = receiver.recv_command().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(all(feature = "interfaces", unix))]
+    #[test]
+    fn list() {
+        let interfaces = Interface::list().unwrap();
+        let (_, loopback) = interfaces
+            .iter()
+            .find(|(_, interface)| interface.address == Ipv4Addr::LOCALHOST)
+            .unwrap();
+        assert_eq!(
+            loopback.broadcast_address(),
+            Ipv4Addr::new(127, 255, 255, 255)
+        );
+        assert_eq!(
+            Interface::find(Ipv4Addr::LOCALHOST).unwrap(),
+            Some(*loopback)
+        );
+        assert_eq!(Interface::find([192, 0, 2, 1].into()).unwrap(), None);
+    }
+
+    #[test]
+    fn interface() {
+        let interface = Interface::with_prefix([10, 1, 2, 3].into(), 16);
+        assert_eq!(interface.netmask, Ipv4Addr::new(255, 255, 0, 0));
+        assert_eq!(
+            interface.broadcast_address(),
+            Ipv4Addr::new(10, 1, 255, 255)
+        );
+        assert_eq!(
+            Interface::with_prefix(Ipv4Addr::LOCALHOST, 0).netmask,
+            Ipv4Addr::UNSPECIFIED
+        );
+        assert_eq!(
+            Interface::with_prefix(Ipv4Addr::LOCALHOST, 32).broadcast_address(),
+            Ipv4Addr::LOCALHOST
+        );
+
+        let socket =
+            ArtNetSocket::bind_interface(Interface::with_prefix(Ipv4Addr::LOCALHOST, 8)).unwrap();
+        assert_eq!(
+            socket.socket().local_addr().unwrap(),
+            "127.0.0.1:6454".parse().unwrap()
+        );
+        assert_eq!(
+            socket.broadcast_address(),
+            "127.255.255.255:6454".parse().unwrap()
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use crate::{ArtCommand, PortAddress, Result};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// The window over which the frames per second of a universe are counted
+const FPS_WINDOW: Duration = Duration::from_secs(1);
+
+/// The number of packets and bytes of a single opcode, as counted by [Stats](struct.Stats.html)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpcodeStats {
+    /// The name of the opcode, e.g. `"ArtDmx"`
+    pub name: &'static str,
+
+    /// The number of packets
+    pub packets: u64,
+
+    /// The total length of the packets
+    pub bytes: u64,
+}
+
+/// The ArtDmx and ArtNzs packets of a single universe, as counted by [Stats](struct.Stats.html)
+#[derive(Debug, Clone, Default)]
+pub struct UniverseStats {
+    /// The number of packets
+    pub packets: u64,
+
+    /// The number of times that the sequence number did not follow the previous one, because packets were lost or reordered
+    pub sequence_gaps: u64,
+
+    last_sequence: u8,
+    frames: VecDeque<Instant>,
+}
+
+impl UniverseStats {
+    /// The number of frames that were received in the second before `now`
+    pub fn fps(&self, now: Instant) -> usize {
+        self.frames
+            .iter()
+            .filter(|frame| now.saturating_duration_since(**frame) < FPS_WINDOW)
+            .count()
+    }
+
+    fn record(&mut self, sequence: u8, now: Instant) {
+        self.packets += 1;
+        // A sequence of 0 means that the sender does not use sequence numbers
+        if sequence != 0 && self.last_sequence != 0 {
+            let expected = if self.last_sequence == 255 {
+                1
+            } else {
+                self.last_sequence + 1
+            };
+            if sequence != expected {
+                self.sequence_gaps += 1;
+            }
+        }
+        self.last_sequence = sequence;
+
+        while let Some(frame) = self.frames.front() {
+            if now.saturating_duration_since(*frame) < FPS_WINDOW {
+                break;
+            }
+            self.frames.pop_front();
+        }
+        self.frames.push_back(now);
+    }
+}
+
+type Exporter<'a> = Box<dyn FnMut(&Stats) + 'a>;
+
+/// Counts the received packets, e.g. to show the health of a gateway on a dashboard or to export it as metrics.
+///
+/// Pass every message from the receive path to `record`, with the result of parsing it. The counters are kept per opcode and per universe. An exporter that is registered with `set_exporter` is called with the statistics at a fixed interval, from `record` and `tick`.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::{Duration, Instant};
+///
+/// let mut stats = Stats::new();
+/// let now = Instant::now();
+/// for sequence in [1, 2, 4] {
+///     let mut output = Output::new(1.into(), vec![0; 512]);
+///     output.sequence = sequence;
+///     let bytes = output.into_command().write_to_buffer().unwrap();
+///     stats.record(bytes.len(), &ArtCommand::from_buffer(&bytes), now);
+/// }
+/// stats.record(3, &ArtCommand::from_buffer(b"Art"), now);
+///
+/// assert_eq!(stats.packets(), 4);
+/// assert_eq!(stats.parse_errors(), 1);
+/// let universe = stats.universe(1.into()).unwrap();
+/// assert_eq!(universe.sequence_gaps, 1);
+/// assert_eq!(universe.fps(now), 3);
+/// assert!(stats.to_prometheus(now).contains("artnet_packets_total{opcode=\"ArtDmx\"} 3"));
+/// ```
+#[derive(Default)]
+pub struct Stats<'a> {
+    packets: u64,
+    bytes: u64,
+    parse_errors: u64,
+    opcodes: BTreeMap<u16, OpcodeStats>,
+    universes: BTreeMap<PortAddress, UniverseStats>,
+    exporter: Option<(Duration, Instant, Exporter<'a>)>,
+}
+
+impl std::fmt::Debug for Stats<'_> {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_struct("Stats")
+            .field("packets", &self.packets)
+            .field("bytes", &self.bytes)
+            .field("parse_errors", &self.parse_errors)
+            .field("opcodes", &self.opcodes)
+            .field("universes", &self.universes)
+            .finish()
+    }
+}
+
+impl<'a> Stats<'a> {
+    /// Create a collector without any packets
+    pub fn new() -> Stats<'a> {
+        Stats::default()
+    }
+
+    /// Call the exporter with the statistics every `interval`, e.g. to update Prometheus gauges. The first call is one interval after `now`.
+    pub fn set_exporter(
+        &mut self,
+        interval: Duration,
+        now: Instant,
+        exporter: impl FnMut(&Stats) + 'a,
+    ) {
+        self.exporter = Some((interval, now + interval, Box::new(exporter)));
+    }
+
+    /// Count a received message of `length` bytes, with the result of parsing it
+    pub fn record(&mut self, length: usize, result: &Result<ArtCommand>, now: Instant) {
+        self.packets += 1;
+        self.bytes += length as u64;
+        let command = match result {
+            Ok(command) => command,
+            Err(_) => {
+                self.parse_errors += 1;
+                self.tick(now);
+                return;
+            }
+        };
+        let opcode = self
+            .opcodes
+            .entry(command.opcode())
+            .or_insert_with(|| OpcodeStats {
+                name: command.name(),
+                packets: 0,
+                bytes: 0,
+            });
+        opcode.packets += 1;
+        opcode.bytes += length as u64;
+
+        let universe = match command {
+            ArtCommand::Output(output) => Some((output.port_address, output.sequence)),
+            #[cfg(feature = "dmx")]
+            ArtCommand::Nzs(nzs) => Some((nzs.port_address, nzs.sequence)),
+            _ => None,
+        };
+        if let Some((port_address, sequence)) = universe {
+            self.universes
+                .entry(port_address)
+                .or_default()
+                .record(sequence, now);
+        }
+        self.tick(now);
+    }
+
+    /// Call the exporter if its interval passed. This only needs to be called when no messages are received.
+    pub fn tick(&mut self, now: Instant) {
+        let due = match &self.exporter {
+            Some((_, next, _)) => now >= *next,
+            None => false,
+        };
+        if !due {
+            return;
+        }
+        // Take the exporter out, so it can borrow the statistics
+        if let Some((interval, _, mut exporter)) = self.exporter.take() {
+            exporter(self);
+            self.exporter = Some((interval, now + interval, exporter));
+        }
+    }
+
+    /// When `tick` should be called next, if an exporter is set
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.exporter.as_ref().map(|(_, next, _)| *next)
+    }
+
+    /// The number of received messages, including the ones that could not be parsed
+    pub fn packets(&self) -> u64 {
+        self.packets
+    }
+
+    /// The total length of the received messages
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// The number of messages that could not be parsed
+    pub fn parse_errors(&self) -> u64 {
+        self.parse_errors
+    }
+
+    /// The packets per opcode, ordered by opcode
+    pub fn opcodes(&self) -> impl Iterator<Item = (u16, &OpcodeStats)> {
+        self.opcodes.iter().map(|(opcode, stats)| (*opcode, stats))
+    }
+
+    /// The packets of the given universe, if any were received
+    pub fn universe(&self, port_address: PortAddress) -> Option<&UniverseStats> {
+        self.universes.get(&port_address)
+    }
+
+    /// The universes that packets were received for, ordered by Port-Address
+    pub fn universes(&self) -> impl Iterator<Item = (PortAddress, &UniverseStats)> {
+        self.universes
+            .iter()
+            .map(|(port_address, stats)| (*port_address, stats))
+    }
+
+    /// Reset all counters. The exporter is kept.
+    pub fn reset(&mut self) {
+        self.packets = 0;
+        self.bytes = 0;
+        self.parse_errors = 0;
+        self.opcodes.clear();
+        self.universes.clear();
+    }
+
+    /// The statistics in the Prometheus text format, with the frames per second of the universes at `now`
+    pub fn to_prometheus(&self, now: Instant) -> String {
+        let mut result = String::new();
+        // Writing to a String can not fail
+        let _ = writeln!(result, "# TYPE artnet_packets_total counter");
+        for stats in self.opcodes.values() {
+            let _ = writeln!(
+                result,
+                "artnet_packets_total{{opcode=\"{}\"}} {}",
+                stats.name, stats.packets
+            );
+        }
+        let _ = writeln!(result, "# TYPE artnet_bytes_total counter");
+        let _ = writeln!(result, "artnet_bytes_total {}", self.bytes);
+        let _ = writeln!(result, "# TYPE artnet_parse_errors_total counter");
+        let _ = writeln!(result, "artnet_parse_errors_total {}", self.parse_errors);
+        let _ = writeln!(result, "# TYPE artnet_universe_fps gauge");
+        for (port_address, stats) in &self.universes {
+            let _ = writeln!(
+                result,
+                "artnet_universe_fps{{universe=\"{}\"}} {}",
+                u16::from(*port_address),
+                stats.fps(now)
+            );
+        }
+        let _ = writeln!(result, "# TYPE artnet_universe_sequence_gaps_total counter");
+        for (port_address, stats) in &self.universes {
+            let _ = writeln!(
+                result,
+                "artnet_universe_sequence_gaps_total{{universe=\"{}\"}} {}",
+                u16::from(*port_address),
+                stats.sequence_gaps
+            );
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Output, Poll};
+
+    fn output(sequence: u8) -> Result<ArtCommand> {
+        let mut output = Output::new(2.into(), vec![0; 8]);
+        output.sequence = sequence;
+        Ok(output.into_command())
+    }
+
+    #[test]
+    fn universes() {
+        let mut stats = Stats::new();
+        let start = Instant::now();
+        // 255 wraps around to 1, 0 disables the sequence
+        for (i, sequence) in [254, 255, 1, 0, 7, 3].iter().enumerate() {
+            let now = start + Duration::from_millis(i as u64 * 250);
+            stats.record(26, &output(*sequence), now);
+        }
+        stats.record(14, &Ok(Poll::default().into()), start);
+
+        let universe = stats.universe(2.into()).unwrap();
+        assert_eq!(universe.packets, 6);
+        assert_eq!(universe.sequence_gaps, 1);
+        // Frames at 1000 ms and 1250 ms
+        assert_eq!(universe.fps(start + Duration::from_millis(1250)), 4);
+        assert_eq!(universe.fps(start + Duration::from_millis(2200)), 1);
+
+        let opcodes: Vec<_> = stats
+            .opcodes()
+            .map(|(opcode, s)| (opcode, s.packets))
+            .collect();
+        assert_eq!(opcodes, vec![(0x2000, 1), (0x5000, 6)]);
+        assert_eq!(stats.bytes(), 6 * 26 + 14);
+
+        stats.reset();
+        assert_eq!(stats.packets(), 0);
+        assert!(stats.universes().next().is_none());
+    }
+
+    #[test]
+    fn exporter() {
+        let mut exported = Vec::new();
+        let start = Instant::now();
+        {
+            let mut stats = Stats::new();
+            stats.set_exporter(Duration::from_secs(1), start, |stats: &Stats| {
+                exported.push(stats.packets())
+            });
+            stats.record(26, &output(1), start);
+            stats.tick(start + Duration::from_millis(500));
+            stats.record(26, &output(2), start + Duration::from_secs(1));
+            assert_eq!(stats.next_deadline(), Some(start + Duration::from_secs(2)));
+            stats.tick(start + Duration::from_secs(2));
+        }
+        assert_eq!(exported, vec![2, 2]);
+    }
+}
@@ -0,0 +1,201 @@
+//! [proptest](https://docs.rs/proptest) strategies that generate valid commands, to be used in property-based tests.
+//!
+//! ```rust
+//! use artnet_protocol::{strategies, ArtCommand};
+//! use proptest::prelude::*;
+//!
+//! proptest!(|(output in strategies::output())| {
+//!     let bytes = ArtCommand::Output(output).write_to_buffer().unwrap();
+//!     prop_assert!(ArtCommand::from_buffer(&bytes).is_ok());
+//! });
+//! ```
+use crate::{ArtCommand, ArtTalkToMe, Output, Poll, PollReply, PortAddress, Uid};
+use core::convert::TryFrom;
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+/// Any valid Port-Address, from 0 to 32_767
+pub fn port_address() -> impl Strategy<Value = PortAddress> {
+    (0u16..=32_767).prop_map(|value| PortAddress::try_from(value).unwrap())
+}
+
+/// Any combination of TalkToMe flags
+pub fn talk_to_me() -> impl Strategy<Value = ArtTalkToMe> {
+    any::<u8>().prop_map(ArtTalkToMe::from_bits_truncate)
+}
+
+/// A protocol version that is at least 14
+pub fn protocol_version() -> impl Strategy<Value = [u8; 2]> {
+    (14u16..).prop_map(u16::to_be_bytes)
+}
+
+/// A valid ArtPoll
+pub fn poll() -> impl Strategy<Value = Poll> {
+    (
+        protocol_version(),
+        talk_to_me(),
+        any::<u8>(),
+        port_address(),
+        port_address(),
+        any::<[u8; 2]>(),
+        any::<[u8; 2]>(),
+    )
+        .prop_map(
+            |(version, talk_to_me, diagnostics_priority, bottom, top, esta_code, oem)| Poll {
+                version,
+                talk_to_me,
+                diagnostics_priority,
+                target_port_top: u16::from(top).to_be_bytes(),
+                target_port_bottom: u16::from(bottom).to_be_bytes(),
+                esta_code,
+                oem,
+            },
+        )
+}
+
+/// A valid ArtDmx, with an even amount of 2 to 512 bytes of data
+pub fn output() -> impl Strategy<Value = Output> {
+    (
+        protocol_version(),
+        any::<u8>(),
+        any::<u8>(),
+        port_address(),
+        vec(any::<[u8; 2]>(), 1..=256),
+    )
+        .prop_map(|(version, sequence, physical, port_address, pairs)| {
+            let data: Vec<u8> = pairs.concat();
+            Output {
+                version,
+                sequence,
+                physical,
+                port_address,
+                data: data.into(),
+                ..Output::default()
+            }
+        })
+}
+
+/// A null terminated ASCII string of at most `N - 1` characters, padded with zeroes
+fn name<const N: usize>() -> impl Strategy<Value = [u8; N]> {
+    vec(0x20u8..0x7F, 0..N).prop_map(|chars| {
+        let mut result = [0; N];
+        result[..chars.len()].copy_from_slice(&chars);
+        result
+    })
+}
+
+/// A valid ArtPollReply. The port is always 0x1936 and the spare and filler bytes are zero
+pub fn poll_reply() -> impl Strategy<Value = PollReply> {
+    (
+        (
+            any::<[u8; 4]>(),
+            any::<[u8; 2]>(),
+            any::<[u8; 2]>(),
+            any::<[u8; 2]>(),
+            any::<u8>(),
+            any::<u8>(),
+            any::<u16>(),
+        ),
+        (name::<18>(), name::<64>(), name::<64>()),
+        (
+            0u8..=4,
+            any::<[u8; 4]>(),
+            any::<[u8; 4]>(),
+            any::<[u8; 4]>(),
+            any::<[u8; 4]>(),
+            any::<[u8; 4]>(),
+        ),
+        (
+            any::<[u8; 3]>(),
+            any::<u8>(),
+            any::<[u8; 6]>(),
+            any::<[u8; 4]>(),
+            any::<u8>(),
+            any::<u8>(),
+        ),
+        (
+            any::<[u8; 4]>(),
+            any::<u8>(),
+            any::<[u8; 6]>(),
+            any::<[u8; 2]>(),
+            any::<[u8; 2]>(),
+            any::<u8>(),
+        ),
+    )
+        .prop_map(
+            |(
+                (address, version, port_address, oem, ubea_version, status_1, esta_code),
+                (short_name, long_name, node_report),
+                (num_ports, port_types, good_input, good_output, swin, swout),
+                ([sw_video, sw_macro, sw_remote], style, mac, bind_ip, bind_index, status_2),
+                (
+                    good_output_b,
+                    status_3,
+                    default_responder,
+                    user,
+                    refresh_rate,
+                    background_queue_policy,
+                ),
+            )| PollReply {
+                address: address.into(),
+                port: 0x1936,
+                version,
+                port_address,
+                oem,
+                ubea_version,
+                status_1,
+                esta_code,
+                short_name,
+                long_name,
+                node_report,
+                num_ports: [0, num_ports],
+                port_types,
+                good_input,
+                good_output,
+                swin,
+                swout,
+                sw_video,
+                sw_macro,
+                sw_remote,
+                spare: [0; 3],
+                style,
+                mac,
+                bind_ip,
+                bind_index,
+                status_2,
+                good_output_b,
+                status_3,
+                default_responder: Uid(default_responder),
+                user,
+                refresh_rate,
+                background_queue_policy,
+                filler: [0; 10],
+            },
+        )
+}
+
+/// Any of the implemented commands
+pub fn art_command() -> impl Strategy<Value = ArtCommand> {
+    prop_oneof![
+        poll().prop_map(ArtCommand::Poll),
+        poll_reply().prop_map(|reply| ArtCommand::PollReply(Box::new(reply))),
+        output().prop_map(ArtCommand::Output),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParseOptions;
+
+    proptest! {
+        #[test]
+        fn strict_round_trip(command in art_command()) {
+            let opcode = command.opcode();
+            let bytes = command.write_to_buffer().unwrap();
+            let parsed = ArtCommand::from_buffer_with(&bytes, &ParseOptions::strict()).unwrap();
+            prop_assert_eq!(parsed.opcode(), opcode);
+            prop_assert_eq!(parsed.write_to_buffer().unwrap(), bytes);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{ArtCommand, Output};
+use std::time::{Duration, Instant};
+
+/// Buffers ArtDmx until an ArtSync arrives, so that all universes of a frame are output at the same time.
+///
+/// A receiver starts in immediate mode, where every ArtDmx is returned right away. Once an ArtSync is received, ArtDmx is held back until the next ArtSync. When no ArtSync is received within the timeout, 4 seconds by default, the receiver returns to immediate mode.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::Instant;
+///
+/// let mut receiver = SyncReceiver::new();
+/// let now = Instant::now();
+/// receiver.handle(ArtSync::default().into(), now);
+///
+/// assert!(receiver.handle(Output::new(1.into(), vec![1, 2]).into(), now).is_empty());
+/// assert!(receiver.handle(Output::new(2.into(), vec![3, 4]).into(), now).is_empty());
+/// // Both universes are output at the same time
+/// assert_eq!(receiver.handle(ArtSync::default().into(), now).len(), 2);
+/// ```
+#[derive(Debug)]
+pub struct SyncReceiver {
+    pending: Vec<Output>,
+    last_sync: Option<Instant>,
+    timeout: Duration,
+}
+
+impl Default for SyncReceiver {
+    fn default() -> SyncReceiver {
+        SyncReceiver::new()
+    }
+}
+
+impl SyncReceiver {
+    /// The time after the last ArtSync after which a receiver returns to immediate mode, as given by the Art-Net specification
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(4);
+
+    /// Create a receiver with the default timeout
+    pub fn new() -> SyncReceiver {
+        SyncReceiver::with_timeout(SyncReceiver::DEFAULT_TIMEOUT)
+    }
+
+    /// Create a receiver with the given timeout
+    pub fn with_timeout(timeout: Duration) -> SyncReceiver {
+        SyncReceiver {
+            pending: Vec::new(),
+            last_sync: None,
+            timeout,
+        }
+    }
+
+    /// Whether ArtDmx is held back until the next ArtSync
+    pub fn is_synchronous(&self, now: Instant) -> bool {
+        self.last_sync
+            .is_some_and(|last_sync| now < last_sync + self.timeout)
+    }
+
+    /// Handle a received command, and return the ArtDmx that should be output now. Commands other than `Output` and `Sync` are ignored.
+    pub fn handle(&mut self, command: ArtCommand, now: Instant) -> Vec<Output> {
+        match command {
+            ArtCommand::Output(output) => {
+                // Only the newest data of a universe is output
+                self.pending
+                    .retain(|pending| pending.port_address != output.port_address);
+                self.pending.push(output);
+                if self.is_synchronous(now) {
+                    Vec::new()
+                } else {
+                    core::mem::take(&mut self.pending)
+                }
+            }
+            ArtCommand::Sync(_) => {
+                self.last_sync = Some(now);
+                core::mem::take(&mut self.pending)
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArtSync;
+
+    fn output(port_address: u8, value: u8) -> ArtCommand {
+        Output::new(port_address.into(), vec![value, value]).into()
+    }
+
+    #[test]
+    fn immediate() {
+        let mut receiver = SyncReceiver::new();
+        let now = Instant::now();
+        assert_eq!(receiver.handle(output(1, 1), now).len(), 1);
+        assert!(!receiver.is_synchronous(now));
+    }
+
+    #[test]
+    fn newest_data() {
+        let mut receiver = SyncReceiver::new();
+        let now = Instant::now();
+        receiver.handle(ArtSync::default().into(), now);
+        receiver.handle(output(1, 1), now);
+        receiver.handle(output(1, 2), now);
+        let outputs = receiver.handle(ArtSync::default().into(), now);
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].data.as_ref(), &vec![2, 2]);
+    }
+
+    #[test]
+    fn timeout() {
+        let mut receiver = SyncReceiver::new();
+        let start = Instant::now();
+        receiver.handle(ArtSync::default().into(), start);
+        assert!(receiver.handle(output(1, 1), start).is_empty());
+
+        // The sender stopped sending ArtSync
+        let later = start + Duration::from_secs(4);
+        assert!(!receiver.is_synchronous(later));
+        assert_eq!(receiver.handle(output(1, 2), later).len(), 1);
+    }
+}
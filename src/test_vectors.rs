@@ -0,0 +1,415 @@
+//! Hand-built Art-Net messages, with the commands that they decode to.
+//!
+//! Downstream crates can use these to check their own handling of Art-Net messages, e.g. a node that should answer the polls of different controllers. The messages were written byte by byte from the Art-Net 4 specification, independently of the serializer of this crate. They are not captures of real traffic, so they do not cover the quirks of individual devices.
+//!
+//! ```rust
+//! use artnet_protocol::{test_vectors, ArtCommand};
+//!
+//! for packet in test_vectors::ALL {
+//!     let command = ArtCommand::from_buffer(packet.bytes).unwrap();
+//!     assert_eq!(command.opcode(), packet.opcode);
+//!     assert!(packet.matches(&command), "{}", packet.description);
+//! }
+//! ```
+//!
+//! This module is only available with the `test-utils` feature.
+
+use crate::{ArtCommand, ArtSync, ArtTalkToMe, Output, Poll, PollReply};
+#[cfg(feature = "media")]
+use crate::{FrameRate, Timecode};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// A known-good Art-Net message, together with the command that it decodes to
+pub struct GoldenPacket {
+    /// A short description of the message
+    pub description: &'static str,
+
+    /// The message, including the Art-Net header and opcode
+    pub bytes: &'static [u8],
+
+    /// The opcode of the message
+    pub opcode: u16,
+
+    /// Builds the command that `bytes` decodes to
+    pub expected: fn() -> ArtCommand,
+}
+
+impl GoldenPacket {
+    /// Whether the given command contains the same values as `expected`.
+    ///
+    /// The commands are compared by the messages that this crate serializes them to, so fields that this crate does not support yet are ignored.
+    pub fn matches(&self, command: &ArtCommand) -> bool {
+        let mut expected = Vec::new();
+        let mut actual = Vec::new();
+        (self.expected)().write_to(&mut expected).is_ok()
+            && command.write_to(&mut actual).is_ok()
+            && expected == actual
+    }
+}
+
+/// Every packet in this module
+pub const ALL: &[GoldenPacket] = &[
+    POLL,
+    POLL_ART_NET_4,
+    POLL_REPLY,
+    POLL_REPLY_ART_NET_3,
+    OUTPUT,
+    SYNC,
+    #[cfg(feature = "media")]
+    TIME_CODE,
+];
+
+/// An ArtPoll without the Art-Net 4 targeted mode fields
+pub const POLL: GoldenPacket = GoldenPacket {
+    description: "ArtPoll without the Art-Net 4 fields",
+    bytes: POLL_BYTES,
+    opcode: 0x2000,
+    expected: || {
+        Poll {
+            version: [0, 14],
+            talk_to_me: ArtTalkToMe::EMIT_CHANGES,
+            diagnostics_priority: 0x10,
+            ..Poll::default()
+        }
+        .into()
+    },
+};
+
+/// An Art-Net 4 ArtPoll, that includes the targeted mode and vendor fields
+pub const POLL_ART_NET_4: GoldenPacket = GoldenPacket {
+    description: "Art-Net 4 ArtPoll",
+    bytes: POLL_ART_NET_4_BYTES,
+    opcode: 0x2000,
+    expected: || {
+        Poll {
+            version: [0, 14],
+            talk_to_me: ArtTalkToMe::EMIT_CHANGES | ArtTalkToMe::ENABLE_DIAGNOSTICS,
+            diagnostics_priority: 0x80,
+            target_port_top: [0x00, 0x0F],
+            target_port_bottom: [0x00, 0x00],
+            esta_code: [0x7F, 0xF0],
+            oem: [0x00, 0xFF],
+        }
+        .into()
+    },
+};
+
+/// An ArtPollReply of a node with 4 DMX outputs
+pub const POLL_REPLY: GoldenPacket = GoldenPacket {
+    description: "ArtPollReply with 4 DMX output ports",
+    bytes: POLL_REPLY_BYTES,
+    opcode: 0x2100,
+    expected: || {
+        let mut reply = PollReply {
+            address: [10, 0, 0, 20].into(),
+            port: 6454,
+            version: [1, 2],
+            oem: [0x00, 0xFF],
+            status_1: 0xE0,
+            esta_code: 0x7FF0,
+            num_ports: [0, 4],
+            port_types: [0x80; 4],
+            good_output: [0x80; 4],
+            swout: [0, 1, 2, 3],
+            mac: [0x02, 0x00, 0x00, 0x0A, 0x00, 0x14],
+            bind_ip: [10, 0, 0, 20],
+            bind_index: 1,
+            status_2: 0x0E,
+            ..PollReply::default()
+        };
+        set_name(&mut reply.short_name, "DMX Node");
+        set_name(&mut reply.long_name, "4-port DMX node");
+        set_name(
+            &mut reply.node_report,
+            "#0001 [0042] Power On Tests successful",
+        );
+        Box::new(reply).into()
+    },
+};
+
+/// An ArtPollReply in the shorter Art-Net 3 layout, which ends after the MAC address. The missing fields decode as zero.
+pub const POLL_REPLY_ART_NET_3: GoldenPacket = GoldenPacket {
+    description: "ArtPollReply truncated to the Art-Net 3 length",
+    bytes: POLL_REPLY_ART_NET_3_BYTES,
+    opcode: 0x2100,
+    expected: || {
+        let mut reply = PollReply {
+            address: [2, 0, 0, 7].into(),
+            port: 6454,
+            version: [1, 2],
+            oem: [0x00, 0xFF],
+            status_1: 0xE0,
+            esta_code: 0x7FF0,
+            num_ports: [0, 1],
+            port_types: [0x80, 0, 0, 0],
+            good_output: [0x80, 0, 0, 0],
+            mac: [0x02, 0x00, 0x00, 0x02, 0x00, 0x07],
+            ..PollReply::default()
+        };
+        set_name(&mut reply.short_name, "Single");
+        set_name(&mut reply.long_name, "Single universe node");
+        set_name(&mut reply.node_report, "#0001 [0001] Ok");
+        Box::new(reply).into()
+    },
+};
+
+/// An ArtDmx with 24 channels for the first universe
+pub const OUTPUT: GoldenPacket = GoldenPacket {
+    description: "ArtDmx with 24 channels",
+    bytes: OUTPUT_BYTES,
+    opcode: 0x5000,
+    expected: || {
+        Output {
+            sequence: 1,
+            ..Output::new(0.into(), [0xFF, 0x80, 0x00].repeat(8))
+        }
+        .into()
+    },
+};
+
+/// An ArtSync
+pub const SYNC: GoldenPacket = GoldenPacket {
+    description: "ArtSync",
+    bytes: SYNC_BYTES,
+    opcode: 0x5200,
+    expected: || ArtSync::default().into(),
+};
+
+/// An ArtTimeCode with SMPTE time code
+#[cfg(feature = "media")]
+pub const TIME_CODE: GoldenPacket = GoldenPacket {
+    description: "ArtTimeCode at 01:56:34:12, SMPTE 30 fps",
+    bytes: TIME_CODE_BYTES,
+    opcode: 0x9700,
+    expected: || Timecode::new(1, 56, 34, 12, FrameRate::Smpte).into(),
+};
+
+#[rustfmt::skip]
+const POLL_BYTES: &[u8] = &[
+    // Art-Net header and opcode 0x2000
+    b'A', b'r', b't', b'-', b'N', b'e', b't', 0, 0x00, 0x20,
+    // Protocol version 14
+    0x00, 0x0E,
+    // Talk to me, emit changes
+    0x02,
+    // Diagnostics priority, DpLow
+    0x10,
+];
+
+#[rustfmt::skip]
+const POLL_ART_NET_4_BYTES: &[u8] = &[
+    // Art-Net header and opcode 0x2000
+    b'A', b'r', b't', b'-', b'N', b'e', b't', 0, 0x00, 0x20,
+    // Protocol version 14
+    0x00, 0x0E,
+    // Talk to me, emit changes and enable diagnostics
+    0x06,
+    // Diagnostics priority, DpHigh
+    0x80,
+    // Target port address top, 15
+    0x00, 0x0F,
+    // Target port address bottom, 0
+    0x00, 0x00,
+    // Esta code 0x7FF0
+    0x7F, 0xF0,
+    // Oem
+    0x00, 0xFF,
+];
+
+#[rustfmt::skip]
+const POLL_REPLY_BYTES: &[u8] = &[
+    // Art-Net header and opcode 0x2100
+    b'A', b'r', b't', b'-', b'N', b'e', b't', 0, 0x00, 0x21,
+    // Address, 10.0.0.20
+    0x0A, 0x00, 0x00, 0x14,
+    // Port, 6454 in little endian
+    0x36, 0x19,
+    // Firmware version 1.2
+    0x01, 0x02,
+    // Net and sub switch
+    0x00, 0x00,
+    // Oem
+    0x00, 0xFF,
+    // Ubea version
+    0x00,
+    // Status 1, indicators normal and Port-Addresses programmed by network
+    0xE0,
+    // Esta code 0x7FF0, reserved for prototyping
+    0xF0, 0x7F,
+    // Short name "DMX Node"
+    b'D', b'M', b'X', b' ', b'N', b'o', b'd', b'e', 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00,
+    // Long name "4-port DMX node"
+    b'4', b'-', b'p', b'o', b'r', b't', b' ', b'D', b'M', b'X', b' ', b'n', b'o', b'd', b'e',
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    // Node report "#0001 [0042] Power On Tests successful"
+    b'#', b'0', b'0', b'0', b'1', b' ', b'[', b'0', b'0', b'4', b'2', b']', b' ', b'P', b'o',
+    b'w', b'e', b'r', b' ', b'O', b'n', b' ', b'T', b'e', b's', b't', b's', b' ', b's', b'u',
+    b'c', b'c', b'e', b's', b's', b'f', b'u', b'l', 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    // 4 ports
+    0x00, 0x04,
+    // Port types, DMX512 outputs
+    0x80, 0x80, 0x80, 0x80,
+    // Good input
+    0x00, 0x00, 0x00, 0x00,
+    // Good output, data is being transmitted
+    0x80, 0x80, 0x80, 0x80,
+    // Swin
+    0x00, 0x00, 0x00, 0x00,
+    // Swout
+    0x00, 0x01, 0x02, 0x03,
+    // Sw video, sw macro and sw remote
+    0x00, 0x00, 0x00,
+    // Spare
+    0x00, 0x00, 0x00,
+    // Style, StNode
+    0x00,
+    // Mac
+    0x02, 0x00, 0x00, 0x0A, 0x00, 0x14,
+    // Bind ip
+    0x0A, 0x00, 0x00, 0x14,
+    // Bind index
+    0x01,
+    // Status 2, web configuration, DHCP capable and 15 bit Port-Addresses
+    0x0E,
+    // Filler
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+#[rustfmt::skip]
+const POLL_REPLY_ART_NET_3_BYTES: &[u8] = &[
+    // Art-Net header and opcode 0x2100
+    b'A', b'r', b't', b'-', b'N', b'e', b't', 0, 0x00, 0x21,
+    // Address, 2.0.0.7
+    0x02, 0x00, 0x00, 0x07,
+    // Port, 6454 in little endian
+    0x36, 0x19,
+    // Firmware version 1.2
+    0x01, 0x02,
+    // Net and sub switch
+    0x00, 0x00,
+    // Oem
+    0x00, 0xFF,
+    // Ubea version
+    0x00,
+    // Status 1, indicators normal and Port-Addresses programmed by network
+    0xE0,
+    // Esta code 0x7FF0, reserved for prototyping
+    0xF0, 0x7F,
+    // Short name "Single"
+    b'S', b'i', b'n', b'g', b'l', b'e', 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00,
+    // Long name "Single universe node"
+    b'S', b'i', b'n', b'g', b'l', b'e', b' ', b'u', b'n', b'i', b'v', b'e', b'r', b's', b'e',
+    b' ', b'n', b'o', b'd', b'e', 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    // Node report "#0001 [0001] Ok"
+    b'#', b'0', b'0', b'0', b'1', b' ', b'[', b'0', b'0', b'0', b'1', b']', b' ', b'O', b'k',
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00,
+    // 1 ports
+    0x00, 0x01,
+    // Port types, DMX512 outputs
+    0x80, 0x00, 0x00, 0x00,
+    // Good input
+    0x00, 0x00, 0x00, 0x00,
+    // Good output, data is being transmitted
+    0x80, 0x00, 0x00, 0x00,
+    // Swin
+    0x00, 0x00, 0x00, 0x00,
+    // Swout
+    0x00, 0x00, 0x00, 0x00,
+    // Sw video, sw macro and sw remote
+    0x00, 0x00, 0x00,
+    // Spare
+    0x00, 0x00, 0x00,
+    // Style, StNode
+    0x00,
+    // Mac
+    0x02, 0x00, 0x00, 0x02, 0x00, 0x07,
+];
+
+#[rustfmt::skip]
+const OUTPUT_BYTES: &[u8] = &[
+    // Art-Net header and opcode 0x5000
+    b'A', b'r', b't', b'-', b'N', b'e', b't', 0, 0x00, 0x50,
+    // Protocol version 14
+    0x00, 0x0E,
+    // Sequence
+    0x01,
+    // Physical
+    0x00,
+    // Port-Address 0, SubUni then Net
+    0x00, 0x00,
+    // Length 24, big endian
+    0x00, 0x18,
+    // 8 RGB fixtures, orange
+    0xFF, 0x80, 0x00, 0xFF, 0x80, 0x00, 0xFF, 0x80, 0x00, 0xFF, 0x80, 0x00, 0xFF, 0x80, 0x00,
+    0xFF, 0x80, 0x00, 0xFF, 0x80, 0x00, 0xFF, 0x80, 0x00,
+];
+
+#[rustfmt::skip]
+const SYNC_BYTES: &[u8] = &[
+    // Art-Net header and opcode 0x5200
+    b'A', b'r', b't', b'-', b'N', b'e', b't', 0, 0x00, 0x52,
+    // Protocol version 14
+    0x00, 0x0E,
+    // Aux 1 and aux 2
+    0x00, 0x00,
+];
+
+#[cfg(feature = "media")]
+#[rustfmt::skip]
+const TIME_CODE_BYTES: &[u8] = &[
+    // Art-Net header and opcode 0x9700
+    b'A', b'r', b't', b'-', b'N', b'e', b't', 0, 0x00, 0x97,
+    // Protocol version 14
+    0x00, 0x0E,
+    // Filler 1
+    0x00,
+    // Stream id, the master stream
+    0x00,
+    // Frames, seconds, minutes and hours, 01:56:34:12
+    0x0C, 0x22, 0x38, 0x01,
+    // Type, SMPTE 30 fps
+    0x03,
+];
+
+fn set_name(field: &mut [u8], name: &str) {
+    field[..name.len()].copy_from_slice(name.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParseOptions;
+
+    #[test]
+    fn decode() {
+        for packet in ALL {
+            let command = ArtCommand::from_buffer(packet.bytes).unwrap();
+            assert_eq!(command.opcode(), packet.opcode, "{}", packet.description);
+            assert!(packet.matches(&command), "{}", packet.description);
+        }
+    }
+
+    #[test]
+    fn strict() {
+        // Including the Art-Net 3 reply, which is padded with zeroes
+        for packet in ALL {
+            let command = ArtCommand::from_buffer_with(packet.bytes, &ParseOptions::strict());
+            assert!(packet.matches(&command.unwrap()), "{}", packet.description);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{FrameRate, Timecode};
+use std::time::Instant;
+
+/// Generates ArtTimeCode packets at the frame rate of the time code, e.g. to synchronize lighting with video playback.
+///
+/// The time of every frame is calculated from the moment that the sender was started, so the time code does not drift, also at 29.97 frames per second. Drop frame time code skips the frame numbers that are dropped. If `tick` is called late, the frames that were missed are skipped and the current frame is sent. The time code wraps around at midnight.
+///
+/// Call `tick` at `next_deadline`, and send the returned time code to the network.
+///
+/// ```rust
+/// use artnet_protocol::*;
+/// use std::time::{Duration, Instant};
+///
+/// let start = Instant::now();
+/// let mut sender = TimecodeSender::new(Timecode::new(1, 0, 0, 0, FrameRate::Ebu), start);
+/// assert_eq!(sender.tick(start).unwrap().frames, 0);
+/// assert_eq!(sender.tick(start), None);
+///
+/// assert_eq!(sender.next_deadline(), start + Duration::from_millis(40));
+/// let timecode = sender.tick(start + Duration::from_millis(40)).unwrap();
+/// assert_eq!(timecode.frames, 1);
+/// let command = timecode.into_command();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimecodeSender {
+    start: Timecode,
+    rate: FrameRate,
+    started: Instant,
+    next: u64,
+}
+
+impl TimecodeSender {
+    /// Start sending at the given time code now. The frame rate and stream of the time code are used for every frame, and unknown frame rates are sent as 30 fps.
+    pub fn new(start: Timecode, now: Instant) -> TimecodeSender {
+        let rate = start.frame_rate().unwrap_or(FrameRate::Smpte);
+        TimecodeSender {
+            start: Timecode {
+                kind: rate.kind(),
+                ..start
+            },
+            rate,
+            started: now,
+            next: 0,
+        }
+    }
+
+    /// The frame rate of the time code
+    pub fn frame_rate(&self) -> FrameRate {
+        self.rate
+    }
+
+    /// Continue at the given time code now, e.g. when the video is paused or skips. The frame rate and stream stay the same.
+    pub fn seek(&mut self, timecode: &Timecode, now: Instant) {
+        *self = TimecodeSender::new(
+            Timecode {
+                kind: self.rate.kind(),
+                stream_id: self.start.stream_id,
+                ..timecode.clone()
+            },
+            now,
+        );
+    }
+
+    /// The time code of the frame that is playing at `now`
+    pub fn current(&self, now: Instant) -> Timecode {
+        self.timecode(self.frames_at(now).saturating_sub(1))
+    }
+
+    /// The time code that should be sent now, or `None` if it was already sent
+    pub fn tick(&mut self, now: Instant) -> Option<Timecode> {
+        let frames = self.frames_at(now);
+        if frames <= self.next {
+            return None;
+        }
+        self.next = frames;
+        Some(self.timecode(frames - 1))
+    }
+
+    /// When the next frame starts, and `tick` should be called again
+    pub fn next_deadline(&self) -> Instant {
+        self.started + self.rate.frame_offset(self.next)
+    }
+
+    /// The number of frames that started before or at `now`
+    fn frames_at(&self, now: Instant) -> u64 {
+        self.rate
+            .frames_in(now.saturating_duration_since(self.started))
+    }
+
+    /// The time code of the given frame since the start
+    fn timecode(&self, frame: u64) -> Timecode {
+        let per_day = u64::from(self.rate.frames_per_day());
+        // The start has a valid frame rate, so it has a frame count
+        let start = u64::from(self.start.frame_count().unwrap_or(0));
+        let count = ((start + frame % per_day) % per_day) as u32;
+        Timecode {
+            stream_id: self.start.stream_id,
+            ..Timecode::from_frame_count(count, self.rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn drop_frame() {
+        let start = Instant::now();
+        let mut sender =
+            TimecodeSender::new(Timecode::new(0, 0, 59, 29, FrameRate::DropFrame), start);
+        assert_eq!(sender.tick(start).unwrap().seconds, 59);
+        let deadline = sender.next_deadline();
+        assert_eq!(deadline - start, FrameRate::DropFrame.frame_duration());
+        assert_eq!(sender.tick(deadline - Duration::from_nanos(1)), None);
+        let next = sender.tick(deadline).unwrap();
+        assert_eq!((next.minutes, next.seconds, next.frames), (1, 0, 2));
+
+        // 30000 frames take exactly 1001 seconds, without drifting
+        let later = start + Duration::from_secs(1001);
+        let timecode = sender.tick(later).unwrap();
+        let first = Timecode::new(0, 0, 59, 29, FrameRate::DropFrame)
+            .frame_count()
+            .unwrap();
+        assert_eq!(timecode.frame_count(), Some(first + 30_000));
+        assert_eq!(
+            sender.next_deadline(),
+            later + FrameRate::DropFrame.frame_duration()
+        );
+    }
+
+    #[test]
+    fn seek_and_wrap() {
+        let start = Instant::now();
+        let mut timecode = Timecode::new(23, 59, 59, 23, FrameRate::Film);
+        timecode.stream_id = 2;
+        let mut sender = TimecodeSender::new(timecode, start);
+        // Skipped frames are not sent, and the time code wraps around at midnight
+        let next = sender.tick(start + Duration::from_millis(100)).unwrap();
+        assert_eq!((next.hours, next.seconds, next.frames), (0, 0, 1));
+        assert_eq!(next.stream_id, 2);
+
+        let now = start + Duration::from_secs(5);
+        sender.seek(&Timecode::new(10, 0, 0, 0, FrameRate::Smpte), now);
+        assert_eq!(sender.frame_rate(), FrameRate::Film);
+        let current = sender.current(now + Duration::from_secs(1));
+        assert_eq!((current.hours, current.seconds, current.frames), (10, 1, 0));
+        assert_eq!(current.stream_id, 2);
+        assert_eq!(sender.tick(now).unwrap().hours, 10);
+    }
+}
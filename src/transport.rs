@@ -0,0 +1,226 @@
+use crate::{ArtCommand, RecvBuffer};
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Sends and receives datagrams for the [Controller](struct.Controller.html), and the `handle_readable` functions of [Node](struct.Node.html), [Dispatcher](struct.Dispatcher.html) and [DiscoveryManager](struct.DiscoveryManager.html).
+///
+/// This is implemented for `std::net::UdpSocket`, and for `mio::net::UdpSocket` with the `mio` feature. Implement it to run Art-Net over another link, e.g. a tunnel or a serial gateway, or use a [MemoryNetwork](struct.MemoryNetwork.html) in tests.
+///
+/// Receiving must not block: when no datagram is waiting, `recv_from` returns an error of kind `WouldBlock`. The `handle_readable` functions read until then, so a socket must be in non-blocking mode.
+pub trait Transport {
+    /// Send a datagram to the given address, and return the number of bytes that were sent
+    fn send_to(&self, buffer: &[u8], addr: SocketAddr) -> io::Result<usize>;
+
+    /// Receive a single datagram, and return its length and the address that sent it. Returns an error of kind `WouldBlock` if no datagram is waiting.
+    fn recv_from(&self, buffer: &mut [u8]) -> io::Result<(usize, SocketAddr)>;
+}
+
+impl Transport for std::net::UdpSocket {
+    fn send_to(&self, buffer: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        std::net::UdpSocket::send_to(self, buffer, addr)
+    }
+
+    fn recv_from(&self, buffer: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        std::net::UdpSocket::recv_from(self, buffer)
+    }
+}
+
+#[cfg(feature = "mio")]
+impl Transport for mio::net::UdpSocket {
+    fn send_to(&self, buffer: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        mio::net::UdpSocket::send_to(self, buffer, addr)
+    }
+
+    fn recv_from(&self, buffer: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        mio::net::UdpSocket::recv_from(self, buffer)
+    }
+}
+
+/// Receive datagrams until the transport would block, and pass every valid command to `handle`. Anything that is not a valid Art-Net message is ignored.
+pub(crate) fn drain<T: Transport + ?Sized>(
+    transport: &T,
+    buffer: &mut RecvBuffer,
+    mut handle: impl FnMut(ArtCommand, SocketAddr) -> io::Result<()>,
+) -> io::Result<()> {
+    loop {
+        match transport.recv_from(buffer) {
+            Ok((length, addr)) => {
+                if let Ok(command) = buffer.parse(length) {
+                    handle(command, addr)?;
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+type Queues = HashMap<SocketAddr, VecDeque<(Vec<u8>, SocketAddr)>>;
+
+/// An in-memory network, to test applications that use a [Transport](trait.Transport.html) without real sockets.
+///
+/// Every [MemoryTransport](struct.MemoryTransport.html) that is bound to the network receives the datagrams that are sent to its address. A datagram to `255.255.255.255` is delivered to every other transport with the same port. Like UDP, datagrams to an address that nothing is bound to are dropped.
+///
+/// ```rust
+/// use artnet_protocol::*;
+///
+/// let network = MemoryNetwork::new();
+/// let node = network.bind("10.0.0.2:6454".parse().unwrap()).unwrap();
+/// let mut controller = Controller::with_transport(
+///     network.bind("10.0.0.1:6454".parse().unwrap()).unwrap(),
+///     "255.255.255.255:6454".parse().unwrap(),
+/// );
+/// controller.update().unwrap();
+///
+/// let mut buffer = RecvBuffer::new();
+/// let (length, _) = node.recv_from(&mut buffer).unwrap();
+/// assert!(matches!(buffer.parse(length), Ok(ArtCommand::Poll(_))));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryNetwork {
+    queues: Arc<Mutex<Queues>>,
+}
+
+impl MemoryNetwork {
+    /// Create an empty network
+    pub fn new() -> MemoryNetwork {
+        MemoryNetwork::default()
+    }
+
+    /// Bind a transport to the given address. Returns an error of kind `AddrInUse` if another transport is bound to it.
+    pub fn bind(&self, addr: SocketAddr) -> io::Result<MemoryTransport> {
+        let mut queues = lock(&self.queues);
+        if queues.contains_key(&addr) {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                "Another transport is bound to this address",
+            ));
+        }
+        queues.insert(addr, VecDeque::new());
+        Ok(MemoryTransport {
+            queues: self.queues.clone(),
+            addr,
+        })
+    }
+}
+
+/// A transport on a [MemoryNetwork](struct.MemoryNetwork.html). Its address is released when it is dropped.
+#[derive(Debug)]
+pub struct MemoryTransport {
+    queues: Arc<Mutex<Queues>>,
+    addr: SocketAddr,
+}
+
+impl MemoryTransport {
+    /// The address that this transport is bound to
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn send_to(&self, buffer: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        let broadcast = match addr {
+            SocketAddr::V4(addr) => addr.ip().is_broadcast(),
+            SocketAddr::V6(_) => false,
+        };
+        for (peer, queue) in lock(&self.queues).iter_mut() {
+            let receives = if broadcast {
+                peer.port() == addr.port() && *peer != self.addr
+            } else {
+                *peer == addr
+            };
+            if receives {
+                queue.push_back((buffer.to_vec(), self.addr));
+            }
+        }
+        Ok(buffer.len())
+    }
+
+    fn recv_from(&self, buffer: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        let datagram = lock(&self.queues)
+            .get_mut(&self.addr)
+            .and_then(VecDeque::pop_front);
+        match datagram {
+            Some((data, source)) => {
+                // Like UDP, the rest of a datagram that does not fit is discarded
+                let length = data.len().min(buffer.len());
+                buffer[..length].copy_from_slice(&data[..length]);
+                Ok((length, source))
+            }
+            None => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+}
+
+impl Drop for MemoryTransport {
+    fn drop(&mut self) {
+        lock(&self.queues).remove(&self.addr);
+    }
+}
+
+/// Lock the queues, also when another thread panicked while holding the lock
+fn lock(queues: &Mutex<Queues>) -> MutexGuard<'_, Queues> {
+    queues.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Controller, DiscoveryEvent, Node, PollReply, RecvBuffer};
+
+    #[test]
+    fn memory_network() {
+        let network = MemoryNetwork::new();
+        let a = network.bind("10.0.0.1:6454".parse().unwrap()).unwrap();
+        let b = network.bind("10.0.0.2:6454".parse().unwrap()).unwrap();
+        let other_port = network.bind("10.0.0.3:1234".parse().unwrap()).unwrap();
+        assert!(network.bind(a.local_addr()).is_err());
+
+        let mut buffer = [0; 4];
+        a.send_to(b"unicast", b.local_addr()).unwrap();
+        a.send_to(b"all", "255.255.255.255:6454".parse().unwrap())
+            .unwrap();
+        assert_eq!(b.recv_from(&mut buffer).unwrap(), (4, a.local_addr()));
+        assert_eq!(&buffer, b"unic");
+        assert_eq!(b.recv_from(&mut buffer).unwrap().0, 3);
+        for transport in &[&a, &b, &other_port] {
+            let err = transport.recv_from(&mut buffer).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        }
+
+        let addr = b.local_addr();
+        drop(b);
+        assert!(network.bind(addr).is_ok());
+    }
+
+    #[test]
+    fn controller_and_node() {
+        let network = MemoryNetwork::new();
+        let transport = network.bind("10.0.0.2:6454".parse().unwrap()).unwrap();
+        let mut node = Node::new(PollReply {
+            address: [10, 0, 0, 2].into(),
+            num_ports: [0, 1],
+            port_types: [0x80, 0, 0, 0],
+            swout: [1, 0, 0, 0],
+            ..PollReply::default()
+        });
+        let mut controller = Controller::with_transport(
+            network.bind("10.0.0.1:6454".parse().unwrap()).unwrap(),
+            "255.255.255.255:6454".parse().unwrap(),
+        );
+
+        controller.update().unwrap();
+        assert_eq!(node.handle_readable(&transport).unwrap().len(), 1);
+        let events = controller.update().unwrap();
+        assert!(matches!(events[..], [DiscoveryEvent::NodeAdded(_)]));
+
+        controller.send_dmx(1.into(), &[1, 2, 3, 4]).unwrap();
+        let mut buffer = RecvBuffer::new();
+        let (length, _) = transport.recv_from(&mut buffer).unwrap();
+        let output = buffer.parse(length).unwrap();
+        assert_eq!(output.as_output().unwrap().data.as_ref(), &vec![1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,234 @@
+use crate::{ArtCommand, Trigger, TriggerKey};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// The triggers that a handler of a [TriggerDispatcher](struct.TriggerDispatcher.html) is registered for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TriggerRoute {
+    /// A single sub key of a general key, e.g. show 3
+    Exact(TriggerKey, u8),
+
+    /// Every sub key of a key, e.g. every macro. A `TriggerKey::ManufacturerSpecific` key only matches triggers for the Oem code of the dispatcher, see `Trigger::trigger_key`.
+    Key(TriggerKey),
+
+    /// A manufacturer specific key and sub key, or every sub key if it is `None`. These only match triggers for the Oem code of the dispatcher.
+    Oem(u8, Option<u8>),
+
+    /// Every trigger that the dispatcher accepts
+    Any,
+}
+
+impl TriggerRoute {
+    /// If this route matches the given trigger. This does not check the Oem code of manufacturer specific triggers.
+    pub fn matches(&self, trigger: &Trigger) -> bool {
+        match *self {
+            TriggerRoute::Exact(key, sub_key) => {
+                trigger.trigger_key() == Some(key) && trigger.sub_key == sub_key
+            }
+            TriggerRoute::Key(key) => trigger.trigger_key() == Some(key),
+            TriggerRoute::Oem(key, sub_key) => {
+                !trigger.is_general()
+                    && trigger.key == key
+                    && sub_key.is_none_or(|sub_key| trigger.sub_key == sub_key)
+            }
+            TriggerRoute::Any => true,
+        }
+    }
+}
+
+impl From<TriggerKey> for TriggerRoute {
+    fn from(key: TriggerKey) -> TriggerRoute {
+        TriggerRoute::Key(key)
+    }
+}
+
+impl From<(TriggerKey, u8)> for TriggerRoute {
+    fn from((key, sub_key): (TriggerKey, u8)) -> TriggerRoute {
+        TriggerRoute::Exact(key, sub_key)
+    }
+}
+
+type Handler<'a> = Box<dyn FnMut(&Trigger) + 'a>;
+
+/// Dispatches received ArtTrigger packets to the handlers that are registered for their key and sub key.
+///
+/// General triggers, with the Oem code `Trigger::OEM_GENERAL`, are always accepted. Manufacturer specific triggers are only accepted if the dispatcher was created `with_oem` for their Oem code, and are ignored otherwise. Every handler whose route matches an accepted trigger is called, in the order in which they were registered.
+///
+/// ```rust
+/// use artnet_protocol::*;
+///
+/// let mut shows = Vec::new();
+/// let mut dispatcher = TriggerDispatcher::new();
+/// dispatcher.route(TriggerKey::Show, |trigger: &Trigger| shows.push(trigger.sub_key));
+/// dispatcher.route((TriggerKey::Macro, 1), |_: &Trigger| println!("Macro 1"));
+///
+/// assert_eq!(dispatcher.dispatch(&Trigger::new(TriggerKey::Show, 3).into_command()), 1);
+/// // A trigger for the devices of another manufacturer is ignored
+/// assert_eq!(dispatcher.dispatch(&Trigger::with_oem(0x1234, 3, 3).into_command()), 0);
+/// drop(dispatcher);
+/// assert_eq!(shows, vec![3]);
+/// ```
+#[derive(Default)]
+pub struct TriggerDispatcher<'a> {
+    oem: Option<u16>,
+    routes: Vec<(TriggerRoute, Handler<'a>)>,
+}
+
+impl core::fmt::Debug for TriggerDispatcher<'_> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.debug_struct("TriggerDispatcher")
+            .field("oem", &self.oem)
+            .field(
+                "routes",
+                &self
+                    .routes
+                    .iter()
+                    .map(|(route, _)| route)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl<'a> TriggerDispatcher<'a> {
+    /// Create a dispatcher without any handlers, that only accepts general triggers
+    pub fn new() -> TriggerDispatcher<'a> {
+        TriggerDispatcher::default()
+    }
+
+    /// Create a dispatcher without any handlers, that also accepts the manufacturer specific triggers for the given Oem code
+    pub fn with_oem(oem: u16) -> TriggerDispatcher<'a> {
+        TriggerDispatcher {
+            oem: Some(oem),
+            routes: Vec::new(),
+        }
+    }
+
+    /// The Oem code of the manufacturer specific triggers that are accepted, if any
+    pub fn oem(&self) -> Option<u16> {
+        self.oem
+    }
+
+    /// Register a handler for the given key, key and sub key, or `TriggerRoute`
+    pub fn route(
+        &mut self,
+        route: impl Into<TriggerRoute>,
+        handler: impl FnMut(&Trigger) + 'a,
+    ) -> &mut TriggerDispatcher<'a> {
+        self.routes.push((route.into(), Box::new(handler)));
+        self
+    }
+
+    /// If the given trigger is general, or for the Oem code of this dispatcher
+    pub fn accepts(&self, trigger: &Trigger) -> bool {
+        trigger.is_general() || self.oem == Some(trigger.oem_code())
+    }
+
+    /// Call the handlers that are registered for this ArtTrigger packet. Other commands are ignored.
+    ///
+    /// Returns the number of handlers that were called.
+    pub fn dispatch(&mut self, command: &ArtCommand) -> usize {
+        match command {
+            ArtCommand::OpTrigger(trigger) => self.dispatch_trigger(trigger),
+            _ => 0,
+        }
+    }
+
+    /// Call the handlers that are registered for the given trigger, if it is accepted.
+    ///
+    /// Returns the number of handlers that were called.
+    pub fn dispatch_trigger(&mut self, trigger: &Trigger) -> usize {
+        if !self.accepts(trigger) {
+            return 0;
+        }
+        let mut called = 0;
+        for (route, handler) in &mut self.routes {
+            if route.matches(trigger) {
+                handler(trigger);
+                called += 1;
+            }
+        }
+        called
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArtSync;
+    use alloc::vec;
+
+    #[test]
+    fn routes() {
+        let mut exact = 0;
+        let mut macros = Vec::new();
+        let mut oem = Vec::new();
+        let mut specific = 0;
+        let mut any = 0;
+        let mut dispatcher = TriggerDispatcher::with_oem(0x1234);
+        dispatcher
+            .route((TriggerKey::Show, 2), |_: &Trigger| exact += 1)
+            .route(TriggerKey::Macro, |trigger: &Trigger| {
+                macros.push(trigger.sub_key)
+            })
+            .route(TriggerRoute::Oem(1, None), |trigger: &Trigger| {
+                oem.push(trigger.sub_key)
+            })
+            .route(TriggerKey::ManufacturerSpecific(0x90), |_: &Trigger| {
+                specific += 1
+            })
+            .route(TriggerRoute::Any, |_: &Trigger| any += 1);
+
+        assert_eq!(
+            dispatcher.dispatch(&Trigger::new(TriggerKey::Show, 2).into()),
+            2
+        );
+        assert_eq!(
+            dispatcher.dispatch(&Trigger::new(TriggerKey::Show, 3).into()),
+            1
+        );
+        assert_eq!(
+            dispatcher.dispatch(&Trigger::new(TriggerKey::Macro, 7).into()),
+            2
+        );
+        // Key 1 of this manufacturer is not a macro
+        assert_eq!(
+            dispatcher.dispatch(&Trigger::with_oem(0x1234, 1, 5).into()),
+            2
+        );
+        assert_eq!(
+            dispatcher.dispatch(&Trigger::with_oem(0x4321, 1, 5).into()),
+            0
+        );
+        // The same key is undefined for general triggers, and manufacturer specific for this manufacturer
+        let general = Trigger::new(TriggerKey::from_key(0x90), 0);
+        assert_eq!(dispatcher.dispatch(&general.into()), 1);
+        assert_eq!(
+            dispatcher.dispatch(&Trigger::with_oem(0x1234, 0x90, 0).into()),
+            2
+        );
+        assert_eq!(dispatcher.dispatch(&ArtSync::default().into()), 0);
+        drop(dispatcher);
+
+        assert_eq!(exact, 1);
+        assert_eq!(macros, vec![7]);
+        assert_eq!(oem, vec![5]);
+        assert_eq!(specific, 1);
+        assert_eq!(any, 6);
+    }
+
+    #[test]
+    fn general_only() {
+        let mut called = 0;
+        let mut dispatcher = TriggerDispatcher::new();
+        dispatcher.route(TriggerRoute::Any, |_: &Trigger| called += 1);
+        assert_eq!(dispatcher.oem(), None);
+        assert_eq!(dispatcher.dispatch_trigger(&Trigger::with_oem(0, 0, 0)), 0);
+        assert_eq!(
+            dispatcher.dispatch_trigger(&Trigger::with_oem(Trigger::OEM_GENERAL, 9, 0)),
+            1
+        );
+        drop(dispatcher);
+        assert_eq!(called, 1);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::convert::{Convertable, Cursor, WriteBuffer};
+use crate::Result;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// The unique id of an RDM device, made of a 16 bit ESTA manufacturer id and a 32 bit device id.
+///
+/// ```
+/// use artnet_protocol::Uid;
+/// let uid = Uid::new(0x7FF0, 1);
+/// assert_eq!(uid.to_string(), "7FF0:00000001");
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Uid(pub [u8; 6]);
+
+impl Uid {
+    /// The UID that addresses all devices of all manufacturers
+    pub const BROADCAST: Uid = Uid([0xFF; 6]);
+
+    /// Create a UID from its manufacturer id and device id
+    pub fn new(manufacturer: u16, device: u32) -> Uid {
+        let mut uid = [0; 6];
+        uid[..2].copy_from_slice(&manufacturer.to_be_bytes());
+        uid[2..].copy_from_slice(&device.to_be_bytes());
+        Uid(uid)
+    }
+
+    /// The ESTA manufacturer id
+    pub fn manufacturer(&self) -> u16 {
+        u16::from_be_bytes([self.0[0], self.0[1]])
+    }
+
+    /// The id of the device, which is unique for the manufacturer
+    pub fn device(&self) -> u32 {
+        u32::from_be_bytes([self.0[2], self.0[3], self.0[4], self.0[5]])
+    }
+}
+
+impl fmt::Display for Uid {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{:04X}:{:08X}", self.manufacturer(), self.device())
+    }
+}
+
+impl fmt::Debug for Uid {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Uid({})", self)
+    }
+}
+
+impl<T> Convertable<T> for Uid {
+    fn from_cursor(cursor: &mut Cursor) -> Result<Self> {
+        let mut uid = [0; 6];
+        cursor.read_exact(&mut uid)?;
+        Ok(Uid(uid))
+    }
+    fn write_to_buffer(&self, buffer: &mut dyn WriteBuffer, _: &T) -> Result<()> {
+        buffer.extend_from_slice(&self.0);
+        Ok(())
+    }
+    fn serialized_len(&self, _: &T) -> usize {
+        6
+    }
+    #[cfg(test)]
+    fn get_test_value() -> Self {
+        Uid::new(0x7FF0, 1)
+    }
+    #[cfg(test)]
+    fn is_equal(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+/// A list of UIDs until the end of the message
+impl<T> Convertable<T> for Vec<Uid> {
+    fn from_cursor(cursor: &mut Cursor) -> Result<Self> {
+        let mut uids = Vec::with_capacity(cursor.remaining().len() / 6);
+        while cursor.remaining().len() >= 6 {
+            uids.push(Convertable::<T>::from_cursor(cursor)?);
+        }
+        Ok(uids)
+    }
+    fn write_to_buffer(&self, buffer: &mut dyn WriteBuffer, _: &T) -> Result<()> {
+        for uid in self {
+            buffer.extend_from_slice(&uid.0);
+        }
+        Ok(())
+    }
+    fn serialized_len(&self, _: &T) -> usize {
+        self.len() * 6
+    }
+    #[cfg(test)]
+    fn get_test_value() -> Self {
+        alloc::vec![Uid::new(0x7FF0, 1), Uid::new(0x7FF0, 2)]
+    }
+    #[cfg(test)]
+    fn is_equal(&self, other: &Self) -> bool {
+        self == other
+    }
+}